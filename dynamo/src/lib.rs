@@ -4,38 +4,358 @@
 
 //! Codegen crate for Hyperion
 
-use quote::quote;
-use syn::{parse_macro_input, ItemFn};
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+use std::collections::HashMap;
+use syn::{
+    parse_macro_input, spanned::Spanned, Attribute, FnArg, Ident, Item, ItemFn, ItemMod, LitStr,
+    PatType, Type,
+};
 
-/// Creates a route handler with a function
+/// Creates a route handler with a function.
+///
+/// Every argument of the function is extracted from the request: `&Request` borrows the request itself,
+/// any other type is built through `hyperion::http::extract::FromRequest`.
+///
+/// The function may return either a `Response<T>` or a `Result<Response<T>, E>` where `E: Into<Error>`.
+/// `async` functions implement `AsyncRequestHandler` instead, which requires the `async` feature of hyperion.
 #[proc_macro_attribute]
 pub fn route(
-    attibutes: proc_macro::TokenStream,
+    _attributes: proc_macro::TokenStream,
     item: proc_macro::TokenStream,
 ) -> proc_macro::TokenStream {
     let function = parse_macro_input!(item as ItemFn);
-    let function_identifier = function.sig.ident;
-    let block = function.block;
-    let return_type = match function.sig.output {
+
+    handler(&function).into()
+}
+
+macro_rules! method_route {
+    ($($attribute:ident -> $method:ident,)*) => {
+        $(
+            /// Creates a route handler with a function, like `#[route]`, that serves `
+            #[doc = stringify!($method)]
+            /// ` requests on the given path.
+            ///
+            /// Handlers declared inside a `#[service]` module are registered in its router automatically.
+            /// A function may carry several method attributes to serve several routes.
+            #[proc_macro_attribute]
+            pub fn $attribute(
+                attributes: proc_macro::TokenStream,
+                item: proc_macro::TokenStream,
+            ) -> proc_macro::TokenStream {
+                let path = parse_macro_input!(attributes as LitStr);
+                let function = parse_macro_input!(item as ItemFn);
+                let function_identifier = &function.sig.ident;
+                let handler = handler(&function);
+                let shared = forwarded(&function).shared;
+
+                // The method attributes below this one have not run yet, their routes are collected here instead
+                let mut routes = vec![quote!((hyperion::http::method::Method::$method, #path))];
+                let mut errors = vec![];
+
+                for attribute in &function.attrs {
+                    let Some(method) = method_of(attribute) else {
+                        continue;
+                    };
+
+                    match attribute.parse_args::<LitStr>() {
+                        Ok(path) => routes.push(quote!((hyperion::http::method::Method::#method, #path))),
+                        Err(error) => errors.push(error.to_compile_error()),
+                    }
+
+                    // Uses the attribute, which would otherwise never expand and leave its import unused
+                    let attribute_path = attribute.path();
+                    errors.push(quote!(const _: () = { use #attribute_path as _; };));
+                }
+
+                quote!(
+                    #handler
+
+                    #(#shared)*
+                    impl #function_identifier {
+                        /// The method this handler serves, that of its first method attribute
+                        pub const METHOD: hyperion::http::method::Method = hyperion::http::method::Method::$method;
+
+                        /// The path pattern this handler serves, that of its first method attribute
+                        pub const PATH: &'static str = #path;
+
+                        /// Every method and path pattern this handler serves, one per method attribute
+                        pub const ROUTES: &'static [(hyperion::http::method::Method, &'static str)] = &[#(#routes),*];
+                    }
+
+                    #(#errors)*
+                )
+                .into()
+            }
+        )*
+
+        /// Names of the method attributes along with the `Method` variant they register
+        const METHOD_ATTRIBUTES: &[(&str, &str)] = &[$((stringify!($attribute), stringify!($method)),)*];
+    };
+}
+
+/// Returns the `Method` variant registered by a method attribute such as `#[get("/path")]`
+fn method_of(attribute: &Attribute) -> Option<Ident> {
+    let segment = attribute.path().segments.last()?;
+
+    METHOD_ATTRIBUTES
+        .iter()
+        .find(|(name, _)| segment.ident == name)
+        .map(|(_, method)| Ident::new(method, segment.ident.span()))
+}
+
+/// The attributes of a handler function, sorted by the generated items they are forwarded to
+struct Forwarded<'a> {
+    /// Documentation, for the handler struct
+    docs: Vec<&'a Attribute>,
+    /// Conditional compilation and lint levels, for the struct and every impl block
+    shared: Vec<&'a Attribute>,
+    /// Any other attribute, for the method running the body of the function
+    body: Vec<&'a Attribute>,
+}
+
+/// Sorts the attributes of a handler function, method attributes are consumed and not forwarded
+fn forwarded(function: &ItemFn) -> Forwarded<'_> {
+    let mut forwarded = Forwarded {
+        docs: vec![],
+        shared: vec![],
+        body: vec![],
+    };
+
+    for attribute in &function.attrs {
+        let path = attribute.path();
+
+        if path.is_ident("doc") {
+            forwarded.docs.push(attribute);
+        } else if [
+            "cfg", "cfg_attr", "allow", "warn", "deny", "forbid", "expect",
+        ]
+        .iter()
+        .any(|name| path.is_ident(name))
+        {
+            forwarded.shared.push(attribute);
+        } else if method_of(attribute).is_none() {
+            forwarded.body.push(attribute);
+        }
+    }
+
+    forwarded
+}
+
+method_route!(
+    get -> Get,
+    head -> Head,
+    post -> Post,
+    put -> Put,
+    delete -> Delete,
+    connect -> Connect,
+    options -> Options,
+    trace -> Trace,
+    patch -> Patch,
+);
+
+/// Collects every handler in the annotated inline module that uses a method attribute such as `#[get("/path")]`
+/// and generates a `pub fn router() -> Router` registering all of them.
+///
+/// When any route handler is `async` the router is an `AsyncRouter`, blocking handlers are then wrapped in `Blocking`.
+///
+/// Two handlers for the same method and path are rejected at compile time. Paths that only differ in the names of
+/// their `{placeholder}` segments are considered the same.
+#[proc_macro_attribute]
+pub fn service(
+    _attributes: proc_macro::TokenStream,
+    item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let mut module = parse_macro_input!(item as ItemMod);
+
+    let Some((_, items)) = &mut module.content else {
+        return quote_spanned!(module.span() =>
+            compile_error!("`#[service]` can only be used on inline modules");
+        )
+        .into();
+    };
+
+    // Only route handlers decide, other functions of the module are free to be async
+    let asynchronous = items.iter().any(|item| {
+        matches!(item, Item::Fn(function) if function.sig.asyncness.is_some()
+            && function.attrs.iter().any(|attribute| method_of(attribute).is_some()))
+    });
+
+    let mut registered = HashMap::new();
+    let mut registrations = vec![];
+    let mut errors = vec![];
+
+    for item in items.iter() {
+        let Item::Fn(function) = item else {
+            continue;
+        };
+
+        let shared = forwarded(function).shared;
+        let conditions = shared
+            .iter()
+            .filter(|attribute| attribute.path().is_ident("cfg"));
+
+        for attribute in &function.attrs {
+            let Some(method) = method_of(attribute) else {
+                continue;
+            };
+
+            let path = match attribute.parse_args::<LitStr>() {
+                Ok(path) => path,
+                Err(error) => {
+                    errors.push(error.to_compile_error());
+                    continue;
+                }
+            };
+
+            if let Some(existing) = registered.insert(
+                (method.to_string(), normalize(&path.value())),
+                &function.sig.ident,
+            ) {
+                let message = format!(
+                    "Duplicate route {} {}, already handled by `{existing}`",
+                    method.to_string().to_uppercase(),
+                    path.value(),
+                );
+
+                errors.push(quote_spanned!(path.span() => compile_error!(#message);));
+            }
+
+            let function_identifier = &function.sig.ident;
+
+            let handler = match (asynchronous, function.sig.asyncness.is_some()) {
+                (false, _) => quote!(
+                    Box::new(#function_identifier) as Box<dyn hyperion::http::router::RequestHandler>
+                ),
+                (true, true) => quote!(
+                    Box::new(#function_identifier) as Box<dyn hyperion::http::async_server::AsyncRequestHandler>
+                ),
+                (true, false) => quote!(
+                    Box::new(hyperion::http::async_server::Blocking::from(#function_identifier))
+                        as Box<dyn hyperion::http::async_server::AsyncRequestHandler>
+                ),
+            };
+
+            let conditions = conditions.clone();
+
+            registrations.push(quote!(
+                #(#conditions)*
+                (
+                    hyperion::http::router::RequestIdentifier {
+                        method: hyperion::http::method::Method::#method,
+                        path: #path.to_string(),
+                    },
+                    #handler,
+                ),
+            ));
+        }
+    }
+
+    let router = if asynchronous {
+        quote!(hyperion::http::async_server::AsyncRouter)
+    } else {
+        quote!(hyperion::http::router::Router)
+    };
+
+    let constructor = quote!(
+        /// Router containing every handler declared in this module
+        pub fn router() -> #router {
+            hyperion::http::router::Router::from([#(#registrations)*])
+        }
+
+        #(#errors)*
+    );
+
+    items.push(Item::Verbatim(constructor));
+
+    quote!(#module).into()
+}
+
+/// Replaces the names of `{placeholder}` segments so that equivalent route patterns compare equal
+fn normalize(path: &str) -> String {
+    path.split('/')
+        .map(|segment| {
+            if segment.starts_with('{') && segment.ends_with('}') {
+                "{}"
+            } else {
+                segment
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Generates a unit struct named after the function that implements `RequestHandler` by running its body,
+/// or `AsyncRequestHandler` for `async` functions
+fn handler(function: &ItemFn) -> TokenStream {
+    let function_identifier = &function.sig.ident;
+    let Forwarded { docs, shared, body } = forwarded(function);
+    let visibility = &function.vis;
+    let block = &function.block;
+    let return_type = match &function.sig.output {
         syn::ReturnType::Default => quote!(hyperion::http::response::Response<()>),
         syn::ReturnType::Type(_, typ) => quote!(#typ),
     };
 
-    quote!(
-        #[allow(non_camel_case_types)]
-        struct #function_identifier;
+    let extractions = function.sig.inputs.iter().map(|input| match input {
+        FnArg::Receiver(receiver) => quote_spanned!(receiver.span() =>
+            compile_error!("Route handlers cannot take `self`");
+        ),
+        FnArg::Typed(PatType { pat, ty, .. }) => match ty.as_ref() {
+            Type::Reference(reference) if reference.mutability.is_none() => quote!(
+                let #pat: #ty = &request;
+            ),
+            Type::Reference(reference) => quote_spanned!(reference.span() =>
+                compile_error!("Route handlers can only borrow the request immutably");
+            ),
+            typ => quote_spanned!(typ.span() =>
+                let #pat: #typ = <#typ as hyperion::http::extract::FromRequest>::from_request(&request)?;
+            ),
+        },
+    });
+
+    let implementation = if function.sig.asyncness.is_some() {
+        quote!(
+            #(#shared)*
+            impl hyperion::http::async_server::AsyncRequestHandler for #function_identifier {
+                #(#body)*
+                fn handle(&self, request: hyperion::http::request::Request) -> hyperion::http::async_server::HandlerFuture<'_> {
+                    Box::pin(async move {
+                        #(#extractions)*
 
-        impl hyperion::http::router::RequestHandler for #function_identifier {
-            fn handle(&self, req: hyperion::http::request::Request) -> Result<hyperion::http::response::Response<Box<dyn hyperion::http::response::Body>>, hyperion::http::error::Error> {
-                let response: #return_type = #block;
+                        // The block runs in a closure so that `?` returns from the handler rather than from `handle`
+                        #[allow(clippy::redundant_closure_call)]
+                        let response = (async move || -> #return_type #block)().await;
 
-                Ok(Response::new(
-                    Box::new(response.body) as Box<dyn hyperion::http::response::Body>,
-                    response.headers,
-                    response.status,
-                ))
+                        hyperion::http::response::IntoResponse::into_response(response)
+                    })
+                }
             }
-        }
+        )
+    } else {
+        quote!(
+            #(#shared)*
+            impl hyperion::http::router::RequestHandler for #function_identifier {
+                #(#body)*
+                fn handle(&self, request: hyperion::http::request::Request) -> Result<hyperion::http::response::Response<Box<dyn hyperion::http::response::Body>>, hyperion::http::error::Error> {
+                    #(#extractions)*
+
+                    // The block runs in a closure so that `?` returns from the handler rather than from `handle`
+                    #[allow(clippy::redundant_closure_call)]
+                    let response = (move || -> #return_type #block)();
+
+                    hyperion::http::response::IntoResponse::into_response(response)
+                }
+            }
+        )
+    };
+
+    quote!(
+        #(#docs)*
+        #(#shared)*
+        #[allow(non_camel_case_types)]
+        #visibility struct #function_identifier;
+
+        #implementation
     )
-    .into()
 }
@@ -0,0 +1,347 @@
+use crate::http::{
+    error::{Error, ErrorHook},
+    host::VirtualHosts,
+    limits::{timed_out, too_slow, Limits, Pace, Timeouts},
+    request::{received, Request, RequestParser},
+    response::{Body, Response},
+    router::{RequestHandler, Router},
+};
+use std::{future::Future, pin::Pin, sync::Arc, time::Instant};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    time::timeout,
+};
+
+/// The future returned by an [`AsyncRequestHandler`]
+pub type HandlerFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<Response<Box<dyn Body>>, Error>> + Send + 'a>>;
+
+/// Defining behavior of an asynchronous HTTP request handler
+pub trait AsyncRequestHandler: Send + Sync {
+    /// A function that handles an HTTP request for a certain method / path pair without blocking the runtime
+    fn handle(&self, request: Request) -> HandlerFuture<'_>;
+}
+
+/// Routing table for asynchronous HTTP request handlers
+pub type AsyncRouter = Router<dyn AsyncRequestHandler>;
+
+/// Virtual hosts for asynchronous HTTP request handlers
+pub type AsyncVirtualHosts = VirtualHosts<dyn AsyncRequestHandler>;
+
+/// Adapter that runs a blocking [`RequestHandler`] on the runtime's blocking thread pool
+pub struct Blocking(Arc<dyn RequestHandler>);
+
+impl<T: RequestHandler + 'static> From<T> for Blocking {
+    fn from(handler: T) -> Self {
+        Self(Arc::new(handler))
+    }
+}
+
+impl AsyncRequestHandler for Blocking {
+    fn handle(&self, request: Request) -> HandlerFuture<'_> {
+        let handler = self.0.clone();
+
+        Box::pin(async move {
+            tokio::task::spawn_blocking(move || handler.handle(request))
+                .await
+                .map_err(|error| Error::IoError(std::io::Error::other(error)))?
+        })
+    }
+}
+
+impl From<Router> for AsyncRouter {
+    fn from(router: Router) -> Self {
+        Self {
+            inner: router
+                .inner
+                .into_iter()
+                .map(|(identifier, handler)| {
+                    let handler: Box<dyn AsyncRequestHandler> =
+                        Box::new(Blocking(Arc::from(handler)));
+
+                    (identifier, handler)
+                })
+                .collect(),
+            patterns: router.patterns,
+        }
+    }
+}
+
+impl From<Router> for AsyncVirtualHosts {
+    fn from(router: Router) -> Self {
+        AsyncRouter::from(router).into()
+    }
+}
+
+impl From<VirtualHosts> for AsyncVirtualHosts {
+    fn from(hosts: VirtualHosts) -> Self {
+        hosts.map(AsyncRouter::from)
+    }
+}
+
+/// HTTP server running on the tokio runtime, serving each connection as a task instead of an OS thread.
+///
+/// Requests are routed by [`VirtualHosts`] and given as much time as the [`Timeouts`] allow, just like on the
+/// blocking [`Server`](super::server::Server). A handler that runs out of time is dropped, though [`Blocking`]
+/// handlers still finish on their thread. Responses that take the connection over, such as WebSocket and
+/// Server-Sent Events, are handed the socket on the blocking thread pool.
+///
+/// Unlike the blocking server, it serves HTTP/1.1 over TCP only.
+pub struct AsyncServer {
+    listener: TcpListener,
+    hosts: Arc<AsyncVirtualHosts>,
+    timeouts: Timeouts,
+    limits: Limits,
+    on_error: Option<ErrorHook>,
+}
+
+impl AsyncServer {
+    #[must_use]
+    /// Create a new [```AsyncServer```] instance from a router or a set of virtual hosts, blocking [`Router`]s are
+    /// served through [`Blocking`]
+    pub fn new(listener: TcpListener, router: impl Into<AsyncVirtualHosts>) -> Self {
+        Self {
+            listener,
+            hosts: Arc::new(router.into()),
+            timeouts: Timeouts::default(),
+            limits: Limits::default(),
+            on_error: None,
+        }
+    }
+
+    #[must_use]
+    /// Give clients and handlers only as much time as the timeouts allow
+    pub fn timeouts(mut self, timeouts: Timeouts) -> Self {
+        self.timeouts = timeouts;
+
+        self
+    }
+
+    #[must_use]
+    /// Refuse requests larger than the limits allow
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+
+        self
+    }
+
+    #[must_use]
+    /// Report the errors of connections that failed before a response could be written, which are dropped otherwise
+    pub fn on_error(
+        mut self,
+        hook: impl Fn(&dyn std::error::Error) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_error = Some(Arc::new(hook));
+
+        self
+    }
+
+    async fn handle_request(
+        mut stream: TcpStream,
+        hosts: &AsyncVirtualHosts,
+        timeouts: &Timeouts,
+        limits: &Limits,
+        accepted: Instant,
+    ) -> Result<(), Error> {
+        let mut response = match parse(&mut stream, timeouts, limits, accepted).await {
+            Ok(request) => dispatch(hosts, request, timeouts).await,
+            Err(error) => error.into(),
+        };
+
+        let take_over = response.body.take_over();
+        let response: Vec<u8> = response.into();
+
+        timeout(timeouts.write, stream.write_all(&response))
+            .await
+            .map_err(|_| std::io::Error::from(std::io::ErrorKind::TimedOut))??;
+
+        // Protocols like WebSocket keep the connection after the handshake response, and block while they do
+        if let Some(take_over) = take_over {
+            let stream = stream.into_std()?;
+
+            stream.set_nonblocking(false)?;
+            stream.set_write_timeout(Some(timeouts.write))?;
+
+            tokio::task::spawn_blocking(move || take_over(Box::new(stream)));
+
+            return Ok(());
+        }
+
+        stream.shutdown().await?;
+
+        Ok(())
+    }
+
+    /// Starts accepting connections, spawning a task per connection
+    pub async fn listen(&self) {
+        loop {
+            if let Ok((stream, _)) = self.listener.accept().await {
+                let accepted = Instant::now();
+                let hosts = self.hosts.clone();
+                let timeouts = self.timeouts;
+                let limits = self.limits;
+                let on_error = self.on_error.clone();
+
+                tokio::spawn(async move {
+                    if let Err(error) =
+                        AsyncServer::handle_request(stream, &hosts, &timeouts, &limits, accepted)
+                            .await
+                    {
+                        if let Some(on_error) = on_error {
+                            on_error(&error);
+                        }
+                    }
+                });
+            }
+        }
+    }
+}
+
+/// Runs the handler of a request, answering with 503 Service Unavailable if it takes longer than the handler timeout
+async fn dispatch(
+    hosts: &AsyncVirtualHosts,
+    request: Request,
+    timeouts: &Timeouts,
+) -> Response<Box<dyn Body>> {
+    let result = match hosts.route(request) {
+        Ok((handler, request)) => match timeouts.handler {
+            Some(duration) => timeout(duration, handler.handle(request))
+                .await
+                .unwrap_or_else(|_| {
+                    Err(Error::ServiceUnavailable(
+                        "The request took too long to handle".to_string(),
+                    ))
+                }),
+            None => handler.handle(request).await,
+        },
+        Err(error) => Err(error),
+    };
+
+    match result {
+        Ok(response) => response,
+        Err(error) => error.into(),
+    }
+}
+
+/// Reads a request from the stream, framed by the same [`RequestParser`] and within the same time as on blocking
+/// connections
+async fn parse(
+    stream: &mut TcpStream,
+    timeouts: &Timeouts,
+    limits: &Limits,
+    accepted: Instant,
+) -> Result<Request, Error> {
+    let mut parser = RequestParser::new(*limits);
+    let mut buffer = vec![0; 2048];
+
+    let mut pace = Pace::new(accepted, timeouts.header_read, timeouts.min_rate);
+    let mut reading_head = true;
+
+    loop {
+        // Whatever part of the body did not arrive together with the head has a deadline of its own
+        if reading_head && !parser.reading_head() {
+            reading_head = false;
+            pace = Pace::new(Instant::now(), timeouts.body_read, timeouts.min_rate);
+        }
+
+        let wanted = parser.wanted(buffer.len());
+        let remaining = pace.remaining().map_err(timed_out)?;
+
+        let read_byte_count = timeout(remaining, stream.read(&mut buffer[..wanted]))
+            .await
+            .map_err(|_| timed_out(too_slow()))??;
+
+        pace.receive(read_byte_count);
+
+        if let Some(request) = parser.feed(received(&buffer, read_byte_count)?)? {
+            return Ok(Request {
+                peer_addr: stream.peer_addr().ok(),
+                ..request
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AsyncServer, AsyncVirtualHosts};
+    use crate::http::request::Request;
+    use crate::http::{
+        host::VirtualHosts,
+        response::Response,
+        route,
+        router::routes,
+        sse::{self, Event},
+        status::HttpStatus,
+    };
+    use std::{
+        io::{Read, Write},
+        net::{SocketAddr, TcpStream},
+    };
+    use tokio::net::TcpListener;
+
+    #[route]
+    fn hello() -> Response<String> {
+        Response::new("hello".to_string(), vec![], HttpStatus::Ok)
+    }
+
+    #[route]
+    fn events(request: &Request) -> Response<sse::EventStream> {
+        sse::stream(request, |sender| {
+            let _ = sender.send(&Event::new().data("first"));
+        })
+    }
+
+    /// Serves the hosts on a runtime of their own, returning the address to connect to
+    fn serve(hosts: impl Into<AsyncVirtualHosts>) -> SocketAddr {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let listener = runtime.block_on(TcpListener::bind("127.0.0.1:0")).unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = AsyncServer::new(listener, hosts);
+
+        std::thread::spawn(move || runtime.block_on(server.listen()));
+
+        address
+    }
+
+    /// Sends a `GET` request and reads until the server closes the connection
+    fn get(address: SocketAddr, host: &str, path: &str) -> String {
+        let mut stream = TcpStream::connect(address).unwrap();
+        let mut response = String::new();
+
+        write!(stream, "GET {path} HTTP/1.1\r\nHost: {host}\r\n\r\n").unwrap();
+        stream.read_to_string(&mut response).unwrap();
+
+        response
+    }
+
+    #[test]
+    fn requests_are_routed_by_host_and_path() {
+        let address = serve(VirtualHosts::new().host("example.com", routes!(Get "/" -> hello,)));
+
+        let found = get(address, "Example.com:80", "/");
+        assert!(found.starts_with("HTTP/1.1 200 Ok\r\n"), "{found}");
+        assert!(found.ends_with("hello"));
+
+        let missing = get(address, "example.com", "/missing");
+        assert!(missing.starts_with("HTTP/1.1 404 "), "{missing}");
+
+        let misdirected = get(address, "other.example", "/");
+        assert!(misdirected.starts_with("HTTP/1.1 421 "), "{misdirected}");
+    }
+
+    #[test]
+    fn responses_can_take_the_connection_over() {
+        let address = serve(routes!(Get "/events" -> events,));
+
+        let response = get(address, "localhost", "/events");
+
+        assert!(response.starts_with("HTTP/1.1 200 Ok\r\n"), "{response}");
+        assert!(response.contains("text/event-stream"));
+        assert!(response.ends_with("data: first\n\n"), "{response}");
+    }
+}
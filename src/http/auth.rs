@@ -0,0 +1,442 @@
+use crate::http::{
+    error::Error,
+    extract::FromRequest,
+    header::HttpHeader,
+    request::Request,
+    response::{Body, Response},
+    router::RequestHandler,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use std::ops::Deref;
+
+/// Realm named in challenges unless an [`Authenticate`] is given another one
+pub(crate) const DEFAULT_REALM: &str = "hyperion";
+
+/// Header API keys are sent in unless an [`Authenticate`] is given another one
+const DEFAULT_API_KEY_HEADER: &str = "X-API-Key";
+
+/// A username and password sent with HTTP Basic authentication, RFC 7617
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Basic {
+    /// The user the client authenticates as
+    pub username: String,
+    /// The password of the user
+    pub password: String,
+}
+
+/// A token sent in an `Authorization: Bearer` header, RFC 6750
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bearer(pub String);
+
+/// A key sent in an `X-API-Key` header, or the header an [`Authenticate`] accepted it in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiKey(pub String);
+
+/// The credentials a request was sent with, in any of the supported schemes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Credentials {
+    /// HTTP Basic username and password
+    Basic(Basic),
+    /// Bearer token
+    Bearer(Bearer),
+    /// API key
+    ApiKey(ApiKey),
+}
+
+/// Ways a client can send its [`Credentials`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scheme {
+    /// `Authorization: Basic`, challenged with `WWW-Authenticate: Basic`
+    Basic,
+    /// `Authorization: Bearer`, challenged with `WWW-Authenticate: Bearer`
+    Bearer,
+    /// `X-API-Key` or another header, which has no challenge
+    ApiKey,
+}
+
+impl Scheme {
+    /// Returns the credentials sent with the request in this scheme, looking for API keys in `api_key_header`
+    fn credentials(self, request: &Request, api_key_header: &str) -> Option<Credentials> {
+        match self {
+            Scheme::Basic => basic(request).map(Credentials::Basic),
+            Scheme::Bearer => bearer(request).map(Credentials::Bearer),
+            Scheme::ApiKey => api_key(request, api_key_header).map(Credentials::ApiKey),
+        }
+    }
+
+    /// Returns the `WWW-Authenticate` header inviting clients to authenticate with this scheme
+    fn challenge(self, realm: &str) -> Option<HttpHeader> {
+        match self {
+            // Announces that usernames and passwords are encoded as UTF-8, RFC 7617 Section 2.1
+            Scheme::Basic => Some(HttpHeader::WwwAuthenticate(format!(
+                "Basic realm=\"{realm}\", charset=\"UTF-8\""
+            ))),
+            Scheme::Bearer => Some(HttpHeader::WwwAuthenticate(format!(
+                "Bearer realm=\"{realm}\""
+            ))),
+            Scheme::ApiKey => None,
+        }
+    }
+}
+
+/// Returns the parameters of the `Authorization` header when it uses the scheme, which is case-insensitive
+fn authorization(request: &Request, scheme: &str) -> Option<String> {
+    request.headers.iter().find_map(|header| match header {
+        HttpHeader::Authorization(value) => value
+            .split_once(' ')
+            .filter(|(name, _)| name.eq_ignore_ascii_case(scheme))
+            .map(|(_, parameters)| parameters.trim().to_string()),
+        _ => None,
+    })
+}
+
+fn basic(request: &Request) -> Option<Basic> {
+    let decoded = STANDARD.decode(authorization(request, "Basic")?).ok()?;
+    let (username, password) = String::from_utf8(decoded)
+        .ok()?
+        .split_once(':')
+        .map(|(username, password)| (username.to_string(), password.to_string()))?;
+
+    Some(Basic { username, password })
+}
+
+fn bearer(request: &Request) -> Option<Bearer> {
+    authorization(request, "Bearer")
+        .filter(|token| !token.is_empty())
+        .map(Bearer)
+}
+
+fn api_key(request: &Request, name: &str) -> Option<ApiKey> {
+    request
+        .headers
+        .iter()
+        .find(|header| header.name().eq_ignore_ascii_case(name))
+        .map(HttpHeader::value)
+        .filter(|key| !key.is_empty())
+        .map(ApiKey)
+}
+
+/// Answers a request without usable credentials with 401 Unauthorized, challenging the client to use `scheme`
+fn unauthorized(scheme: Scheme) -> Error {
+    Error::Unauthorized {
+        challenges: scheme.challenge(DEFAULT_REALM).into_iter().collect(),
+    }
+}
+
+impl FromRequest for Basic {
+    fn from_request(request: &Request) -> Result<Self, Error> {
+        basic(request).ok_or_else(|| unauthorized(Scheme::Basic))
+    }
+}
+
+impl FromRequest for Bearer {
+    fn from_request(request: &Request) -> Result<Self, Error> {
+        bearer(request).ok_or_else(|| unauthorized(Scheme::Bearer))
+    }
+}
+
+impl FromRequest for ApiKey {
+    fn from_request(request: &Request) -> Result<Self, Error> {
+        // A key accepted by an `Authenticate` may have been sent in another header
+        if let Some(Credentials::ApiKey(key)) = request.extensions.get::<Credentials>() {
+            return Ok(key.clone());
+        }
+
+        api_key(request, DEFAULT_API_KEY_HEADER).ok_or_else(|| unauthorized(Scheme::ApiKey))
+    }
+}
+
+impl Deref for Bearer {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Deref for ApiKey {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// What a [`Verifier`] makes of the credentials a request was sent with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Access {
+    /// The request is passed on to the handler
+    Granted,
+    /// The credentials are invalid, answered with 401 Unauthorized and a challenge to try again
+    Denied,
+    /// The credentials are valid but not for this request, answered with 403 Forbidden and the reason
+    Forbidden(String),
+}
+
+/// Decides whether credentials are valid, and whether they grant access to the request they were sent with.
+///
+/// Implemented for closures taking the credentials and the request
+pub trait Verifier: Send + Sync {
+    /// Checks the credentials sent with a request
+    fn verify(&self, credentials: &Credentials, request: &Request) -> Access;
+}
+
+impl<F: Fn(&Credentials, &Request) -> Access + Send + Sync> Verifier for F {
+    fn verify(&self, credentials: &Credentials, request: &Request) -> Access {
+        self(credentials, request)
+    }
+}
+
+/// Request handler that passes requests on to another handler only once a [`Verifier`] accepted their
+/// credentials.
+///
+/// Requests without credentials in any of the accepted schemes, or with credentials the verifier denies,
+/// are answered with 401 Unauthorized and a `WWW-Authenticate` challenge per scheme. The handler can read
+/// the credentials again through the [`Basic`], [`Bearer`] and [`ApiKey`] extractors.
+pub struct Authenticate<H, V> {
+    handler: H,
+    verifier: V,
+    schemes: Vec<Scheme>,
+    realm: String,
+    api_key_header: String,
+}
+
+impl<H: RequestHandler, V: Verifier> Authenticate<H, V> {
+    /// Guards `handler`, accepting credentials in every scheme
+    #[must_use]
+    pub fn new(handler: H, verifier: V) -> Self {
+        Self {
+            handler,
+            verifier,
+            schemes: vec![Scheme::Basic, Scheme::Bearer, Scheme::ApiKey],
+            realm: DEFAULT_REALM.to_string(),
+            api_key_header: DEFAULT_API_KEY_HEADER.to_string(),
+        }
+    }
+
+    /// Accepts credentials only in the given schemes, the first one present in a request is verified
+    #[must_use]
+    pub fn schemes(mut self, schemes: impl IntoIterator<Item = Scheme>) -> Self {
+        self.schemes = schemes.into_iter().collect();
+
+        self
+    }
+
+    /// Sets the realm named in challenges, telling clients which credentials to use
+    #[must_use]
+    pub fn realm(mut self, realm: impl Into<String>) -> Self {
+        self.realm = realm.into();
+
+        self
+    }
+
+    /// Looks for API keys in the given header instead of `X-API-Key`, names are case-insensitive
+    #[must_use]
+    pub fn api_key_header(mut self, name: impl Into<String>) -> Self {
+        self.api_key_header = name.into();
+
+        self
+    }
+
+    fn unauthorized(&self) -> Error {
+        Error::Unauthorized {
+            challenges: self
+                .schemes
+                .iter()
+                .filter_map(|scheme| scheme.challenge(&self.realm))
+                .collect(),
+        }
+    }
+}
+
+impl<H: RequestHandler, V: Verifier> RequestHandler for Authenticate<H, V> {
+    fn handle(&self, request: Request) -> Result<Response<Box<dyn Body>>, Error> {
+        let mut request = request;
+
+        let credentials = self
+            .schemes
+            .iter()
+            .find_map(|scheme| scheme.credentials(&request, &self.api_key_header))
+            .ok_or_else(|| self.unauthorized())?;
+
+        match self.verifier.verify(&credentials, &request) {
+            Access::Granted => {
+                request.extensions.insert(credentials);
+
+                self.handler.handle(request)
+            }
+            Access::Denied => Err(self.unauthorized()),
+            Access::Forbidden(reason) => Err(Error::Forbidden(reason)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Access, ApiKey, Authenticate, Basic, Bearer, Credentials, Scheme};
+    use crate::http::{
+        header::HttpHeader, method::Method, response::Response, route, router::routes,
+        status::HttpStatus, testing::TestClient,
+    };
+    use base64::{engine::general_purpose::STANDARD, Engine};
+
+    #[route]
+    fn whoami(credentials: Option<Basic>, token: Option<Bearer>) -> Response<String> {
+        let name = match (credentials, token) {
+            (Some(Basic { username, .. }), _) => username,
+            (None, Some(Bearer(token))) => token,
+            (None, None) => "a key holder".to_string(),
+        };
+
+        Response::new(name, vec![], HttpStatus::Ok)
+    }
+
+    #[route]
+    fn show_key(ApiKey(key): ApiKey) -> Response<String> {
+        Response::new(key, vec![], HttpStatus::Ok)
+    }
+
+    fn verify(credentials: &Credentials, _: &crate::http::request::Request) -> Access {
+        match credentials {
+            Credentials::Basic(Basic { username, password }) if password == "hunter2" => {
+                if username == "mallory" {
+                    Access::Forbidden("Mallory is banned".to_string())
+                } else {
+                    Access::Granted
+                }
+            }
+            Credentials::Bearer(Bearer(token)) if token == "token" => Access::Granted,
+            Credentials::ApiKey(ApiKey(key)) if key == "key" => Access::Granted,
+            _ => Access::Denied,
+        }
+    }
+
+    fn client(schemes: &[Scheme]) -> TestClient {
+        let guarded = Authenticate::new(whoami, verify)
+            .schemes(schemes.iter().copied())
+            .realm("tests");
+
+        TestClient::new(routes!(Get "/" -> guarded, Get "/key" -> show_key,))
+    }
+
+    fn with(header: HttpHeader) -> Vec<HttpHeader> {
+        vec![header]
+    }
+
+    fn basic(username: &str, password: &str) -> HttpHeader {
+        HttpHeader::Authorization(format!(
+            "Basic {}",
+            STANDARD.encode(format!("{username}:{password}"))
+        ))
+    }
+
+    const ALL: [Scheme; 3] = [Scheme::Basic, Scheme::Bearer, Scheme::ApiKey];
+
+    #[test]
+    fn valid_credentials_are_granted_access() {
+        let client = client(&ALL);
+
+        client
+            .request(Method::Get, "/", with(basic("alice", "hunter2")), None)
+            .assert_status(HttpStatus::Ok)
+            .assert_body("alice");
+        client
+            .request(
+                Method::Get,
+                "/",
+                with(HttpHeader::Authorization("bearer token".to_string())),
+                None,
+            )
+            .assert_body("token");
+        client
+            .request(
+                Method::Get,
+                "/",
+                with(HttpHeader::XApiKey("key".to_string())),
+                None,
+            )
+            .assert_body("a key holder");
+    }
+
+    #[test]
+    fn missing_or_invalid_credentials_are_challenged() {
+        let client = client(&ALL);
+
+        for headers in [vec![], with(basic("alice", "wrong"))] {
+            let response = client.request(Method::Get, "/", headers, None);
+
+            response
+                .assert_status(HttpStatus::Unauthorized)
+                .assert_header(&HttpHeader::WwwAuthenticate(
+                    "Basic realm=\"tests\", charset=\"UTF-8\"".to_string(),
+                ))
+                .assert_header(&HttpHeader::WwwAuthenticate(
+                    "Bearer realm=\"tests\"".to_string(),
+                ));
+        }
+    }
+
+    #[test]
+    fn verifiers_can_forbid_valid_credentials() {
+        client(&ALL)
+            .request(Method::Get, "/", with(basic("mallory", "hunter2")), None)
+            .assert_status(HttpStatus::Forbidden);
+    }
+
+    #[test]
+    fn only_the_accepted_schemes_are_used() {
+        let response = client(&[Scheme::Bearer]).request(
+            Method::Get,
+            "/",
+            with(basic("alice", "hunter2")),
+            None,
+        );
+
+        response.assert_status(HttpStatus::Unauthorized);
+        assert_eq!(
+            response.header("WWW-Authenticate").as_deref(),
+            Some("Bearer realm=\"tests\"")
+        );
+    }
+
+    #[test]
+    fn api_keys_can_be_sent_in_another_header() {
+        let guarded = Authenticate::new(show_key, verify)
+            .schemes([Scheme::ApiKey])
+            .api_key_header("X-Service-Token");
+        let client = TestClient::new(routes!(Get "/" -> guarded,));
+
+        client
+            .request(
+                Method::Get,
+                "/",
+                with(HttpHeader::new("x-service-token", "key").unwrap()),
+                None,
+            )
+            .assert_status(HttpStatus::Ok)
+            .assert_body("key");
+        client
+            .request(
+                Method::Get,
+                "/",
+                with(HttpHeader::XApiKey("key".to_string())),
+                None,
+            )
+            .assert_status(HttpStatus::Unauthorized);
+    }
+
+    #[test]
+    fn extractors_require_their_credentials() {
+        let client = client(&ALL);
+
+        client
+            .request(
+                Method::Get,
+                "/key",
+                with(HttpHeader::XApiKey("secret".to_string())),
+                None,
+            )
+            .assert_body("secret");
+        client.get("/key").assert_status(HttpStatus::Unauthorized);
+    }
+}
@@ -0,0 +1,763 @@
+use crate::http::{
+    error::Error,
+    header::HttpHeader,
+    method::Method,
+    request::{Extensions, Request},
+    response::{self, Response},
+    status::HttpStatus,
+};
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, ErrorKind, Write},
+    net::{TcpStream, ToSocketAddrs},
+    sync::{Mutex, PoisonError},
+    time::Duration,
+};
+use thiserror::Error;
+
+/// Errors that can happen while sending a request
+#[derive(Debug, Error)]
+pub enum ClientError {
+    /// Connecting, reading or writing failed, or timed out
+    #[error("Io error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// The URL or `Host` header does not name an `http://` server
+    #[error("Invalid URL: {0}")]
+    InvalidUrl(String),
+
+    /// The server sent something that is not a valid HTTP/1.1 response
+    #[error("Invalid response: {0}")]
+    InvalidResponse(String),
+
+    /// The server kept redirecting past the limit of the client
+    #[error("Too many redirects")]
+    TooManyRedirects,
+}
+
+impl From<Error> for ClientError {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::IoError(error) => ClientError::Io(error),
+            Error::BadGateway(message) => ClientError::InvalidResponse(message),
+            error => ClientError::InvalidResponse(error.to_string()),
+        }
+    }
+}
+
+/// The parts of an `http://` URL needed to send a request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Url {
+    /// Hostname or IP address, IPv6 addresses keep their brackets
+    pub host: String,
+    /// Port of the server, 80 unless given
+    pub port: u16,
+    /// Path of the target, starting with `/`
+    pub path: String,
+    /// Query string of the target, without the leading `?`
+    pub query: Option<String>,
+}
+
+impl Url {
+    /// Parses an absolute `http://` URL, dropping any fragment
+    /// # Errors
+    /// - If the URL uses another scheme, has no host or an invalid port
+    pub fn parse(url: &str) -> Result<Self, ClientError> {
+        let invalid = || ClientError::InvalidUrl(url.to_string());
+
+        let rest = url.strip_prefix("http://").ok_or_else(invalid)?;
+        let rest = rest.split('#').next().unwrap_or_default();
+
+        let (authority, target) = rest.split_at(rest.find(['/', '?']).unwrap_or(rest.len()));
+        let (host, port) = split_authority(authority).ok_or_else(invalid)?;
+
+        let (path, query) = match target.split_once('?') {
+            Some((path, query)) => (path, Some(query.to_string())),
+            None => (target, None),
+        };
+
+        Ok(Self {
+            host,
+            port,
+            path: if path.is_empty() { "/" } else { path }.to_string(),
+            query,
+        })
+    }
+
+    /// Resolves the `Location` of a redirect against this URL
+    /// # Errors
+    /// - If the location is an absolute URL that cannot be parsed
+    pub fn join(&self, location: &str) -> Result<Self, ClientError> {
+        if location.contains("://") {
+            return Url::parse(location);
+        }
+
+        if let Some(rest) = location.strip_prefix("//") {
+            return Url::parse(&format!("http://{rest}"));
+        }
+
+        let target = if location.starts_with('/') {
+            location.to_string()
+        } else {
+            // Relative to the directory of the current path
+            let directory = &self.path[..=self.path.rfind('/').unwrap_or_default()];
+
+            format!("{directory}{location}")
+        };
+
+        Url::parse(&format!("http://{}{target}", self.authority()))
+    }
+
+    /// Host and port as sent in the `Host` header, leaving out the default port
+    #[must_use]
+    pub fn authority(&self) -> String {
+        match self.port {
+            80 => self.host.clone(),
+            port => format!("{}:{port}", self.host),
+        }
+    }
+}
+
+/// Splits `host:port`, defaulting to port 80
+fn split_authority(authority: &str) -> Option<(String, u16)> {
+    let (host, port) = match authority.rsplit_once(':') {
+        // The colons of a bracketed IPv6 address are not a port separator
+        Some((host, port)) if !port.contains(']') => (host, port.parse().ok()?),
+        _ => (authority, 80),
+    };
+
+    (!host.is_empty()).then(|| (host.to_string(), port))
+}
+
+/// A blocking HTTP/1.1 client that keeps connections open for reuse.
+///
+/// Requests and responses are the same [`Request`] and [`Response`] types the server works with.
+#[derive(Debug)]
+pub struct Client {
+    timeout: Option<Duration>,
+    max_redirects: usize,
+    max_idle_per_host: usize,
+    pool: Mutex<HashMap<String, Vec<BufReader<TcpStream>>>>,
+}
+
+impl Client {
+    /// Creates a client with a 30 second timeout that follows up to 10 redirects
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            timeout: Some(Duration::from_secs(30)),
+            max_redirects: 10,
+            max_idle_per_host: 8,
+            pool: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Limits how long connecting, and every single read or write, may take, `None` waits forever
+    #[must_use]
+    pub fn timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+
+        self
+    }
+
+    /// Limits how many redirects are followed, 0 returns redirects as they are
+    #[must_use]
+    pub fn max_redirects(mut self, max_redirects: usize) -> Self {
+        self.max_redirects = max_redirects;
+
+        self
+    }
+
+    /// Limits how many idle connections are kept open to each server
+    #[must_use]
+    pub fn max_idle_per_host(mut self, max_idle_per_host: usize) -> Self {
+        self.max_idle_per_host = max_idle_per_host;
+
+        self
+    }
+
+    /// Sends a `GET` request to the URL
+    /// # Errors
+    /// - If the URL is invalid, the exchange failed or the server redirected too often
+    pub fn get(&self, url: &str) -> Result<Response<Vec<u8>>, ClientError> {
+        self.request(Method::Get, url, vec![], None)
+    }
+
+    /// Sends a request to the URL, following redirects.
+    ///
+    /// The `Host` header is set from the URL, and `Authorization` is dropped when redirected to another server.
+    /// # Errors
+    /// - If the URL is invalid, the exchange failed or the server redirected too often
+    pub fn request(
+        &self,
+        method: Method,
+        url: &str,
+        headers: Vec<HttpHeader>,
+        body: Option<Vec<u8>>,
+    ) -> Result<Response<Vec<u8>>, ClientError> {
+        let origin = Url::parse(url)?;
+
+        let mut url = origin.clone();
+        let mut method = method;
+        let mut body = body;
+        let mut headers: Vec<HttpHeader> = headers
+            .into_iter()
+            .filter(|header| !matches!(header, HttpHeader::Host(_)))
+            .collect();
+
+        for _ in 0..=self.max_redirects {
+            let mut request_headers = vec![HttpHeader::Host(url.authority())];
+            request_headers.extend(headers.iter().cloned());
+
+            let response = self.send(Request {
+                method,
+                path: url.path.clone(),
+                query: url.query.clone(),
+                params: HashMap::new(),
+                http_version: "HTTP/1.1".to_string(),
+                headers: request_headers,
+                body: body.clone(),
+                peer_addr: None,
+                extensions: Extensions::default(),
+            })?;
+
+            let code = u16::from(&response.status);
+
+            let location = response.headers.iter().find_map(|header| match header {
+                HttpHeader::Location(location) => Some(location.trim().to_string()),
+                _ => None,
+            });
+
+            let Some(location) = location.filter(|_| matches!(code, 301 | 302 | 303 | 307 | 308))
+            else {
+                return Ok(response);
+            };
+
+            url = url.join(&location)?;
+
+            // Like browsers, only 307 and 308 repeat the method and body of a POST
+            if code == 303 && method != Method::Head
+                || matches!(code, 301 | 302) && method == Method::Post
+            {
+                method = Method::Get;
+                body = None;
+
+                // The headers describing the dropped body would now describe nothing
+                headers.retain(|header| {
+                    !matches!(
+                        header,
+                        HttpHeader::ContentLength(_) | HttpHeader::ContentType(_)
+                    )
+                });
+            }
+
+            if (&url.host, url.port) != (&origin.host, origin.port) {
+                headers.retain(|header| !matches!(header, HttpHeader::Authorization(_)));
+            }
+        }
+
+        Err(ClientError::TooManyRedirects)
+    }
+
+    /// Sends a single request to the server named by its `Host` header, without following redirects.
+    ///
+    /// A `Content-Length` header is added for bodies that lack one.
+    /// # Errors
+    /// - If the request has no `Host` header
+    /// - If connecting, writing or reading failed or timed out
+    /// - If the response is not valid HTTP/1.1
+    pub fn send(&self, request: Request) -> Result<Response<Vec<u8>>, ClientError> {
+        let mut request = request;
+
+        let host = request
+            .headers
+            .iter()
+            .find_map(|header| match header {
+                HttpHeader::Host(host) => Some(host.trim().to_string()),
+                _ => None,
+            })
+            .ok_or_else(|| ClientError::InvalidUrl("missing Host header".to_string()))?;
+
+        let (host, port) =
+            split_authority(&host).ok_or_else(|| ClientError::InvalidUrl(host.clone()))?;
+        let address = format!("{host}:{port}");
+
+        let has_length = request
+            .headers
+            .iter()
+            .any(|header| matches!(header, HttpHeader::ContentLength(_)));
+
+        if let (Some(body), false) = (&request.body, has_length) {
+            request
+                .headers
+                .push(HttpHeader::ContentLength(body.len().to_string()));
+        }
+
+        let method = request.method;
+        let bytes: Vec<u8> = request.into();
+
+        // The server may have closed an idle connection in the meantime, which is retried on a fresh one
+        if let Some(mut connection) = self.checkout(&address) {
+            let written = write(&mut connection, &bytes);
+            let unsent = written.is_err();
+
+            match written
+                .map_err(ClientError::from)
+                .and_then(|()| read_response(&mut connection, method))
+            {
+                Ok((response, reusable)) => {
+                    if reusable {
+                        self.checkin(&address, connection);
+                    }
+
+                    return Ok(response);
+                }
+                // A request that was written may have been acted on, only sending it again must do no harm
+                Err(ClientError::Io(error))
+                    if is_stale(&error) && (unsent || method.is_idempotent()) => {}
+                Err(error) => return Err(error),
+            }
+        }
+
+        let mut connection = self.connect(&address)?;
+
+        write(&mut connection, &bytes)?;
+
+        let (response, reusable) = read_response(&mut connection, method)?;
+
+        if reusable {
+            self.checkin(&address, connection);
+        }
+
+        Ok(response)
+    }
+
+    fn connect(&self, address: &str) -> Result<BufReader<TcpStream>, ClientError> {
+        Ok(BufReader::new(connect(address, self.timeout)?))
+    }
+
+    fn checkout(&self, address: &str) -> Option<BufReader<TcpStream>> {
+        self.pool
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get_mut(address)?
+            .pop()
+    }
+
+    fn checkin(&self, address: &str, connection: BufReader<TcpStream>) {
+        let mut pool = self.pool.lock().unwrap_or_else(PoisonError::into_inner);
+        let idle = pool.entry(address.to_string()).or_default();
+
+        if idle.len() < self.max_idle_per_host {
+            idle.push(connection);
+        }
+    }
+}
+
+impl Default for Client {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Connects to the first address `address` resolves to that accepts, `timeout` applies to connecting as well as
+/// every later read and write
+pub(crate) fn connect(address: &str, timeout: Option<Duration>) -> std::io::Result<TcpStream> {
+    let mut last_error = None;
+
+    for socket_address in address.to_socket_addrs()? {
+        let stream = match timeout {
+            Some(timeout) => TcpStream::connect_timeout(&socket_address, timeout),
+            None => TcpStream::connect(socket_address),
+        };
+
+        match stream {
+            Ok(stream) => {
+                stream.set_read_timeout(timeout)?;
+                stream.set_write_timeout(timeout)?;
+                stream.set_nodelay(true)?;
+
+                return Ok(stream);
+            }
+            Err(error) => last_error = Some(error),
+        }
+    }
+
+    Err(last_error.unwrap_or_else(|| {
+        std::io::Error::new(ErrorKind::NotFound, format!("{address} did not resolve"))
+    }))
+}
+
+/// Whether an error means a pooled connection was closed before the request reached the server
+fn is_stale(error: &std::io::Error) -> bool {
+    matches!(
+        error.kind(),
+        ErrorKind::UnexpectedEof
+            | ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+            | ErrorKind::BrokenPipe
+    )
+}
+
+/// Writes a request to a connection
+fn write(connection: &mut BufReader<TcpStream>, request: &[u8]) -> std::io::Result<()> {
+    connection.get_mut().write_all(request)?;
+    connection.get_mut().flush()
+}
+
+/// Reads a response, skipping interim `1xx` responses
+fn read_response(
+    reader: &mut impl BufRead,
+    method: Method,
+) -> Result<(Response<Vec<u8>>, bool), ClientError> {
+    let (response, persistent) = loop {
+        let (response, persistent) = response::read_response(reader, method)?;
+
+        if !matches!(u16::from(&response.status), 100..=199)
+            || response.status == HttpStatus::SwitchingProtocols
+        {
+            break (response, persistent);
+        }
+    };
+
+    let code = u16::from(&response.status);
+
+    // Without a length or chunked framing the body ran up to the end of the connection
+    let delimited = method == Method::Head
+        || matches!(code, 204 | 304)
+        || response.headers.iter().any(|header| match header {
+            HttpHeader::ContentLength(_) => true,
+            HttpHeader::TransferEncoding(encoding) => encoding
+                .rsplit(',')
+                .next()
+                .is_some_and(|last| last.trim().eq_ignore_ascii_case("chunked")),
+            _ => false,
+        });
+
+    let reusable = persistent && delimited && code != 101;
+
+    Ok((response, reusable))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        io::Read,
+        net::TcpListener,
+        thread::{self, JoinHandle},
+    };
+
+    /// Answers requests with the scripted responses in order, closing the connection after those marked so.
+    /// `{port}` in a response is replaced by the port of the server.
+    ///
+    /// Returns the address of the server, and the heads of the requests it received along with the number of
+    /// the connection each arrived on.
+    fn serve(script: Vec<(&'static str, bool)>) -> (String, JoinHandle<Vec<(usize, String)>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+
+        let port = listener.local_addr().unwrap().port();
+
+        let server = thread::spawn(move || {
+            let mut script = script.into_iter();
+            let mut received = vec![];
+
+            for (number, stream) in listener.incoming().enumerate() {
+                let mut reader = BufReader::new(stream.unwrap());
+
+                loop {
+                    let mut head = String::new();
+
+                    while !head.ends_with("\r\n\r\n") {
+                        if reader.read_line(&mut head).unwrap() == 0 {
+                            break;
+                        }
+                    }
+
+                    if head.is_empty() {
+                        break;
+                    }
+
+                    let length = head
+                        .lines()
+                        .find_map(|line| line.strip_prefix("Content-Length: "))
+                        .map_or(0, |length| length.parse().unwrap());
+
+                    reader.read_exact(&mut vec![0; length]).unwrap();
+                    received.push((number, head));
+
+                    let Some((response, close)) = script.next() else {
+                        return received;
+                    };
+
+                    let response = response.replace("{port}", &port.to_string());
+
+                    reader.get_mut().write_all(response.as_bytes()).unwrap();
+
+                    if close {
+                        break;
+                    }
+                }
+
+                if script.len() == 0 {
+                    return received;
+                }
+            }
+
+            received
+        });
+
+        (address, server)
+    }
+
+    fn connections(received: &[(usize, String)]) -> Vec<usize> {
+        received.iter().map(|(number, _)| *number).collect()
+    }
+
+    #[test]
+    fn connections_are_reused() {
+        let (address, server) = serve(vec![
+            ("HTTP/1.1 200 Ok\r\nContent-Length: 3\r\n\r\none", false),
+            ("HTTP/1.1 200 Ok\r\nContent-Length: 3\r\n\r\ntwo", false),
+        ]);
+
+        let client = Client::new();
+
+        client
+            .get(&format!("http://{address}/"))
+            .unwrap()
+            .assert_body("one");
+        client
+            .get(&format!("http://{address}/"))
+            .unwrap()
+            .assert_body("two");
+
+        drop(client);
+
+        assert_eq!(connections(&server.join().unwrap()), [0, 0]);
+    }
+
+    #[test]
+    fn closed_connections_are_not_reused() {
+        let (address, server) = serve(vec![
+            (
+                "HTTP/1.1 200 Ok\r\nConnection: keep-alive, close\r\nContent-Length: 0\r\n\r\n",
+                true,
+            ),
+            ("HTTP/1.0 200 Ok\r\nContent-Length: 0\r\n\r\n", true),
+            (
+                "HTTP/1.0 200 Ok\r\nConnection: Keep-Alive\r\nContent-Length: 0\r\n\r\n",
+                false,
+            ),
+            ("HTTP/1.1 200 Ok\r\nContent-Length: 0\r\n\r\n", false),
+            // Without framing the body runs up to the end of the connection
+            ("HTTP/1.1 200 Ok\r\n\r\nall of it", true),
+            ("HTTP/1.1 200 Ok\r\nContent-Length: 0\r\n\r\n", false),
+        ]);
+
+        let client = Client::new();
+
+        for _ in 0..6 {
+            client
+                .get(&format!("http://{address}/"))
+                .unwrap()
+                .assert_status(HttpStatus::Ok);
+        }
+
+        drop(client);
+
+        assert_eq!(connections(&server.join().unwrap()), [0, 1, 2, 2, 2, 3]);
+    }
+
+    #[test]
+    fn only_persistent_delimited_responses_are_reusable() {
+        let reusable =
+            |response: &str, method| read_response(&mut response.as_bytes(), method).unwrap().1;
+
+        assert!(reusable(
+            "HTTP/1.1 200 Ok\r\nContent-Length: 0\r\n\r\n",
+            Method::Get
+        ));
+        assert!(reusable(
+            "HTTP/1.1 200 Ok\r\nConnection: keep-alive\r\n\r\n",
+            Method::Head
+        ));
+        assert!(reusable(
+            "HTTP/1.0 200 Ok\r\nConnection: Keep-Alive\r\nContent-Length: 0\r\n\r\n",
+            Method::Get
+        ));
+        assert!(reusable(
+            "HTTP/1.1 100 Continue\r\n\r\nHTTP/1.1 204 No Content\r\n\r\n",
+            Method::Get
+        ));
+
+        assert!(!reusable(
+            "HTTP/1.1 200 Ok\r\nConnection: keep-alive, close\r\nContent-Length: 0\r\n\r\n",
+            Method::Get
+        ));
+        assert!(!reusable("HTTP/1.1 200 Ok\r\nConnection: Upgrade\r\nConnection: CLOSE\r\nContent-Length: 0\r\n\r\n", Method::Get));
+        assert!(!reusable(
+            "HTTP/1.0 200 Ok\r\nContent-Length: 0\r\n\r\n",
+            Method::Get
+        ));
+        assert!(!reusable(
+            "HTTP/1.1 200 Ok\r\n\r\nuntil the end",
+            Method::Get
+        ));
+        assert!(!reusable(
+            "HTTP/1.1 101 Switching Protocols\r\nConnection: upgrade\r\n\r\n",
+            Method::Get
+        ));
+    }
+
+    #[test]
+    fn chunked_bodies_are_decoded() {
+        let (address, server) = serve(vec![
+            (
+                "HTTP/1.1 200 Ok\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n6;name=value\r\n world\r\n0\r\nTrailer: value\r\n\r\n",
+                false,
+            ),
+            ("HTTP/1.1 204 No Content\r\n\r\n", false),
+        ]);
+
+        let client = Client::new();
+
+        client
+            .get(&format!("http://{address}/"))
+            .unwrap()
+            .assert_body("hello world")
+            .assert_header(&HttpHeader::ContentLength("11".to_string()));
+
+        // The whole body was read, so the connection is ready for the next response
+        client
+            .get(&format!("http://{address}/"))
+            .unwrap()
+            .assert_status(HttpStatus::NoContent);
+
+        drop(client);
+
+        assert_eq!(connections(&server.join().unwrap()), [0, 0]);
+    }
+
+    #[test]
+    fn redirects_are_followed() {
+        let (address, server) = serve(vec![
+            (
+                "HTTP/1.1 303 See Other\r\nLocation: next?page=2\r\nContent-Length: 0\r\n\r\n",
+                false,
+            ),
+            (
+                "HTTP/1.1 307 Temporary Redirect\r\nLocation: /last\r\nContent-Length: 0\r\n\r\n",
+                false,
+            ),
+            ("HTTP/1.1 200 Ok\r\nContent-Length: 4\r\n\r\ndone", false),
+        ]);
+
+        let response = Client::new()
+            .request(
+                Method::Post,
+                &format!("http://{address}/first/form"),
+                vec![
+                    HttpHeader::Authorization("Bearer token".to_string()),
+                    HttpHeader::ContentType("text/plain".to_string()),
+                ],
+                Some(b"body".to_vec()),
+            )
+            .unwrap();
+
+        response.assert_body("done");
+
+        let received = server.join().unwrap();
+        let heads: Vec<&str> = received.iter().map(|(_, head)| head.as_str()).collect();
+
+        assert!(heads[0].starts_with("POST /first/form HTTP/1.1\r\n"));
+        assert!(heads[0].contains("Content-Length: 4\r\n"));
+
+        // 303 turns the POST into a GET without a body, 307 keeps the method it was given
+        for head in &heads[1..] {
+            assert!(head.contains("Authorization: Bearer token\r\n"));
+            assert!(!head.contains("Content-Type"));
+            assert!(!head.contains("Content-Length"));
+        }
+
+        assert!(heads[1].starts_with("GET /first/next?page=2 HTTP/1.1\r\n"));
+        assert!(heads[2].starts_with("GET /last HTTP/1.1\r\n"));
+    }
+
+    #[test]
+    fn credentials_are_not_sent_to_other_servers() {
+        let (address, server) = serve(vec![
+            (
+                "HTTP/1.1 302 Found\r\nLocation: http://localhost:{port}/\r\nConnection: close\r\nContent-Length: 0\r\n\r\n",
+                true,
+            ),
+            ("HTTP/1.1 200 Ok\r\nContent-Length: 0\r\n\r\n", false),
+        ]);
+
+        let client = Client::new();
+
+        client
+            .request(
+                Method::Get,
+                &format!("http://{address}/"),
+                vec![HttpHeader::Authorization("Bearer token".to_string())],
+                None,
+            )
+            .unwrap()
+            .assert_status(HttpStatus::Ok);
+
+        drop(client);
+
+        let received = server.join().unwrap();
+
+        assert!(received[0].1.contains("Authorization"));
+        assert!(!received[1].1.contains("Authorization"));
+    }
+
+    #[test]
+    fn redirects_are_limited() {
+        let (address, server) = serve(vec![
+            (
+                "HTTP/1.1 302 Found\r\nLocation: /\r\nContent-Length: 0\r\n\r\n",
+                false,
+            ),
+            (
+                "HTTP/1.1 302 Found\r\nLocation: /\r\nContent-Length: 0\r\n\r\n",
+                false,
+            ),
+        ]);
+
+        let result = Client::new()
+            .max_redirects(1)
+            .get(&format!("http://{address}/"));
+
+        assert!(matches!(result, Err(ClientError::TooManyRedirects)));
+
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn stale_connections_are_retried() {
+        // The server closes the connection without saying so, which the client only finds out on its next request
+        let (address, server) = serve(vec![
+            ("HTTP/1.1 200 Ok\r\nContent-Length: 5\r\n\r\nfirst", true),
+            ("HTTP/1.1 200 Ok\r\nContent-Length: 6\r\n\r\nsecond", false),
+        ]);
+
+        let client = Client::new();
+
+        client
+            .get(&format!("http://{address}/"))
+            .unwrap()
+            .assert_body("first");
+        client
+            .get(&format!("http://{address}/"))
+            .unwrap()
+            .assert_body("second");
+
+        drop(client);
+
+        assert_eq!(connections(&server.join().unwrap()), [0, 1]);
+    }
+}
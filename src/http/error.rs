@@ -1,9 +1,13 @@
-use std::{num::ParseIntError, string::FromUtf8Error};
+use std::{num::ParseIntError, string::FromUtf8Error, sync::Arc};
 use thiserror::Error;
 
 use crate::http::{header::HttpHeader, response::Response, status::HttpStatus};
 
-use super::{method::Method, response::Body};
+use super::{http2::error::Http2Error, method::Method, response::Body};
+
+/// Called with the errors that happen where there is no caller left to return them to, such as while serving
+/// a connection in the background
+pub type ErrorHook = Arc<dyn Fn(&dyn std::error::Error) + Send + Sync>;
 
 /// Representation of Different types of errors that can happened during the processing of a request
 #[derive(Debug, Error)]
@@ -25,6 +29,77 @@ pub enum Error {
         path: String,
     },
 
+    /// The request lacks valid credentials
+    #[error("Unauthorized")]
+    Unauthorized {
+        /// `WWW-Authenticate` challenges telling the client how to authenticate
+        challenges: Vec<HttpHeader>,
+    },
+
+    /// The credentials are valid, but do not grant access to what was requested
+    #[error("Forbidden")]
+    Forbidden(String),
+
+    /// The client did not send the request in time
+    #[error("Request Timeout")]
+    RequestTimeout(String),
+
+    /// The request body is larger than the server accepts
+    #[error("Payload Too Large")]
+    PayloadTooLarge(String),
+
+    /// The request line is longer than the server accepts
+    #[error("URI Too Long")]
+    URITooLong(String),
+
+    /// The request headers are more or larger than the server accepts
+    #[error("Request Header Fields Too Large")]
+    RequestHeaderFieldsTooLarge(String),
+
+    /// The request was sent to a server that is not configured to serve its host
+    #[error("Misdirected Request")]
+    MisdirectedRequest {
+        /// The host the request was sent to
+        host: Option<String>,
+    },
+
+    /// The request asked to switch to a protocol, or a version of one, the server does not speak
+    #[error("Upgrade Required")]
+    UpgradeRequired {
+        /// Headers announcing what the server supports instead
+        supported: Vec<HttpHeader>,
+    },
+
+    /// The client sent more requests than its rate limit allows
+    #[error("Too Many Requests")]
+    TooManyRequests {
+        /// Seconds until the client may send another request
+        retry_after: u64,
+        /// Headers describing the rate limit the client ran into
+        headers: Vec<HttpHeader>,
+    },
+
+    /// A server the request was passed on to sent an invalid response
+    #[error("Bad Gateway")]
+    BadGateway(String),
+
+    /// A server the request was passed on to did not respond in time
+    #[error("Gateway Timeout")]
+    GatewayTimeout(String),
+
+    /// The server could not handle the request in time
+    #[error("Service Unavailable")]
+    ServiceUnavailable(String),
+
+    /// The response needs a version of HTTP other than the one the request was sent with
+    #[error("HTTP Version Not Supported")]
+    HttpVersionNotSupported(String),
+
+    /// A handler needs an extension of the request that no handler in front of it attached, such as the claims
+    /// of a token on a route that is not behind a matching `Jwt` handler
+    #[error("Missing Extension")]
+    MissingExtension(String),
+
     /// Something went wrong on the server side
     #[error("Internal Server Error: Failed to parse request")]
     RequestParseError(#[from] FromUtf8Error),
@@ -36,26 +111,95 @@ impl From<serde_json::Error> for Error {
     }
 }
 
+impl From<serde_yaml::Error> for Error {
+    fn from(value: serde_yaml::Error) -> Self {
+        Error::BadRequest(value.to_string())
+    }
+}
+
+impl From<xml_serde::Error> for Error {
+    fn from(value: xml_serde::Error) -> Self {
+        Error::BadRequest(value.to_string())
+    }
+}
+
+impl From<Http2Error> for Error {
+    fn from(error: Http2Error) -> Self {
+        match error {
+            Http2Error::Io(error) => Error::IoError(error),
+            error => Error::BadRequest(error.to_string()),
+        }
+    }
+}
+
 impl From<Error> for Response<Box<dyn Body>> {
     fn from(error: Error) -> Self {
         let body = match &error {
             Error::IoError(error) => error.to_string(),
-            Error::BadRequest(error) => error.clone(),
+            Error::BadRequest(error)
+            | Error::Forbidden(error)
+            | Error::RequestTimeout(error)
+            | Error::PayloadTooLarge(error)
+            | Error::URITooLong(error)
+            | Error::RequestHeaderFieldsTooLarge(error)
+            | Error::BadGateway(error)
+            | Error::ServiceUnavailable(error)
+            | Error::HttpVersionNotSupported(error)
+            | Error::MissingExtension(error)
+            | Error::GatewayTimeout(error) => error.clone(),
             Error::RequestParseError(error) => error.to_string(),
             Error::NotFound { method, path } => {
                 format!("The path {method:?} {path} was not found")
             }
+            Error::MisdirectedRequest { host: Some(host) } => {
+                format!("The host {host} is not served here")
+            }
+            Error::MisdirectedRequest { host: None } => "Missing Host header".to_string(),
+            Error::UpgradeRequired { .. } => "Unsupported protocol upgrade".to_string(),
+            Error::Unauthorized { .. } => "Missing or invalid credentials".to_string(),
+            Error::TooManyRequests { retry_after, .. } => {
+                format!("Too many requests, retry after {retry_after} seconds")
+            }
         };
 
-        let headers = vec![
-            HttpHeader::ContentType("text/plain".to_string()),
-            HttpHeader::Authorization("Bearer token".to_string()),
-        ];
+        let mut headers = vec![HttpHeader::ContentType("text/plain".to_string())];
+
+        if let Error::UpgradeRequired { supported } = &error {
+            headers.extend(supported.iter().cloned());
+        }
+
+        if let Error::Unauthorized { challenges } = &error {
+            headers.extend(challenges.iter().cloned());
+        }
+
+        if let Error::TooManyRequests {
+            retry_after,
+            headers: limit,
+        } = &error
+        {
+            headers.push(HttpHeader::RetryAfter(retry_after.to_string()));
+            headers.extend(limit.iter().cloned());
+        }
 
         let status = match &error {
-            Error::RequestParseError(_) | Error::IoError(_) => HttpStatus::InternalServerError,
+            Error::RequestParseError(_) | Error::IoError(_) | Error::MissingExtension(_) => {
+                HttpStatus::InternalServerError
+            }
             Error::BadRequest(_) => HttpStatus::BadRequest,
+            Error::Unauthorized { .. } => HttpStatus::Unauthorized,
+            Error::Forbidden(_) => HttpStatus::Forbidden,
+            Error::RequestTimeout(_) => HttpStatus::RequestTimeout,
+            Error::PayloadTooLarge(_) => HttpStatus::PayloadTooLarge,
+            Error::URITooLong(_) => HttpStatus::URITooLong,
+            Error::RequestHeaderFieldsTooLarge(_) => HttpStatus::RequestHeaderFieldsTooLarge,
+            Error::ServiceUnavailable(_) => HttpStatus::ServiceUnavailable,
+            Error::BadGateway(_) => HttpStatus::BadGateway,
+            Error::GatewayTimeout(_) => HttpStatus::GatewayTimeout,
+            Error::HttpVersionNotSupported(_) => HttpStatus::HttpVersionNotSupported,
             Error::NotFound { .. } => HttpStatus::NotFound,
+            Error::MisdirectedRequest { .. } => HttpStatus::MisdirectedRequest,
+            Error::UpgradeRequired { .. } => HttpStatus::UpgradeRequired,
+            Error::TooManyRequests { .. } => HttpStatus::TooManyRequests,
         };
 
         Response::new(Box::new(body), headers, status)
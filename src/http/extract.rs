@@ -0,0 +1,240 @@
+use crate::error;
+use crate::http::{error::Error, header::HttpHeader, request::Request};
+use serde::de::DeserializeOwned;
+use std::{collections::HashMap, ops::Deref};
+
+/// Types that can be extracted from an incoming request, used for the arguments of `#[route]` handlers
+pub trait FromRequest: Sized {
+    /// Extracts the value from the request
+    /// # Errors
+    /// - If the request does not contain a valid value, which results in a 400 Bad Request,
+    ///   or 401 Unauthorized for missing credentials
+    fn from_request(request: &Request) -> Result<Self, Error>;
+}
+
+/// Parameters captured from `{name}` segments of the route pattern.
+///
+/// `T` is either a struct with a field per placeholder, or a single value when the route has exactly one placeholder.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path<T>(pub T);
+
+/// Parameters deserialized from the URL encoded query string
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Query<T>(pub T);
+
+/// A request body deserialized from JSON
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Json<T>(pub T);
+
+/// All headers sent with the request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Headers(pub Vec<HttpHeader>);
+
+impl<T: DeserializeOwned> FromRequest for Path<T> {
+    fn from_request(request: &Request) -> Result<Self, Error> {
+        // Round-trip through the URL encoding so that values such as numbers are parsed from their textual form
+        let encoded = serde_urlencoded::to_string(&request.params)
+            .map_err(|error| error!(BadRequest, error.to_string()))?;
+
+        let error = match serde_urlencoded::from_str(&encoded) {
+            Ok(params) => return Ok(Path(params)),
+            Err(error) => error,
+        };
+
+        if request.params.len() != 1 {
+            return Err(error!(
+                BadRequest,
+                format!("Invalid path parameters: {error}")
+            ));
+        }
+
+        serde_urlencoded::from_str::<HashMap<String, T>>(&encoded)
+            .map_err(|error| error!(BadRequest, format!("Invalid path parameter: {error}")))?
+            .into_values()
+            .next()
+            .map(Path)
+            .ok_or(error!(BadRequest, "Missing path parameter"))
+    }
+}
+
+impl<T: DeserializeOwned> FromRequest for Query<T> {
+    fn from_request(request: &Request) -> Result<Self, Error> {
+        serde_urlencoded::from_str(request.query.as_deref().unwrap_or_default())
+            .map(Query)
+            .map_err(|error| error!(BadRequest, format!("Invalid query string: {error}")))
+    }
+}
+
+impl<T: DeserializeOwned> FromRequest for Json<T> {
+    fn from_request(request: &Request) -> Result<Self, Error> {
+        request.json().map(Json)
+    }
+}
+
+impl FromRequest for Headers {
+    fn from_request(request: &Request) -> Result<Self, Error> {
+        Ok(Headers(request.headers.clone()))
+    }
+}
+
+impl<T: FromRequest> FromRequest for Option<T> {
+    fn from_request(request: &Request) -> Result<Self, Error> {
+        Ok(T::from_request(request).ok())
+    }
+}
+
+macro_rules! deref_impl {
+    ($($extractor:ident<$inner:ident>,)*) => {
+        $(
+            impl<$inner> Deref for $extractor<$inner> {
+                type Target = $inner;
+
+                fn deref(&self) -> &Self::Target {
+                    &self.0
+                }
+            }
+        )*
+    };
+}
+
+deref_impl!(Path<T>, Query<T>, Json<T>,);
+
+impl Deref for Headers {
+    type Target = [HttpHeader];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::http::{
+        header::HttpHeader, method::Method, service, status::HttpStatus, testing::TestClient,
+    };
+    use serde_json::json;
+
+    #[service]
+    mod extractors {
+        use crate::http::{
+            extract::{Headers, Json, Path, Query},
+            get,
+            header::HttpHeader,
+            post,
+            request::Request,
+            response::Response,
+            status::HttpStatus,
+        };
+        use serde::{Deserialize, Serialize};
+
+        #[derive(Deserialize)]
+        struct Page {
+            page: u32,
+            sort: Option<String>,
+        }
+
+        #[derive(Deserialize, Serialize)]
+        struct Item {
+            name: String,
+            quantity: u32,
+        }
+
+        fn text(body: String) -> Response<String> {
+            Response::new(body, vec![], HttpStatus::Ok)
+        }
+
+        #[get("/items/{id}")]
+        fn by_id(Path(id): Path<u32>) -> Response<String> {
+            text(format!("item {id}"))
+        }
+
+        #[get("/items")]
+        fn items(Query(page): Query<Page>) -> Response<String> {
+            text(format!(
+                "page {} by {}",
+                page.page,
+                page.sort.unwrap_or_default()
+            ))
+        }
+
+        #[get("/search")]
+        fn search(query: Option<Query<Page>>) -> Response<String> {
+            text(format!("{:?}", query.map(|Query(page)| page.page)))
+        }
+
+        #[post("/items")]
+        fn create(Json(item): Json<Item>) -> Response<String> {
+            Response::Created().json(Item {
+                quantity: item.quantity * 2,
+                ..item
+            })
+        }
+
+        #[get("/agent")]
+        fn agent(headers: Headers, request: &Request) -> Response<String> {
+            let value = headers
+                .iter()
+                .find(|header| header.name() == "User-Agent")
+                .map(HttpHeader::value)
+                .unwrap_or_default();
+
+            text(format!("{value} on {}", request.path))
+        }
+    }
+
+    fn client() -> TestClient {
+        TestClient::new(extractors::router())
+    }
+
+    #[test]
+    fn path_parameters_are_deserialized() {
+        client().get("/items/12").assert_body("item 12");
+        client()
+            .get("/items/twelve")
+            .assert_status(HttpStatus::BadRequest);
+    }
+
+    #[test]
+    fn query_strings_are_deserialized() {
+        client()
+            .get("/items?page=2&sort=name")
+            .assert_body("page 2 by name");
+        client().get("/items?page=3").assert_body("page 3 by ");
+        client().get("/items").assert_status(HttpStatus::BadRequest);
+        client()
+            .get("/items?page=-1")
+            .assert_status(HttpStatus::BadRequest);
+    }
+
+    #[test]
+    fn optional_extractors_never_fail() {
+        client().get("/search?page=4").assert_body("Some(4)");
+        client().get("/search?page=x").assert_body("None");
+    }
+
+    #[test]
+    fn json_bodies_are_deserialized() {
+        client()
+            .post("/items", r#"{"name":"apple","quantity":3}"#)
+            .assert_status(HttpStatus::Created)
+            .assert_json(&json!({"name": "apple", "quantity": 6}));
+        client()
+            .post("/items", r#"{"name":"apple"}"#)
+            .assert_status(HttpStatus::BadRequest);
+        client()
+            .post("/items", "not json")
+            .assert_status(HttpStatus::BadRequest);
+    }
+
+    #[test]
+    fn headers_and_the_request_itself() {
+        client()
+            .request(
+                Method::Get,
+                "/agent",
+                vec![HttpHeader::UserAgent("tests".to_string())],
+                None,
+            )
+            .assert_body("tests on /agent");
+    }
+}
@@ -3,29 +3,32 @@ use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 
 macro_rules! header_impl {
-    ($($rfc_section:literal $name:literal -> $variant:ident,)*) => {
+    ($($reference:literal $name:literal -> $variant:ident,)*) => {
         /// Representation of a HTTP Header
         #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
         #[serde(rename_all = "kebab-case")]
         pub enum HttpHeader {
             $(
-                /// HTTP `
-                #[doc = $name]
-                ///` header As defined in RFC 2616
-                #[doc = stringify!(Section $name)]
+                #[doc = concat!("HTTP `", $name, "` header, ", $reference)]
                 $variant(String),
             )*
+
+            /// Any other header, with its name as it was received
+            Other(String, String),
         }
 
         impl HttpHeader {
-            /// Creates a new HTTP header with the given name and value
+            /// Creates a new HTTP header with the given name and value, names are case-insensitive.
+            ///
+            /// Headers without a variant of their own are kept as [`HttpHeader::Other`]
             /// # Errors
-            /// - Invalid header name provided
+            /// - If the name is not a token, RFC 9110 Section 5.1
             // @todo - Add Validation for Header Values
             pub fn new(name: &str, value: &str) -> Result<HttpHeader> {
                 match name {
-                    $($name => Ok(HttpHeader::$variant(value.to_string())),)*
-                    header => Err(anyhow!("Unknown header '{header}'")),
+                    $(_ if name.eq_ignore_ascii_case($name) => Ok(HttpHeader::$variant(value.to_string())),)*
+                    _ if is_token(name) => Ok(HttpHeader::Other(name.to_string(), value.to_string())),
+                    header => Err(anyhow!("Invalid header name '{header}'")),
                 }
             }
 
@@ -34,6 +37,7 @@ macro_rules! header_impl {
             pub fn name(&self) -> String {
                 match self {
                     $($crate::http::header::HttpHeader::$variant(_) => $name.to_string(),)*
+                    $crate::http::header::HttpHeader::Other(name, _) => name.to_string(),
                 }
             }
 
@@ -42,29 +46,65 @@ macro_rules! header_impl {
             pub fn value(&self) -> String {
                 match self {
                     $($crate::http::header::HttpHeader::$variant(value) => value.to_string(),)*
+                    $crate::http::header::HttpHeader::Other(_, value) => value.to_string(),
                 }
             }
         }
     };
 }
 
+/// Checks that a header name only has the characters RFC 9110 Section 5.6.2 allows in a token
+fn is_token(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&byte))
+}
+
 header_impl!(
-    14.1 "Accept" -> Accept,
-    14.2 "Accept-Charset" -> AcceptCharset,
-    14.3 "Accept-Encoding" -> AcceptEncoding,
-    14.4 "Accept-Language" -> AcceptLanguage,
-    14.5 "Accept-Ranges" -> AcceptRanges,
-    14.6 "Age" -> Age,
-    14.9 "Cache-Control" -> CacheControl,
-    14.8 "Authorization" -> Authorization,
-    14.10 "Connection" -> Connection,
-    14.13 "Content-Length" -> ContentLength,
-    14.16 "Content-Range" -> ContentRange,
-    14.17 "Content-Type" -> ContentType,
-    14.18 "Date" -> Date,
-    14.23 "Host" -> Host,
-    14.32 "Pragma" -> Pragma,
-    14.43 "User-Agent" -> UserAgent,
+    "as defined in RFC 2616 Section 14.1" "Accept" -> Accept,
+    "as defined in RFC 2616 Section 14.2" "Accept-Charset" -> AcceptCharset,
+    "as defined in RFC 2616 Section 14.3" "Accept-Encoding" -> AcceptEncoding,
+    "as defined in RFC 2616 Section 14.4" "Accept-Language" -> AcceptLanguage,
+    "as defined in RFC 2616 Section 14.5" "Accept-Ranges" -> AcceptRanges,
+    "as defined in RFC 2616 Section 14.6" "Age" -> Age,
+    "as defined in RFC 2616 Section 14.9" "Cache-Control" -> CacheControl,
+    "as defined in RFC 2616 Section 14.8" "Authorization" -> Authorization,
+    "as defined in RFC 2616 Section 14.10" "Connection" -> Connection,
+    "as defined in RFC 2616 Section 14.13" "Content-Length" -> ContentLength,
+    "as defined in RFC 2616 Section 14.16" "Content-Range" -> ContentRange,
+    "as defined in RFC 2616 Section 14.17" "Content-Type" -> ContentType,
+    "as defined in RFC 2616 Section 14.18" "Date" -> Date,
+    "as defined in RFC 2616 Section 14.23" "Host" -> Host,
+    "as defined in RFC 2616 Section 14.30" "Location" -> Location,
+    "as defined in RFC 2616 Section 14.32" "Pragma" -> Pragma,
+    "as defined in RFC 2616 Section 14.33" "Proxy-Authenticate" -> ProxyAuthenticate,
+    "as defined in RFC 2616 Section 14.34" "Proxy-Authorization" -> ProxyAuthorization,
+    "as defined in RFC 2616 Section 14.37" "Retry-After" -> RetryAfter,
+    "as defined in RFC 2616 Section 14.39" "TE" -> Te,
+    "as defined in RFC 2616 Section 14.40" "Trailer" -> Trailer,
+    "as defined in RFC 2616 Section 14.41" "Transfer-Encoding" -> TransferEncoding,
+    "as defined in RFC 2616 Section 14.42" "Upgrade" -> Upgrade,
+    "as defined in RFC 2616 Section 14.43" "User-Agent" -> UserAgent,
+    "as defined in RFC 2616 Section 14.44" "Vary" -> Vary,
+    "as defined in RFC 2616 Section 14.45" "Via" -> Via,
+    "as defined in RFC 2616 Section 14.47" "WWW-Authenticate" -> WwwAuthenticate,
+    "as defined in RFC 2068 Section 19.7.1.1" "Keep-Alive" -> KeepAlive,
+    "as defined in RFC 7540 Section 3.2.1" "HTTP2-Settings" -> Http2Settings,
+    "as defined in RFC 7239 Section 4" "Forwarded" -> Forwarded,
+    "a de facto standard that RFC 7239 replaces with `Forwarded`" "X-Forwarded-For" -> XForwardedFor,
+    "a de facto standard that RFC 7239 replaces with `Forwarded`" "X-Forwarded-Host" -> XForwardedHost,
+    "a de facto standard that RFC 7239 replaces with `Forwarded`" "X-Forwarded-Proto" -> XForwardedProto,
+    "a common convention for API keys that no RFC defines" "X-API-Key" -> XApiKey,
+    "from the IETF draft RateLimit header fields for HTTP" "RateLimit-Limit" -> RateLimitLimit,
+    "from the IETF draft RateLimit header fields for HTTP" "RateLimit-Remaining" -> RateLimitRemaining,
+    "from the IETF draft RateLimit header fields for HTTP" "RateLimit-Reset" -> RateLimitReset,
+    "from the Server-sent events section of the HTML Living Standard" "Last-Event-ID" -> LastEventId,
+    "as defined in RFC 6455 Section 11.3.1" "Sec-WebSocket-Key" -> SecWebSocketKey,
+    "as defined in RFC 6455 Section 11.3.2" "Sec-WebSocket-Extensions" -> SecWebSocketExtensions,
+    "as defined in RFC 6455 Section 11.3.3" "Sec-WebSocket-Accept" -> SecWebSocketAccept,
+    "as defined in RFC 6455 Section 11.3.4" "Sec-WebSocket-Protocol" -> SecWebSocketProtocol,
+    "as defined in RFC 6455 Section 11.3.5" "Sec-WebSocket-Version" -> SecWebSocketVersion,
 );
 
 impl Display for HttpHeader {
@@ -75,3 +115,35 @@ impl Display for HttpHeader {
         write!(f, "{header_name}: {value}")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::HttpHeader;
+
+    #[test]
+    fn names_are_matched_case_insensitively() {
+        let header = HttpHeader::new("content-type", "text/plain").unwrap();
+
+        assert_eq!(header, HttpHeader::ContentType("text/plain".to_string()));
+        assert_eq!(header.name(), "Content-Type");
+    }
+
+    #[test]
+    fn unknown_headers_are_kept() {
+        let header = HttpHeader::new("X-Client-Id", "42").unwrap();
+
+        assert_eq!(
+            header,
+            HttpHeader::Other("X-Client-Id".to_string(), "42".to_string())
+        );
+        assert_eq!(header.name(), "X-Client-Id");
+        assert_eq!(header.value(), "42");
+    }
+
+    #[test]
+    fn invalid_names_are_rejected() {
+        assert!(HttpHeader::new("Bad Name", "value").is_err());
+        assert!(HttpHeader::new("", "value").is_err());
+        assert!(HttpHeader::new("X-Caf\u{e9}", "value").is_err());
+    }
+}
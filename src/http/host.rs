@@ -0,0 +1,256 @@
+use crate::http::{
+    error::Error,
+    header::HttpHeader,
+    request::Request,
+    response::{Body, Response},
+    router::{RequestHandler, Router},
+};
+use std::collections::HashMap;
+
+/// Routes requests to different [`Router`]s depending on their `Host` header.
+///
+/// Hosts are matched exactly first, then against wildcard patterns such as `*.example.com` (longest suffix wins),
+/// and finally fall back to the default router. Requests for any other host are answered with 421 Misdirected Request.
+pub struct VirtualHosts<H: ?Sized = dyn RequestHandler> {
+    exact: HashMap<String, Router<H>>,
+    wildcard: Vec<(String, Router<H>)>,
+    default: Option<Router<H>>,
+}
+
+impl<H: ?Sized> VirtualHosts<H> {
+    /// Creates an empty set of virtual hosts without a default router
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            exact: HashMap::new(),
+            wildcard: vec![],
+            default: None,
+        }
+    }
+
+    /// Serves the given host with the router, `*.example.com` matches every subdomain of `example.com`
+    #[must_use]
+    pub fn host(mut self, pattern: &str, router: Router<H>) -> Self {
+        let pattern = normalize(pattern);
+
+        if let Some(suffix) = pattern.strip_prefix('*') {
+            self.wildcard.push((suffix.to_string(), router));
+            // Keep the most specific patterns first
+            self.wildcard
+                .sort_by_key(|(suffix, _)| std::cmp::Reverse(suffix.len()));
+        } else {
+            self.exact.insert(pattern, router);
+        }
+
+        self
+    }
+
+    /// Serves every host that no other pattern matches, as well as requests without a `Host` header
+    #[must_use]
+    pub fn fallback(mut self, router: Router<H>) -> Self {
+        self.default = Some(router);
+
+        self
+    }
+
+    /// Get the designated router for a given `Host` header value
+    #[must_use]
+    pub fn select(&self, host: Option<&str>) -> Option<&Router<H>> {
+        let Some(host) = host.map(normalize) else {
+            return self.default.as_ref();
+        };
+
+        self.exact
+            .get(&host)
+            .or_else(|| {
+                self.wildcard
+                    .iter()
+                    .find(|(suffix, _)| {
+                        host.len() > suffix.len() && host.ends_with(suffix.as_str())
+                    })
+                    .map(|(_, router)| router)
+            })
+            .or(self.default.as_ref())
+    }
+
+    /// Finds the handler of a request among the routes of its host, see [`Router::route`]
+    /// # Errors
+    /// - [`Error::MisdirectedRequest`] if no router serves the host of the request
+    /// - [`Error::NotFound`] if no route of the host matches the request
+    pub fn route(&self, request: Request) -> Result<(&H, Request), Error> {
+        let host = request
+            .headers
+            .iter()
+            .find(|header| matches!(header, HttpHeader::Host(_)))
+            .map(HttpHeader::value);
+
+        match self.select(host.as_deref()) {
+            Some(router) => router.route(request),
+            None => Err(Error::MisdirectedRequest { host }),
+        }
+    }
+
+    /// Converts the router of every host into one for another kind of handler
+    #[cfg(feature = "async")]
+    pub(crate) fn map<G: ?Sized>(
+        self,
+        convert: impl Fn(Router<H>) -> Router<G>,
+    ) -> VirtualHosts<G> {
+        VirtualHosts {
+            exact: self
+                .exact
+                .into_iter()
+                .map(|(host, router)| (host, convert(router)))
+                .collect(),
+            wildcard: self
+                .wildcard
+                .into_iter()
+                .map(|(suffix, router)| (suffix, convert(router)))
+                .collect(),
+            default: self.default.map(convert),
+        }
+    }
+}
+
+impl VirtualHosts {
+    /// Handles a request with the router of its host, answering with 421 Misdirected Request when there is none
+    pub fn dispatch(&self, request: Request) -> Response<Box<dyn Body>> {
+        match self
+            .route(request)
+            .and_then(|(handler, request)| handler.handle(request))
+        {
+            Ok(response) => response,
+            Err(error) => error.into(),
+        }
+    }
+}
+
+impl<H: ?Sized> Default for VirtualHosts<H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<H: ?Sized> From<Router<H>> for VirtualHosts<H> {
+    fn from(router: Router<H>) -> Self {
+        Self::new().fallback(router)
+    }
+}
+
+/// Lowercases a host and strips its port and trailing dot
+fn normalize(host: &str) -> String {
+    let host = host.trim();
+
+    // Bracketed IPv6 literals contain colons of their own
+    let host = match host.rfind(':') {
+        Some(index) if !host[index..].contains(']') => &host[..index],
+        _ => host,
+    };
+
+    host.trim_end_matches('.').to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::VirtualHosts;
+    use crate::http::{
+        error::Error,
+        header::HttpHeader,
+        method::Method,
+        request::Request,
+        response::{Body, Response},
+        router::{routes, RequestHandler, Router},
+        status::HttpStatus,
+        testing::TestClient,
+    };
+
+    /// Answers with the name of the router it belongs to
+    struct Named(&'static str);
+
+    impl RequestHandler for Named {
+        fn handle(&self, _request: Request) -> Result<Response<Box<dyn Body>>, Error> {
+            Ok(Response::new(
+                Box::new(self.0.to_string()) as Box<dyn Body>,
+                vec![],
+                HttpStatus::Ok,
+            ))
+        }
+    }
+
+    fn router(name: &'static str) -> Router {
+        let handler = Named(name);
+
+        routes!(Get "/" -> handler,)
+    }
+
+    fn served_by(client: &TestClient, host: Option<&str>) -> String {
+        let headers = host
+            .map(|host| vec![HttpHeader::Host(host.to_string())])
+            .unwrap_or_default();
+        let response = client.request(Method::Get, "/", headers, None);
+
+        response.assert_status(HttpStatus::Ok);
+        response.text()
+    }
+
+    #[test]
+    fn hosts_are_matched_exactly_then_by_wildcard() {
+        let client = TestClient::new(
+            VirtualHosts::new()
+                .host("example.com", router("apex"))
+                .host("*.example.com", router("subdomain"))
+                .host("*.api.example.com", router("api"))
+                .host("[::1]", router("ipv6")),
+        );
+
+        assert_eq!(served_by(&client, Some("example.com")), "apex");
+        assert_eq!(served_by(&client, Some("www.example.com")), "subdomain");
+        assert_eq!(served_by(&client, Some("v1.api.example.com")), "api");
+        assert_eq!(served_by(&client, Some("[::1]:8080")), "ipv6");
+    }
+
+    #[test]
+    fn hosts_are_normalized() {
+        let client = TestClient::new(VirtualHosts::new().host("Example.COM", router("apex")));
+
+        for host in [
+            "example.com",
+            "EXAMPLE.com",
+            "example.com:8080",
+            "example.com.",
+        ] {
+            assert_eq!(served_by(&client, Some(host)), "apex", "{host}");
+        }
+    }
+
+    #[test]
+    fn unmatched_hosts_fall_back_to_the_default() {
+        let client = TestClient::new(
+            VirtualHosts::new()
+                .host("*.example.com", router("subdomain"))
+                .fallback(router("default")),
+        );
+
+        // A wildcard needs a label of its own in front of the domain
+        assert_eq!(served_by(&client, Some("example.com")), "default");
+        assert_eq!(served_by(&client, Some("other.org")), "default");
+        assert_eq!(served_by(&client, None), "default");
+    }
+
+    #[test]
+    fn unserved_hosts_are_misdirected() {
+        let client = TestClient::new(VirtualHosts::new().host("example.com", router("apex")));
+
+        client
+            .request(
+                Method::Get,
+                "/",
+                vec![HttpHeader::Host("other.org".to_string())],
+                None,
+            )
+            .assert_status(HttpStatus::MisdirectedRequest);
+        client
+            .get("/")
+            .assert_status(HttpStatus::MisdirectedRequest);
+    }
+}
@@ -0,0 +1,1885 @@
+use super::{
+    error::{connection_error, stream_error, ErrorCode, Http2Error},
+    frame::{Frame, FrameHeader},
+    hpack::{Decoder, Encoder, HeaderField},
+    Settings, PREFACE,
+};
+use crate::http::{
+    error::Error,
+    header::HttpHeader,
+    host::VirtualHosts,
+    limits::{Deadline, Limits, Pace, Timeouts},
+    listener::Connection,
+    method::Method,
+    request::{Extensions, Request},
+    response::{Body, Response},
+    server,
+    status::HttpStatus,
+};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    io::{ErrorKind, Read},
+    panic::AssertUnwindSafe,
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// How often the connection checks for finished handlers while it waits for frames
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// Largest flow control window, RFC 9113 Section 6.9.1
+const MAX_WINDOW: i64 = 0x7fff_ffff;
+
+/// Window of the connection before any `WINDOW_UPDATE`
+const DEFAULT_WINDOW: i64 = 65_535;
+
+/// Streams the client may reset per [`RESET_PERIOD`] before the connection is closed as a rapid reset
+/// attack, CVE-2023-44487
+const MAX_RESETS: u32 = 200;
+
+/// Period over which the resets of the client are counted
+const RESET_PERIOD: Duration = Duration::from_secs(30);
+
+/// Headers that only apply to a single HTTP/1.1 hop, which HTTP/2 forbids
+const CONNECTION_SPECIFIC: [&str; 5] = [
+    "connection",
+    "keep-alive",
+    "proxy-connection",
+    "transfer-encoding",
+    "upgrade",
+];
+
+/// A response produced by a handler thread for a stream, along with whether it answers a `HEAD` request
+type Finished = (u32, bool, Response<Box<dyn Body>>);
+
+/// A request/response exchange on the connection
+#[derive(Debug)]
+struct Stream {
+    headers: Vec<HeaderField>,
+    body: Vec<u8>,
+    /// Whether the client has sent everything it is going to send
+    remote_closed: bool,
+    send_window: i64,
+    receive_window: i64,
+    /// Response body being sent, along with how much of it has been sent so far
+    outgoing: Option<(Vec<u8>, usize)>,
+    /// How long the client has left to send the body
+    pace: Pace,
+    /// Whether the request was answered before it was complete, because its body exceeded the limit or did not
+    /// arrive in time, after which the rest of it is discarded
+    refused: bool,
+}
+
+/// A header block spread over a `HEADERS` frame and `CONTINUATION` frames
+#[derive(Debug)]
+struct PendingHeaders {
+    stream: u32,
+    block: Vec<u8>,
+    end_stream: bool,
+    /// How long the client has left to finish the block
+    pace: Pace,
+}
+
+impl PendingHeaders {
+    /// Starts a block with the fragment of a `HEADERS` frame, giving the client the header timeout to finish it
+    fn new(stream: u32, block: Vec<u8>, end_stream: bool, timeouts: &Timeouts) -> Self {
+        let mut pace = Pace::new(Instant::now(), timeouts.header_read, timeouts.min_rate);
+        pace.receive(block.len());
+
+        Self {
+            stream,
+            block,
+            end_stream,
+            pace,
+        }
+    }
+}
+
+/// What waiting for the next frame produced
+enum Incoming {
+    Frame(Frame),
+    Pending,
+    Closed,
+}
+
+/// The server side of an HTTP/2 connection.
+///
+/// Every request is handled on its own thread, so slow handlers don't hold up the other streams,
+/// while frames are read and written on the thread owning the connection.
+pub struct Http2Connection<C: Connection> {
+    connection: C,
+    hosts: Arc<VirtualHosts>,
+    timeouts: Timeouts,
+    limits: Limits,
+    local: Settings,
+    remote: Settings,
+    decoder: Decoder,
+    encoder: Encoder,
+    streams: BTreeMap<u32, Stream>,
+    last_stream: u32,
+    send_window: i64,
+    receive_window: i64,
+    pending_headers: Option<PendingHeaders>,
+    sender: Sender<Finished>,
+    receiver: Receiver<Finished>,
+    /// Streams whose handlers are still running, including those the client reset in the meantime
+    handling: HashSet<u32>,
+    /// Streams the client reset since `resets_since`
+    resets: u32,
+    resets_since: Instant,
+    /// When the last stream was answered, after which the client has as long to start another one as it has to send
+    /// the headers of a request
+    idle_since: Option<Instant>,
+    input: Vec<u8>,
+    output: Vec<u8>,
+    going_away: bool,
+}
+
+impl<C: Connection> Http2Connection<C> {
+    /// Prepares to serve HTTP/2 on a connection, announcing the given settings and refusing request bodies larger
+    /// than the limits allow.
+    ///
+    /// The timeouts apply to every stream: its header block has to arrive within the header timeout, its body
+    /// within the body timeout, both at the minimum rate, and its handler is given the handler timeout. A
+    /// connection without streams is closed once it stays idle for the header timeout, starting with the preface.
+    #[must_use]
+    pub fn new(
+        connection: C,
+        hosts: Arc<VirtualHosts>,
+        settings: Settings,
+        timeouts: Timeouts,
+        limits: Limits,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel();
+
+        Self {
+            connection,
+            hosts,
+            timeouts,
+            limits,
+            local: settings,
+            remote: Settings::PROTOCOL_DEFAULTS,
+            decoder: Decoder::new(settings.header_table_size as usize),
+            encoder: Encoder::new(),
+            streams: BTreeMap::new(),
+            last_stream: 0,
+            send_window: DEFAULT_WINDOW,
+            receive_window: DEFAULT_WINDOW,
+            pending_headers: None,
+            sender,
+            receiver,
+            handling: HashSet::new(),
+            resets: 0,
+            resets_since: Instant::now(),
+            idle_since: Some(Instant::now()),
+            input: vec![],
+            output: vec![],
+            going_away: false,
+        }
+    }
+
+    /// Serves streams until the client closes the connection, then shuts it down.
+    ///
+    /// `upgrade` is an HTTP/1.1 request that switched to h2c along with the client's `HTTP2-Settings`,
+    /// which is answered on stream 1.
+    /// # Errors
+    /// - If the client broke the protocol, after telling it why with a `GOAWAY` frame
+    /// - If reading from or writing to the connection failed
+    pub fn serve(mut self, upgrade: Option<(Request, Vec<(u16, u32)>)>) -> Result<(), Http2Error> {
+        let result = self.run(upgrade);
+
+        let code = match &result {
+            Err(Http2Error::Connection { code, .. }) => *code,
+            _ => ErrorCode::NoError,
+        };
+
+        self.send(&Frame::GoAway {
+            last_stream: self.last_stream,
+            code,
+        });
+
+        // The client may well be gone already
+        let _ = self.flush();
+        let _ = self.connection.shutdown();
+
+        result
+    }
+
+    fn run(&mut self, upgrade: Option<(Request, Vec<(u16, u32)>)>) -> Result<(), Http2Error> {
+        self.send(&Frame::Settings {
+            ack: false,
+            values: self.local.values(),
+        });
+
+        // The connection window can only be changed with a window update
+        let window = i64::from(self.local.initial_window_size);
+
+        if window > DEFAULT_WINDOW {
+            self.send_window_update(0, window - DEFAULT_WINDOW);
+            self.receive_window = window;
+        }
+
+        if let Some((request, settings)) = upgrade {
+            self.apply_settings(&settings)?;
+
+            self.last_stream = 1;
+            self.streams.insert(1, self.open_stream(vec![], true));
+            self.spawn(1, request);
+        }
+
+        self.flush()?;
+        self.read_preface()?;
+
+        loop {
+            self.collect_finished();
+            self.send_data();
+            self.flush()?;
+
+            let wait = self.enforce_deadlines()?;
+
+            if self.going_away && self.streams.is_empty() && self.handling.is_empty() {
+                return Ok(());
+            }
+
+            let result = match self.receive(wait)? {
+                Incoming::Frame(frame) => self.handle(frame),
+                Incoming::Pending => Ok(()),
+                Incoming::Closed => return Ok(()),
+            };
+
+            match result {
+                Err(Http2Error::Stream { stream, code, .. }) => self.reset(stream, code),
+                result => result?,
+            }
+        }
+    }
+
+    fn read_preface(&mut self) -> Result<(), Http2Error> {
+        let mut buffer = [0; PREFACE.len()];
+
+        let mut reader = Deadline::new(
+            &mut self.connection,
+            self.idle_since.unwrap_or_else(Instant::now),
+            self.timeouts.header_read,
+            self.timeouts.min_rate,
+        );
+
+        while self.input.len() < PREFACE.len() {
+            let read_byte_count = reader.read(&mut buffer)?;
+
+            if read_byte_count == 0 {
+                return Err(connection_error(
+                    ErrorCode::ProtocolError,
+                    "connection closed before the preface",
+                ));
+            }
+
+            self.input.extend(&buffer[..read_byte_count]);
+        }
+
+        if !self.input.starts_with(PREFACE) {
+            return Err(connection_error(
+                ErrorCode::ProtocolError,
+                "invalid connection preface",
+            ));
+        }
+
+        self.input.drain(..PREFACE.len());
+
+        Ok(())
+    }
+
+    /// Answers streams whose requests did not arrive in time with 408 Request Timeout, and closes the connection
+    /// once it stayed idle for too long. Returns how long to wait for frames before checking again.
+    /// # Errors
+    /// - If a header block was not finished in time, which cannot be abandoned without breaking header compression
+    fn enforce_deadlines(&mut self) -> Result<Option<Duration>, Http2Error> {
+        if let Some(pending) = &self.pending_headers {
+            return Ok(Some(pending.pace.remaining()?));
+        }
+
+        let mut wait: Option<Duration> = None;
+        let mut expired = vec![];
+
+        for (&id, stream) in &self.streams {
+            if stream.remote_closed || stream.refused {
+                continue;
+            }
+
+            match stream.pace.remaining() {
+                Ok(remaining) => wait = Some(wait.map_or(remaining, |wait| wait.min(remaining))),
+                Err(_) => expired.push(id),
+            }
+        }
+
+        for id in expired {
+            if let Some(stream) = self.streams.get_mut(&id) {
+                stream.refused = true;
+                stream.body = vec![];
+            }
+
+            self.refuse(
+                id,
+                Error::RequestTimeout("The request was not received in time".to_string()),
+            );
+        }
+
+        if !self.streams.is_empty() || !self.handling.is_empty() {
+            self.idle_since = None;
+
+            return Ok(wait);
+        }
+
+        let idle_since = *self.idle_since.get_or_insert_with(Instant::now);
+        let remaining =
+            (idle_since + self.timeouts.header_read).saturating_duration_since(Instant::now());
+
+        if remaining.is_zero() {
+            self.going_away = true;
+        }
+
+        Ok(Some(remaining))
+    }
+
+    /// Reads the next frame, giving up once `wait` has passed or after a short while when handlers are running so
+    /// their responses go out
+    fn receive(&mut self, wait: Option<Duration>) -> Result<Incoming, Http2Error> {
+        let mut buffer = [0; 16_384];
+        let mut read = false;
+
+        loop {
+            if let Some(header) = self.input.first_chunk::<{ FrameHeader::LENGTH }>() {
+                let header = FrameHeader::parse(*header);
+
+                if header.length > self.local.max_frame_size {
+                    return Err(connection_error(
+                        ErrorCode::FrameSizeError,
+                        "frame larger than SETTINGS_MAX_FRAME_SIZE",
+                    ));
+                }
+
+                let end = FrameHeader::LENGTH + header.length as usize;
+
+                if self.input.len() >= end {
+                    let frame = Frame::parse(header, &self.input[FrameHeader::LENGTH..end]);
+
+                    self.input.drain(..end);
+
+                    return match frame {
+                        Ok(frame) => Ok(Incoming::Frame(frame)),
+                        Err(Http2Error::Stream { stream, code, .. }) => {
+                            self.reset(stream, code);
+                            Ok(Incoming::Pending)
+                        }
+                        Err(error) => Err(error),
+                    };
+                }
+            }
+
+            // Deadlines are checked again before waiting for the rest of a frame, so trickling it in doesn't evade them
+            if read {
+                return Ok(Incoming::Pending);
+            }
+
+            let poll = (!self.handling.is_empty()).then_some(POLL_INTERVAL);
+            let timeout = match (wait, poll) {
+                (Some(wait), Some(poll)) => Some(wait.min(poll)),
+                (wait, poll) => wait.or(poll),
+            };
+
+            // A timeout of zero would wait indefinitely
+            self.connection
+                .set_read_timeout(timeout.map(|timeout| timeout.max(Duration::from_millis(1))))?;
+
+            match self.connection.read(&mut buffer) {
+                Ok(0) => return Ok(Incoming::Closed),
+                Ok(read_byte_count) => {
+                    self.input.extend(&buffer[..read_byte_count]);
+                    read = true;
+                }
+                Err(error)
+                    if matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) =>
+                {
+                    return Ok(Incoming::Pending);
+                }
+                Err(error) if error.kind() == ErrorKind::Interrupted => {}
+                // Clients commonly hang up without closing TLS cleanly once they are done
+                Err(error)
+                    if matches!(
+                        error.kind(),
+                        ErrorKind::UnexpectedEof | ErrorKind::ConnectionReset
+                    ) =>
+                {
+                    return Ok(Incoming::Closed);
+                }
+                Err(error) => return Err(error.into()),
+            }
+        }
+    }
+
+    fn handle(&mut self, frame: Frame) -> Result<(), Http2Error> {
+        // A header block may not be interleaved with any other frame
+        if let Some(pending) = &self.pending_headers {
+            if !matches!(frame, Frame::Continuation { stream, .. } if stream == pending.stream) {
+                return Err(connection_error(
+                    ErrorCode::ProtocolError,
+                    "header block interrupted",
+                ));
+            }
+        }
+
+        match frame {
+            Frame::Data {
+                stream,
+                data,
+                end_stream,
+                flow_controlled,
+            } => self.receive_data(stream, data, end_stream, flow_controlled),
+            Frame::Headers {
+                stream,
+                block,
+                end_stream,
+                end_headers,
+            } => {
+                if end_headers {
+                    return self.receive_headers(stream, &block, end_stream);
+                }
+
+                self.pending_headers = Some(PendingHeaders::new(
+                    stream,
+                    block,
+                    end_stream,
+                    &self.timeouts,
+                ));
+
+                Ok(())
+            }
+            Frame::Continuation {
+                block, end_headers, ..
+            } => {
+                let Some(mut pending) = self.pending_headers.take() else {
+                    return Err(connection_error(
+                        ErrorCode::ProtocolError,
+                        "CONTINUATION without HEADERS",
+                    ));
+                };
+
+                pending.pace.receive(block.len());
+                pending.block.extend(block);
+
+                // Blocks over the header list limit are still buffered and decoded, so that only their stream is
+                // refused, but one twice that size is not worth the memory and closes the connection instead
+                if pending.block.len() / 2 > self.local.max_header_list_size as usize {
+                    return Err(connection_error(
+                        ErrorCode::EnhanceYourCalm,
+                        "header block too large",
+                    ));
+                }
+
+                if end_headers {
+                    return self.receive_headers(
+                        pending.stream,
+                        &pending.block,
+                        pending.end_stream,
+                    );
+                }
+
+                self.pending_headers = Some(pending);
+
+                Ok(())
+            }
+            Frame::RstStream { stream, .. } => {
+                if stream > self.last_stream {
+                    return Err(connection_error(
+                        ErrorCode::ProtocolError,
+                        "RST_STREAM on an idle stream",
+                    ));
+                }
+
+                self.streams.remove(&stream);
+
+                self.count_reset()
+            }
+            Frame::Settings { ack: false, values } => {
+                self.apply_settings(&values)?;
+                self.send(&Frame::Settings {
+                    ack: true,
+                    values: vec![],
+                });
+
+                Ok(())
+            }
+            Frame::Ping { ack: false, data } => {
+                self.send(&Frame::Ping { ack: true, data });
+
+                Ok(())
+            }
+            Frame::GoAway { .. } => {
+                self.going_away = true;
+
+                Ok(())
+            }
+            Frame::WindowUpdate { stream, increment } => {
+                self.receive_window_update(stream, increment)
+            }
+            Frame::PushPromise { .. } => Err(connection_error(
+                ErrorCode::ProtocolError,
+                "clients cannot push",
+            )),
+            Frame::Settings { ack: true, .. }
+            | Frame::Ping { ack: true, .. }
+            | Frame::Priority { .. }
+            | Frame::Unknown { .. } => Ok(()),
+        }
+    }
+
+    fn receive_headers(
+        &mut self,
+        id: u32,
+        block: &[u8],
+        end_stream: bool,
+    ) -> Result<(), Http2Error> {
+        let max_list_size = self.local.max_header_list_size as usize;
+
+        // Every block has to be decoded, even for refused streams, to keep the compression contexts in sync
+        let fields = self.decoder.decode(block, max_list_size)?;
+
+        let too_large = || {
+            Error::RequestHeaderFieldsTooLarge(format!(
+                "The header list exceeds {max_list_size} bytes"
+            ))
+        };
+
+        if let Some(stream) = self.streams.get_mut(&id) {
+            if stream.remote_closed {
+                return Err(stream_error(
+                    id,
+                    ErrorCode::StreamClosed,
+                    "HEADERS on a closed stream",
+                ));
+            }
+
+            if !end_stream {
+                return Err(stream_error(
+                    id,
+                    ErrorCode::ProtocolError,
+                    "trailers must end the stream",
+                ));
+            }
+
+            // Trailers are accepted but not passed on, just like unknown headers
+            stream.remote_closed = true;
+
+            // A refused stream was answered already
+            if stream.refused {
+                return Ok(());
+            }
+
+            if fields.is_none() {
+                stream.refused = true;
+                self.refuse(id, too_large());
+
+                return Ok(());
+            }
+
+            return self.dispatch(id);
+        }
+
+        if id.is_multiple_of(2) || id <= self.last_stream {
+            return Err(connection_error(
+                ErrorCode::ProtocolError,
+                "invalid stream identifier",
+            ));
+        }
+
+        self.last_stream = id;
+
+        // Streams reset by the client count until their handlers finish, or resetting them would make room for
+        // any number of handlers
+        let reset_handlers = self
+            .handling
+            .iter()
+            .filter(|id| !self.streams.contains_key(id))
+            .count();
+
+        if self.streams.len() + reset_handlers >= self.local.max_concurrent_streams as usize {
+            return Err(stream_error(
+                id,
+                ErrorCode::RefusedStream,
+                "too many concurrent streams",
+            ));
+        }
+
+        let (fields, limits) = match fields {
+            Some(fields) => {
+                let limits = self.check_limits(&fields);
+
+                (fields, limits)
+            }
+            None => (vec![], Err(too_large())),
+        };
+
+        let mut stream = self.open_stream(fields, end_stream);
+
+        // Refused before its handler ever sees it, the rest of the request is discarded as it arrives
+        if let Err(error) = limits {
+            stream.refused = true;
+            self.streams.insert(id, stream);
+            self.refuse(id, error);
+
+            return Ok(());
+        }
+
+        self.streams.insert(id, stream);
+
+        if end_stream {
+            return self.dispatch(id);
+        }
+
+        Ok(())
+    }
+
+    /// Checks a header list against the same limits as the head of an HTTP/1.1 request, laid out as one, and the
+    /// announced length of the body
+    fn check_limits(&self, fields: &[HeaderField]) -> Result<(), Error> {
+        let pseudo = |wanted: &str| {
+            fields
+                .iter()
+                .find(|(name, _)| name == wanted)
+                .map_or("", |(_, value)| value.as_str())
+        };
+
+        let mut head = format!("{} {} HTTP/2\r\n", pseudo(":method"), pseudo(":path"));
+
+        for (name, value) in fields.iter().filter(|(name, _)| !name.starts_with(':')) {
+            head.push_str(name);
+            head.push_str(": ");
+            head.push_str(value);
+            head.push_str("\r\n");
+        }
+
+        self.limits.check_head(head.as_bytes())?;
+
+        fields
+            .iter()
+            .filter(|(name, _)| name == "content-length")
+            .filter_map(|(_, value)| value.parse().ok())
+            .try_for_each(|length| self.limits.check_body(length))
+    }
+
+    fn receive_data(
+        &mut self,
+        id: u32,
+        data: Vec<u8>,
+        end_stream: bool,
+        flow_controlled: u32,
+    ) -> Result<(), Http2Error> {
+        let length = i64::from(flow_controlled);
+        let window = i64::from(self.local.initial_window_size);
+
+        self.receive_window -= length;
+
+        if self.receive_window < 0 {
+            return Err(connection_error(
+                ErrorCode::FlowControlError,
+                "connection flow control window exceeded",
+            ));
+        }
+
+        // Hand back credit once half of the window has been used up
+        if self.receive_window <= window / 2 {
+            self.send_window_update(0, window - self.receive_window);
+            self.receive_window = window;
+        }
+
+        let Some(stream) = self.streams.get_mut(&id) else {
+            if id > self.last_stream {
+                return Err(connection_error(
+                    ErrorCode::ProtocolError,
+                    "DATA on an idle stream",
+                ));
+            }
+
+            return Err(stream_error(
+                id,
+                ErrorCode::StreamClosed,
+                "DATA on a closed stream",
+            ));
+        };
+
+        if stream.remote_closed {
+            return Err(stream_error(
+                id,
+                ErrorCode::StreamClosed,
+                "DATA on a closed stream",
+            ));
+        }
+
+        stream.receive_window -= length;
+
+        if stream.receive_window < 0 {
+            return Err(stream_error(
+                id,
+                ErrorCode::FlowControlError,
+                "stream flow control window exceeded",
+            ));
+        }
+
+        if end_stream {
+            stream.remote_closed = true;
+        }
+
+        stream.pace.receive(data.len());
+
+        // A refused body is not read any further, nor given more room in the window
+        if stream.refused {
+            return Ok(());
+        }
+
+        if let Err(error) = self.limits.check_body(stream.body.len() + data.len()) {
+            stream.refused = true;
+            stream.body = vec![];
+
+            self.refuse(id, error);
+
+            return Ok(());
+        }
+
+        stream.body.extend(data);
+
+        if end_stream {
+            return self.dispatch(id);
+        }
+
+        if stream.receive_window <= window / 2 {
+            let increment = window - stream.receive_window;
+            stream.receive_window = window;
+
+            self.send_window_update(id, increment);
+        }
+
+        Ok(())
+    }
+
+    fn receive_window_update(&mut self, id: u32, increment: u32) -> Result<(), Http2Error> {
+        let window = match id {
+            0 => &mut self.send_window,
+            id => match self.streams.get_mut(&id) {
+                Some(stream) => &mut stream.send_window,
+                // Updates may still arrive for streams that were just closed
+                None => return Ok(()),
+            },
+        };
+
+        let overflow = if id == 0 {
+            connection_error(ErrorCode::FlowControlError, "connection window overflow")
+        } else {
+            stream_error(id, ErrorCode::FlowControlError, "stream window overflow")
+        };
+
+        if increment == 0 {
+            return Err(match overflow {
+                Http2Error::Stream { .. } => {
+                    stream_error(id, ErrorCode::ProtocolError, "window update of zero")
+                }
+                _ => connection_error(ErrorCode::ProtocolError, "window update of zero"),
+            });
+        }
+
+        *window += i64::from(increment);
+
+        if *window > MAX_WINDOW {
+            return Err(overflow);
+        }
+
+        Ok(())
+    }
+
+    fn apply_settings(&mut self, values: &[(u16, u32)]) -> Result<(), Http2Error> {
+        let previous = self.remote;
+
+        for &(identifier, value) in values {
+            self.remote.apply(identifier, value)?;
+        }
+
+        if self.remote.header_table_size != previous.header_table_size {
+            self.encoder
+                .set_max_size(self.remote.header_table_size as usize);
+        }
+
+        // A new initial window size applies retroactively to every open stream
+        let delta =
+            i64::from(self.remote.initial_window_size) - i64::from(previous.initial_window_size);
+
+        for stream in self.streams.values_mut() {
+            stream.send_window += delta;
+
+            if stream.send_window > MAX_WINDOW {
+                return Err(connection_error(
+                    ErrorCode::FlowControlError,
+                    "stream window overflow",
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn open_stream(&self, headers: Vec<HeaderField>, remote_closed: bool) -> Stream {
+        Stream {
+            headers,
+            body: vec![],
+            remote_closed,
+            send_window: i64::from(self.remote.initial_window_size),
+            receive_window: i64::from(self.local.initial_window_size),
+            outgoing: None,
+            pace: Pace::new(
+                Instant::now(),
+                self.timeouts.body_read,
+                self.timeouts.min_rate,
+            ),
+            refused: false,
+        }
+    }
+
+    /// Turns a complete stream into a request and hands it to a handler
+    fn dispatch(&mut self, id: u32) -> Result<(), Http2Error> {
+        let Some(stream) = self.streams.get_mut(&id) else {
+            return Ok(());
+        };
+
+        let headers = std::mem::take(&mut stream.headers);
+        let body = std::mem::take(&mut stream.body);
+
+        let mut request = into_request(headers, body)
+            .map_err(|reason| stream_error(id, ErrorCode::ProtocolError, reason))?;
+        request.peer_addr = self.connection.peer_addr();
+
+        self.spawn(id, request);
+
+        Ok(())
+    }
+
+    fn spawn(&mut self, id: u32, request: Request) {
+        let hosts = self.hosts.clone();
+        let sender = self.sender.clone();
+        let head = request.method == Method::Head;
+        let timeout = self.timeouts.handler;
+
+        self.handling.insert(id);
+
+        std::thread::spawn(move || {
+            // A panicking handler still has to answer, or the connection would wait for it forever
+            let mut response = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                server::dispatch(&hosts, request, timeout)
+            }))
+            .unwrap_or_else(|_| {
+                Response::new(Box::new(()), vec![], HttpStatus::InternalServerError)
+            });
+
+            // Bodies that take the connection over, such as event streams, need an HTTP/1.1 connection of their own
+            if response.body.take_over().is_some() {
+                response = Error::HttpVersionNotSupported(
+                    "The response can only be streamed over HTTP/1.1".to_string(),
+                )
+                .into();
+            }
+
+            let _ = sender.send((id, head, response));
+        });
+    }
+
+    /// Answers a stream with an error instead of a handler, in the order responses come in
+    fn refuse(&mut self, id: u32, error: Error) {
+        self.handling.insert(id);
+
+        let _ = self.sender.send((id, false, error.into()));
+    }
+
+    /// Counts a reset of a stream by the client, closing the connection once it resets too many
+    fn count_reset(&mut self) -> Result<(), Http2Error> {
+        if self.resets_since.elapsed() > RESET_PERIOD {
+            self.resets = 0;
+            self.resets_since = Instant::now();
+        }
+
+        self.resets += 1;
+
+        if self.resets > MAX_RESETS {
+            return Err(connection_error(
+                ErrorCode::EnhanceYourCalm,
+                "too many streams reset",
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Starts sending the responses of the handlers that are done
+    fn collect_finished(&mut self) {
+        while let Ok((id, head, response)) = self.receiver.try_recv() {
+            self.handling.remove(&id);
+
+            // The client may have reset the stream in the meantime
+            if !self.streams.contains_key(&id) {
+                continue;
+            }
+
+            let body = if head { vec![] } else { response.body.bytes() };
+
+            let status = u16::from(&response.status).to_string();
+            let headers: Vec<(String, String)> = response
+                .headers
+                .iter()
+                .map(|header| (header.name().to_ascii_lowercase(), header.value()))
+                .filter(|(name, _)| !CONNECTION_SPECIFIC.contains(&name.as_str()))
+                .collect();
+
+            let block = self.encoder.encode(
+                std::iter::once((":status", status.as_str())).chain(
+                    headers
+                        .iter()
+                        .map(|(name, value)| (name.as_str(), value.as_str())),
+                ),
+            );
+
+            self.send_headers(id, &block, body.is_empty());
+
+            if body.is_empty() {
+                self.close(id);
+            } else if let Some(stream) = self.streams.get_mut(&id) {
+                stream.outgoing = Some((body, 0));
+            }
+        }
+    }
+
+    /// Sends as much of the pending response bodies as the flow control windows allow
+    fn send_data(&mut self) {
+        let max_frame_size = self.remote.max_frame_size as usize;
+        let mut finished = vec![];
+
+        for (&id, stream) in &mut self.streams {
+            let Some((body, sent)) = &mut stream.outgoing else {
+                continue;
+            };
+
+            while *sent < body.len() {
+                let window = self.send_window.min(stream.send_window);
+                let Ok(window) = usize::try_from(window) else {
+                    break;
+                };
+
+                let length = (body.len() - *sent).min(max_frame_size).min(window);
+
+                if length == 0 {
+                    break;
+                }
+
+                let end_stream = *sent + length == body.len();
+
+                Frame::Data {
+                    stream: id,
+                    data: body[*sent..*sent + length].to_vec(),
+                    end_stream,
+                    flow_controlled: 0,
+                }
+                .encode(&mut self.output);
+
+                // Frames are far smaller than the largest window, which fits an i64
+                let length_i64 = i64::try_from(length).unwrap_or(MAX_WINDOW);
+
+                *sent += length;
+                self.send_window -= length_i64;
+                stream.send_window -= length_i64;
+
+                if end_stream {
+                    finished.push(id);
+                }
+            }
+        }
+
+        for id in finished {
+            self.close(id);
+        }
+    }
+
+    /// Forgets a stream once its response is complete, asking the client to stop sending a request it has
+    /// not finished, RFC 9113 Section 8.1
+    fn close(&mut self, id: u32) {
+        if self
+            .streams
+            .remove(&id)
+            .is_some_and(|stream| !stream.remote_closed)
+        {
+            self.send(&Frame::RstStream {
+                stream: id,
+                code: ErrorCode::NoError,
+            });
+        }
+    }
+
+    /// Sends a header block, split into `CONTINUATION` frames when it exceeds the peer's frame size
+    fn send_headers(&mut self, id: u32, block: &[u8], end_stream: bool) {
+        let mut fragments = block.chunks(self.remote.max_frame_size as usize).peekable();
+
+        let mut first = true;
+
+        while let Some(fragment) = fragments.next() {
+            let end_headers = fragments.peek().is_none();
+
+            let frame = if first {
+                Frame::Headers {
+                    stream: id,
+                    block: fragment.to_vec(),
+                    end_stream,
+                    end_headers,
+                }
+            } else {
+                Frame::Continuation {
+                    stream: id,
+                    block: fragment.to_vec(),
+                    end_headers,
+                }
+            };
+
+            first = false;
+            self.send(&frame);
+        }
+    }
+
+    fn send_window_update(&mut self, id: u32, increment: i64) {
+        if let Ok(increment) = u32::try_from(increment) {
+            self.send(&Frame::WindowUpdate {
+                stream: id,
+                increment,
+            });
+        }
+    }
+
+    fn reset(&mut self, id: u32, code: ErrorCode) {
+        self.streams.remove(&id);
+        self.send(&Frame::RstStream { stream: id, code });
+    }
+
+    fn send(&mut self, frame: &Frame) {
+        frame.encode(&mut self.output);
+    }
+
+    fn flush(&mut self) -> Result<(), Http2Error> {
+        if !self.output.is_empty() {
+            self.connection.write_all(&self.output)?;
+            self.connection.flush()?;
+            self.output.clear();
+        }
+
+        Ok(())
+    }
+}
+
+/// Builds a request from the decoded header fields and body of a stream, RFC 9113 Section 8.3
+fn into_request(fields: Vec<HeaderField>, body: Vec<u8>) -> Result<Request, &'static str> {
+    let mut method = None;
+    let mut target = None;
+    let mut authority = None;
+    let mut scheme = None;
+    let mut headers = vec![];
+
+    for (name, value) in fields {
+        if let Some(pseudo) = name.strip_prefix(':') {
+            if !headers.is_empty() {
+                return Err("pseudo-header after regular headers");
+            }
+
+            let slot = match pseudo {
+                "method" => &mut method,
+                "path" => &mut target,
+                "authority" => &mut authority,
+                "scheme" => &mut scheme,
+                _ => return Err("unknown pseudo-header"),
+            };
+
+            if slot.replace(value).is_some() {
+                return Err("duplicate pseudo-header");
+            }
+
+            continue;
+        }
+
+        if name.as_bytes().iter().any(u8::is_ascii_uppercase) {
+            return Err("uppercase header name");
+        }
+
+        if CONNECTION_SPECIFIC.contains(&name.as_str()) || (name == "te" && value != "trailers") {
+            return Err("connection-specific header");
+        }
+
+        headers.push(HttpHeader::new(&name, &value).map_err(|_| "invalid header name")?);
+    }
+
+    let method: Method = method
+        .ok_or("missing :method")?
+        .parse()
+        .map_err(|_| "unknown method")?;
+
+    let target = match (target, method) {
+        (Some(target), _) if scheme.is_some() => target,
+        (target, Method::Connect) => target.or(authority.clone()).unwrap_or_default(),
+        _ => return Err("missing :path or :scheme"),
+    };
+
+    // Routing by host works the same as for HTTP/1.1
+    if let Some(authority) = authority {
+        if !headers
+            .iter()
+            .any(|header| matches!(header, HttpHeader::Host(_)))
+        {
+            headers.insert(0, HttpHeader::Host(authority));
+        }
+    }
+
+    let (path, query) = match target.split_once('?') {
+        Some((path, query)) => (path.to_string(), Some(query.to_string())),
+        None => (target, None),
+    };
+
+    Ok(Request {
+        method,
+        path,
+        query,
+        params: HashMap::new(),
+        http_version: "HTTP/2.0".to_string(),
+        headers,
+        body: (!body.is_empty()).then_some(body),
+        peer_addr: None,
+        extensions: Extensions::default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Http2Connection, MAX_RESETS};
+    use crate::http::{
+        host::VirtualHosts,
+        http2::{
+            error::{ErrorCode, Http2Error},
+            frame::{Frame, FrameHeader},
+            hpack::{Decoder, Encoder},
+            Settings, PREFACE,
+        },
+        limits::{Limits, Timeouts},
+        listener::Connection,
+        request::Request,
+        response::Response,
+        route,
+        router::routes,
+        server,
+        sse::{self, EventStream},
+        status::HttpStatus,
+    };
+    use std::{
+        io::{ErrorKind, Read, Write},
+        net::{SocketAddr, TcpListener, TcpStream},
+        sync::Arc,
+        thread::JoinHandle,
+        time::Duration,
+    };
+
+    #[route]
+    fn hello() -> Response<String> {
+        Response::new("hello world".to_string(), vec![], HttpStatus::Ok)
+    }
+
+    #[route]
+    fn echo(request: &Request) -> Response<String> {
+        let body = String::from_utf8_lossy(request.body.as_deref().unwrap_or_default());
+
+        Response::new(body.into_owned(), vec![], HttpStatus::Ok)
+    }
+
+    #[route]
+    fn events(request: &Request) -> Response<EventStream> {
+        sse::stream(request, |_| {
+            panic!("event streams cannot take over HTTP/2 streams")
+        })
+    }
+
+    fn hosts() -> Arc<VirtualHosts> {
+        Arc::new(routes!(Get "/" -> hello, Post "/echo" -> echo, Get "/events" -> events,).into())
+    }
+
+    /// Both ends of a loopback TCP connection, the client's first
+    fn socket_pair() -> (TcpStream, TcpStream) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+
+        client
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+
+        (client, server)
+    }
+
+    /// The client end of a connection served on a thread of its own
+    struct Client {
+        stream: TcpStream,
+        encoder: Encoder,
+        decoder: Decoder,
+        server: JoinHandle<Result<(), Http2Error>>,
+    }
+
+    impl Client {
+        /// Connects with prior knowledge, announcing the client's settings, and waits for the handshake to finish
+        fn connect(settings: Settings, limits: Limits, client_settings: &[(u16, u32)]) -> Self {
+            let (stream, connection) = socket_pair();
+
+            let server = std::thread::spawn(move || {
+                Http2Connection::new(connection, hosts(), settings, Timeouts::default(), limits)
+                    .serve(None)
+            });
+
+            let mut client = Self {
+                stream,
+                encoder: Encoder::new(),
+                decoder: Decoder::new(4096),
+                server,
+            };
+
+            client.stream.write_all(PREFACE).unwrap();
+            client.send(&Frame::Settings {
+                ack: false,
+                values: client_settings.to_vec(),
+            });
+
+            // The server announces its own settings before it acknowledges the client's
+            while !matches!(client.next(), Some(Frame::Settings { ack: true, .. })) {}
+
+            client
+        }
+
+        fn send(&mut self, frame: &Frame) {
+            let mut buffer = vec![];
+            frame.encode(&mut buffer);
+
+            self.stream.write_all(&buffer).unwrap();
+        }
+
+        fn headers(&mut self, method: &str, path: &str, extra: &[(&str, &str)]) -> Vec<u8> {
+            let fields = [
+                (":method", method),
+                (":scheme", "http"),
+                (":path", path),
+                (":authority", "localhost"),
+            ];
+
+            self.encoder
+                .encode(fields.into_iter().chain(extra.iter().copied()))
+        }
+
+        fn request(&mut self, id: u32, method: &str, path: &str, end_stream: bool) {
+            let block = self.headers(method, path, &[]);
+
+            self.send(&Frame::Headers {
+                stream: id,
+                block,
+                end_stream,
+                end_headers: true,
+            });
+        }
+
+        fn data(&mut self, id: u32, data: &[u8], end_stream: bool) {
+            self.send(&Frame::Data {
+                stream: id,
+                data: data.to_vec(),
+                end_stream,
+                flow_controlled: 0,
+            });
+        }
+
+        /// Reads the next frame, `None` once the server closed the connection
+        fn next(&mut self) -> Option<Frame> {
+            let mut header = [0; FrameHeader::LENGTH];
+
+            match self.stream.read_exact(&mut header) {
+                Ok(()) => {}
+                Err(error)
+                    if matches!(
+                        error.kind(),
+                        ErrorKind::UnexpectedEof | ErrorKind::ConnectionReset
+                    ) =>
+                {
+                    return None;
+                }
+                Err(error) => panic!("No frame arrived: {error}"),
+            }
+
+            let header = FrameHeader::parse(header);
+            let mut payload = vec![0; header.length as usize];
+
+            self.stream.read_exact(&mut payload).unwrap();
+
+            Some(Frame::parse(header, &payload).unwrap())
+        }
+
+        /// Reads the status and body of the response on a stream, skipping the frames in between
+        fn response(&mut self, id: u32) -> (String, String) {
+            let mut status = String::new();
+            let mut body = vec![];
+
+            loop {
+                match self.next().expect("connection closed before the response") {
+                    Frame::Headers {
+                        stream,
+                        block,
+                        end_stream,
+                        ..
+                    } if stream == id => {
+                        let fields = self.decoder.decode(&block, usize::MAX).unwrap().unwrap();
+
+                        status = fields
+                            .into_iter()
+                            .find_map(|(name, value)| (name == ":status").then_some(value))
+                            .unwrap();
+
+                        if end_stream {
+                            break;
+                        }
+                    }
+                    Frame::Data {
+                        stream,
+                        data,
+                        end_stream,
+                        ..
+                    } if stream == id => {
+                        body.extend(data);
+
+                        if end_stream {
+                            break;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            (status, String::from_utf8(body).unwrap())
+        }
+
+        /// Reads until the server resets a stream, returning the stream and the code
+        fn reset(&mut self) -> (u32, ErrorCode) {
+            loop {
+                if let Frame::RstStream { stream, code } =
+                    self.next().expect("connection closed before the reset")
+                {
+                    return (stream, code);
+                }
+            }
+        }
+
+        /// Reads until the server goes away, returning its error code along with what serving the connection returned
+        fn go_away(mut self) -> (ErrorCode, Result<(), Http2Error>) {
+            loop {
+                if let Frame::GoAway { code, .. } =
+                    self.next().expect("connection closed without GOAWAY")
+                {
+                    return (code, self.server.join().unwrap());
+                }
+            }
+        }
+    }
+
+    fn connect() -> Client {
+        Client::connect(Settings::default(), Limits::default(), &[])
+    }
+
+    #[test]
+    fn streams_are_answered_independently() {
+        let mut client = connect();
+
+        client.request(1, "POST", "/echo", false);
+        client.request(3, "GET", "/", true);
+
+        assert_eq!(
+            client.response(3),
+            ("200".to_string(), "hello world".to_string())
+        );
+
+        client.data(1, b"ping", true);
+
+        assert_eq!(client.response(1), ("200".to_string(), "ping".to_string()));
+    }
+
+    #[test]
+    fn responses_wait_for_window_updates() {
+        let mut client = Client::connect(Settings::default(), Limits::default(), &[(0x4, 5)]);
+
+        client.request(1, "GET", "/", true);
+
+        assert!(matches!(
+            client.next(),
+            Some(Frame::Headers {
+                end_stream: false,
+                ..
+            })
+        ));
+        assert_eq!(
+            client.next(),
+            Some(Frame::Data {
+                stream: 1,
+                data: b"hello".to_vec(),
+                end_stream: false,
+                flow_controlled: 5,
+            })
+        );
+
+        // Nothing more is sent with the window used up, so the answer to a ping comes first
+        client.send(&Frame::Ping {
+            ack: false,
+            data: [7; 8],
+        });
+        assert_eq!(
+            client.next(),
+            Some(Frame::Ping {
+                ack: true,
+                data: [7; 8]
+            })
+        );
+
+        client.send(&Frame::WindowUpdate {
+            stream: 1,
+            increment: 3,
+        });
+        assert_eq!(
+            client.next(),
+            Some(Frame::Data {
+                stream: 1,
+                data: b" wo".to_vec(),
+                end_stream: false,
+                flow_controlled: 3,
+            })
+        );
+
+        client.send(&Frame::WindowUpdate {
+            stream: 1,
+            increment: 10,
+        });
+        assert_eq!(
+            client.next(),
+            Some(Frame::Data {
+                stream: 1,
+                data: b"rld".to_vec(),
+                end_stream: true,
+                flow_controlled: 3,
+            })
+        );
+    }
+
+    #[test]
+    fn received_data_is_credited_back() {
+        let settings = Settings {
+            initial_window_size: 64,
+            ..Settings::default()
+        };
+        let mut client = Client::connect(settings, Limits::default(), &[]);
+
+        client.request(1, "POST", "/echo", false);
+        client.data(1, &[b'a'; 40], false);
+
+        assert_eq!(
+            client.next(),
+            Some(Frame::WindowUpdate {
+                stream: 1,
+                increment: 40,
+            })
+        );
+
+        client.data(1, &[b'a'; 65], false);
+
+        assert_eq!(client.reset(), (1, ErrorCode::FlowControlError));
+    }
+
+    #[test]
+    fn invalid_window_updates_are_refused() {
+        let mut client = connect();
+
+        client.request(1, "POST", "/echo", false);
+        client.send(&Frame::WindowUpdate {
+            stream: 1,
+            increment: 0,
+        });
+
+        assert_eq!(client.reset(), (1, ErrorCode::ProtocolError));
+
+        client.send(&Frame::WindowUpdate {
+            stream: 0,
+            increment: 0x7fff_ffff,
+        });
+
+        let (code, result) = client.go_away();
+
+        assert_eq!(code, ErrorCode::FlowControlError);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn header_blocks_continue_over_frames() {
+        let mut client = connect();
+
+        let block = client.headers("GET", "/", &[("x-padding", &"x".repeat(64))]);
+        let (first, rest) = block.split_at(10);
+        let (second, third) = rest.split_at(10);
+
+        client.send(&Frame::Headers {
+            stream: 1,
+            block: first.to_vec(),
+            end_stream: true,
+            end_headers: false,
+        });
+        client.send(&Frame::Continuation {
+            stream: 1,
+            block: second.to_vec(),
+            end_headers: false,
+        });
+        client.send(&Frame::Continuation {
+            stream: 1,
+            block: third.to_vec(),
+            end_headers: true,
+        });
+
+        assert_eq!(
+            client.response(1),
+            ("200".to_string(), "hello world".to_string())
+        );
+    }
+
+    #[test]
+    fn interrupted_header_blocks_close_the_connection() {
+        let mut client = connect();
+
+        let block = client.headers("GET", "/", &[]);
+
+        client.send(&Frame::Headers {
+            stream: 1,
+            block,
+            end_stream: true,
+            end_headers: false,
+        });
+        client.send(&Frame::Ping {
+            ack: false,
+            data: [0; 8],
+        });
+
+        assert_eq!(client.go_away().0, ErrorCode::ProtocolError);
+
+        let mut client = connect();
+
+        client.send(&Frame::Continuation {
+            stream: 1,
+            block: vec![],
+            end_headers: true,
+        });
+
+        assert_eq!(client.go_away().0, ErrorCode::ProtocolError);
+    }
+
+    #[test]
+    fn reset_floods_close_the_connection() {
+        let mut client = connect();
+
+        for id in (1..).step_by(2).take(MAX_RESETS as usize + 1) {
+            client.request(id, "POST", "/echo", false);
+            client.send(&Frame::RstStream {
+                stream: id,
+                code: ErrorCode::Cancel,
+            });
+        }
+
+        let (code, result) = client.go_away();
+
+        assert_eq!(code, ErrorCode::EnhanceYourCalm);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn requests_over_the_limits_are_refused() {
+        let limits = Limits {
+            request_line: 64,
+            header_count: 3,
+            body: 10,
+            ..Limits::default()
+        };
+        let mut client = Client::connect(Settings::default(), limits, &[]);
+
+        let block = client.headers(
+            "GET",
+            "/",
+            &[("x-a", "1"), ("x-b", "2"), ("x-c", "3"), ("x-d", "4")],
+        );
+        client.send(&Frame::Headers {
+            stream: 1,
+            block,
+            end_stream: true,
+            end_headers: true,
+        });
+        assert_eq!(client.response(1).0, "431");
+
+        client.request(3, "GET", &format!("/{}", "a".repeat(64)), true);
+        assert_eq!(client.response(3).0, "414");
+
+        let block = client.headers("POST", "/echo", &[("content-length", "100")]);
+        client.send(&Frame::Headers {
+            stream: 5,
+            block,
+            end_stream: false,
+            end_headers: true,
+        });
+        assert_eq!(client.response(5).0, "413");
+
+        client.request(7, "POST", "/echo", false);
+        client.data(7, &[b'a'; 11], false);
+        assert_eq!(client.response(7).0, "413");
+
+        // The other streams are served as usual
+        client.request(9, "GET", "/", true);
+        assert_eq!(client.response(9).0, "200");
+    }
+
+    #[test]
+    fn oversized_header_lists_only_refuse_their_stream() {
+        let settings = Settings {
+            max_header_list_size: 300,
+            ..Settings::default()
+        };
+        let mut client = Client::connect(settings, Limits::default(), &[]);
+
+        let big = "a".repeat(200);
+        let block = client.headers("GET", "/", &[("x-kept", "indexed"), ("x-big", &big)]);
+        let (first, rest) = block.split_at(10);
+
+        client.send(&Frame::Headers {
+            stream: 1,
+            block: first.to_vec(),
+            end_stream: true,
+            end_headers: false,
+        });
+        client.send(&Frame::Continuation {
+            stream: 1,
+            block: rest.to_vec(),
+            end_headers: true,
+        });
+        assert_eq!(client.response(1).0, "431");
+
+        // The next block refers to the fields the refused one added to the dynamic table
+        let block = client.headers("GET", "/", &[("x-kept", "indexed")]);
+        assert!(block.len() < 10, "{block:?}");
+
+        client.send(&Frame::Headers {
+            stream: 3,
+            block,
+            end_stream: true,
+            end_headers: true,
+        });
+        assert_eq!(client.response(3).0, "200");
+
+        // Blocks far past the limit are not worth buffering
+        let huge = "a".repeat(2000);
+        let block = client.headers("GET", "/", &[("x-huge", &huge)]);
+
+        client.send(&Frame::Headers {
+            stream: 5,
+            block: block[..10].to_vec(),
+            end_stream: true,
+            end_headers: false,
+        });
+        client.send(&Frame::Continuation {
+            stream: 5,
+            block: block[10..].to_vec(),
+            end_headers: true,
+        });
+        assert_eq!(client.go_away().0, ErrorCode::EnhanceYourCalm);
+    }
+
+    #[test]
+    fn oversized_trailers_refuse_their_stream() {
+        let settings = Settings {
+            max_header_list_size: 300,
+            ..Settings::default()
+        };
+        let mut client = Client::connect(settings, Limits::default(), &[]);
+
+        client.request(1, "POST", "/echo", false);
+        client.data(1, b"body", false);
+
+        let big = "a".repeat(300);
+        let block = client.encoder.encode([("x-trailer", big.as_str())]);
+
+        client.send(&Frame::Headers {
+            stream: 1,
+            block,
+            end_stream: true,
+            end_headers: true,
+        });
+        assert_eq!(client.response(1).0, "431");
+
+        client.request(3, "GET", "/", true);
+        assert_eq!(client.response(3).0, "200");
+    }
+
+    #[test]
+    fn streams_over_the_concurrency_limit_are_refused() {
+        let settings = Settings {
+            max_concurrent_streams: 1,
+            ..Settings::default()
+        };
+        let mut client = Client::connect(settings, Limits::default(), &[]);
+
+        client.request(1, "POST", "/echo", false);
+        client.request(3, "GET", "/", true);
+
+        assert_eq!(client.reset(), (3, ErrorCode::RefusedStream));
+
+        client.data(1, b"done", true);
+
+        assert_eq!(client.response(1), ("200".to_string(), "done".to_string()));
+    }
+
+    #[test]
+    fn streamed_responses_are_refused() {
+        let mut client = connect();
+
+        client.request(1, "GET", "/events", true);
+
+        assert_eq!(client.response(1).0, "505");
+    }
+
+    /// A TCP connection that claims to be encrypted, as TLS connections do
+    struct Encrypted(TcpStream);
+
+    impl Read for Encrypted {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.0.read(buf)
+        }
+    }
+
+    impl Write for Encrypted {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            self.0.flush()
+        }
+    }
+
+    impl Connection for Encrypted {
+        fn shutdown(&mut self) -> std::io::Result<()> {
+            Connection::shutdown(&mut self.0)
+        }
+
+        fn peer_addr(&self) -> Option<SocketAddr> {
+            Connection::peer_addr(&self.0)
+        }
+
+        fn set_read_timeout(&self, timeout: Option<Duration>) -> std::io::Result<()> {
+            Connection::set_read_timeout(&self.0, timeout)
+        }
+
+        fn set_write_timeout(&self, timeout: Option<Duration>) -> std::io::Result<()> {
+            Connection::set_write_timeout(&self.0, timeout)
+        }
+
+        fn is_encrypted(&self) -> bool {
+            true
+        }
+    }
+
+    /// Sends an HTTP/1.1 request asking to switch to h2c, returning the status line of the answer
+    fn upgrade(client: &mut TcpStream) -> String {
+        client
+            .write_all(
+                b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: Upgrade, HTTP2-Settings\r\n\
+                  Upgrade: h2c\r\nHTTP2-Settings: AAMAAABkAAQAAP__\r\n\r\n",
+            )
+            .unwrap();
+
+        let mut head = vec![];
+        let mut byte = [0];
+
+        while !head.ends_with(b"\r\n\r\n") {
+            client.read_exact(&mut byte).unwrap();
+            head.push(byte[0]);
+        }
+
+        let head = String::from_utf8(head).unwrap();
+
+        head.lines().next().unwrap().to_string()
+    }
+
+    #[test]
+    fn cleartext_connections_upgrade_to_h2c() {
+        let (stream, connection) = socket_pair();
+
+        let server = std::thread::spawn(move || {
+            server::handle_connection(
+                connection,
+                hosts(),
+                Settings::default(),
+                Timeouts::default(),
+                Limits::default(),
+            )
+        });
+
+        let mut client = Client {
+            stream,
+            encoder: Encoder::new(),
+            decoder: Decoder::new(4096),
+            server: std::thread::spawn(|| Ok(())),
+        };
+
+        assert_eq!(
+            upgrade(&mut client.stream),
+            "HTTP/1.1 101 Switching Protocols"
+        );
+
+        client.stream.write_all(PREFACE).unwrap();
+        client.send(&Frame::Settings {
+            ack: false,
+            values: vec![],
+        });
+
+        // The upgraded request is answered on stream 1
+        assert_eq!(
+            client.response(1),
+            ("200".to_string(), "hello world".to_string())
+        );
+
+        client.send(&Frame::GoAway {
+            last_stream: 0,
+            code: ErrorCode::NoError,
+        });
+        drop(client);
+
+        server.join().unwrap().unwrap();
+    }
+
+    #[test]
+    fn encrypted_connections_do_not_upgrade_to_h2c() {
+        let (mut client, connection) = socket_pair();
+
+        let server = std::thread::spawn(move || {
+            server::handle_connection(
+                Encrypted(connection),
+                hosts(),
+                Settings::default(),
+                Timeouts::default(),
+                Limits::default(),
+            )
+        });
+
+        assert_eq!(upgrade(&mut client), "HTTP/1.1 200 Ok");
+
+        server.join().unwrap().unwrap();
+    }
+}
@@ -0,0 +1,108 @@
+use thiserror::Error;
+
+/// Reasons for closing a stream or the connection, carried in `RST_STREAM` and `GOAWAY` frames, RFC 9113 Section 7
+#[allow(missing_docs)] // Named after the registry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+    NoError,
+    ProtocolError,
+    InternalError,
+    FlowControlError,
+    SettingsTimeout,
+    StreamClosed,
+    FrameSizeError,
+    RefusedStream,
+    Cancel,
+    CompressionError,
+    ConnectError,
+    EnhanceYourCalm,
+    InadequateSecurity,
+    Http11Required,
+    Unknown(u32),
+}
+
+impl From<u32> for ErrorCode {
+    fn from(value: u32) -> Self {
+        match value {
+            0x0 => ErrorCode::NoError,
+            0x1 => ErrorCode::ProtocolError,
+            0x2 => ErrorCode::InternalError,
+            0x3 => ErrorCode::FlowControlError,
+            0x4 => ErrorCode::SettingsTimeout,
+            0x5 => ErrorCode::StreamClosed,
+            0x6 => ErrorCode::FrameSizeError,
+            0x7 => ErrorCode::RefusedStream,
+            0x8 => ErrorCode::Cancel,
+            0x9 => ErrorCode::CompressionError,
+            0xa => ErrorCode::ConnectError,
+            0xb => ErrorCode::EnhanceYourCalm,
+            0xc => ErrorCode::InadequateSecurity,
+            0xd => ErrorCode::Http11Required,
+            code => ErrorCode::Unknown(code),
+        }
+    }
+}
+
+impl From<ErrorCode> for u32 {
+    fn from(code: ErrorCode) -> Self {
+        match code {
+            ErrorCode::NoError => 0x0,
+            ErrorCode::ProtocolError => 0x1,
+            ErrorCode::InternalError => 0x2,
+            ErrorCode::FlowControlError => 0x3,
+            ErrorCode::SettingsTimeout => 0x4,
+            ErrorCode::StreamClosed => 0x5,
+            ErrorCode::FrameSizeError => 0x6,
+            ErrorCode::RefusedStream => 0x7,
+            ErrorCode::Cancel => 0x8,
+            ErrorCode::CompressionError => 0x9,
+            ErrorCode::ConnectError => 0xa,
+            ErrorCode::EnhanceYourCalm => 0xb,
+            ErrorCode::InadequateSecurity => 0xc,
+            ErrorCode::Http11Required => 0xd,
+            ErrorCode::Unknown(code) => code,
+        }
+    }
+}
+
+/// Errors that end either a single stream or the whole HTTP/2 connection
+#[derive(Debug, Error)]
+pub enum Http2Error {
+    /// The peer broke the protocol in a way that leaves the connection unusable
+    #[error("Connection error {code:?}: {reason}")]
+    Connection {
+        /// Code sent to the peer in the `GOAWAY` frame
+        code: ErrorCode,
+        /// What went wrong
+        reason: &'static str,
+    },
+
+    /// The peer broke the protocol on a single stream, which is reset while the others carry on
+    #[error("Stream {stream} error {code:?}: {reason}")]
+    Stream {
+        /// Identifier of the stream
+        stream: u32,
+        /// Code sent to the peer in the `RST_STREAM` frame
+        code: ErrorCode,
+        /// What went wrong
+        reason: &'static str,
+    },
+
+    /// Reading from or writing to the connection failed
+    #[error("Io error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Shorthand for a connection error
+pub(crate) fn connection_error(code: ErrorCode, reason: &'static str) -> Http2Error {
+    Http2Error::Connection { code, reason }
+}
+
+/// Shorthand for a stream error
+pub(crate) fn stream_error(stream: u32, code: ErrorCode, reason: &'static str) -> Http2Error {
+    Http2Error::Stream {
+        stream,
+        code,
+        reason,
+    }
+}
@@ -0,0 +1,400 @@
+use super::error::{connection_error, stream_error, ErrorCode, Http2Error};
+
+/// Frame types, RFC 9113 Section 6
+mod kind {
+    pub const DATA: u8 = 0x0;
+    pub const HEADERS: u8 = 0x1;
+    pub const PRIORITY: u8 = 0x2;
+    pub const RST_STREAM: u8 = 0x3;
+    pub const SETTINGS: u8 = 0x4;
+    pub const PUSH_PROMISE: u8 = 0x5;
+    pub const PING: u8 = 0x6;
+    pub const GOAWAY: u8 = 0x7;
+    pub const WINDOW_UPDATE: u8 = 0x8;
+    pub const CONTINUATION: u8 = 0x9;
+}
+
+/// Frame flags, RFC 9113 Section 6
+mod flag {
+    pub const END_STREAM: u8 = 0x1;
+    pub const ACK: u8 = 0x1;
+    pub const END_HEADERS: u8 = 0x4;
+    pub const PADDED: u8 = 0x8;
+    pub const PRIORITY: u8 = 0x20;
+}
+
+/// The 9 byte header that precedes every frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameHeader {
+    /// Length of the payload in bytes
+    pub length: u32,
+    /// Type of the frame
+    pub kind: u8,
+    /// Type specific flags
+    pub flags: u8,
+    /// Stream the frame belongs to, 0 for the connection itself
+    pub stream: u32,
+}
+
+impl FrameHeader {
+    /// Size of the header on the wire
+    pub const LENGTH: usize = 9;
+
+    /// Decodes a frame header
+    #[must_use]
+    pub fn parse(bytes: [u8; Self::LENGTH]) -> Self {
+        Self {
+            length: u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]),
+            kind: bytes[3],
+            flags: bytes[4],
+            // The most significant bit is reserved and ignored
+            stream: u32::from_be_bytes([bytes[5], bytes[6], bytes[7], bytes[8]]) & 0x7fff_ffff,
+        }
+    }
+}
+
+/// A decoded HTTP/2 frame
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Frame {
+    /// Part of a request or response body
+    Data {
+        /// Stream the data belongs to
+        stream: u32,
+        /// The data without padding
+        data: Vec<u8>,
+        /// Whether this is the last frame the sender sends on the stream
+        end_stream: bool,
+        /// Size counted against flow control windows, including padding
+        flow_controlled: u32,
+    },
+
+    /// The start of a header block, opening a stream or carrying trailers
+    Headers {
+        /// Stream the headers belong to
+        stream: u32,
+        /// The HPACK encoded header block fragment
+        block: Vec<u8>,
+        /// Whether the sender closes the stream after the headers
+        end_stream: bool,
+        /// Whether the header block is complete or followed by `CONTINUATION` frames
+        end_headers: bool,
+    },
+
+    /// Advisory prioritization, which this server ignores
+    Priority {
+        /// Stream being prioritized
+        stream: u32,
+    },
+
+    /// Immediate termination of a stream
+    RstStream {
+        /// Stream being reset
+        stream: u32,
+        /// Why the stream was reset
+        code: ErrorCode,
+    },
+
+    /// Connection configuration, or the acknowledgement of the peer's configuration
+    Settings {
+        /// Whether the frame acknowledges the peer's settings
+        ack: bool,
+        /// Identifier and value of each setting
+        values: Vec<(u16, u32)>,
+    },
+
+    /// Server push, which clients must never send
+    PushPromise {
+        /// Stream the push is associated with
+        stream: u32,
+    },
+
+    /// Liveness check that the receiver echoes back
+    Ping {
+        /// Whether the frame answers a ping
+        ack: bool,
+        /// Opaque data echoed in the answer
+        data: [u8; 8],
+    },
+
+    /// Graceful or erroneous shutdown of the connection
+    GoAway {
+        /// Last stream the sender may have processed
+        last_stream: u32,
+        /// Why the connection is being shut down
+        code: ErrorCode,
+    },
+
+    /// Flow control credit for a stream or the whole connection
+    WindowUpdate {
+        /// Stream the credit is for, 0 for the connection
+        stream: u32,
+        /// Number of bytes the receiver may send additionally
+        increment: u32,
+    },
+
+    /// Continuation of a header block
+    Continuation {
+        /// Stream the headers belong to
+        stream: u32,
+        /// The HPACK encoded header block fragment
+        block: Vec<u8>,
+        /// Whether the header block is complete
+        end_headers: bool,
+    },
+
+    /// A frame of an extension type, which must be ignored
+    Unknown {
+        /// Type of the frame
+        kind: u8,
+    },
+}
+
+impl Frame {
+    /// Decodes the payload of a frame
+    /// # Errors
+    /// - If the payload does not have the size or stream required by its type
+    pub fn parse(header: FrameHeader, payload: &[u8]) -> Result<Self, Http2Error> {
+        let FrameHeader {
+            kind,
+            flags,
+            stream,
+            ..
+        } = header;
+
+        let requires_stream = matches!(
+            kind,
+            kind::DATA
+                | kind::HEADERS
+                | kind::PRIORITY
+                | kind::RST_STREAM
+                | kind::PUSH_PROMISE
+                | kind::CONTINUATION
+        );
+        let forbids_stream = matches!(kind, kind::SETTINGS | kind::PING | kind::GOAWAY);
+
+        if (requires_stream && stream == 0) || (forbids_stream && stream != 0) {
+            return Err(connection_error(
+                ErrorCode::ProtocolError,
+                "frame sent on the wrong stream",
+            ));
+        }
+
+        let frame = match kind {
+            kind::DATA => Frame::Data {
+                stream,
+                data: unpadded(flags, payload)?.to_vec(),
+                end_stream: flags & flag::END_STREAM != 0,
+                flow_controlled: header.length,
+            },
+            kind::HEADERS => {
+                let mut block = unpadded(flags, payload)?;
+
+                // Stream dependency and weight
+                if flags & flag::PRIORITY != 0 {
+                    block = block.get(5..).ok_or(connection_error(
+                        ErrorCode::FrameSizeError,
+                        "HEADERS frame too short for its priority",
+                    ))?;
+                }
+
+                Frame::Headers {
+                    stream,
+                    block: block.to_vec(),
+                    end_stream: flags & flag::END_STREAM != 0,
+                    end_headers: flags & flag::END_HEADERS != 0,
+                }
+            }
+            kind::PRIORITY if payload.len() != 5 => {
+                return Err(stream_error(
+                    stream,
+                    ErrorCode::FrameSizeError,
+                    "PRIORITY frame must be 5 bytes",
+                ));
+            }
+            kind::PRIORITY => Frame::Priority { stream },
+            kind::RST_STREAM | kind::WINDOW_UPDATE if payload.len() != 4 => {
+                return Err(connection_error(
+                    ErrorCode::FrameSizeError,
+                    "RST_STREAM and WINDOW_UPDATE frames must be 4 bytes",
+                ));
+            }
+            kind::RST_STREAM => Frame::RstStream {
+                stream,
+                code: ErrorCode::from(u32_at(payload, 0)),
+            },
+            kind::SETTINGS => settings(flags, payload)?,
+            kind::PUSH_PROMISE => Frame::PushPromise { stream },
+            kind::PING => Frame::Ping {
+                ack: flags & flag::ACK != 0,
+                data: payload.try_into().map_err(|_| {
+                    connection_error(ErrorCode::FrameSizeError, "PING frame must be 8 bytes")
+                })?,
+            },
+            kind::GOAWAY if payload.len() < 8 => {
+                return Err(connection_error(
+                    ErrorCode::FrameSizeError,
+                    "GOAWAY frame must be at least 8 bytes",
+                ));
+            }
+            kind::GOAWAY => Frame::GoAway {
+                last_stream: u32_at(payload, 0) & 0x7fff_ffff,
+                code: ErrorCode::from(u32_at(payload, 4)),
+            },
+            kind::WINDOW_UPDATE => Frame::WindowUpdate {
+                stream,
+                increment: u32_at(payload, 0) & 0x7fff_ffff,
+            },
+            kind::CONTINUATION => Frame::Continuation {
+                stream,
+                block: payload.to_vec(),
+                end_headers: flags & flag::END_HEADERS != 0,
+            },
+            kind => Frame::Unknown { kind },
+        };
+
+        Ok(frame)
+    }
+
+    /// Appends the encoded frame to the buffer
+    /// # Panics
+    /// - If a payload is larger than the 24 bit length field allows, which callers prevent by splitting it
+    pub fn encode(&self, buffer: &mut Vec<u8>) {
+        let flag_if = |condition: bool, flag: u8| if condition { flag } else { 0 };
+
+        let (kind, flags, stream, payload) = match self {
+            Frame::Data {
+                stream,
+                data,
+                end_stream,
+                ..
+            } => (
+                kind::DATA,
+                flag_if(*end_stream, flag::END_STREAM),
+                *stream,
+                data.clone(),
+            ),
+            Frame::Headers {
+                stream,
+                block,
+                end_stream,
+                end_headers,
+            } => (
+                kind::HEADERS,
+                flag_if(*end_stream, flag::END_STREAM) | flag_if(*end_headers, flag::END_HEADERS),
+                *stream,
+                block.clone(),
+            ),
+            Frame::Continuation {
+                stream,
+                block,
+                end_headers,
+            } => (
+                kind::CONTINUATION,
+                flag_if(*end_headers, flag::END_HEADERS),
+                *stream,
+                block.clone(),
+            ),
+            Frame::RstStream { stream, code } => (
+                kind::RST_STREAM,
+                0,
+                *stream,
+                u32::from(*code).to_be_bytes().to_vec(),
+            ),
+            Frame::Settings { ack, values } => (
+                kind::SETTINGS,
+                flag_if(*ack, flag::ACK),
+                0,
+                values
+                    .iter()
+                    .flat_map(|(identifier, value)| {
+                        [identifier.to_be_bytes().as_slice(), &value.to_be_bytes()].concat()
+                    })
+                    .collect(),
+            ),
+            Frame::Ping { ack, data } => (kind::PING, flag_if(*ack, flag::ACK), 0, data.to_vec()),
+            Frame::GoAway { last_stream, code } => (
+                kind::GOAWAY,
+                0,
+                0,
+                [last_stream.to_be_bytes(), u32::from(*code).to_be_bytes()].concat(),
+            ),
+            Frame::WindowUpdate { stream, increment } => (
+                kind::WINDOW_UPDATE,
+                0,
+                *stream,
+                increment.to_be_bytes().to_vec(),
+            ),
+            Frame::Priority { .. } | Frame::PushPromise { .. } | Frame::Unknown { .. } => {
+                unreachable!("the server never sends {self:?}")
+            }
+        };
+
+        let length = u32::try_from(payload.len())
+            .ok()
+            .filter(|length| *length < 1 << 24)
+            .expect("frame payload exceeds the 24 bit length field");
+
+        buffer.extend(&length.to_be_bytes()[1..]);
+        buffer.push(kind);
+        buffer.push(flags);
+        buffer.extend(stream.to_be_bytes());
+        buffer.extend(payload);
+    }
+}
+
+/// Decodes the identifier and value pairs of a `SETTINGS` payload, also used by the `HTTP2-Settings` header
+#[must_use]
+pub fn parse_settings(payload: &[u8]) -> Vec<(u16, u32)> {
+    payload
+        .chunks_exact(6)
+        .map(|setting| {
+            (
+                u16::from_be_bytes([setting[0], setting[1]]),
+                u32::from_be_bytes([setting[2], setting[3], setting[4], setting[5]]),
+            )
+        })
+        .collect()
+}
+
+/// Decodes a `SETTINGS` frame, which is either an acknowledgement or a list of 6 byte settings
+fn settings(flags: u8, payload: &[u8]) -> Result<Frame, Http2Error> {
+    let ack = flags & flag::ACK != 0;
+
+    if (ack && !payload.is_empty()) || !payload.len().is_multiple_of(6) {
+        return Err(connection_error(
+            ErrorCode::FrameSizeError,
+            "SETTINGS frame has an invalid length",
+        ));
+    }
+
+    Ok(Frame::Settings {
+        ack,
+        values: parse_settings(payload),
+    })
+}
+
+/// Strips the padding of `DATA` and `HEADERS` frames
+fn unpadded(flags: u8, payload: &[u8]) -> Result<&[u8], Http2Error> {
+    if flags & flag::PADDED == 0 {
+        return Ok(payload);
+    }
+
+    let invalid = || connection_error(ErrorCode::ProtocolError, "padding exceeds the payload");
+
+    let (&padding, rest) = payload.split_first().ok_or_else(invalid)?;
+
+    rest.len()
+        .checked_sub(usize::from(padding))
+        .map(|length| &rest[..length])
+        .ok_or_else(invalid)
+}
+
+/// Reads a big endian `u32` at `offset`, the length of the payload has been checked beforehand
+fn u32_at(payload: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes([
+        payload[offset],
+        payload[offset + 1],
+        payload[offset + 2],
+        payload[offset + 3],
+    ])
+}
@@ -0,0 +1,693 @@
+use super::{
+    error::{connection_error, ErrorCode, Http2Error},
+    huffman,
+};
+use std::collections::VecDeque;
+
+/// Header fields every endpoint knows by index, RFC 7541 Appendix A
+const STATIC_TABLE: [(&str, &str); 61] = [
+    (":authority", ""),
+    (":method", "GET"),
+    (":method", "POST"),
+    (":path", "/"),
+    (":path", "/index.html"),
+    (":scheme", "http"),
+    (":scheme", "https"),
+    (":status", "200"),
+    (":status", "204"),
+    (":status", "206"),
+    (":status", "304"),
+    (":status", "400"),
+    (":status", "404"),
+    (":status", "500"),
+    ("accept-charset", ""),
+    ("accept-encoding", "gzip, deflate"),
+    ("accept-language", ""),
+    ("accept-ranges", ""),
+    ("accept", ""),
+    ("access-control-allow-origin", ""),
+    ("age", ""),
+    ("allow", ""),
+    ("authorization", ""),
+    ("cache-control", ""),
+    ("content-disposition", ""),
+    ("content-encoding", ""),
+    ("content-language", ""),
+    ("content-length", ""),
+    ("content-location", ""),
+    ("content-range", ""),
+    ("content-type", ""),
+    ("cookie", ""),
+    ("date", ""),
+    ("etag", ""),
+    ("expect", ""),
+    ("expires", ""),
+    ("from", ""),
+    ("host", ""),
+    ("if-match", ""),
+    ("if-modified-since", ""),
+    ("if-none-match", ""),
+    ("if-range", ""),
+    ("if-unmodified-since", ""),
+    ("last-modified", ""),
+    ("link", ""),
+    ("location", ""),
+    ("max-forwards", ""),
+    ("proxy-authenticate", ""),
+    ("proxy-authorization", ""),
+    ("range", ""),
+    ("referer", ""),
+    ("refresh", ""),
+    ("retry-after", ""),
+    ("server", ""),
+    ("set-cookie", ""),
+    ("strict-transport-security", ""),
+    ("transfer-encoding", ""),
+    ("user-agent", ""),
+    ("vary", ""),
+    ("via", ""),
+    ("www-authenticate", ""),
+];
+
+/// Headers whose values must never be stored in a compression context, as they are easy targets for CRIME-style attacks
+const SENSITIVE: [&str; 4] = [
+    "authorization",
+    "cookie",
+    "proxy-authorization",
+    "set-cookie",
+];
+
+/// A decoded header field
+pub type HeaderField = (String, String);
+
+/// Recently used header fields, shared between the encoder of one endpoint and the decoder of the other
+#[derive(Debug)]
+struct DynamicTable {
+    entries: VecDeque<HeaderField>,
+    size: usize,
+    max_size: usize,
+}
+
+impl DynamicTable {
+    fn new(max_size: usize) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            size: 0,
+            max_size,
+        }
+    }
+
+    /// Size of an entry as defined by RFC 7541 Section 4.1
+    fn entry_size((name, value): &HeaderField) -> usize {
+        name.len() + value.len() + 32
+    }
+
+    fn insert(&mut self, field: HeaderField) {
+        let size = Self::entry_size(&field);
+
+        // An entry larger than the whole table just empties it
+        self.evict(self.max_size.saturating_sub(size));
+
+        if size <= self.max_size {
+            self.size += size;
+            self.entries.push_front(field);
+        }
+    }
+
+    fn resize(&mut self, max_size: usize) {
+        self.max_size = max_size;
+        self.evict(max_size);
+    }
+
+    fn evict(&mut self, target: usize) {
+        while self.size > target {
+            let Some(field) = self.entries.pop_back() else {
+                break;
+            };
+
+            self.size -= Self::entry_size(&field);
+        }
+    }
+
+    /// Looks up an index of the combined static and dynamic address space
+    fn get(&self, index: usize) -> Result<HeaderField, Http2Error> {
+        let field = match index {
+            0 => None,
+            1..=61 => STATIC_TABLE
+                .get(index - 1)
+                .map(|(name, value)| ((*name).to_string(), (*value).to_string())),
+            _ => self.entries.get(index - 62).cloned(),
+        };
+
+        field.ok_or(connection_error(
+            ErrorCode::CompressionError,
+            "header index out of range",
+        ))
+    }
+
+    /// Finds the index of the exact field, or failing that of a field with the same name
+    fn find(&self, name: &str, value: &str) -> Option<(usize, bool)> {
+        let fields = STATIC_TABLE
+            .iter()
+            .map(|(name, value)| (*name, *value))
+            .chain(
+                self.entries
+                    .iter()
+                    .map(|(name, value)| (name.as_str(), value.as_str())),
+            );
+
+        let mut name_match = None;
+
+        for (index, (field_name, field_value)) in fields.enumerate() {
+            if field_name == name {
+                if field_value == value {
+                    return Some((index + 1, true));
+                }
+
+                name_match = name_match.or(Some((index + 1, false)));
+            }
+        }
+
+        name_match
+    }
+}
+
+/// Decompresses header blocks received from the peer
+#[derive(Debug)]
+pub struct Decoder {
+    table: DynamicTable,
+    /// The largest table size the peer may pick, as advertised in our settings
+    max_size_limit: usize,
+}
+
+impl Decoder {
+    /// Creates a decoder whose dynamic table may grow up to `max_size` bytes
+    #[must_use]
+    pub fn new(max_size: usize) -> Self {
+        Self {
+            table: DynamicTable::new(max_size),
+            max_size_limit: max_size,
+        }
+    }
+
+    /// Decodes a complete header block, updating the dynamic table along the way.
+    ///
+    /// Returns `None` if the fields exceed `max_list_size`, counted as for `SETTINGS_MAX_HEADER_LIST_SIZE`.
+    /// Fields past the limit are not kept, since a small block can refer to the same large table entry over and
+    /// over, but the rest of the block is still decoded to keep the dynamic table in sync with the peer
+    /// # Errors
+    /// - If the block is truncated, refers to entries that do not exist, or contains invalid strings
+    pub fn decode(
+        &mut self,
+        block: &[u8],
+        max_list_size: usize,
+    ) -> Result<Option<Vec<HeaderField>>, Http2Error> {
+        let mut input = block;
+        let mut fields = vec![];
+        let mut list_size = 0;
+
+        while let Some(&first) = input.first() {
+            let field = if first & 0x80 != 0 {
+                // Indexed header field
+                let index = decode_integer(&mut input, 7)?;
+                self.table.get(index)?
+            } else if first & 0x40 != 0 {
+                // Literal header field with incremental indexing
+                let field = self.literal(&mut input, 6)?;
+                self.table.insert(field.clone());
+                field
+            } else if first & 0x20 != 0 {
+                // Dynamic table size update
+                let max_size = decode_integer(&mut input, 5)?;
+
+                if max_size > self.max_size_limit {
+                    return Err(connection_error(
+                        ErrorCode::CompressionError,
+                        "dynamic table size exceeds the advertised limit",
+                    ));
+                }
+
+                self.table.resize(max_size);
+
+                continue;
+            } else {
+                // Literal header field without indexing, or never indexed
+                self.literal(&mut input, 4)?
+            };
+
+            // Every field counts its name, value and 32 bytes of overhead, RFC 9113 Section 6.5.2
+            list_size += field.0.len() + field.1.len() + 32;
+
+            if list_size <= max_list_size {
+                fields.push(field);
+            }
+        }
+
+        Ok((list_size <= max_list_size).then_some(fields))
+    }
+
+    fn literal(&self, input: &mut &[u8], prefix: u8) -> Result<HeaderField, Http2Error> {
+        let name = match decode_integer(input, prefix)? {
+            0 => decode_string(input)?,
+            index => self.table.get(index)?.0,
+        };
+
+        Ok((name, decode_string(input)?))
+    }
+}
+
+/// Compresses header blocks sent to the peer
+#[derive(Debug)]
+pub struct Encoder {
+    table: DynamicTable,
+    /// A size change the peer asked for, which has to be announced at the start of the next block
+    pending_resize: Option<usize>,
+}
+
+impl Encoder {
+    /// Creates an encoder with the dynamic table size every peer starts with
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            table: DynamicTable::new(4096),
+            pending_resize: None,
+        }
+    }
+
+    /// Applies the `SETTINGS_HEADER_TABLE_SIZE` of the peer, never growing past the initial size
+    pub fn set_max_size(&mut self, max_size: usize) {
+        let max_size = max_size.min(4096);
+
+        self.table.resize(max_size);
+        self.pending_resize = Some(max_size);
+    }
+
+    /// Encodes a header block, indexing fields so that repeating them costs a single byte
+    pub fn encode<'a>(&mut self, fields: impl IntoIterator<Item = (&'a str, &'a str)>) -> Vec<u8> {
+        let mut block = vec![];
+
+        if let Some(max_size) = self.pending_resize.take() {
+            encode_integer(&mut block, 0x20, 5, max_size);
+        }
+
+        for (name, value) in fields {
+            let sensitive = SENSITIVE.contains(&name);
+
+            match self.table.find(name, value) {
+                Some((index, true)) if !sensitive => encode_integer(&mut block, 0x80, 7, index),
+                found => {
+                    let name_index = found.map_or(0, |(index, _)| index);
+
+                    if sensitive {
+                        // Never indexed, so that intermediaries don't compress it either
+                        encode_integer(&mut block, 0x10, 4, name_index);
+                    } else {
+                        encode_integer(&mut block, 0x40, 6, name_index);
+                    }
+
+                    if name_index == 0 {
+                        encode_string(&mut block, name);
+                    }
+
+                    encode_string(&mut block, value);
+
+                    if !sensitive {
+                        self.table.insert((name.to_string(), value.to_string()));
+                    }
+                }
+            }
+        }
+
+        block
+    }
+}
+
+impl Default for Encoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Decodes an integer with an `prefix` bit prefix, RFC 7541 Section 5.1
+fn decode_integer(input: &mut &[u8], prefix: u8) -> Result<usize, Http2Error> {
+    let truncated = || connection_error(ErrorCode::CompressionError, "truncated header block");
+
+    let (&first, rest) = input.split_first().ok_or_else(truncated)?;
+    *input = rest;
+
+    let mask = (1 << prefix) - 1;
+    let mut value = usize::from(first) & mask;
+
+    if value < mask {
+        return Ok(value);
+    }
+
+    let mut shift = 0;
+
+    loop {
+        let (&byte, rest) = input.split_first().ok_or_else(truncated)?;
+        *input = rest;
+
+        value += usize::from(byte & 0x7f) << shift;
+
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+
+        shift += 7;
+
+        // Nothing legitimate comes close to 2^28
+        if shift > 21 {
+            return Err(connection_error(
+                ErrorCode::CompressionError,
+                "header integer too large",
+            ));
+        }
+    }
+}
+
+/// Encodes an integer with an `prefix` bit prefix, setting the leading `flags` of the first byte
+#[allow(clippy::cast_possible_truncation)] // Every pushed value is masked to fit a byte
+fn encode_integer(buffer: &mut Vec<u8>, flags: u8, prefix: u8, value: usize) {
+    let mask: usize = (1 << prefix) - 1;
+
+    if value < mask {
+        buffer.push(flags | value as u8);
+        return;
+    }
+
+    buffer.push(flags | mask as u8);
+
+    let mut rest = value - mask;
+
+    while rest >= 0x80 {
+        buffer.push((rest & 0x7f) as u8 | 0x80);
+        rest >>= 7;
+    }
+
+    buffer.push(rest as u8);
+}
+
+/// Decodes a length prefixed, optionally Huffman encoded string
+fn decode_string(input: &mut &[u8]) -> Result<String, Http2Error> {
+    let huffman_encoded = input.first().is_some_and(|first| first & 0x80 != 0);
+    let length = decode_integer(input, 7)?;
+
+    if length > input.len() {
+        return Err(connection_error(
+            ErrorCode::CompressionError,
+            "truncated header string",
+        ));
+    }
+
+    let (bytes, rest) = input.split_at(length);
+    *input = rest;
+
+    let bytes = if huffman_encoded {
+        huffman::decode(bytes)?
+    } else {
+        bytes.to_vec()
+    };
+
+    String::from_utf8(bytes)
+        .map_err(|_| connection_error(ErrorCode::CompressionError, "header is not valid UTF-8"))
+}
+
+/// Encodes a string, Huffman encoded whenever that makes it shorter
+fn encode_string(buffer: &mut Vec<u8>, value: &str) {
+    let bytes = value.as_bytes();
+
+    if huffman::encoded_length(bytes) < bytes.len() {
+        let encoded = huffman::encode(bytes);
+        encode_integer(buffer, 0x80, 7, encoded.len());
+        buffer.extend(encoded);
+    } else {
+        encode_integer(buffer, 0, 7, bytes.len());
+        buffer.extend(bytes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_integer, encode_integer, Decoder, Encoder};
+
+    fn hex(text: &str) -> Vec<u8> {
+        let digits: Vec<u8> = text.bytes().filter(u8::is_ascii_hexdigit).collect();
+
+        digits
+            .chunks(2)
+            .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).unwrap(), 16).unwrap())
+            .collect()
+    }
+
+    fn fields(expected: &[(&str, &str)]) -> Vec<(String, String)> {
+        expected
+            .iter()
+            .map(|(name, value)| ((*name).to_string(), (*value).to_string()))
+            .collect()
+    }
+
+    /// A header block in hex, the fields it decodes to and the size of the dynamic table afterwards
+    type Example<'a> = (&'a str, &'a [(&'a str, &'a str)], usize);
+
+    /// Decodes consecutive header blocks with one decoder, checking the fields and table size after each
+    fn decode_all(max_size: usize, blocks: &[Example]) {
+        let mut decoder = Decoder::new(max_size);
+
+        for (block, expected, table_size) in blocks {
+            assert_eq!(
+                decoder.decode(&hex(block), usize::MAX).unwrap().unwrap(),
+                fields(expected)
+            );
+            assert_eq!(decoder.table.size, *table_size);
+        }
+    }
+
+    const FIRST_REQUEST: &[(&str, &str)] = &[
+        (":method", "GET"),
+        (":scheme", "http"),
+        (":path", "/"),
+        (":authority", "www.example.com"),
+    ];
+
+    const SECOND_REQUEST: &[(&str, &str)] = &[
+        (":method", "GET"),
+        (":scheme", "http"),
+        (":path", "/"),
+        (":authority", "www.example.com"),
+        ("cache-control", "no-cache"),
+    ];
+
+    const THIRD_REQUEST: &[(&str, &str)] = &[
+        (":method", "GET"),
+        (":scheme", "https"),
+        (":path", "/index.html"),
+        (":authority", "www.example.com"),
+        ("custom-key", "custom-value"),
+    ];
+
+    const FIRST_RESPONSE: &[(&str, &str)] = &[
+        (":status", "302"),
+        ("cache-control", "private"),
+        ("date", "Mon, 21 Oct 2013 20:13:21 GMT"),
+        ("location", "https://www.example.com"),
+    ];
+
+    const SECOND_RESPONSE: &[(&str, &str)] = &[
+        (":status", "307"),
+        ("cache-control", "private"),
+        ("date", "Mon, 21 Oct 2013 20:13:21 GMT"),
+        ("location", "https://www.example.com"),
+    ];
+
+    const THIRD_RESPONSE: &[(&str, &str)] = &[
+        (":status", "200"),
+        ("cache-control", "private"),
+        ("date", "Mon, 21 Oct 2013 20:13:22 GMT"),
+        ("location", "https://www.example.com"),
+        ("content-encoding", "gzip"),
+        (
+            "set-cookie",
+            "foo=ASDJKHQKBZXOQWEOPIUAXQWEOIU; max-age=3600; version=1",
+        ),
+    ];
+
+    /// RFC 7541 Appendix C.1
+    #[test]
+    fn integers() {
+        for (value, prefix, encoded) in [
+            (10, 5, &[0x0a][..]),
+            (1337, 5, &[0x1f, 0x9a, 0x0a]),
+            (42, 8, &[0x2a]),
+        ] {
+            let mut buffer = vec![];
+            encode_integer(&mut buffer, 0, prefix, value);
+            assert_eq!(buffer, encoded);
+
+            let mut input = encoded;
+            assert_eq!(decode_integer(&mut input, prefix).unwrap(), value);
+            assert!(input.is_empty());
+        }
+    }
+
+    /// RFC 7541 Appendix C.2
+    #[test]
+    fn header_field_representations() {
+        decode_all(
+            4096,
+            &[(
+                "400a 6375 7374 6f6d 2d6b 6579 0d63 7573 746f 6d2d 6865 6164 6572",
+                &[("custom-key", "custom-header")],
+                55,
+            )],
+        );
+        decode_all(
+            4096,
+            &[(
+                "040c 2f73 616d 706c 652f 7061 7468",
+                &[(":path", "/sample/path")],
+                0,
+            )],
+        );
+        decode_all(
+            4096,
+            &[(
+                "1008 7061 7373 776f 7264 0673 6563 7265 74",
+                &[("password", "secret")],
+                0,
+            )],
+        );
+        decode_all(4096, &[("82", &[(":method", "GET")], 0)]);
+    }
+
+    /// RFC 7541 Appendix C.3
+    #[test]
+    fn requests_without_huffman_coding() {
+        decode_all(
+            4096,
+            &[
+                (
+                    "8286 8441 0f77 7777 2e65 7861 6d70 6c65 2e63 6f6d",
+                    FIRST_REQUEST,
+                    57,
+                ),
+                ("8286 84be 5808 6e6f 2d63 6163 6865", SECOND_REQUEST, 110),
+                (
+                    "8287 85bf 400a 6375 7374 6f6d 2d6b 6579 0c63 7573 746f 6d2d 7661 6c75 65",
+                    THIRD_REQUEST,
+                    164,
+                ),
+            ],
+        );
+    }
+
+    /// RFC 7541 Appendix C.4
+    #[test]
+    fn requests_with_huffman_coding() {
+        let blocks = [
+            "8286 8441 8cf1 e3c2 e5f2 3a6b a0ab 90f4 ff",
+            "8286 84be 5886 a8eb 1064 9cbf",
+            "8287 85bf 4088 25a8 49e9 5ba9 7d7f 8925 a849 e95b b8e8 b4bf",
+        ];
+
+        decode_all(
+            4096,
+            &[
+                (blocks[0], FIRST_REQUEST, 57),
+                (blocks[1], SECOND_REQUEST, 110),
+                (blocks[2], THIRD_REQUEST, 164),
+            ],
+        );
+
+        // Indexing every field and Huffman coding every string that shrinks reproduces the examples exactly
+        let mut encoder = Encoder::new();
+
+        for (block, request) in blocks
+            .iter()
+            .zip([FIRST_REQUEST, SECOND_REQUEST, THIRD_REQUEST])
+        {
+            assert_eq!(encoder.encode(request.iter().copied()), hex(block));
+        }
+    }
+
+    /// RFC 7541 Appendix C.5, whose 256 byte table evicts entries
+    #[test]
+    fn responses_without_huffman_coding() {
+        decode_all(
+            256,
+            &[
+                (
+                    "4803 3330 3258 0770 7269 7661 7465 611d 4d6f 6e2c 2032 3120 4f63 7420 3230 3133
+                     2032 303a 3133 3a32 3120 474d 546e 1768 7474 7073 3a2f 2f77 7777 2e65 7861 6d70
+                     6c65 2e63 6f6d",
+                    FIRST_RESPONSE,
+                    222,
+                ),
+                ("4803 3330 37c1 c0bf", SECOND_RESPONSE, 222),
+                (
+                    "88c1 611d 4d6f 6e2c 2032 3120 4f63 7420 3230 3133 2032 303a 3133 3a32 3220 474d
+                     54c0 5a04 677a 6970 7738 666f 6f3d 4153 444a 4b48 514b 425a 584f 5157 454f 5049
+                     5541 5851 5745 4f49 553b 206d 6178 2d61 6765 3d33 3630 303b 2076 6572 7369 6f6e
+                     3d31",
+                    THIRD_RESPONSE,
+                    215,
+                ),
+            ],
+        );
+    }
+
+    /// RFC 7541 Appendix C.6, whose 256 byte table evicts entries
+    #[test]
+    fn responses_with_huffman_coding() {
+        decode_all(
+            256,
+            &[
+                (
+                    "4882 6402 5885 aec3 771a 4b61 96d0 7abe 9410 54d4 44a8 2005 9504 0b81 66e0 82a6
+                     2d1b ff6e 919d 29ad 1718 63c7 8f0b 97c8 e9ae 82ae 43d3",
+                    FIRST_RESPONSE,
+                    222,
+                ),
+                ("4883 640e ffc1 c0bf", SECOND_RESPONSE, 222),
+                (
+                    "88c1 6196 d07a be94 1054 d444 a820 0595 040b 8166 e084 a62d 1bff c05a 839b d9ab
+                     77ad 94e7 821d d7f2 e6c7 b335 dfdf cd5b 3960 d5af 2708 7f36 72c1 ab27 0fb5 291f
+                     9587 3160 65c0 03ed 4ee5 b106 3d50 07",
+                    THIRD_RESPONSE,
+                    215,
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn oversized_header_lists_are_refused() {
+        let block = hex("8286 8441 0f77 7777 2e65 7861 6d70 6c65 2e63 6f6d");
+
+        assert!(Decoder::new(4096).decode(&block, 200).unwrap().is_some());
+
+        let mut decoder = Decoder::new(4096);
+
+        assert_eq!(decoder.decode(&block, 100).unwrap(), None);
+
+        // The field the refused block added to the dynamic table is there for the next block to refer to
+        assert_eq!(
+            decoder.decode(&hex("be"), usize::MAX).unwrap(),
+            Some(vec![(
+                ":authority".to_string(),
+                "www.example.com".to_string()
+            )])
+        );
+    }
+
+    #[test]
+    fn truncated_blocks_are_refused() {
+        let block = hex("8286 8441 0f77 7777 2e65 7861 6d70 6c65 2e63 6f6d");
+
+        for length in 4..block.len() {
+            assert!(Decoder::new(4096)
+                .decode(&block[..length], usize::MAX)
+                .is_err());
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use crate::http::{header::HttpHeader, request::Request};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use error::{connection_error, ErrorCode, Http2Error};
+use std::io::Read;
+
+/// Errors and error codes of the HTTP/2 protocol
+pub mod error;
+
+/// Encoding and decoding of HTTP/2 frames
+pub mod frame;
+
+/// HPACK header compression, RFC 7541
+pub mod hpack;
+
+/// The Huffman code HPACK compresses strings with
+mod huffman;
+
+/// Server side of an HTTP/2 connection, dispatching every stream to the same handlers as HTTP/1.1
+pub mod connection;
+
+/// The first bytes a client sends on every HTTP/2 connection
+pub const PREFACE: &[u8; 24] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+
+/// Settings the server announces to HTTP/2 clients, RFC 9113 Section 6.5.2
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Settings {
+    /// Largest HPACK dynamic table the client may use when compressing headers
+    pub header_table_size: u32,
+    /// Number of streams a client may have open at once
+    pub max_concurrent_streams: u32,
+    /// Bytes a client may send on a stream, and on the connection, before waiting for a window update
+    pub initial_window_size: u32,
+    /// Largest frame payload the server accepts
+    pub max_frame_size: u32,
+    /// Largest uncompressed header list the server accepts, counting 32 extra bytes per field
+    pub max_header_list_size: u32,
+}
+
+impl Settings {
+    /// Values every endpoint assumes until the peer announces its own
+    pub const PROTOCOL_DEFAULTS: Settings = Settings {
+        header_table_size: 4096,
+        max_concurrent_streams: u32::MAX,
+        initial_window_size: 65_535,
+        max_frame_size: 16_384,
+        max_header_list_size: u32::MAX,
+    };
+
+    /// Identifier and value of every setting, as sent in a `SETTINGS` frame
+    #[must_use]
+    pub fn values(&self) -> Vec<(u16, u32)> {
+        vec![
+            (0x1, self.header_table_size),
+            (0x3, self.max_concurrent_streams),
+            (0x4, self.initial_window_size),
+            (0x5, self.max_frame_size),
+            (0x6, self.max_header_list_size),
+        ]
+    }
+
+    /// Updates a single setting received from the peer, ignoring unknown identifiers
+    /// # Errors
+    /// - If the value is out of the range allowed for the setting
+    pub fn apply(&mut self, identifier: u16, value: u32) -> Result<(), Http2Error> {
+        match identifier {
+            0x1 => self.header_table_size = value,
+            0x2 if value > 1 => {
+                return Err(connection_error(
+                    ErrorCode::ProtocolError,
+                    "SETTINGS_ENABLE_PUSH must be 0 or 1",
+                ));
+            }
+            0x3 => self.max_concurrent_streams = value,
+            0x4 if value > 0x7fff_ffff => {
+                return Err(connection_error(
+                    ErrorCode::FlowControlError,
+                    "SETTINGS_INITIAL_WINDOW_SIZE above the maximum window size",
+                ));
+            }
+            0x4 => self.initial_window_size = value,
+            0x5 if !(16_384..=16_777_215).contains(&value) => {
+                return Err(connection_error(
+                    ErrorCode::ProtocolError,
+                    "SETTINGS_MAX_FRAME_SIZE out of range",
+                ));
+            }
+            0x5 => self.max_frame_size = value,
+            0x6 => self.max_header_list_size = value,
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            header_table_size: 4096,
+            max_concurrent_streams: 100,
+            initial_window_size: 1 << 20,
+            max_frame_size: 16_384,
+            max_header_list_size: 1 << 16,
+        }
+    }
+}
+
+/// Reads from a new connection until its first bytes either form the HTTP/2 preface or stop matching it.
+///
+/// Returns whether the client speaks HTTP/2 with prior knowledge, along with every byte read,
+/// which belongs to the first request either way.
+/// # Errors
+/// - If reading from the connection failed
+pub fn sniff(connection: &mut impl Read) -> std::io::Result<(bool, Vec<u8>)> {
+    let mut buffered = vec![];
+    let mut buffer = [0; 1024];
+
+    while buffered.len() < PREFACE.len() && PREFACE.starts_with(&buffered) {
+        let read_byte_count = connection.read(&mut buffer)?;
+
+        if read_byte_count == 0 {
+            break;
+        }
+
+        buffered.extend(&buffer[..read_byte_count]);
+    }
+
+    Ok((buffered.starts_with(PREFACE), buffered))
+}
+
+/// The settings of an HTTP/1.1 request asking to switch to cleartext HTTP/2, RFC 7540 Section 3.2
+#[must_use]
+pub fn upgrade_settings(request: &Request) -> Option<Vec<(u16, u32)>> {
+    let upgrade = request.headers.iter().any(|header| {
+        matches!(header, HttpHeader::Upgrade(protocols)
+            if protocols.split(',').any(|protocol| protocol.trim().eq_ignore_ascii_case("h2c")))
+    });
+
+    if !upgrade {
+        return None;
+    }
+
+    request.headers.iter().find_map(|header| match header {
+        HttpHeader::Http2Settings(settings) => URL_SAFE_NO_PAD
+            .decode(settings.trim().trim_end_matches('='))
+            .ok()
+            .map(|payload| frame::parse_settings(&payload)),
+        _ => None,
+    })
+}
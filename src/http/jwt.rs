@@ -0,0 +1,818 @@
+use crate::http::{
+    auth::{Bearer, DEFAULT_REALM},
+    error::Error,
+    extract::FromRequest,
+    header::HttpHeader,
+    request::Request,
+    response::{Body, Response},
+    router::RequestHandler,
+};
+use base64::{
+    engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD},
+    Engine,
+};
+use ring::{
+    hmac,
+    signature::{
+        RsaPublicKeyComponents, UnparsedPublicKey, ECDSA_P256_SHA256_FIXED,
+        RSA_PKCS1_2048_8192_SHA256,
+    },
+};
+use serde::{de::DeserializeOwned, Deserialize};
+use std::{
+    marker::PhantomData,
+    ops::Deref,
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use thiserror::Error;
+
+/// Why a token was rejected, or a key could not be loaded
+#[derive(Debug, Error)]
+pub enum JwtError {
+    /// The token is not three base64url encoded parts, or its header or claims are not JSON objects
+    #[error("The token is malformed")]
+    Malformed,
+
+    /// The token is signed with an algorithm other than HS256, RS256 and ES256
+    #[error("The token is signed with an unsupported algorithm")]
+    UnsupportedAlgorithm,
+
+    /// The signature does not match any key configured for the algorithm of the token
+    #[error("The token signature is invalid")]
+    InvalidSignature,
+
+    /// The `exp` claim has passed
+    #[error("The token has expired")]
+    Expired,
+
+    /// The `nbf` claim has not been reached yet
+    #[error("The token is not valid yet")]
+    NotYetValid,
+
+    /// The `aud` claim does not name the expected audience
+    #[error("The token is meant for another audience")]
+    InvalidAudience,
+
+    /// The `iss` claim does not name the expected issuer
+    #[error("The token was issued by another issuer")]
+    InvalidIssuer,
+
+    /// The claims do not deserialize into the expected type
+    #[error("The token claims are invalid")]
+    InvalidClaims(#[source] serde_json::Error),
+
+    /// A key or key set could not be loaded
+    #[error("Invalid key: {0}")]
+    InvalidKey(String),
+
+    /// A key set file could not be read
+    #[error("Io error")]
+    Io(#[from] std::io::Error),
+}
+
+/// Signature algorithms a token may be signed with, as named by its `alg` header
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum Algorithm {
+    /// HMAC using SHA-256
+    #[serde(rename = "HS256")]
+    Hs256,
+    /// RSASSA-PKCS1-v1_5 using SHA-256
+    #[serde(rename = "RS256")]
+    Rs256,
+    /// ECDSA using P-256 and SHA-256
+    #[serde(rename = "ES256")]
+    Es256,
+}
+
+/// What a signature is verified with
+#[derive(Debug, Clone)]
+enum Material {
+    Hmac(hmac::Key),
+    Rsa { n: Vec<u8>, e: Vec<u8> },
+    Ec(Vec<u8>),
+}
+
+/// A key tokens are verified against, used only for tokens signed with its algorithm
+#[derive(Debug, Clone)]
+pub struct Key {
+    algorithm: Algorithm,
+    id: Option<String>,
+    material: Material,
+}
+
+impl Key {
+    /// Creates an HS256 key from a shared secret
+    #[must_use]
+    pub fn hs256(secret: impl AsRef<[u8]>) -> Self {
+        Self {
+            algorithm: Algorithm::Hs256,
+            id: None,
+            material: Material::Hmac(hmac::Key::new(hmac::HMAC_SHA256, secret.as_ref())),
+        }
+    }
+
+    /// Loads an RS256 or ES256 key from a PEM encoded public key, either `PUBLIC KEY` holding an RSA or P-256
+    /// key, or `RSA PUBLIC KEY`
+    /// # Errors
+    /// - If the PEM data is invalid or holds another kind of key
+    pub fn from_pem(pem: &str) -> Result<Self, JwtError> {
+        let invalid =
+            || JwtError::InvalidKey("Expected a PEM encoded RSA or P-256 public key".to_string());
+
+        let (label, der) = decode_pem(pem).ok_or_else(invalid)?;
+
+        let (algorithm, material) = match label {
+            "RSA PUBLIC KEY" => rsa_public_key(&der).map(|material| (Algorithm::Rs256, material)),
+            "PUBLIC KEY" => subject_public_key_info(&der),
+            _ => None,
+        }
+        .ok_or_else(invalid)?;
+
+        Ok(Self {
+            algorithm,
+            id: None,
+            material,
+        })
+    }
+
+    /// Sets the key ID, after which the key is only used for tokens naming it in their `kid` header
+    #[must_use]
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+
+        self
+    }
+
+    /// Returns the algorithm of the tokens the key verifies
+    #[must_use]
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    /// Loads the keys of a JSON Web Key Set, RFC 7517 Section 5, skipping keys of other kinds or uses
+    /// # Errors
+    /// - If the set is not valid JSON, or has no usable key
+    pub fn from_jwks(jwks: &str) -> Result<Vec<Self>, JwtError> {
+        let set: KeySet =
+            serde_json::from_str(jwks).map_err(|error| JwtError::InvalidKey(error.to_string()))?;
+
+        let keys: Vec<Self> = set.keys.into_iter().filter_map(Jwk::into_key).collect();
+
+        if keys.is_empty() {
+            return Err(JwtError::InvalidKey(
+                "The key set has no usable key".to_string(),
+            ));
+        }
+
+        Ok(keys)
+    }
+
+    fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+        match &self.material {
+            Material::Hmac(key) => hmac::verify(key, message, signature).is_ok(),
+            Material::Rsa { n, e } => RsaPublicKeyComponents { n, e }
+                .verify(&RSA_PKCS1_2048_8192_SHA256, message, signature)
+                .is_ok(),
+            Material::Ec(point) => UnparsedPublicKey::new(&ECDSA_P256_SHA256_FIXED, point)
+                .verify(message, signature)
+                .is_ok(),
+        }
+    }
+}
+
+/// A JSON Web Key Set
+#[derive(Deserialize)]
+struct KeySet {
+    keys: Vec<Jwk>,
+}
+
+/// A JSON Web Key, with the members of the key types that are supported
+#[derive(Deserialize)]
+struct Jwk {
+    kty: String,
+    kid: Option<String>,
+    alg: Option<String>,
+    #[serde(rename = "use")]
+    usage: Option<String>,
+    k: Option<String>,
+    n: Option<String>,
+    e: Option<String>,
+    crv: Option<String>,
+    x: Option<String>,
+    y: Option<String>,
+}
+
+impl Jwk {
+    fn into_key(self) -> Option<Key> {
+        let decode = |member: Option<String>| URL_SAFE_NO_PAD.decode(member?).ok();
+
+        if self.usage.as_deref().is_some_and(|usage| usage != "sig") {
+            return None;
+        }
+
+        let (algorithm, material) = match (self.kty.as_str(), self.alg.as_deref()) {
+            ("oct", None | Some("HS256")) => (
+                Algorithm::Hs256,
+                Material::Hmac(hmac::Key::new(hmac::HMAC_SHA256, &decode(self.k)?)),
+            ),
+            ("RSA", None | Some("RS256")) => (
+                Algorithm::Rs256,
+                Material::Rsa {
+                    n: unsigned(&decode(self.n)?).to_vec(),
+                    e: unsigned(&decode(self.e)?).to_vec(),
+                },
+            ),
+            ("EC", None | Some("ES256")) if self.crv.as_deref() == Some("P-256") => {
+                let (x, y) = (decode(self.x)?, decode(self.y)?);
+
+                if x.len() != 32 || y.len() != 32 {
+                    return None;
+                }
+
+                // Uncompressed point, SEC 1 Section 2.3.3
+                (
+                    Algorithm::Es256,
+                    Material::Ec([&[4], &x[..], &y[..]].concat()),
+                )
+            }
+            _ => return None,
+        };
+
+        Some(Key {
+            algorithm,
+            id: self.kid,
+            material,
+        })
+    }
+}
+
+/// Returns the label and contents of the first PEM block
+fn decode_pem(pem: &str) -> Option<(&str, Vec<u8>)> {
+    let rest = &pem[pem.find("-----BEGIN ")? + "-----BEGIN ".len()..];
+    let (label, rest) = rest.split_once("-----")?;
+    let (contents, _) = rest.split_once("-----END ")?;
+
+    let contents: String = contents.split_whitespace().collect();
+
+    Some((label, STANDARD.decode(contents).ok()?))
+}
+
+/// Splits a DER element off the front of `input`, returning its tag, its contents and what follows it
+fn der_element(input: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let (&tag, rest) = input.split_first()?;
+    let (&first, rest) = rest.split_first()?;
+
+    let (length, rest) = if first < 0x80 {
+        (usize::from(first), rest)
+    } else {
+        let count = usize::from(first & 0x7f);
+
+        if count == 0 || count > 4 || rest.len() < count {
+            return None;
+        }
+
+        let (length, rest) = rest.split_at(count);
+
+        (
+            length
+                .iter()
+                .fold(0, |length, byte| length << 8 | usize::from(*byte)),
+            rest,
+        )
+    };
+
+    (rest.len() >= length).then(|| (tag, &rest[..length], &rest[length..]))
+}
+
+const SEQUENCE: u8 = 0x30;
+const INTEGER: u8 = 0x02;
+const BIT_STRING: u8 = 0x03;
+const OBJECT_IDENTIFIER: u8 = 0x06;
+
+/// `rsaEncryption`, RFC 8017 Appendix A.1
+const RSA_ENCRYPTION: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x01];
+/// `id-ecPublicKey`, RFC 5480 Section 2.1.1
+const EC_PUBLIC_KEY: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01];
+/// `secp256r1`, RFC 5480 Section 2.1.1.1
+const SECP256R1: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+
+/// Reads a `SubjectPublicKeyInfo`, RFC 5280 Section 4.1, holding an RSA or P-256 key
+fn subject_public_key_info(der: &[u8]) -> Option<(Algorithm, Material)> {
+    let (SEQUENCE, info, _) = der_element(der)? else {
+        return None;
+    };
+    let (SEQUENCE, identifier, rest) = der_element(info)? else {
+        return None;
+    };
+    let (OBJECT_IDENTIFIER, algorithm, parameters) = der_element(identifier)? else {
+        return None;
+    };
+    // The key bits never leave bits of their last byte unused
+    let (BIT_STRING, [0, key @ ..], _) = der_element(rest)? else {
+        return None;
+    };
+
+    match algorithm {
+        RSA_ENCRYPTION => rsa_public_key(key).map(|material| (Algorithm::Rs256, material)),
+        EC_PUBLIC_KEY => match der_element(parameters)? {
+            (OBJECT_IDENTIFIER, SECP256R1, _) => {
+                Some((Algorithm::Es256, Material::Ec(key.to_vec())))
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Reads an `RSAPublicKey`, RFC 8017 Appendix A.1.1
+fn rsa_public_key(der: &[u8]) -> Option<Material> {
+    let (SEQUENCE, key, _) = der_element(der)? else {
+        return None;
+    };
+    let (INTEGER, n, rest) = der_element(key)? else {
+        return None;
+    };
+    let (INTEGER, e, _) = der_element(rest)? else {
+        return None;
+    };
+
+    Some(Material::Rsa {
+        n: unsigned(n).to_vec(),
+        e: unsigned(e).to_vec(),
+    })
+}
+
+/// Strips the leading zeros DER and JWKs may keep in front of big-endian integers
+fn unsigned(integer: &[u8]) -> &[u8] {
+    let start = integer
+        .iter()
+        .position(|byte| *byte != 0)
+        .unwrap_or(integer.len());
+
+    &integer[start..]
+}
+
+/// The JOSE header of a token, RFC 7515 Section 4
+#[derive(Deserialize)]
+struct Header {
+    alg: String,
+    kid: Option<String>,
+}
+
+/// The registered claims that are validated, RFC 7519 Section 4.1
+#[derive(Deserialize)]
+struct Registered {
+    exp: Option<f64>,
+    nbf: Option<f64>,
+    iss: Option<String>,
+    aud: Option<Audience>,
+}
+
+/// The `aud` claim, which is a single audience or a list of them
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Audience {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// Verifies the signature and registered claims of tokens, and deserializes their claims
+#[derive(Debug, Clone)]
+pub struct Validator {
+    keys: Vec<Key>,
+    leeway: Duration,
+    audience: Option<String>,
+    issuer: Option<String>,
+}
+
+impl Validator {
+    /// Accepts tokens signed with any of the keys, allowing a minute of clock skew
+    #[must_use]
+    pub fn new(keys: impl IntoIterator<Item = Key>) -> Self {
+        Self {
+            keys: keys.into_iter().collect(),
+            leeway: Duration::from_secs(60),
+            audience: None,
+            issuer: None,
+        }
+    }
+
+    /// Accepts tokens signed with any of the keys in a JSON Web Key Set file
+    /// # Errors
+    /// - If the file cannot be read or holds no usable key
+    pub fn jwks_file(path: impl AsRef<Path>) -> Result<Self, JwtError> {
+        Ok(Self::new(Key::from_jwks(&std::fs::read_to_string(path)?)?))
+    }
+
+    /// Sets the clock skew tolerated when checking `exp` and `nbf`
+    #[must_use]
+    pub fn leeway(mut self, leeway: Duration) -> Self {
+        self.leeway = leeway;
+
+        self
+    }
+
+    /// Only accepts tokens whose `aud` claim names the audience
+    #[must_use]
+    pub fn audience(mut self, audience: impl Into<String>) -> Self {
+        self.audience = Some(audience.into());
+
+        self
+    }
+
+    /// Only accepts tokens whose `iss` claim is the issuer
+    #[must_use]
+    pub fn issuer(mut self, issuer: impl Into<String>) -> Self {
+        self.issuer = Some(issuer.into());
+
+        self
+    }
+
+    /// Verifies a token and returns its claims, `exp` and `nbf` are checked when present
+    /// # Errors
+    /// - If the token is malformed, its signature invalid, or a registered claim rejects it
+    pub fn validate<C: DeserializeOwned>(&self, token: &str) -> Result<C, JwtError> {
+        let mut parts = token.split('.');
+
+        let (Some(header), Some(payload), Some(signature), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(JwtError::Malformed);
+        };
+
+        // The signature covers the header and payload as they were encoded
+        let signed = &token[..header.len() + 1 + payload.len()];
+
+        let decode = |part: &str| {
+            URL_SAFE_NO_PAD
+                .decode(part)
+                .map_err(|_| JwtError::Malformed)
+        };
+
+        let header: Header =
+            serde_json::from_slice(&decode(header)?).map_err(|_| JwtError::Malformed)?;
+
+        // Keys are bound to one algorithm, so a token cannot choose how its signature is checked
+        let algorithm: Algorithm = serde_json::from_value(serde_json::Value::String(header.alg))
+            .map_err(|_| JwtError::UnsupportedAlgorithm)?;
+
+        let signature = decode(signature)?;
+
+        let verified = self
+            .keys
+            .iter()
+            .filter(|key| key.algorithm == algorithm)
+            .filter(|key| match (&key.id, &header.kid) {
+                (Some(id), Some(kid)) => id == kid,
+                _ => true,
+            })
+            .any(|key| key.verify(signed.as_bytes(), &signature));
+
+        if !verified {
+            return Err(JwtError::InvalidSignature);
+        }
+
+        let claims: serde_json::Value =
+            serde_json::from_slice(&decode(payload)?).map_err(|_| JwtError::Malformed)?;
+
+        self.check(&claims)?;
+
+        serde_json::from_value(claims).map_err(JwtError::InvalidClaims)
+    }
+
+    fn check(&self, claims: &serde_json::Value) -> Result<(), JwtError> {
+        let registered = Registered::deserialize(claims).map_err(JwtError::InvalidClaims)?;
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
+        let leeway = self.leeway.as_secs_f64();
+
+        if registered.exp.is_some_and(|exp| now >= exp + leeway) {
+            return Err(JwtError::Expired);
+        }
+
+        if registered.nbf.is_some_and(|nbf| now + leeway < nbf) {
+            return Err(JwtError::NotYetValid);
+        }
+
+        if let Some(issuer) = &self.issuer {
+            if registered.iss.as_ref() != Some(issuer) {
+                return Err(JwtError::InvalidIssuer);
+            }
+        }
+
+        if let Some(audience) = &self.audience {
+            let named = match &registered.aud {
+                Some(Audience::One(aud)) => aud == audience,
+                Some(Audience::Many(auds)) => auds.contains(audience),
+                None => false,
+            };
+
+            if !named {
+                return Err(JwtError::InvalidAudience);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Claims of the token a request was authenticated with, attached by a [`Jwt`] handler
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Claims<C>(pub C);
+
+impl<C: Clone + Send + Sync + 'static> FromRequest for Claims<C> {
+    fn from_request(request: &Request) -> Result<Self, Error> {
+        request
+            .extensions
+            .get::<Claims<C>>()
+            .cloned()
+            .ok_or_else(|| {
+                Error::MissingExtension(
+                    "No claims of this type, the route is not behind a matching Jwt handler"
+                        .to_string(),
+                )
+            })
+    }
+}
+
+impl<C> Deref for Claims<C> {
+    type Target = C;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// Request handler that passes requests on to another handler only with a valid JWT Bearer token.
+///
+/// The claims of the token are attached to the request as [`Claims<C>`], for the handler to extract.
+/// Requests without a token are challenged with `WWW-Authenticate: Bearer`, invalid tokens are answered
+/// with 401 Unauthorized and `error="invalid_token"`, RFC 6750 Section 3.
+pub struct Jwt<H, C> {
+    handler: H,
+    validator: Validator,
+    claims: PhantomData<fn() -> C>,
+}
+
+impl<H: RequestHandler, C: DeserializeOwned + Clone + Send + Sync + 'static> Jwt<H, C> {
+    /// Guards `handler` with tokens the validator accepts
+    #[must_use]
+    pub fn new(handler: H, validator: Validator) -> Self {
+        Self {
+            handler,
+            validator,
+            claims: PhantomData,
+        }
+    }
+}
+
+impl<H, C> RequestHandler for Jwt<H, C>
+where
+    H: RequestHandler,
+    C: DeserializeOwned + Clone + Send + Sync + 'static,
+{
+    fn handle(&self, request: Request) -> Result<Response<Box<dyn Body>>, Error> {
+        let mut request = request;
+
+        let Bearer(token) = Bearer::from_request(&request)?;
+
+        let claims: C = self
+            .validator
+            .validate(&token)
+            .map_err(|error| Error::Unauthorized {
+                challenges: vec![HttpHeader::WwwAuthenticate(format!(
+                    "Bearer realm=\"{DEFAULT_REALM}\", error=\"invalid_token\", error_description=\"{error}\""
+                ))],
+            })?;
+
+        request.extensions.insert(Claims(claims));
+
+        self.handler.handle(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Claims, Jwt, JwtError, Key, Validator};
+    use crate::http::{
+        header::HttpHeader, method::Method, response::Response, route, router::routes,
+        status::HttpStatus, testing::TestClient,
+    };
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+    use ring::hmac;
+    use serde_json::{json, Value};
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    /// Claims of the examples in RFC 7515 Appendix A, which expired in March 2011
+    const PAYLOAD: &str = "eyJpc3MiOiJqb2UiLA0KICJleHAiOjEzMDA4MTkzODAsDQogImh0dHA6Ly9leGFtcGxlLmNvbS9pc19yb290Ijp0cnVlfQ";
+
+    /// RFC 7515 Appendix A.1
+    const HS256: &str = "eyJ0eXAiOiJKV1QiLA0KICJhbGciOiJIUzI1NiJ9";
+    const HS256_SIGNATURE: &str = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+    const HS256_KEY: &str = r#"{"keys":[{"kty":"oct","k":"AyM1SysPpbyDfgZld3umj1qzKObwVMkoqQ-EstJQLr_T-1qS0gZH75aKtMN3Yj0iPS4hcgUuTwjAzZr1Z9CAow"}]}"#;
+
+    /// RFC 7515 Appendix A.2
+    const RS256: &str = "eyJhbGciOiJSUzI1NiJ9";
+    const RS256_SIGNATURE: &str = "cC4hiUPoj9Eetdgtv3hF80EGrhuB__dzERat0XF9g2VtQgr9PJbu3XOiZj5RZmh7AAuHIm4Bh-0Qc_lF5YKt_O8W2Fp5jujGbds9uJdbF9CUAr7t1dnZcAcQjbKBYNX4BAynRFdiuB--f_nZLgrnbyTyWzO75vRK5h6xBArLIARNPvkSjtQBMHlb1L07Qe7K0GarZRmB_eSN9383LcOLn6_dO--xi12jzDwusC-eOkHWEsqtFZESc6BfI7noOPqvhJ1phCnvWh6IeYI2w9QOYEUipUTI8np6LbgGY9Fs98rqVt5AXLIhWkWywlVmtVrBp0igcN_IoypGlUPQGe77Rw";
+    const RS256_KEY: &str = r#"{"keys":[{"kty":"RSA","e":"AQAB","n":"ofgWCuLjybRlzo0tZWJjNiuSfb4p4fAkd_wWJcyQoTbji9k0l8W26mPddxHmfHQp-Vaw-4qPCJrcS2mJPMEzP1Pt0Bm4d4QlL-yRT-SFd2lZS-pCgNMsD1W_YpRPEwOWvG6b32690r2jZ47soMZo9wGzjb_7OMg0LOL-bSf63kpaSHSXndS5z5rexMdbBYUsLA9e-KXBdQOS-UTo7WTBEMa2R2CapHg665xsmtdVMTBQY4uDZlxvb3qCo5ZwKh9kG4LT6_I5IhlJH7aGhyxXFvUK-DWNmoudF8NAco9_h9iaGNj8q2ethFkMLs91kzk2PAcDTW9gb54h4FRWyuXpoQ"}]}"#;
+
+    /// RFC 7515 Appendix A.3
+    const ES256: &str = "eyJhbGciOiJFUzI1NiJ9";
+    const ES256_SIGNATURE: &str =
+        "DtEhU3ljbEg8L38VWAfUAqOyKAM6-Xx-F4GawxaepmXFCgfTjDxw5djxLa8ISlSApmWQxfKTUJqPP3-Kg6NU1Q";
+    const ES256_KEY: &str = r#"{"keys":[{"kty":"EC","crv":"P-256","x":"f83OJ3D2xF1Bg8vub9tLe1gHMzV76e8Tus9uPHvRVEU","y":"x_FEzRu9m36HLN_tue659LNpXW6pCyStikYjKIWI5a0"}]}"#;
+
+    const SECRET: &[u8] = b"a secret shared with the issuer";
+
+    fn example(header: &str, signature: &str) -> String {
+        format!("{header}.{PAYLOAD}.{signature}")
+    }
+
+    /// Accepts the examples despite their age
+    fn validator(jwks: &str) -> Validator {
+        Validator::new(Key::from_jwks(jwks).unwrap()).leeway(Duration::from_secs(u32::MAX.into()))
+    }
+
+    fn sign(claims: &Value) -> String {
+        let header = URL_SAFE_NO_PAD.encode(r#"{"alg":"HS256","typ":"JWT"}"#);
+        let payload = URL_SAFE_NO_PAD.encode(claims.to_string());
+        let signed = format!("{header}.{payload}");
+        let tag = hmac::sign(
+            &hmac::Key::new(hmac::HMAC_SHA256, SECRET),
+            signed.as_bytes(),
+        );
+
+        format!("{signed}.{}", URL_SAFE_NO_PAD.encode(tag))
+    }
+
+    fn in_an_hour() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + 3600
+    }
+
+    #[test]
+    fn rfc_7515_examples() {
+        let expected = json!({
+            "iss": "joe",
+            "exp": 1_300_819_380,
+            "http://example.com/is_root": true,
+        });
+
+        for (token, jwks) in [
+            (example(HS256, HS256_SIGNATURE), HS256_KEY),
+            (example(RS256, RS256_SIGNATURE), RS256_KEY),
+            (example(ES256, ES256_SIGNATURE), ES256_KEY),
+        ] {
+            assert_eq!(validator(jwks).validate::<Value>(&token).unwrap(), expected);
+            assert!(matches!(
+                Validator::new(Key::from_jwks(jwks).unwrap()).validate::<Value>(&token),
+                Err(JwtError::Expired)
+            ));
+        }
+    }
+
+    #[test]
+    fn keys_only_verify_their_own_algorithm() {
+        let keys = [HS256_KEY, RS256_KEY, ES256_KEY]
+            .into_iter()
+            .flat_map(|jwks| Key::from_jwks(jwks).unwrap());
+        let all = Validator::new(keys).leeway(Duration::from_secs(u32::MAX.into()));
+
+        assert!(all
+            .validate::<Value>(&example(ES256, ES256_SIGNATURE))
+            .is_ok());
+
+        // The RSA signature presented as HMAC, and the HMAC key presented as ES256
+        assert!(matches!(
+            validator(HS256_KEY).validate::<Value>(&example(HS256, RS256_SIGNATURE)),
+            Err(JwtError::InvalidSignature)
+        ));
+        assert!(matches!(
+            validator(HS256_KEY).validate::<Value>(&example(ES256, HS256_SIGNATURE)),
+            Err(JwtError::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn tampered_and_malformed_tokens_are_refused() {
+        let validator = validator(HS256_KEY);
+        let none = URL_SAFE_NO_PAD.encode(r#"{"alg":"none"}"#);
+
+        assert!(matches!(
+            validator.validate::<Value>(&example(HS256, &HS256_SIGNATURE.replace('d', "e"))),
+            Err(JwtError::InvalidSignature)
+        ));
+        assert!(matches!(
+            validator.validate::<Value>(&format!("{none}.{PAYLOAD}.")),
+            Err(JwtError::UnsupportedAlgorithm)
+        ));
+
+        for token in ["", "a.b", "a.b.c.d", "!.!.!", &format!("{HS256}.{PAYLOAD}")] {
+            assert!(matches!(
+                validator.validate::<Value>(token),
+                Err(JwtError::Malformed)
+            ));
+        }
+    }
+
+    #[test]
+    fn registered_claims() {
+        let validator = Validator::new([Key::hs256(SECRET)])
+            .audience("api")
+            .issuer("hyperion");
+        let exp = in_an_hour();
+
+        assert!(validator
+            .validate::<Value>(&sign(
+                &json!({"exp": exp, "aud": ["web", "api"], "iss": "hyperion"})
+            ))
+            .is_ok());
+        assert!(matches!(
+            validator
+                .validate::<Value>(&sign(&json!({"exp": exp, "aud": "web", "iss": "hyperion"}))),
+            Err(JwtError::InvalidAudience)
+        ));
+        assert!(matches!(
+            validator.validate::<Value>(&sign(&json!({"exp": exp, "aud": "api", "iss": "joe"}))),
+            Err(JwtError::InvalidIssuer)
+        ));
+        assert!(matches!(
+            validator
+                .validate::<Value>(&sign(&json!({"nbf": exp, "aud": "api", "iss": "hyperion"}))),
+            Err(JwtError::NotYetValid)
+        ));
+    }
+
+    #[route]
+    fn me(Claims(claims): Claims<Value>) -> Response<String> {
+        let subject = claims["sub"].as_str().unwrap_or_default().to_string();
+
+        Response::new(subject, vec![], HttpStatus::Ok)
+    }
+
+    fn client() -> TestClient {
+        TestClient::new(crate::http::router::Router::from([(
+            crate::http::router::RequestIdentifier {
+                method: Method::Get,
+                path: "/me".to_string(),
+            },
+            Box::new(Jwt::<_, Value>::new(
+                me,
+                Validator::new([Key::hs256(SECRET)]),
+            )) as Box<dyn crate::http::router::RequestHandler>,
+        )]))
+    }
+
+    fn bearer(token: &str) -> Vec<HttpHeader> {
+        vec![HttpHeader::Authorization(format!("Bearer {token}"))]
+    }
+
+    #[test]
+    fn valid_tokens_pass_their_claims_on() {
+        let token = sign(&json!({"sub": "alice", "exp": in_an_hour()}));
+
+        client()
+            .request(Method::Get, "/me", bearer(&token), None)
+            .assert_status(HttpStatus::Ok)
+            .assert_body("alice");
+    }
+
+    #[test]
+    fn claims_need_a_jwt_handler() {
+        let unguarded = me;
+
+        TestClient::new(routes!(Get "/me" -> unguarded,))
+            .get("/me")
+            .assert_status(HttpStatus::InternalServerError)
+            .assert_body("No claims of this type, the route is not behind a matching Jwt handler");
+    }
+
+    #[test]
+    fn requests_without_valid_tokens_are_challenged() {
+        let response = client().get("/me");
+
+        response.assert_status(HttpStatus::Unauthorized);
+        assert!(response
+            .header("WWW-Authenticate")
+            .is_some_and(|challenge| challenge.starts_with("Bearer")));
+
+        let response = client().request(
+            Method::Get,
+            "/me",
+            bearer(&example(HS256, HS256_SIGNATURE)),
+            None,
+        );
+
+        response.assert_status(HttpStatus::Unauthorized);
+        assert!(response
+            .header("WWW-Authenticate")
+            .is_some_and(|challenge| challenge.contains("error=\"invalid_token\"")));
+    }
+}
@@ -0,0 +1,324 @@
+use crate::http::{error::Error, listener::Connection};
+use std::{
+    io::{ErrorKind, Read},
+    time::{Duration, Instant},
+};
+
+/// How long the server waits on each part of an exchange with a client
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timeouts {
+    /// Time a client has to send the request line and headers, counted from when it connected.
+    /// HTTP/2 clients have as long to finish each header block, and to start a stream while none is open
+    pub header_read: Duration,
+    /// Time a client has to send the body, counted from when the headers arrived
+    pub body_read: Duration,
+    /// Time a handler may take before the client is answered with 503 Service Unavailable, `None` waits for it.
+    ///
+    /// The server is what ran out of time, which rules out 408 Request Timeout, meant for clients slow to send
+    /// their request, and 504 Gateway Timeout, meant for a server further upstream.
+    ///
+    /// A blocking handler cannot be interrupted, so one that runs out of time keeps running to the end and its
+    /// response is discarded. To be waited on, every handler of the blocking [`Server`](super::server::Server)
+    /// runs on a thread of its own while this is set. Asynchronous handlers are dropped at their next `.await`.
+    pub handler: Option<Duration>,
+    /// Time a single write to the client may block
+    pub write: Duration,
+    /// Bytes per second a client has to keep up once it started sending the headers or the body, after a second of grace
+    pub min_rate: Option<u32>,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self {
+            header_read: Duration::from_secs(10),
+            body_read: Duration::from_secs(30),
+            handler: None,
+            write: Duration::from_secs(30),
+            min_rate: Some(500),
+        }
+    }
+}
+
+/// How large a request the server accepts, so a single client cannot exhaust its memory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    /// Bytes the request line may take, longer ones are answered with 414 URI Too Long
+    pub request_line: usize,
+    /// Header fields a request may have, more are answered with 431 Request Header Fields Too Large
+    pub header_count: usize,
+    /// Bytes all header fields may take together, more are answered with 431 Request Header Fields Too Large
+    pub header_bytes: usize,
+    /// Bytes the body may take, larger ones are answered with 413 Payload Too Large
+    pub body: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            request_line: 8 * 1024,
+            header_count: 100,
+            header_bytes: 64 * 1024,
+            body: 16 * 1024 * 1024,
+        }
+    }
+}
+
+impl Limits {
+    /// Checks the part of a request head received so far.
+    ///
+    /// Checking every partial head stops the read as soon as a limit is exceeded, instead of buffering the rest
+    pub(crate) fn check_head(&self, head: &[u8]) -> Result<(), Error> {
+        let line_end = head.windows(2).position(|window| window == b"\r\n");
+
+        if line_end.unwrap_or(head.len()) > self.request_line {
+            return Err(Error::URITooLong(format!(
+                "The request line exceeds {} bytes",
+                self.request_line
+            )));
+        }
+
+        let headers = line_end.map_or(&[][..], |line_end| &head[line_end + 2..]);
+
+        if headers.len() > self.header_bytes {
+            return Err(Error::RequestHeaderFieldsTooLarge(format!(
+                "The headers exceed {} bytes",
+                self.header_bytes
+            )));
+        }
+
+        // The line being received counts already, the empty line ending the head does not
+        let header_count = headers
+            .split(|byte| *byte == b'\n')
+            .filter(|line| !matches!(line, [] | [b'\r']))
+            .count();
+
+        if header_count > self.header_count {
+            return Err(Error::RequestHeaderFieldsTooLarge(format!(
+                "The request has more than {} headers",
+                self.header_count
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Checks the announced length of a body before any room is made for it
+    pub(crate) fn check_body(&self, content_length: usize) -> Result<(), Error> {
+        if content_length > self.body {
+            return Err(Error::PayloadTooLarge(format!(
+                "The body exceeds {} bytes",
+                self.body
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Time a client is given before the minimum transfer rate applies
+const RATE_GRACE: Duration = Duration::from_secs(1);
+
+/// How long a client has left to send a request, or part of one, given a deadline and the minimum transfer rate
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Pace {
+    until: Instant,
+    min_rate: Option<u32>,
+    first_byte: Option<Instant>,
+    received: u64,
+}
+
+impl Pace {
+    /// Gives the client until `timeout` after `started`
+    pub(crate) fn new(started: Instant, timeout: Duration, min_rate: Option<u32>) -> Self {
+        Self {
+            until: started + timeout,
+            min_rate: min_rate.filter(|rate| *rate > 0),
+            first_byte: None,
+            received: 0,
+        }
+    }
+
+    /// Returns how long to wait for more, failing with [`TimedOut`](ErrorKind::TimedOut) once the client is too slow
+    pub(crate) fn remaining(&self) -> std::io::Result<Duration> {
+        // Once sending, the client is too slow if more has not arrived by the time what it sent was due at the minimum rate.
+        // Until then it may idle, as browsers do with connections they open ahead of time
+        let until = match (self.min_rate, self.first_byte) {
+            (Some(rate), Some(first_byte)) => {
+                let due = Duration::from_millis(self.received * 1000 / u64::from(rate));
+
+                self.until.min(first_byte + due.max(RATE_GRACE))
+            }
+            _ => self.until,
+        };
+
+        let remaining = until.saturating_duration_since(Instant::now());
+
+        if remaining.is_zero() {
+            return Err(too_slow());
+        }
+
+        Ok(remaining)
+    }
+
+    /// Counts bytes that arrived from the client
+    pub(crate) fn receive(&mut self, byte_count: usize) {
+        self.first_byte.get_or_insert_with(Instant::now);
+        self.received += byte_count as u64;
+    }
+}
+
+/// Reads from a connection until a deadline, failing with [`TimedOut`](ErrorKind::TimedOut) once it has passed
+/// or once the client falls below the minimum transfer rate
+pub(crate) struct Deadline<'a, C: Connection> {
+    connection: &'a mut C,
+    pace: Pace,
+}
+
+impl<'a, C: Connection> Deadline<'a, C> {
+    /// Reads from `connection` until `timeout` after `started`
+    pub(crate) fn new(
+        connection: &'a mut C,
+        started: Instant,
+        timeout: Duration,
+        min_rate: Option<u32>,
+    ) -> Self {
+        Self {
+            connection,
+            pace: Pace::new(started, timeout, min_rate),
+        }
+    }
+}
+
+impl<C: Connection> Read for Deadline<'_, C> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.connection
+            .set_read_timeout(Some(self.pace.remaining()?))?;
+
+        match self.connection.read(buf) {
+            Ok(read_byte_count) => {
+                self.pace.receive(read_byte_count);
+
+                Ok(read_byte_count)
+            }
+            Err(error) if matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                Err(too_slow())
+            }
+            Err(error) => Err(error),
+        }
+    }
+}
+
+/// The error of a read the client did not send enough for in time
+pub(crate) fn too_slow() -> std::io::Error {
+    std::io::Error::new(ErrorKind::TimedOut, "The request was not received in time")
+}
+
+/// Turns a read that ran out of time into a 408 Request Timeout
+pub(crate) fn timed_out(error: std::io::Error) -> Error {
+    if error.kind() == ErrorKind::TimedOut {
+        Error::RequestTimeout(error.to_string())
+    } else {
+        error.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Limits, Timeouts};
+    use crate::http::{
+        error::Error, request::Request, response::Response, route, router::routes,
+        status::HttpStatus, testing::TestClient,
+    };
+    use std::time::Duration;
+
+    const LIMITS: Limits = Limits {
+        request_line: 32,
+        header_count: 2,
+        header_bytes: 64,
+        body: 16,
+    };
+
+    #[route]
+    fn echo(request: &Request) -> Response<String> {
+        let body = String::from_utf8_lossy(request.body.as_deref().unwrap_or_default());
+
+        Response::new(body.into_owned(), vec![], HttpStatus::Ok)
+    }
+
+    #[route]
+    fn slow() -> Response<String> {
+        std::thread::sleep(Duration::from_millis(200));
+
+        Response::new(String::new(), vec![], HttpStatus::Ok)
+    }
+
+    fn client() -> TestClient {
+        TestClient::new(routes!(Post "/" -> echo, Get "/slow" -> slow,)).limits(LIMITS)
+    }
+
+    #[test]
+    fn partial_heads_are_checked_as_they_arrive() {
+        assert!(LIMITS.check_head(b"GET / HTTP/1.1\r\nHost: a\r\n").is_ok());
+        assert!(matches!(
+            LIMITS.check_head(&[b'a'; 33]),
+            Err(Error::URITooLong(_))
+        ));
+        assert!(matches!(
+            LIMITS.check_head(b"GET / HTTP/1.1\r\nA: 1\r\nB: 2\r\nC"),
+            Err(Error::RequestHeaderFieldsTooLarge(_))
+        ));
+        assert!(matches!(
+            LIMITS.check_head(format!("GET / HTTP/1.1\r\nA: {}", "a".repeat(62)).as_bytes()),
+            Err(Error::RequestHeaderFieldsTooLarge(_))
+        ));
+        assert!(matches!(
+            LIMITS.check_body(17),
+            Err(Error::PayloadTooLarge(_))
+        ));
+    }
+
+    #[test]
+    fn requests_within_the_limits_are_served() {
+        client()
+            .post("/", "sixteen bytes...")
+            .assert_status(HttpStatus::Ok)
+            .assert_body("sixteen bytes...");
+    }
+
+    #[test]
+    fn long_request_lines_are_refused() {
+        client()
+            .post(&format!("/{}", "a".repeat(32)), "")
+            .assert_status(HttpStatus::URITooLong);
+    }
+
+    #[test]
+    fn large_heads_are_refused() {
+        client()
+            .send_raw("POST / HTTP/1.1\r\nA: 1\r\nB: 2\r\nC: 3\r\n\r\n")
+            .assert_status(HttpStatus::RequestHeaderFieldsTooLarge);
+        client()
+            .send_raw(format!("POST / HTTP/1.1\r\nA: {}\r\n\r\n", "a".repeat(64)))
+            .assert_status(HttpStatus::RequestHeaderFieldsTooLarge);
+    }
+
+    #[test]
+    fn large_bodies_are_refused_before_they_are_read() {
+        client()
+            .send_raw("POST / HTTP/1.1\r\nContent-Length: 1000000\r\n\r\n")
+            .assert_status(HttpStatus::PayloadTooLarge);
+    }
+
+    #[test]
+    fn slow_handlers_are_answered_for() {
+        let timeouts = Timeouts {
+            handler: Some(Duration::from_millis(20)),
+            ..Timeouts::default()
+        };
+
+        client()
+            .timeouts(timeouts)
+            .get("/slow")
+            .assert_status(HttpStatus::ServiceUnavailable);
+    }
+}
@@ -0,0 +1,538 @@
+use std::{
+    io::{Read, Write},
+    net::{Shutdown, SocketAddr, TcpListener, TcpStream},
+    time::Duration,
+};
+
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+
+/// A bidirectional byte stream between the server and a single client
+pub trait Connection: Read + Write + Send + 'static {
+    /// Shuts down both the read and write halves of the connection
+    /// # Errors
+    /// - If the underlying socket could not be shut down
+    fn shutdown(&mut self) -> std::io::Result<()>;
+
+    /// Returns the address of the client, if the transport has one
+    fn peer_addr(&self) -> Option<SocketAddr>;
+
+    /// Makes reads fail with [`WouldBlock`](std::io::ErrorKind::WouldBlock) or [`TimedOut`](std::io::ErrorKind::TimedOut)
+    /// once nothing arrived for `timeout`, or block indefinitely for `None`
+    /// # Errors
+    /// - If the underlying socket rejected the timeout
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> std::io::Result<()>;
+
+    /// Makes writes fail once they blocked for `timeout`, or block indefinitely for `None`
+    /// # Errors
+    /// - If the underlying socket rejected the timeout
+    fn set_write_timeout(&self, timeout: Option<Duration>) -> std::io::Result<()>;
+
+    /// Whether the transport encrypts the connection, which rules out cleartext protocols such as h2c
+    fn is_encrypted(&self) -> bool {
+        false
+    }
+}
+
+/// A source of incoming client connections that [`Server`](super::server::Server) accepts from
+pub trait Listener: Send + Sync + 'static {
+    /// The type of connection produced by the listener
+    type Connection: Connection;
+
+    /// Blocks until a client connects
+    /// # Errors
+    /// - If accepting the connection failed
+    fn accept(&self) -> std::io::Result<Self::Connection>;
+
+    /// Human readable address the listener is bound to, used for logging
+    fn address(&self) -> String;
+}
+
+impl Connection for TcpStream {
+    fn shutdown(&mut self) -> std::io::Result<()> {
+        TcpStream::shutdown(self, Shutdown::Both)
+    }
+
+    fn peer_addr(&self) -> Option<SocketAddr> {
+        TcpStream::peer_addr(self).ok()
+    }
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> std::io::Result<()> {
+        TcpStream::set_read_timeout(self, timeout)
+    }
+
+    fn set_write_timeout(&self, timeout: Option<Duration>) -> std::io::Result<()> {
+        TcpStream::set_write_timeout(self, timeout)
+    }
+}
+
+impl Listener for TcpListener {
+    type Connection = TcpStream;
+
+    fn accept(&self) -> std::io::Result<Self::Connection> {
+        TcpListener::accept(self).map(|(stream, _)| stream)
+    }
+
+    fn address(&self) -> String {
+        self.local_addr().map_or_else(
+            |_| "tcp socket".to_string(),
+            |address| format!("http://{address}"),
+        )
+    }
+}
+
+#[cfg(unix)]
+impl Connection for UnixStream {
+    fn shutdown(&mut self) -> std::io::Result<()> {
+        UnixStream::shutdown(self, Shutdown::Both)
+    }
+
+    fn peer_addr(&self) -> Option<SocketAddr> {
+        None
+    }
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> std::io::Result<()> {
+        UnixStream::set_read_timeout(self, timeout)
+    }
+
+    fn set_write_timeout(&self, timeout: Option<Duration>) -> std::io::Result<()> {
+        UnixStream::set_write_timeout(self, timeout)
+    }
+}
+
+#[cfg(unix)]
+impl Listener for UnixListener {
+    type Connection = UnixStream;
+
+    fn accept(&self) -> std::io::Result<Self::Connection> {
+        UnixListener::accept(self).map(|(stream, _)| stream)
+    }
+
+    fn address(&self) -> String {
+        self.local_addr()
+            .ok()
+            .and_then(|address| address.as_pathname().map(|path| path.display().to_string()))
+            .map_or_else(|| "unix socket".to_string(), |path| format!("unix:{path}"))
+    }
+}
+
+/// A listening socket inherited from the service manager through systemd-style socket activation
+#[cfg(unix)]
+#[derive(Debug)]
+pub enum Inherited {
+    /// An inherited TCP socket
+    Tcp(TcpListener),
+
+    /// An inherited Unix domain socket
+    Unix(UnixListener),
+}
+
+/// A connection accepted from an [`Inherited`] listener
+#[cfg(unix)]
+#[derive(Debug)]
+pub enum InheritedConnection {
+    /// A connection over TCP
+    Tcp(TcpStream),
+
+    /// A connection over a Unix domain socket
+    Unix(UnixStream),
+}
+
+#[cfg(unix)]
+impl Inherited {
+    /// The first file descriptor passed by the service manager, as defined by `sd_listen_fds(3)`
+    const LISTEN_FDS_START: i32 = 3;
+
+    /// Takes ownership of the sockets passed through the `LISTEN_PID` and `LISTEN_FDS` environment variables.
+    ///
+    /// Like `sd_listen_fds(3)` with `unset_environment` set, the variables are removed so that child processes do
+    /// not take the sockets for theirs, and the sockets are closed on `exec`.
+    ///
+    /// Returns an empty list when the process was not socket activated.
+    /// # Errors
+    /// - If `LISTEN_FDS` is not a valid number
+    /// - If any of the descriptors is not a listening stream socket, in which case none of them is taken
+    pub fn from_env() -> std::io::Result<Vec<Inherited>> {
+        use std::os::fd::FromRawFd;
+
+        let pid = std::env::var("LISTEN_PID");
+        let count = std::env::var("LISTEN_FDS");
+
+        for variable in ["LISTEN_PID", "LISTEN_FDS", "LISTEN_FDNAMES"] {
+            std::env::remove_var(variable);
+        }
+
+        let for_this_process = pid
+            .ok()
+            .and_then(|pid| pid.parse::<u32>().ok())
+            .is_some_and(|pid| pid == std::process::id());
+
+        if !for_this_process {
+            return Ok(vec![]);
+        }
+
+        let count = count
+            .map_err(std::io::Error::other)?
+            .parse::<i32>()
+            .map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidInput, error))?;
+
+        let fds = Self::LISTEN_FDS_START..Self::LISTEN_FDS_START + count;
+
+        // Every descriptor is checked before any is wrapped, which would close it again on failure
+        let unix = fds
+            .clone()
+            .map(prepare)
+            .collect::<std::io::Result<Vec<_>>>()?;
+
+        Ok(fds
+            .zip(unix)
+            .map(|(fd, unix)| {
+                // SAFETY: The service manager hands these descriptors to this process exclusively, each one
+                // is a listening socket of the kind it is wrapped as, and is wrapped exactly once
+                unsafe {
+                    if unix {
+                        Inherited::Unix(UnixListener::from_raw_fd(fd))
+                    } else {
+                        Inherited::Tcp(TcpListener::from_raw_fd(fd))
+                    }
+                }
+            })
+            .collect())
+    }
+}
+
+/// Checks that a descriptor passed by the service manager is a listening stream socket, and marks it close-on-exec.
+///
+/// Returns whether it is a Unix domain socket rather than a TCP one
+#[cfg(unix)]
+fn prepare(fd: i32) -> std::io::Result<bool> {
+    let invalid = |message: &str| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("Inherited descriptor {fd} {message}"),
+        )
+    };
+
+    if socket_option(fd, libc::SO_TYPE)? != libc::SOCK_STREAM {
+        return Err(invalid("is not a stream socket"));
+    }
+
+    if socket_option(fd, libc::SO_ACCEPTCONN)? == 0 {
+        return Err(invalid("is not listening"));
+    }
+
+    // SAFETY: A zeroed address is valid for any family, and the length tells `getsockname` how much it may write
+    let family = unsafe {
+        let mut address: libc::sockaddr_storage = std::mem::zeroed();
+        let mut length = socket_length::<libc::sockaddr_storage>();
+
+        if libc::getsockname(fd, (&raw mut address).cast(), &raw mut length) == -1 {
+            return Err(std::io::Error::last_os_error());
+        }
+
+        i32::from(address.ss_family)
+    };
+
+    let unix = match family {
+        libc::AF_UNIX => true,
+        libc::AF_INET | libc::AF_INET6 => false,
+        _ => return Err(invalid("is neither a TCP nor a Unix domain socket")),
+    };
+
+    // SAFETY: Only the descriptor flags of a descriptor owned by this process are changed
+    unsafe {
+        let flags = libc::fcntl(fd, libc::F_GETFD);
+
+        if flags == -1 || libc::fcntl(fd, libc::F_SETFD, flags | libc::FD_CLOEXEC) == -1 {
+            return Err(std::io::Error::last_os_error());
+        }
+    }
+
+    Ok(unix)
+}
+
+/// Reads an integer socket option at the socket level
+#[cfg(unix)]
+fn socket_option(fd: i32, option: libc::c_int) -> std::io::Result<libc::c_int> {
+    let mut value: libc::c_int = 0;
+    let mut length = socket_length::<libc::c_int>();
+
+    // SAFETY: `value` and `length` point to an integer and its size, which is what these options are
+    let result = unsafe {
+        libc::getsockopt(
+            fd,
+            libc::SOL_SOCKET,
+            option,
+            (&raw mut value).cast(),
+            &raw mut length,
+        )
+    };
+
+    if result == -1 {
+        return Err(std::io::Error::last_os_error());
+    }
+
+    Ok(value)
+}
+
+#[cfg(unix)]
+#[allow(clippy::cast_possible_truncation)] // Socket addresses and options are far smaller than 4 GiB
+fn socket_length<T>() -> libc::socklen_t {
+    std::mem::size_of::<T>() as libc::socklen_t
+}
+
+#[cfg(unix)]
+impl Listener for Inherited {
+    type Connection = InheritedConnection;
+
+    fn accept(&self) -> std::io::Result<Self::Connection> {
+        match self {
+            Inherited::Tcp(listener) => Listener::accept(listener).map(InheritedConnection::Tcp),
+            Inherited::Unix(listener) => Listener::accept(listener).map(InheritedConnection::Unix),
+        }
+    }
+
+    fn address(&self) -> String {
+        match self {
+            Inherited::Tcp(listener) => listener.address(),
+            Inherited::Unix(listener) => listener.address(),
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Read for InheritedConnection {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            InheritedConnection::Tcp(stream) => stream.read(buf),
+            InheritedConnection::Unix(stream) => stream.read(buf),
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Write for InheritedConnection {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            InheritedConnection::Tcp(stream) => stream.write(buf),
+            InheritedConnection::Unix(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            InheritedConnection::Tcp(stream) => stream.flush(),
+            InheritedConnection::Unix(stream) => stream.flush(),
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Connection for InheritedConnection {
+    fn shutdown(&mut self) -> std::io::Result<()> {
+        match self {
+            InheritedConnection::Tcp(stream) => Connection::shutdown(stream),
+            InheritedConnection::Unix(stream) => Connection::shutdown(stream),
+        }
+    }
+
+    fn peer_addr(&self) -> Option<SocketAddr> {
+        match self {
+            InheritedConnection::Tcp(stream) => Connection::peer_addr(stream),
+            InheritedConnection::Unix(stream) => Connection::peer_addr(stream),
+        }
+    }
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> std::io::Result<()> {
+        match self {
+            InheritedConnection::Tcp(stream) => Connection::set_read_timeout(stream, timeout),
+            InheritedConnection::Unix(stream) => Connection::set_read_timeout(stream, timeout),
+        }
+    }
+
+    fn set_write_timeout(&self, timeout: Option<Duration>) -> std::io::Result<()> {
+        match self {
+            InheritedConnection::Tcp(stream) => Connection::set_write_timeout(stream, timeout),
+            InheritedConnection::Unix(stream) => Connection::set_write_timeout(stream, timeout),
+        }
+    }
+}
+
+/// A connection that replays bytes already read from it before reading any further,
+/// so protocol detection can peek at the start of a connection
+#[derive(Debug)]
+pub(crate) struct Rewind<C> {
+    buffered: Vec<u8>,
+    inner: C,
+}
+
+impl<C: Connection> Rewind<C> {
+    pub(crate) fn new(buffered: Vec<u8>, inner: C) -> Self {
+        Self { buffered, inner }
+    }
+}
+
+impl<C: Connection> Read for Rewind<C> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.buffered.is_empty() {
+            return self.inner.read(buf);
+        }
+
+        let length = buf.len().min(self.buffered.len());
+
+        buf[..length].copy_from_slice(&self.buffered[..length]);
+        self.buffered.drain(..length);
+
+        Ok(length)
+    }
+}
+
+impl<C: Connection> Write for Rewind<C> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<C: Connection> Connection for Rewind<C> {
+    fn shutdown(&mut self) -> std::io::Result<()> {
+        self.inner.shutdown()
+    }
+
+    fn peer_addr(&self) -> Option<SocketAddr> {
+        self.inner.peer_addr()
+    }
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> std::io::Result<()> {
+        self.inner.set_read_timeout(timeout)
+    }
+
+    fn set_write_timeout(&self, timeout: Option<Duration>) -> std::io::Result<()> {
+        self.inner.set_write_timeout(timeout)
+    }
+
+    fn is_encrypted(&self) -> bool {
+        self.inner.is_encrypted()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Connection, Listener};
+    use std::{
+        io::Read,
+        net::{TcpListener, TcpStream},
+    };
+
+    /// Writes through the accepted connection and reads it back on the client side
+    fn exchange(connection: &mut impl Connection, client: &mut impl Read) {
+        connection.write_all(b"hello").unwrap();
+        Connection::shutdown(connection).unwrap();
+
+        let mut received = String::new();
+        client.read_to_string(&mut received).unwrap();
+
+        assert_eq!(received, "hello");
+    }
+
+    #[test]
+    fn tcp_listeners_accept_connections() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        assert_eq!(listener.address(), format!("http://{address}"));
+
+        let mut client = TcpStream::connect(address).unwrap();
+        let mut connection = Listener::accept(&listener).unwrap();
+
+        assert_eq!(
+            Connection::peer_addr(&connection),
+            Some(client.local_addr().unwrap())
+        );
+        exchange(&mut connection, &mut client);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unix_listeners_accept_connections() {
+        use std::os::unix::net::{UnixListener, UnixStream};
+
+        let directory = std::env::temp_dir().join(format!("hyperion-unix-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("server.sock");
+        let _ = std::fs::remove_file(&path);
+
+        let listener = UnixListener::bind(&path).unwrap();
+
+        assert_eq!(listener.address(), format!("unix:{}", path.display()));
+
+        let mut client = UnixStream::connect(&path).unwrap();
+        let mut connection = Listener::accept(&listener).unwrap();
+
+        // Unix domain sockets have no address a client could be told apart by
+        assert_eq!(Connection::peer_addr(&connection), None);
+        exchange(&mut connection, &mut client);
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn inherited_descriptors_are_checked() {
+        use super::prepare;
+        use std::{net::UdpSocket, os::fd::AsRawFd};
+
+        let tcp = TcpListener::bind("127.0.0.1:0").unwrap();
+        let fd = tcp.as_raw_fd();
+
+        // SAFETY: Clearing the descriptor flags of a socket this test owns
+        unsafe { libc::fcntl(fd, libc::F_SETFD, 0) };
+
+        assert!(!prepare(fd).unwrap());
+        // SAFETY: Reading the descriptor flags of a socket this test owns
+        assert_ne!(
+            unsafe { libc::fcntl(fd, libc::F_GETFD) } & libc::FD_CLOEXEC,
+            0
+        );
+
+        let client = TcpStream::connect(tcp.local_addr().unwrap()).unwrap();
+        assert!(prepare(client.as_raw_fd()).is_err(), "not listening");
+
+        let udp = UdpSocket::bind("127.0.0.1:0").unwrap();
+        assert!(prepare(udp.as_raw_fd()).is_err(), "not a stream socket");
+    }
+
+    /// Every case shares one test, the variables are global to the process
+    #[cfg(unix)]
+    #[test]
+    fn activation_variables_are_read_once() {
+        use super::Inherited;
+
+        let unset = || {
+            ["LISTEN_PID", "LISTEN_FDS", "LISTEN_FDNAMES"]
+                .iter()
+                .all(|variable| std::env::var_os(variable).is_none())
+        };
+
+        assert!(Inherited::from_env().unwrap().is_empty());
+
+        // Sockets meant for another process, such as a parent that did not unset the variables
+        std::env::set_var("LISTEN_PID", (std::process::id() + 1).to_string());
+        std::env::set_var("LISTEN_FDS", "1");
+        std::env::set_var("LISTEN_FDNAMES", "http");
+
+        assert!(Inherited::from_env().unwrap().is_empty());
+        assert!(unset());
+
+        std::env::set_var("LISTEN_PID", std::process::id().to_string());
+        std::env::set_var("LISTEN_FDS", "one");
+
+        let error = Inherited::from_env().unwrap_err();
+
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+        assert!(unset());
+    }
+}
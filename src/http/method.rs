@@ -1,4 +1,6 @@
+use crate::{error, http::error::Error};
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 
 #[allow(missing_docs)] // Self explanatory
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Hash)]
@@ -14,3 +16,29 @@ pub enum Method {
     Trace,
     Patch,
 }
+
+impl Method {
+    /// Whether sending the request more than once has the same effect as sending it once, RFC 9110 Section 9.2.2
+    #[must_use]
+    pub fn is_idempotent(self) -> bool {
+        matches!(
+            self,
+            Method::Get
+                | Method::Head
+                | Method::Put
+                | Method::Delete
+                | Method::Options
+                | Method::Trace
+        )
+    }
+}
+
+impl FromStr for Method {
+    type Err = Error;
+
+    fn from_str(method: &str) -> Result<Self, Self::Err> {
+        // Leveraging serde to deserialize methods instead of manually converting from string to [HttpMethod]
+        serde_json::from_str(&format!("\"{method}\""))
+            .map_err(|_| error!(BadRequest, format!("Unknown HTTP Method: {method}")))
+    }
+}
@@ -15,13 +15,60 @@ pub mod response;
 /// Includes implementations for parsing responses
 pub mod request;
 
+/// Content negotiation based on the `Accept` header
+pub mod negotiation;
+
 /// Method definitions for HTTP Request
 pub mod method;
 
+/// Typed extractors for the arguments of route handlers
+pub mod extract;
+
 /// Type Definitions / Implementations for routing
 pub mod router;
 
+/// Virtual hosts, routing requests by their `Host` header
+pub mod host;
+
+/// Listeners and connections the server can accept clients from: TCP, Unix domain sockets and inherited sockets
+pub mod listener;
+
+/// Timeouts and size limits protecting the server from clients that are slow to send their requests or send too much
+pub mod limits;
+
+/// HTTP/2 framing, header compression and connection handling
+pub mod http2;
+
+/// WebSocket handshake, framing and message loop, RFC 6455
+pub mod websocket;
+
+/// Server-Sent Events, streamed to the client over a response that stays open
+pub mod sse;
+
+/// Blocking HTTP/1.1 client sharing the request and response types of the server
+pub mod client;
+
+/// Reverse proxy forwarding requests to a pool of upstream servers
+pub mod proxy;
+
+/// Rate limiting of requests by client address, API key or route
+pub mod rate_limit;
+
+/// Authentication with HTTP Basic credentials, Bearer tokens or API keys
+pub mod auth;
+
+/// Verification of JSON Web Tokens sent as Bearer tokens, RFC 7519
+#[cfg(feature = "jwt")]
+pub mod jwt;
+
 /// HTTP server Abstraction layer
 pub mod server;
 
-pub use dynamo::route;
+/// In-process test client running requests through the server without opening sockets
+pub mod testing;
+
+/// Asynchronous HTTP server running on tokio
+#[cfg(feature = "async")]
+pub mod async_server;
+
+pub use dynamo::{connect, delete, get, head, options, patch, post, put, route, service, trace};
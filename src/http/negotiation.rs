@@ -0,0 +1,279 @@
+use crate::http::{header::HttpHeader, request::Request};
+use std::{cmp::Ordering, str::FromStr};
+
+/// A single media range of an `Accept` header as defined in RFC 2616 Section 14.1, e.g. `application/*;q=0.8`
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaRange {
+    /// The top-level type, or `*`
+    pub kind: String,
+
+    /// The subtype, or `*`
+    pub subtype: String,
+
+    /// The relative quality factor in the range `0.0..=1.0`
+    pub quality: f32,
+}
+
+impl MediaRange {
+    /// Checks whether the given `type/subtype` media type falls within this range
+    #[must_use]
+    pub fn matches(&self, media_type: &str) -> bool {
+        let Some((kind, subtype)) = media_type.split_once('/') else {
+            return false;
+        };
+
+        (self.kind == "*" || self.kind.eq_ignore_ascii_case(kind))
+            && (self.subtype == "*" || self.subtype.eq_ignore_ascii_case(subtype))
+    }
+
+    /// More specific ranges take precedence over less specific ones when both match a media type
+    fn specificity(&self) -> u8 {
+        match (self.kind.as_str(), self.subtype.as_str()) {
+            ("*", _) => 0,
+            (_, "*") => 1,
+            _ => 2,
+        }
+    }
+}
+
+impl FromStr for MediaRange {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut parameters = value.split(';').map(str::trim);
+
+        let media_type = parameters.next().unwrap_or_default();
+
+        let Some((kind, subtype)) = media_type.split_once('/') else {
+            return Err(format!("Invalid media range '{media_type}'"));
+        };
+
+        if kind.is_empty() || subtype.is_empty() || (kind == "*" && subtype != "*") {
+            return Err(format!("Invalid media range '{media_type}'"));
+        }
+
+        let mut quality = 1.0;
+
+        for parameter in parameters {
+            if let Some(("q" | "Q", value)) = parameter.split_once('=') {
+                quality = value
+                    .trim()
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|quality| (0.0..=1.0).contains(quality))
+                    .ok_or(format!("Invalid quality value '{value}'"))?;
+            }
+        }
+
+        Ok(Self {
+            kind: kind.to_string(),
+            subtype: subtype.to_string(),
+            quality,
+        })
+    }
+}
+
+/// Parses the value of an `Accept` header into its media ranges, skipping malformed entries
+#[must_use]
+pub fn parse_accept(value: &str) -> Vec<MediaRange> {
+    value
+        .split(',')
+        .filter(|range| !range.trim().is_empty())
+        .filter_map(|range| range.parse().ok())
+        .collect()
+}
+
+/// Formats a negotiated response can be rendered in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// `application/json`
+    Json,
+    /// `application/xml`
+    Xml,
+    /// `application/yaml`
+    Yaml,
+    /// `text/plain`
+    Text,
+}
+
+impl Format {
+    /// Every format supported by the negotiation helpers, in order of preference
+    pub const ALL: [Format; 4] = [Format::Json, Format::Xml, Format::Yaml, Format::Text];
+
+    /// Returns the media type the format is served with
+    #[must_use]
+    pub fn media_type(&self) -> &'static str {
+        match self {
+            Format::Json => "application/json",
+            Format::Xml => "application/xml",
+            Format::Yaml => "application/yaml",
+            Format::Text => "text/plain",
+        }
+    }
+
+    /// Alternative media types that clients commonly use for the same format
+    fn aliases(self) -> &'static [&'static str] {
+        match self {
+            Format::Json | Format::Text => &[],
+            Format::Xml => &["text/xml"],
+            Format::Yaml => &["application/x-yaml", "text/yaml"],
+        }
+    }
+
+    /// The quality the client assigned to this format, using the most specific matching range.
+    ///
+    /// Wildcards only match the canonical media type, aliases have to be named explicitly.
+    fn quality(self, ranges: &[MediaRange]) -> f32 {
+        let canonical = ranges
+            .iter()
+            .filter(|range| range.matches(self.media_type()))
+            .max_by_key(|range| range.specificity());
+
+        self.aliases()
+            .iter()
+            .filter_map(|media_type| {
+                ranges
+                    .iter()
+                    .find(|range| range.specificity() == 2 && range.matches(media_type))
+            })
+            .chain(canonical)
+            .max_by_key(|range| range.specificity())
+            .map_or(0.0, |range| range.quality)
+    }
+}
+
+/// Picks the format from `available` that is most acceptable for the given `Accept` ranges.
+///
+/// Ties are broken by the order of `available`. Returns [`None`] when every format is rejected.
+#[must_use]
+pub fn select(ranges: &[MediaRange], available: &[Format]) -> Option<Format> {
+    if ranges.is_empty() {
+        return available.first().copied();
+    }
+
+    available
+        .iter()
+        .map(|format| (*format, format.quality(ranges)))
+        .filter(|(_, quality)| *quality > 0.0)
+        // `max_by` returns the last maximum, reverse first so that earlier formats win ties
+        .rev()
+        .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+        .map(|(format, _)| format)
+}
+
+/// Picks the format from `available` that is most acceptable for the given request.
+///
+/// A request without an `Accept` header accepts anything, so the first available format is used.
+#[must_use]
+pub fn negotiate(request: &Request, available: &[Format]) -> Option<Format> {
+    let ranges = request
+        .headers
+        .iter()
+        .filter(|header| matches!(header, HttpHeader::Accept(_)))
+        .flat_map(|header| parse_accept(&header.value()))
+        .collect::<Vec<_>>();
+
+    select(&ranges, available)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_accept, select, Format, MediaRange};
+    use crate::http::{
+        header::HttpHeader, method::Method, request::Request, response::Response, route,
+        router::routes, status::HttpStatus, testing::TestClient,
+    };
+    use serde::Serialize;
+
+    fn pick(accept: &str) -> Option<Format> {
+        select(&parse_accept(accept), &Format::ALL)
+    }
+
+    #[test]
+    fn media_ranges() {
+        assert_eq!(
+            "application/*; q=0.5".parse::<MediaRange>(),
+            Ok(MediaRange {
+                kind: "application".to_string(),
+                subtype: "*".to_string(),
+                quality: 0.5,
+            })
+        );
+        assert!("*/json".parse::<MediaRange>().is_err());
+        assert!("text".parse::<MediaRange>().is_err());
+        assert!("text/plain;q=2".parse::<MediaRange>().is_err());
+        assert_eq!(parse_accept("text/html, nonsense, */*;q=0.1").len(), 2);
+    }
+
+    #[test]
+    fn the_most_acceptable_format_is_selected() {
+        assert_eq!(pick("application/xml"), Some(Format::Xml));
+        assert_eq!(
+            pick("application/json;q=0.5, application/yaml"),
+            Some(Format::Yaml)
+        );
+        assert_eq!(pick("text/*"), Some(Format::Text));
+        assert_eq!(pick(""), Some(Format::Json));
+        assert_eq!(pick("image/png"), None);
+    }
+
+    #[test]
+    fn ties_are_broken_by_preference() {
+        assert_eq!(pick("*/*"), Some(Format::Json));
+        assert_eq!(
+            pick("application/xml, application/json"),
+            Some(Format::Json)
+        );
+    }
+
+    #[test]
+    fn specific_ranges_override_wildcards() {
+        assert_eq!(pick("application/json;q=0, */*"), Some(Format::Xml));
+        assert_eq!(pick("*/*;q=0.1, text/plain"), Some(Format::Text));
+    }
+
+    #[test]
+    fn aliases_must_be_named_explicitly() {
+        assert_eq!(pick("text/yaml"), Some(Format::Yaml));
+        assert_eq!(pick("text/xml;q=0.9, text/*;q=0.5"), Some(Format::Xml));
+    }
+
+    #[derive(Serialize)]
+    struct Greeting {
+        message: &'static str,
+    }
+
+    #[route]
+    fn greeting(request: &Request) -> Response<String> {
+        Response::Ok().negotiate(request, Greeting { message: "hello" })
+    }
+
+    fn accepting(accept: &str) -> Response<Vec<u8>> {
+        TestClient::new(routes!(Get "/" -> greeting,)).request(
+            Method::Get,
+            "/",
+            vec![HttpHeader::Accept(accept.to_string())],
+            None,
+        )
+    }
+
+    #[test]
+    fn responses_are_rendered_in_the_negotiated_format() {
+        accepting("application/json")
+            .assert_status(HttpStatus::Ok)
+            .assert_header(&HttpHeader::ContentType("application/json".to_string()))
+            .assert_header(&HttpHeader::Vary("Accept".to_string()))
+            .assert_json(&serde_json::json!({"message": "hello"}));
+
+        let yaml = accepting("application/x-yaml");
+        yaml.assert_header(&HttpHeader::ContentType("application/yaml".to_string()));
+        assert_eq!(yaml.text().trim(), "message: hello");
+    }
+
+    #[test]
+    fn unacceptable_requests_are_refused() {
+        accepting("image/png")
+            .assert_status(HttpStatus::NotAcceptable)
+            .assert_header(&HttpHeader::Vary("Accept".to_string()));
+    }
+}
@@ -0,0 +1,869 @@
+use crate::error;
+use crate::http::{
+    client::{self, ClientError, Url},
+    error::{Error, ErrorHook},
+    header::HttpHeader,
+    listener::Connection,
+    request::Request,
+    response::{read_response_head, Body, Response, ResponseBody, TakeOver},
+    router::RequestHandler,
+    status::HttpStatus,
+};
+use std::{
+    fmt::Debug,
+    io::{BufReader, ErrorKind, Read, Write},
+    net::{IpAddr, TcpStream},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, MutexGuard, PoisonError,
+    },
+    time::{Duration, Instant},
+};
+
+/// How a [`Proxy`] picks the upstream that serves a request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Balance {
+    /// Takes turns, in the order the upstreams were given
+    #[default]
+    RoundRobin,
+
+    /// Picks the upstream with the fewest requests in flight
+    LeastConnections,
+}
+
+/// An upstream server along with what the proxy learned about it
+#[derive(Debug)]
+struct Upstream {
+    url: Url,
+    in_flight: AtomicUsize,
+    health: Mutex<Health>,
+}
+
+/// Failures of an upstream in a row, and until when it is skipped after too many of them
+#[derive(Debug, Default)]
+struct Health {
+    failures: u32,
+    down_until: Option<Instant>,
+}
+
+impl Upstream {
+    fn health(&self) -> MutexGuard<'_, Health> {
+        self.health.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn is_down(&self) -> bool {
+        self.health()
+            .down_until
+            .is_some_and(|until| Instant::now() < until)
+    }
+}
+
+/// Counts a request against the upstream serving it until the response is relayed
+struct InFlight(Arc<Upstream>);
+
+impl InFlight {
+    fn new(upstream: &Arc<Upstream>) -> Self {
+        upstream.in_flight.fetch_add(1, Ordering::Relaxed);
+
+        Self(upstream.clone())
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.in_flight.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Why an exchange with an upstream failed
+enum Failure {
+    /// The upstream could not be reached, so the request was never sent
+    Connect(Error),
+
+    /// The request was sent, retrying it elsewhere could repeat it
+    Exchange(Error),
+}
+
+/// Request handler that forwards requests to upstream servers and relays their responses.
+///
+/// Hop-by-hop headers are stripped in both directions and `Host` is rewritten to the upstream, while the client
+/// is recorded in `X-Forwarded-For`, `X-Forwarded-Host`, `Forwarded` and `Via`. Response bodies are streamed to
+/// HTTP/1.1 clients as they arrive. Upstreams that keep failing are skipped for a while, and requests that
+/// could not reach an upstream are retried on the next one. Failures are answered with 502 Bad Gateway,
+/// timeouts with 504 Gateway Timeout. Protocol upgrades, to `WebSocket` or otherwise, are not passed on.
+///
+/// Request bodies are not streamed: the server reads a body in full, up to [`Limits::body`](super::limits::Limits),
+/// before any handler sees the request, and the proxy sends it on in one piece. Uploads that should not be held
+/// in memory need a server with a limit sized for them, or a route around the proxy.
+///
+/// A proxy serving several routes is shared through an [`Arc`], which keeps the balancing in one place.
+pub struct Proxy {
+    upstreams: Vec<Arc<Upstream>>,
+    balance: Balance,
+    next: AtomicUsize,
+    connect_timeout: Duration,
+    timeout: Duration,
+    max_fails: u32,
+    fail_timeout: Duration,
+    preserve_host: bool,
+    on_error: Option<ErrorHook>,
+}
+
+impl Debug for Proxy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Proxy")
+            .field("upstreams", &self.upstreams)
+            .field("balance", &self.balance)
+            .field("connect_timeout", &self.connect_timeout)
+            .field("timeout", &self.timeout)
+            .field("max_fails", &self.max_fails)
+            .field("fail_timeout", &self.fail_timeout)
+            .field("preserve_host", &self.preserve_host)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Proxy {
+    /// Creates a proxy taking turns between the `http://` upstreams, the path of an upstream URL
+    /// is prepended to the path of every request sent to it
+    /// # Errors
+    /// - If an upstream URL is invalid, or no upstream is given
+    pub fn new<I>(upstreams: I) -> Result<Self, ClientError>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let upstreams = upstreams
+            .into_iter()
+            .map(|url| {
+                Ok(Arc::new(Upstream {
+                    url: Url::parse(url.as_ref())?,
+                    in_flight: AtomicUsize::new(0),
+                    health: Mutex::default(),
+                }))
+            })
+            .collect::<Result<Vec<_>, ClientError>>()?;
+
+        if upstreams.is_empty() {
+            return Err(ClientError::InvalidUrl("No upstream given".to_string()));
+        }
+
+        Ok(Self {
+            upstreams,
+            balance: Balance::default(),
+            next: AtomicUsize::new(0),
+            connect_timeout: Duration::from_secs(5),
+            timeout: Duration::from_secs(60),
+            max_fails: 3,
+            fail_timeout: Duration::from_secs(10),
+            preserve_host: false,
+            on_error: None,
+        })
+    }
+
+    /// Sets how upstreams are picked, round-robin by default
+    #[must_use]
+    pub fn balance(mut self, balance: Balance) -> Self {
+        self.balance = balance;
+
+        self
+    }
+
+    /// Limits how long connecting to an upstream may take, 5 seconds by default
+    #[must_use]
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = timeout;
+
+        self
+    }
+
+    /// Limits how long every single read from or write to an upstream may take, a minute by default
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+
+        self
+    }
+
+    /// Takes an upstream out of rotation after this many failures in a row, 3 by default and 0 never does
+    #[must_use]
+    pub fn max_fails(mut self, max_fails: u32) -> Self {
+        self.max_fails = max_fails;
+
+        self
+    }
+
+    /// Sets how long an upstream stays out of rotation, 10 seconds by default
+    #[must_use]
+    pub fn fail_timeout(mut self, fail_timeout: Duration) -> Self {
+        self.fail_timeout = fail_timeout;
+
+        self
+    }
+
+    /// Passes the `Host` header of the client on instead of the authority of the upstream
+    #[must_use]
+    pub fn preserve_host(mut self, preserve_host: bool) -> Self {
+        self.preserve_host = preserve_host;
+
+        self
+    }
+
+    /// Reports failures to relay a response body, which happen after the response head went out to the client
+    /// and are dropped otherwise
+    #[must_use]
+    pub fn on_error(
+        mut self,
+        hook: impl Fn(&dyn std::error::Error) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_error = Some(Arc::new(hook));
+
+        self
+    }
+
+    /// Picks the next upstream to try, skipping those already tried and, while others are left, those that are down
+    fn select(&self, tried: &[usize]) -> Option<usize> {
+        let count = self.upstreams.len();
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+
+        let untried: Vec<usize> = (0..count)
+            .map(|offset| (start + offset) % count)
+            .filter(|index| !tried.contains(index))
+            .collect();
+
+        let healthy: Vec<usize> = untried
+            .iter()
+            .copied()
+            .filter(|index| !self.upstreams[*index].is_down())
+            .collect();
+
+        // Trying an upstream that is down is the only way left to notice it recovered
+        let candidates = if healthy.is_empty() { untried } else { healthy };
+
+        match self.balance {
+            Balance::RoundRobin => candidates.first().copied(),
+            Balance::LeastConnections => candidates
+                .into_iter()
+                .min_by_key(|index| self.upstreams[*index].in_flight.load(Ordering::Relaxed)),
+        }
+    }
+
+    /// Records the outcome of an exchange, taking the upstream out of rotation after `max_fails` failures in a row
+    fn record(&self, upstream: &Upstream, success: bool) {
+        let mut health = upstream.health();
+
+        if success {
+            *health = Health::default();
+
+            return;
+        }
+
+        health.failures += 1;
+
+        if self.max_fails > 0 && health.failures >= self.max_fails {
+            health.failures = 0;
+            health.down_until = Some(Instant::now() + self.fail_timeout);
+        }
+    }
+
+    fn exchange(
+        &self,
+        upstream: &Arc<Upstream>,
+        request: &Request,
+    ) -> Result<Response<Box<dyn Body>>, Failure> {
+        let authority = upstream.url.authority();
+
+        let gateway_error = |error: Error| match error {
+            Error::IoError(error)
+                if matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) =>
+            {
+                Error::GatewayTimeout(format!("Upstream {authority} timed out"))
+            }
+            Error::IoError(error) => {
+                Error::BadGateway(format!("Upstream {authority} failed: {error}"))
+            }
+            error => error,
+        };
+
+        let address = format!("{}:{}", upstream.url.host, upstream.url.port);
+
+        let stream = client::connect(&address, Some(self.connect_timeout))
+            .map_err(|error| Failure::Connect(gateway_error(error.into())))?;
+
+        self.relay(stream, upstream, request.clone())
+            .map_err(|error| Failure::Exchange(gateway_error(error)))
+    }
+
+    /// Sends the request to the upstream and reads the head of its response, leaving the body to be streamed
+    fn relay(
+        &self,
+        mut stream: TcpStream,
+        upstream: &Arc<Upstream>,
+        mut request: Request,
+    ) -> Result<Response<Box<dyn Body>>, Error> {
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+
+        request.path = format!(
+            "{}{}",
+            upstream.url.path.trim_end_matches('/'),
+            request.path
+        );
+
+        let has_host = request
+            .headers
+            .iter()
+            .any(|header| matches!(header, HttpHeader::Host(_)));
+
+        if !self.preserve_host || !has_host {
+            request
+                .headers
+                .retain(|header| !matches!(header, HttpHeader::Host(_)));
+            request
+                .headers
+                .insert(0, HttpHeader::Host(upstream.url.authority()));
+        }
+
+        let method = request.method;
+
+        stream.write_all(&Vec::from(request))?;
+        stream.flush()?;
+
+        let mut reader = BufReader::new(stream);
+
+        let Response {
+            mut headers,
+            status,
+            ..
+        } = loop {
+            let head = read_response_head(&mut reader)?;
+
+            // Upgrades are stripped from forwarded requests, so what follows would not be HTTP any more
+            if head.status == HttpStatus::SwitchingProtocols {
+                return Err(error!(
+                    BadGateway,
+                    format!(
+                        "Upstream {} switched protocols without being asked to",
+                        upstream.url.authority()
+                    )
+                ));
+            }
+
+            if !matches!(u16::from(&head.status), 100..=199) {
+                break head;
+            }
+        };
+
+        let body = ResponseBody::new(reader, method, status, &headers)?;
+
+        // A decoded chunked body is relayed up to the end of the connection instead
+        strip_hop_by_hop(&mut headers);
+        append(&mut headers, HttpHeader::Via, "1.1 hyperion");
+
+        let relay = match body {
+            ResponseBody::Empty => Relay::Buffered(vec![]),
+            body => Relay::Streaming(Box::new(body), InFlight::new(upstream)),
+        };
+
+        Ok(Response::new(
+            Box::new(Relayed(Mutex::new(relay), self.on_error.clone())),
+            headers,
+            status,
+        ))
+    }
+}
+
+impl RequestHandler for Proxy {
+    fn handle(&self, request: Request) -> Result<Response<Box<dyn Body>>, Error> {
+        let request = forwarded(request);
+
+        let mut tried = vec![];
+        let mut last_error = None;
+
+        while let Some(index) = self.select(&tried) {
+            tried.push(index);
+
+            let upstream = &self.upstreams[index];
+            let in_flight = InFlight::new(upstream);
+
+            let result = self.exchange(upstream, &request);
+            self.record(upstream, result.is_ok());
+            drop(in_flight);
+
+            match result {
+                Ok(response) => return Ok(response),
+                // Nothing reached the upstream, so another one can safely take the request
+                Err(Failure::Connect(error)) => last_error = Some(error),
+                Err(Failure::Exchange(error)) => return Err(error),
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| error!(BadGateway, "No upstream available")))
+    }
+}
+
+/// Where the body of a proxied response is at
+enum Relay {
+    /// Still to be read from the upstream
+    Streaming(Box<ResponseBody<BufReader<TcpStream>>>, InFlight),
+
+    /// Read in full, or handed off to be streamed
+    Buffered(Vec<u8>),
+}
+
+/// Body of a proxied response, streamed from the upstream to the client once the response head is sent.
+///
+/// Where the connection cannot be taken over, as on HTTP/2 connections, the body is read in full instead.
+struct Relayed(Mutex<Relay>, Option<ErrorHook>);
+
+impl Debug for Relayed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Relayed").finish_non_exhaustive()
+    }
+}
+
+impl Body for Relayed {
+    fn bytes(&self) -> Vec<u8> {
+        let mut relay = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if let Relay::Streaming(body, _) = &mut *relay {
+            let mut bytes = vec![];
+
+            if let Err(error) = body.read_to_end(&mut bytes) {
+                if let Some(on_error) = &self.1 {
+                    on_error(&error);
+                }
+            }
+
+            *relay = Relay::Buffered(bytes);
+        }
+
+        match &*relay {
+            Relay::Buffered(bytes) => bytes.clone(),
+            Relay::Streaming(..) => vec![],
+        }
+    }
+
+    fn take_over(&mut self) -> Option<TakeOver> {
+        let relay = self.0.get_mut().unwrap_or_else(PoisonError::into_inner);
+
+        match std::mem::replace(relay, Relay::Buffered(vec![])) {
+            Relay::Streaming(mut body, in_flight) => {
+                let on_error = self.1.clone();
+
+                Some(Box::new(move |mut connection: Box<dyn Connection>| {
+                    if let Err(error) = std::io::copy(&mut body, &mut connection) {
+                        if let Some(on_error) = on_error {
+                            on_error(&error);
+                        }
+                    }
+
+                    // The client may well be gone already
+                    let _ = connection.flush();
+                    let _ = connection.shutdown();
+
+                    drop(in_flight);
+                }))
+            }
+            buffered @ Relay::Buffered(_) => {
+                *relay = buffered;
+
+                None
+            }
+        }
+    }
+}
+
+/// Prepares a request for the upstreams by stripping hop-by-hop headers and recording the client and the proxy
+fn forwarded(mut request: Request) -> Request {
+    strip_hop_by_hop(&mut request.headers);
+
+    let host = request.headers.iter().find_map(|header| match header {
+        HttpHeader::Host(host) => Some(host.trim().to_string()),
+        _ => None,
+    });
+
+    let client = request.peer_addr.map(|address| address.ip());
+
+    if let Some(client) = client {
+        append(
+            &mut request.headers,
+            HttpHeader::XForwardedFor,
+            &client.to_string(),
+        );
+    }
+
+    let has_forwarded_host = request
+        .headers
+        .iter()
+        .any(|header| matches!(header, HttpHeader::XForwardedHost(_)));
+
+    if let (Some(host), false) = (&host, has_forwarded_host) {
+        request
+            .headers
+            .push(HttpHeader::XForwardedHost(host.clone()));
+    }
+
+    // RFC 7239 Section 4, IPv6 addresses and ports need quoting
+    let element: Vec<String> = [
+        client.map(|client| match client {
+            IpAddr::V4(client) => format!("for={client}"),
+            IpAddr::V6(client) => format!("for=\"[{client}]\""),
+        }),
+        host.map(|host| format!("host=\"{}\"", host.replace(['"', '\\'], ""))),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    if !element.is_empty() {
+        append(
+            &mut request.headers,
+            HttpHeader::Forwarded,
+            &element.join(";"),
+        );
+    }
+
+    let version = request
+        .http_version
+        .strip_prefix("HTTP/")
+        .unwrap_or("1.1")
+        .to_string();
+
+    append(
+        &mut request.headers,
+        HttpHeader::Via,
+        &format!("{version} hyperion"),
+    );
+
+    // The body was read in full, so its length is known whichever way it arrived
+    request
+        .headers
+        .retain(|header| !matches!(header, HttpHeader::ContentLength(_)));
+
+    if let Some(body) = &request.body {
+        request
+            .headers
+            .push(HttpHeader::ContentLength(body.len().to_string()));
+    }
+
+    // Connections to upstreams are not reused
+    request
+        .headers
+        .push(HttpHeader::Connection("close".to_string()));
+    request.http_version = "HTTP/1.1".to_string();
+
+    request
+}
+
+/// Removes the headers that only apply to a single connection, RFC 9110 Section 7.6.1,
+/// along with those listed in the `Connection` header
+fn strip_hop_by_hop(headers: &mut Vec<HttpHeader>) {
+    let listed: Vec<String> = headers
+        .iter()
+        .filter_map(|header| match header {
+            HttpHeader::Connection(names) => Some(names),
+            _ => None,
+        })
+        .flat_map(|names| names.split(','))
+        .map(|name| name.trim().to_string())
+        .collect();
+
+    headers.retain(|header| {
+        let hop_by_hop = matches!(
+            header,
+            HttpHeader::Connection(_)
+                | HttpHeader::KeepAlive(_)
+                | HttpHeader::ProxyAuthenticate(_)
+                | HttpHeader::ProxyAuthorization(_)
+                | HttpHeader::Te(_)
+                | HttpHeader::Trailer(_)
+                | HttpHeader::TransferEncoding(_)
+                | HttpHeader::Upgrade(_)
+                | HttpHeader::Http2Settings(_)
+        );
+
+        !hop_by_hop
+            && !listed
+                .iter()
+                .any(|name| name.eq_ignore_ascii_case(&header.name()))
+    });
+}
+
+/// Adds a value to a header holding a comma separated list, creating the header if it is missing
+fn append(headers: &mut Vec<HttpHeader>, header: fn(String) -> HttpHeader, value: &str) {
+    let name = header(String::new()).name();
+
+    match headers.iter_mut().find(|existing| existing.name() == name) {
+        Some(existing) => *existing = header(format!("{}, {value}", existing.value())),
+        None => headers.push(header(value.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::{method::Method, request::Extensions, router::routes, testing::TestClient};
+    use std::{
+        collections::HashMap,
+        io::BufRead,
+        net::TcpListener,
+        sync::mpsc,
+        thread::{self, JoinHandle},
+    };
+
+    /// Reads a request head and its sized body, leaving the connection open for the response
+    fn read_request(reader: &mut BufReader<TcpStream>) -> String {
+        let mut head = String::new();
+
+        while !head.ends_with("\r\n\r\n") {
+            assert_ne!(reader.read_line(&mut head).unwrap(), 0, "Truncated request");
+        }
+
+        let length = head
+            .lines()
+            .find_map(|line| line.strip_prefix("Content-Length: "))
+            .map_or(0, |length| length.parse().unwrap());
+
+        reader.read_exact(&mut vec![0; length]).unwrap();
+
+        head
+    }
+
+    /// An upstream answering a connection each with the given responses in order, which returns the heads
+    /// of the requests it received
+    fn upstream(responses: Vec<&'static str>) -> (String, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        let server = thread::spawn(move || {
+            responses
+                .into_iter()
+                .map(|response| {
+                    let (stream, _) = listener.accept().unwrap();
+                    let mut reader = BufReader::new(stream);
+
+                    let head = read_request(&mut reader);
+                    reader.get_mut().write_all(response.as_bytes()).unwrap();
+
+                    head
+                })
+                .collect()
+        });
+
+        (url, server)
+    }
+
+    fn named(name: &'static str, requests: usize) -> (String, JoinHandle<Vec<String>>) {
+        let response = match name {
+            "a" => "HTTP/1.1 200 Ok\r\nContent-Length: 1\r\n\r\na",
+            _ => "HTTP/1.1 200 Ok\r\nContent-Length: 1\r\n\r\nb",
+        };
+
+        upstream(vec![response; requests])
+    }
+
+    fn get(path: &str) -> Request {
+        Request {
+            method: Method::Get,
+            path: path.to_string(),
+            query: None,
+            params: HashMap::new(),
+            http_version: "HTTP/1.1".to_string(),
+            headers: vec![HttpHeader::Host("example.com".to_string())],
+            body: None,
+            peer_addr: None,
+            extensions: Extensions::default(),
+        }
+    }
+
+    fn body(response: &Response<Box<dyn Body>>) -> String {
+        String::from_utf8(response.body.bytes()).unwrap()
+    }
+
+    #[test]
+    fn upstreams_take_turns() {
+        let (a, first) = named("a", 2);
+        let (b, second) = named("b", 2);
+
+        let proxy = Proxy::new([a, b]).unwrap();
+
+        let bodies: Vec<String> = (0..4)
+            .map(|_| body(&proxy.handle(get("/")).unwrap()))
+            .collect();
+
+        assert_eq!(bodies, ["a", "b", "a", "b"]);
+
+        first.join().unwrap();
+        second.join().unwrap();
+    }
+
+    #[test]
+    fn least_connections_skips_busy_upstreams() {
+        let (a, first) = named("a", 1);
+        let (b, second) = named("b", 2);
+
+        let proxy = Proxy::new([a, b])
+            .unwrap()
+            .balance(Balance::LeastConnections);
+
+        // The first response is in flight until its body is relayed
+        let busy = proxy.handle(get("/")).unwrap();
+
+        assert_eq!(body(&proxy.handle(get("/")).unwrap()), "b");
+        assert_eq!(body(&proxy.handle(get("/")).unwrap()), "b");
+        assert_eq!(body(&busy), "a");
+
+        first.join().unwrap();
+        second.join().unwrap();
+    }
+
+    #[test]
+    fn unreachable_upstreams_are_skipped() {
+        // Nothing listens on the port once the listener is gone
+        let closed = TcpListener::bind("127.0.0.1:0").unwrap();
+        let down = format!("http://{}", closed.local_addr().unwrap());
+        drop(closed);
+
+        let (b, server) = named("b", 3);
+
+        let proxy = Proxy::new([down.clone(), b]).unwrap().max_fails(1);
+
+        for _ in 0..3 {
+            assert_eq!(body(&proxy.handle(get("/")).unwrap()), "b");
+        }
+
+        server.join().unwrap();
+
+        // With no upstream left to try, the failure reaches the client
+        let error = Proxy::new([down]).unwrap().handle(get("/")).unwrap_err();
+
+        assert!(matches!(error, Error::BadGateway(_)));
+    }
+
+    #[test]
+    fn hop_by_hop_headers_are_stripped() {
+        let (url, server) = upstream(vec![
+            "HTTP/1.1 200 Ok\r\nConnection: keep-alive, X-Internal\r\nKeep-Alive: timeout=5\r\nX-Internal: 1\r\nVia: 1.1 upstream\r\nContent-Length: 2\r\n\r\nok",
+        ]);
+
+        let proxy = Proxy::new([format!("{url}/base")]).unwrap();
+        let client = TestClient::new(routes!(Get "/path" -> proxy,));
+
+        let response = client.request(
+            Method::Get,
+            "/path?query",
+            vec![
+                HttpHeader::Host("example.com".to_string()),
+                HttpHeader::Connection("X-Secret".to_string()),
+                HttpHeader::new("X-Secret", "1").unwrap(),
+                HttpHeader::KeepAlive("timeout=5".to_string()),
+                HttpHeader::Te("trailers".to_string()),
+                HttpHeader::Via("1.0 front".to_string()),
+                HttpHeader::new("X-Custom", "kept").unwrap(),
+            ],
+            None,
+        );
+
+        response
+            .assert_status(HttpStatus::Ok)
+            .assert_body("ok")
+            .assert_header(&HttpHeader::Via("1.1 upstream, 1.1 hyperion".to_string()));
+        assert_eq!(response.header("X-Internal"), None);
+        assert_eq!(response.header("Keep-Alive"), None);
+
+        let head = server.join().unwrap().remove(0);
+        let authority = url.trim_start_matches("http://");
+
+        assert!(
+            head.starts_with("GET /base/path?query HTTP/1.1\r\n"),
+            "{head}"
+        );
+        assert!(head.contains(&format!("Host: {authority}\r\n")), "{head}");
+        assert!(head.contains("X-Custom: kept\r\n"), "{head}");
+        assert!(head.contains("Via: 1.0 front, 1.1 hyperion\r\n"), "{head}");
+        assert!(head.contains("X-Forwarded-For: 127.0.0.1\r\n"), "{head}");
+        assert!(head.contains("X-Forwarded-Host: example.com\r\n"), "{head}");
+        assert!(head.contains("Connection: close\r\n"), "{head}");
+
+        for stripped in ["x-secret", "keep-alive", "te"] {
+            assert!(
+                !head
+                    .to_ascii_lowercase()
+                    .contains(&format!("\r\n{stripped}:")),
+                "{head}"
+            );
+        }
+    }
+
+    #[test]
+    fn response_heads_are_relayed_before_the_body_arrives() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let (finish, finished) = mpsc::channel::<()>();
+
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+
+            read_request(&mut reader);
+
+            let stream = reader.get_mut();
+
+            stream
+                .write_all(b"HTTP/1.1 200 Ok\r\nTransfer-Encoding: chunked\r\n\r\n6\r\nfirst \r\n")
+                .unwrap();
+
+            // The rest of the body only follows once the proxy has passed the head on
+            finished.recv().unwrap();
+
+            stream.write_all(b"6\r\nsecond\r\n0\r\n\r\n").unwrap();
+        });
+
+        let response = Proxy::new([url]).unwrap().handle(get("/")).unwrap();
+
+        // The decoded body is relayed up to the end of the connection, without the framing of the upstream
+        assert_eq!(response.status, HttpStatus::Ok);
+        assert!(!response.headers.iter().any(|header| matches!(
+            header,
+            HttpHeader::TransferEncoding(_) | HttpHeader::ContentLength(_)
+        )));
+
+        finish.send(()).unwrap();
+
+        assert_eq!(body(&response), "first second");
+
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn bodies_are_streamed_to_the_client() {
+        let (url, server) = upstream(vec![
+            "HTTP/1.1 200 Ok\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n6\r\n world\r\n0\r\n\r\n",
+        ]);
+
+        let proxy = Proxy::new([url]).unwrap();
+
+        TestClient::new(routes!(Get "/" -> proxy,))
+            .get("/")
+            .assert_status(HttpStatus::Ok)
+            .assert_body("hello world");
+
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn interim_responses_are_skipped_and_protocol_switches_refused() {
+        let (url, server) = upstream(vec![
+            "HTTP/1.1 100 Continue\r\n\r\nHTTP/1.1 200 Ok\r\nContent-Length: 2\r\n\r\nok",
+            "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\r\n",
+        ]);
+
+        let proxy = Proxy::new([url]).unwrap();
+
+        assert_eq!(body(&proxy.handle(get("/")).unwrap()), "ok");
+        assert!(matches!(proxy.handle(get("/")), Err(Error::BadGateway(_))));
+
+        server.join().unwrap();
+    }
+}
@@ -0,0 +1,584 @@
+use crate::http::{
+    error::Error,
+    header::HttpHeader,
+    request::Request,
+    response::{Body, Response},
+    router::RequestHandler,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant, SystemTime},
+};
+
+/// How a [`RateLimit`] counts the requests of a client
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Algorithm {
+    /// Refills the quota continuously, allowing bursts of up to the whole quota
+    #[default]
+    TokenBucket,
+
+    /// Counts the requests of the last window, weighting those of the previous window by how much of it
+    /// still overlaps
+    SlidingWindow,
+}
+
+/// What the requests counted against the same quota have in common
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Key {
+    /// The IP address of the client
+    #[default]
+    Ip,
+
+    /// The value of a header, such as `X-API-Key`, names are case-insensitive
+    Header(String),
+
+    /// The method and path of the request
+    Route,
+}
+
+impl Key {
+    /// Returns the key a request is counted under, `None` when the request lacks it, such as a request without
+    /// the header, or from a client without an IP address.
+    ///
+    /// Keys start with their kind, as in `ip:192.0.2.1`, `header:<value>` or `route:Get /`, so that a header
+    /// value cannot pass for an address in a store shared by several limits
+    #[must_use]
+    pub fn of(&self, request: &Request) -> Option<String> {
+        match self {
+            Key::Ip => request
+                .peer_addr
+                .map(|address| format!("ip:{}", address.ip())),
+            Key::Header(name) => request
+                .headers
+                .iter()
+                .find(|header| header.name().eq_ignore_ascii_case(name))
+                .map(|header| format!("header:{}", header.value())),
+            Key::Route => Some(format!("route:{:?} {}", request.method, request.path)),
+        }
+    }
+}
+
+/// What a [`RateLimit`] does with requests that lack its [`Key`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Missing {
+    /// Counts them by the IP address of the client instead, and passes them on without counting them when that
+    /// is unknown as well, rather than have every such client share a single quota
+    #[default]
+    Ip,
+
+    /// Passes them on without counting them
+    Bypass,
+
+    /// Answers them with 400 Bad Request
+    Reject,
+}
+
+/// What a [`Store`] keeps for each key, with wall-clock times so that servers can share it
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum State {
+    /// State of a [`Algorithm::TokenBucket`]
+    TokenBucket {
+        /// Tokens left in the bucket
+        tokens: f64,
+        /// When the tokens were counted
+        updated: SystemTime,
+    },
+
+    /// State of a [`Algorithm::SlidingWindow`]
+    SlidingWindow {
+        /// When the current window started
+        start: SystemTime,
+        /// Requests counted in the current window
+        current: u32,
+        /// Requests counted in the window before it
+        previous: u32,
+    },
+}
+
+/// Keeps the [`State`] of every key, in memory with [`MemoryStore`] or in a backend shared between servers
+pub trait Store: Send + Sync {
+    /// Replaces the state of `key` with what `update` makes of it, as one atomic step. `update` is given
+    /// `None` for keys without a state, and may be called again if the step has to be retried.
+    /// States left untouched for `ttl` may be dropped
+    fn update(&self, key: &str, ttl: Duration, update: &mut dyn FnMut(Option<State>) -> State);
+}
+
+/// Keeps states in memory, dropping those of keys that were not seen for a while
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    states: Mutex<MemoryStates>,
+}
+
+/// States along with when they expire, and when expired ones are dropped next
+#[derive(Debug, Default)]
+struct MemoryStates {
+    entries: HashMap<String, (State, Instant)>,
+    next_sweep: Option<Instant>,
+}
+
+impl Store for MemoryStore {
+    fn update(&self, key: &str, ttl: Duration, update: &mut dyn FnMut(Option<State>) -> State) {
+        let mut states = self.states.lock().unwrap_or_else(PoisonError::into_inner);
+        let now = Instant::now();
+
+        if states.next_sweep.is_none_or(|sweep| sweep <= now) {
+            states.entries.retain(|_, (_, expires)| *expires > now);
+            states.next_sweep = Some(now + ttl);
+        }
+
+        let state = states
+            .entries
+            .get(key)
+            .filter(|(_, expires)| *expires > now)
+            .map(|(state, _)| *state);
+
+        let state = update(state);
+
+        states.entries.insert(key.to_string(), (state, now + ttl));
+    }
+}
+
+/// Whether a request fits the quota of its key, and what is left of the quota
+struct Decision {
+    allowed: bool,
+    remaining: u32,
+    reset: Duration,
+    retry_after: Duration,
+}
+
+impl Algorithm {
+    /// Counts a request against the state of its key
+    fn count(
+        self,
+        state: Option<State>,
+        limit: u32,
+        window: Duration,
+        now: SystemTime,
+    ) -> (State, Decision) {
+        let limit = f64::from(limit);
+        let window = window.as_secs_f64();
+        let since = |time: SystemTime| now.duration_since(time).unwrap_or_default().as_secs_f64();
+
+        match self {
+            Algorithm::TokenBucket => {
+                let rate = limit / window;
+
+                let tokens = match state {
+                    Some(State::TokenBucket { tokens, updated }) => {
+                        (tokens + since(updated) * rate).min(limit)
+                    }
+                    _ => limit,
+                };
+
+                let allowed = tokens >= 1.0;
+                let tokens = if allowed { tokens - 1.0 } else { tokens };
+
+                let decision = Decision {
+                    allowed,
+                    remaining: whole(tokens),
+                    reset: seconds((limit - tokens) / rate),
+                    retry_after: seconds((1.0 - tokens) / rate),
+                };
+
+                (
+                    State::TokenBucket {
+                        tokens,
+                        updated: now,
+                    },
+                    decision,
+                )
+            }
+            Algorithm::SlidingWindow => {
+                let (mut start, mut current, mut previous) = match state {
+                    Some(State::SlidingWindow {
+                        start,
+                        current,
+                        previous,
+                    }) => (start, current, previous),
+                    _ => (now, 0, 0),
+                };
+
+                // Move on to the window `now` falls in, requests from two or more windows ago no longer count
+                let passed = (since(start) / window).floor();
+
+                if passed >= 1.0 {
+                    previous = if passed < 2.0 { current } else { 0 };
+                    current = 0;
+                    start += Duration::from_secs_f64(window * passed);
+                }
+
+                let progress = since(start) / window;
+                let estimate = f64::from(previous) * (1.0 - progress) + f64::from(current);
+
+                let allowed = estimate + 1.0 <= limit;
+
+                if allowed {
+                    current += 1;
+                }
+
+                // Denied requests wait until the previous window weighs little enough, and once the current
+                // window is full, until it has become the previous one
+                let retry_after = if allowed {
+                    0.0
+                } else if f64::from(current) + 1.0 > limit {
+                    window * (1.0 - progress) + window * (1.0 - (limit - 1.0) / f64::from(current))
+                } else {
+                    window * (1.0 - (limit - 1.0 - f64::from(current)) / f64::from(previous))
+                        - window * progress
+                };
+
+                let decision = Decision {
+                    allowed,
+                    remaining: whole(limit - estimate - f64::from(u8::from(allowed))),
+                    reset: seconds(window * (1.0 - progress)),
+                    retry_after: seconds(retry_after),
+                };
+
+                (
+                    State::SlidingWindow {
+                        start,
+                        current,
+                        previous,
+                    },
+                    decision,
+                )
+            }
+        }
+    }
+
+    /// How long a state stays relevant after it was last updated
+    fn ttl(self, window: Duration) -> Duration {
+        match self {
+            Algorithm::TokenBucket => window,
+            Algorithm::SlidingWindow => window * 2,
+        }
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Clamped to zero, and never above the limit
+fn whole(requests: f64) -> u32 {
+    requests.max(0.0).floor() as u32
+}
+
+fn seconds(seconds: f64) -> Duration {
+    Duration::from_secs_f64(seconds.max(0.0))
+}
+
+/// Rounds up, so that a client waiting the advertised time is not turned away again
+fn header_seconds(duration: Duration) -> u64 {
+    duration.as_secs() + u64::from(duration.subsec_nanos() > 0)
+}
+
+/// Request handler that passes requests on to another handler only while their client stays within its quota.
+///
+/// Responses carry `RateLimit-Limit`, `RateLimit-Remaining` and `RateLimit-Reset` headers, requests over the
+/// quota are answered with 429 Too Many Requests and a `Retry-After` header. Limiters sharing a [`Store`]
+/// share the quotas of their keys, which limits a client across several routes.
+pub struct RateLimit<H> {
+    handler: H,
+    limit: u32,
+    window: Duration,
+    algorithm: Algorithm,
+    key: Key,
+    missing: Missing,
+    store: Arc<dyn Store>,
+}
+
+impl<H: RequestHandler> RateLimit<H> {
+    /// Passes `limit` requests per `window` from every client address on to `handler`, counted in a
+    /// token bucket kept in memory
+    /// # Panics
+    /// - If `limit` or `window` is zero
+    #[must_use]
+    pub fn new(handler: H, limit: u32, window: Duration) -> Self {
+        assert!(limit > 0, "A rate limit must allow at least one request");
+        assert!(!window.is_zero(), "A rate limit window must not be empty");
+
+        Self {
+            handler,
+            limit,
+            window,
+            algorithm: Algorithm::default(),
+            key: Key::default(),
+            missing: Missing::default(),
+            store: Arc::new(MemoryStore::default()),
+        }
+    }
+
+    /// Sets how requests are counted
+    #[must_use]
+    pub fn algorithm(mut self, algorithm: Algorithm) -> Self {
+        self.algorithm = algorithm;
+
+        self
+    }
+
+    /// Sets what the requests counted against the same quota have in common, the client address by default
+    #[must_use]
+    pub fn key(mut self, key: Key) -> Self {
+        self.key = key;
+
+        self
+    }
+
+    /// Sets what happens to requests that lack the key, they are counted by client address by default
+    #[must_use]
+    pub fn missing(mut self, missing: Missing) -> Self {
+        self.missing = missing;
+
+        self
+    }
+
+    /// Keeps the quotas in the given store instead of a store of its own
+    #[must_use]
+    pub fn store(mut self, store: Arc<dyn Store>) -> Self {
+        self.store = store;
+
+        self
+    }
+
+    fn acquire(&self, key: &str) -> Option<Decision> {
+        let mut decision = None;
+
+        self.store
+            .update(key, self.algorithm.ttl(self.window), &mut |state| {
+                let (state, counted) =
+                    self.algorithm
+                        .count(state, self.limit, self.window, SystemTime::now());
+
+                decision = Some(counted);
+
+                state
+            });
+
+        decision
+    }
+}
+
+impl<H: RequestHandler> RequestHandler for RateLimit<H> {
+    fn handle(&self, request: Request) -> Result<Response<Box<dyn Body>>, Error> {
+        let key = self.key.of(&request).or_else(|| match self.missing {
+            Missing::Ip => Key::Ip.of(&request),
+            Missing::Bypass | Missing::Reject => None,
+        });
+
+        let key = match (key, self.missing) {
+            (Some(key), _) => key,
+            (None, Missing::Ip | Missing::Bypass) => return self.handler.handle(request),
+            (None, Missing::Reject) => {
+                return Err(Error::BadRequest(
+                    "The request lacks what its rate limit is counted by".to_string(),
+                ))
+            }
+        };
+
+        // A store that did not count the request has nothing to hold it back with
+        let Some(decision) = self.acquire(&key) else {
+            return self.handler.handle(request);
+        };
+
+        let headers = vec![
+            HttpHeader::RateLimitLimit(self.limit.to_string()),
+            HttpHeader::RateLimitRemaining(decision.remaining.to_string()),
+            HttpHeader::RateLimitReset(header_seconds(decision.reset).to_string()),
+        ];
+
+        if !decision.allowed {
+            return Err(Error::TooManyRequests {
+                retry_after: header_seconds(decision.retry_after).max(1),
+                headers,
+            });
+        }
+
+        let mut response = self.handler.handle(request)?;
+        response.headers.extend(headers);
+
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Algorithm, Key, MemoryStore, Missing, RateLimit};
+    use crate::http::{
+        header::HttpHeader,
+        method::Method,
+        request::{Extensions, Request},
+        response::Response,
+        route,
+        router::routes,
+        status::HttpStatus,
+        testing::TestClient,
+    };
+    use std::{
+        collections::HashMap,
+        net::{Ipv4Addr, SocketAddr},
+        sync::Arc,
+        time::Duration,
+    };
+
+    #[route]
+    fn ok() -> Response<String> {
+        Response::new("ok".to_string(), vec![], HttpStatus::Ok)
+    }
+
+    fn from(client: TestClient, last_octet: u8) -> TestClient {
+        client.peer_addr(Some(SocketAddr::from((
+            Ipv4Addr::new(192, 0, 2, last_octet),
+            1234,
+        ))))
+    }
+
+    fn limited(algorithm: Algorithm, key: Key) -> TestClient {
+        let limited = Arc::new(
+            RateLimit::new(ok, 2, Duration::from_secs(60))
+                .algorithm(algorithm)
+                .key(key),
+        );
+        let other = limited.clone();
+
+        TestClient::new(routes!(Get "/" -> limited, Get "/other" -> other,))
+    }
+
+    #[test]
+    fn requests_over_the_quota_are_refused() {
+        // A token is refilled every 30 seconds, while the full previous window keeps counting for half the next one
+        for (algorithm, retry_after) in [
+            (Algorithm::TokenBucket, "30"),
+            (Algorithm::SlidingWindow, "90"),
+        ] {
+            let client = limited(algorithm, Key::Ip);
+
+            client
+                .get("/")
+                .assert_status(HttpStatus::Ok)
+                .assert_body("ok");
+            assert_eq!(
+                client.get("/").header("RateLimit-Remaining").as_deref(),
+                Some("0")
+            );
+
+            let refused = client.get("/");
+            refused.assert_status(HttpStatus::TooManyRequests);
+            assert_eq!(refused.header("RateLimit-Limit").as_deref(), Some("2"));
+            assert_eq!(refused.header("Retry-After").as_deref(), Some(retry_after));
+        }
+    }
+
+    #[test]
+    fn clients_have_quotas_of_their_own() {
+        let client = limited(Algorithm::TokenBucket, Key::Ip);
+        let client = from(client, 1);
+
+        client.get("/").assert_status(HttpStatus::Ok);
+        client.get("/").assert_status(HttpStatus::Ok);
+        client.get("/").assert_status(HttpStatus::TooManyRequests);
+
+        from(client, 2).get("/").assert_status(HttpStatus::Ok);
+    }
+
+    #[test]
+    fn requests_without_the_key() {
+        let client = |missing: Missing| {
+            let limited = RateLimit::new(ok, 1, Duration::from_secs(60))
+                .key(Key::Header("X-Client".to_string()))
+                .missing(missing);
+
+            TestClient::new(routes!(Get "/" -> limited,))
+        };
+        let keyed = |client: &TestClient, key: &str| {
+            client.request(
+                Method::Get,
+                "/",
+                vec![HttpHeader::new("x-client", key).unwrap()],
+                None,
+            )
+        };
+
+        let by_ip = client(Missing::Ip);
+        by_ip.get("/").assert_status(HttpStatus::Ok);
+        by_ip.get("/").assert_status(HttpStatus::TooManyRequests);
+        from(by_ip, 1).get("/").assert_status(HttpStatus::Ok);
+
+        let bypassed = client(Missing::Bypass);
+        bypassed.get("/").assert_status(HttpStatus::Ok);
+        bypassed.get("/").assert_status(HttpStatus::Ok);
+        keyed(&bypassed, "a").assert_status(HttpStatus::Ok);
+        keyed(&bypassed, "a").assert_status(HttpStatus::TooManyRequests);
+        keyed(&bypassed, "b").assert_status(HttpStatus::Ok);
+
+        client(Missing::Reject)
+            .get("/")
+            .assert_status(HttpStatus::BadRequest);
+    }
+
+    #[test]
+    fn requests_without_an_address_are_not_counted_together() {
+        let limited = RateLimit::new(ok, 1, Duration::from_secs(60));
+        let client = TestClient::new(routes!(Get "/" -> limited,)).peer_addr(None);
+
+        client.get("/").assert_status(HttpStatus::Ok);
+        client.get("/").assert_status(HttpStatus::Ok);
+        assert_eq!(client.get("/").header("RateLimit-Remaining"), None);
+    }
+
+    #[test]
+    fn keys_of_different_kinds_do_not_meet() {
+        let request = Request {
+            method: Method::Get,
+            path: "/".to_string(),
+            query: None,
+            params: HashMap::new(),
+            http_version: "HTTP/1.1".to_string(),
+            headers: vec![HttpHeader::new("X-Client", "192.0.2.1").unwrap()],
+            body: None,
+            peer_addr: Some(SocketAddr::from((Ipv4Addr::new(192, 0, 2, 1), 1234))),
+            extensions: Extensions::default(),
+        };
+
+        assert_eq!(Key::Ip.of(&request).as_deref(), Some("ip:192.0.2.1"));
+        assert_eq!(
+            Key::Header("x-client".to_string()).of(&request).as_deref(),
+            Some("header:192.0.2.1")
+        );
+        assert_eq!(Key::Route.of(&request).as_deref(), Some("route:Get /"));
+
+        // A header claiming to be an address is counted apart from requests that come from it
+        let store = Arc::new(MemoryStore::default());
+        let by_header = RateLimit::new(ok, 1, Duration::from_secs(60))
+            .key(Key::Header("X-Client".to_string()))
+            .store(store.clone());
+        let by_ip = RateLimit::new(ok, 1, Duration::from_secs(60)).store(store);
+        let client = from(
+            TestClient::new(routes!(Get "/" -> by_header, Get "/other" -> by_ip,)),
+            1,
+        );
+
+        client
+            .request(Method::Get, "/", request.headers, None)
+            .assert_status(HttpStatus::Ok);
+        client.get("/other").assert_status(HttpStatus::Ok);
+    }
+
+    #[test]
+    fn routes_can_share_a_quota() {
+        let client = limited(Algorithm::TokenBucket, Key::Route);
+
+        client.get("/").assert_status(HttpStatus::Ok);
+        client.get("/").assert_status(HttpStatus::Ok);
+        client.get("/").assert_status(HttpStatus::TooManyRequests);
+        client.get("/other").assert_status(HttpStatus::Ok);
+
+        let store = Arc::new(MemoryStore::default());
+        let first = RateLimit::new(ok, 1, Duration::from_secs(60)).store(store.clone());
+        let second = RateLimit::new(ok, 1, Duration::from_secs(60)).store(store);
+        let client = TestClient::new(routes!(Get "/" -> first, Get "/other" -> second,));
+
+        client.get("/").assert_status(HttpStatus::Ok);
+        client
+            .get("/other")
+            .assert_status(HttpStatus::TooManyRequests);
+    }
+}
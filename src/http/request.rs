@@ -1,20 +1,32 @@
 use crate::error;
 use crate::http::error::Error;
 use crate::http::header::HttpHeader;
+use crate::http::limits::{timed_out, Deadline, Limits, Timeouts};
+use crate::http::listener::Connection;
 use crate::http::method::Method;
-use serde::Serialize;
-use std::fmt::Display;
+use serde::{de::DeserializeOwned, Serialize};
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt::{Debug, Display};
 use std::io::Read;
-use std::net::TcpStream;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Instant;
 
 /// Representation of a HTTP Request
 #[derive(Debug, Serialize, Clone)]
 pub struct Request {
     /// The Method of the HTTP request
     pub method: Method,
-    /// The Path of the HTTP request
+    /// The Path of the HTTP request, without the query string
     pub path: String,
 
+    /// The raw query string of the HTTP request, without the leading `?`
+    pub query: Option<String>,
+
+    /// Parameters captured from the path by the route pattern that matched the request
+    pub params: HashMap<String, String>,
+
     /// The HTTP Version used in the HTTP request
     pub http_version: String,
 
@@ -23,13 +35,91 @@ pub struct Request {
 
     /// The body of the HTTP request
     pub body: Option<Vec<u8>>,
+
+    /// Address of the client that sent the request, if the transport has one
+    pub peer_addr: Option<SocketAddr>,
+
+    /// Values attached to the request by the handlers it passed through
+    #[serde(skip)]
+    pub extensions: Extensions,
+}
+
+/// Values attached to a request, at most one of each type
+#[derive(Clone, Default)]
+pub struct Extensions(HashMap<TypeId, Arc<dyn Any + Send + Sync>>);
+
+impl Extensions {
+    /// Attaches a value, replacing any earlier value of the same type
+    pub fn insert<T: Send + Sync + 'static>(&mut self, value: T) {
+        self.0.insert(TypeId::of::<T>(), Arc::new(value));
+    }
+
+    /// Returns the value of the given type, if one was attached
+    #[must_use]
+    pub fn get<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.0
+            .get(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_ref())
+    }
+}
+
+impl Debug for Extensions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Extensions")
+            .field("len", &self.0.len())
+            .finish()
+    }
+}
+
+impl Request {
+    /// Returns the raw body of the request
+    /// # Errors
+    /// - If the request has no body
+    pub fn body(&self) -> Result<&[u8], Error> {
+        self.body
+            .as_deref()
+            .ok_or(error!(BadRequest, "Missing request body"))
+    }
+
+    /// Deserializes the JSON body of the request
+    /// # Errors
+    /// - If the request has no body
+    /// - If the body is not valid JSON for `T`
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, Error> {
+        Ok(serde_json::from_slice(self.body()?)?)
+    }
+
+    /// Deserializes the XML body of the request
+    /// # Errors
+    /// - If the request has no body
+    /// - If the body is not valid XML for `T`
+    pub fn xml<T: DeserializeOwned>(&self) -> Result<T, Error> {
+        let body = String::from_utf8(self.body()?.to_vec())
+            .map_err(|error| error!(BadRequest, error.to_string()))?;
+
+        Ok(xml_serde::from_string(body)?)
+    }
+
+    /// Deserializes the YAML body of the request
+    /// # Errors
+    /// - If the request has no body
+    /// - If the body is not valid YAML for `T`
+    pub fn yaml<T: DeserializeOwned>(&self) -> Result<T, Error> {
+        Ok(serde_yaml::from_slice(self.body()?)?)
+    }
 }
 
 impl Display for Request {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let method = format!("{:?}", self.method).to_uppercase();
 
-        write!(f, "{} {} {}\r\n", method, self.path, self.http_version)?;
+        write!(f, "{} {}", method, self.path)?;
+
+        if let Some(query) = &self.query {
+            write!(f, "?{query}")?;
+        }
+
+        write!(f, " {}\r\n", self.http_version)?;
 
         for header in &self.headers {
             write!(f, "{header}\r\n")?;
@@ -47,6 +137,18 @@ impl Display for Request {
     }
 }
 
+impl From<Request> for Vec<u8> {
+    fn from(mut request: Request) -> Self {
+        // The body is written as is, it need not be valid UTF-8 like the head
+        let body = request.body.take();
+        let mut bytes = request.to_string().into_bytes();
+
+        bytes.extend(body.unwrap_or_default());
+
+        bytes
+    }
+}
+
 /// Implementation of parsing an HTTP Response from a stream of bytes
 pub trait HttpStream {
     /// Function that is used to parse a HTTP Request
@@ -72,15 +174,18 @@ impl HttpStream for Vec<u8> {
             .next()
             .ok_or(error!(BadRequest, "Missing Method"))?;
 
-        let path = req_line.next().ok_or(error!(BadRequest, "Invalid Path"))?;
+        let target = req_line.next().ok_or(error!(BadRequest, "Invalid Path"))?;
+
+        let (path, query) = match target.split_once('?') {
+            Some((path, query)) => (path, Some(query.to_string())),
+            None => (target, None),
+        };
 
         let http_version = req_line
             .next()
             .ok_or(error!(BadRequest, "Invalid HTTP Version"))?;
 
-        // Leveraging serde to deserialize methods instead of manually converting from string to [HttpMethod]
-        let method: Method = serde_json::from_str(&format!("\"{method}\""))
-            .map_err(|_| error!(BadRequest, format!("Unknown HTTP Method: {method}")))?;
+        let method: Method = method.parse()?;
 
         let mut headers = vec![];
 
@@ -89,70 +194,202 @@ impl HttpStream for Vec<u8> {
                 return Err(error!(BadRequest, format!("Invalid Header: {line}")));
             };
 
-            match HttpHeader::new(header_name, value)
-                .map_err(|error| error!(BadRequest, error.to_string()))
-            {
-                Ok(h) => headers.push(h),
-                Err(er) => println!("{er:?}"),
-            };
+            headers.push(
+                HttpHeader::new(header_name, value)
+                    .map_err(|error| error!(BadRequest, error.to_string()))?,
+            );
         }
 
         Ok(Request {
             method,
             path: path.to_string(),
+            query,
+            params: HashMap::new(),
             http_version: http_version.to_string(),
             headers,
             body: None,
+            peer_addr: None,
+            extensions: Extensions::default(),
         })
     }
 }
 
-impl HttpStream for TcpStream {
+impl<C: Connection> HttpStream for C {
     fn parse(&mut self) -> anyhow::Result<Request, Error> {
-        let mut buffer = vec![0; 2048];
-        let mut request_buffer = vec![];
-        let mut body_buffer = vec![];
+        read_request(
+            self,
+            &Timeouts::default(),
+            &Limits::default(),
+            Instant::now(),
+        )
+    }
+}
 
-        'parse_loop: loop {
-            let read_byte_count = self.read(&mut buffer)?;
+/// Frames a request out of the bytes read from a connection without reading them itself, so that blocking and
+/// asynchronous servers share the same parsing and limits
+pub(crate) struct RequestParser {
+    limits: Limits,
+    buffer: Vec<u8>,
 
-            for (idx, window) in buffer.windows(4).enumerate() {
-                if window == [13, 10, 13, 10] || read_byte_count < 4 {
-                    request_buffer.extend(&buffer[..idx]);
+    /// The parsed head along with the length of its body, once the head is complete
+    head: Option<(Request, Option<usize>)>,
+}
 
-                    // Add any remaining overflow to body bytes buffer
-                    body_buffer.extend(&buffer[idx + 4..]);
+impl RequestParser {
+    pub(crate) fn new(limits: Limits) -> Self {
+        Self {
+            limits,
+            buffer: vec![],
+            head: None,
+        }
+    }
 
-                    break 'parse_loop;
-                }
+    /// Whether the head of the request is still incomplete
+    pub(crate) fn reading_head(&self) -> bool {
+        self.head.is_none()
+    }
+
+    /// How many bytes to read next, at most `capacity`, so that nothing past the body is read
+    pub(crate) fn wanted(&self, capacity: usize) -> usize {
+        match &self.head {
+            Some((_, length)) => length
+                .unwrap_or_default()
+                .saturating_sub(self.buffer.len())
+                .min(capacity),
+            None => capacity,
+        }
+    }
+
+    /// Takes the next bytes read from the connection, returning the request once it is complete
+    /// # Errors
+    /// - If the request exceeds the limits
+    /// - If the head of the request is invalid
+    pub(crate) fn feed(&mut self, bytes: &[u8]) -> Result<Option<Request>, Error> {
+        // Start searching a little before the new bytes in case the terminator straddles two reads
+        let search_start = self.buffer.len().saturating_sub(3);
+
+        self.buffer.extend_from_slice(bytes);
+
+        let (mut request, length) = match self.head.take() {
+            Some(head) => head,
+            None => match self.parse_head(search_start)? {
+                Some(head) => head,
+                None => return Ok(None),
+            },
+        };
+
+        if let Some(length) = length {
+            if self.buffer.len() < length {
+                self.head = Some((request, Some(length)));
+
+                return Ok(None);
             }
 
-            request_buffer.extend(&buffer[..read_byte_count]);
+            self.buffer.truncate(length);
+            request.body = Some(std::mem::take(&mut self.buffer));
         }
 
-        let mut request = request_buffer.parse()?;
+        Ok(Some(request))
+    }
+
+    /// Parses the head once its terminator arrived, keeping only what follows it in the buffer
+    fn parse_head(
+        &mut self,
+        search_start: usize,
+    ) -> Result<Option<(Request, Option<usize>)>, Error> {
+        let Some(index) = self.buffer[search_start..]
+            .windows(4)
+            .position(|window| window == b"\r\n\r\n")
+        else {
+            self.limits.check_head(&self.buffer)?;
 
-        if let Some(content_length) = request
+            return Ok(None);
+        };
+
+        let head_length = search_start + index;
+
+        self.limits.check_head(&self.buffer[..head_length])?;
+
+        // Any overflow after the head already belongs to the body
+        let body = self.buffer.split_off(head_length + 4);
+        self.buffer.truncate(head_length);
+
+        let request = self.buffer.parse()?;
+        self.buffer = body;
+
+        let length = request
             .headers
             .iter()
             .find(|header| matches!(header, HttpHeader::ContentLength(_)))
-            .and_then(|header| header.value().parse::<u16>().ok())
-        {
-            // This condition is to check the following
-            // Do we need to extend? or is the overflow of bytes above it enough to store all the remaining body bytes
-            // i.e Are there any remaining bytes to read other than the overflow above
-            if content_length as usize > body_buffer.len() {
-                buffer.clear();
-                buffer.resize(content_length as usize, 0);
-
-                let read_byte_count = self.read(&mut buffer)?;
-
-                body_buffer.extend(&buffer[..read_byte_count]);
-            }
+            .map(|header| header.value().parse::<usize>())
+            .transpose()?;
 
-            request.body = Some(body_buffer);
+        if let Some(length) = length {
+            self.limits.check_body(length)?;
         }
 
-        Ok(request)
+        Ok(Some((request, length)))
     }
 }
+
+/// Reads a request from a connection, giving the client until the timeouts run out to send it
+pub(crate) fn read_request<C: Connection>(
+    connection: &mut C,
+    timeouts: &Timeouts,
+    limits: &Limits,
+    accepted: Instant,
+) -> Result<Request, Error> {
+    let peer_addr = connection.peer_addr();
+    let mut parser = RequestParser::new(*limits);
+    let mut buffer = vec![0; 2048];
+
+    let mut head_reader = Deadline::new(
+        connection,
+        accepted,
+        timeouts.header_read,
+        timeouts.min_rate,
+    );
+
+    while parser.reading_head() {
+        let read_byte_count = head_reader.read(&mut buffer).map_err(timed_out)?;
+
+        if let Some(request) = parser.feed(received(&buffer, read_byte_count)?)? {
+            return Ok(Request {
+                peer_addr,
+                ..request
+            });
+        }
+    }
+
+    // Read whatever part of the body did not arrive together with the head
+    let mut body_reader = Deadline::new(
+        connection,
+        Instant::now(),
+        timeouts.body_read,
+        timeouts.min_rate,
+    );
+
+    loop {
+        let wanted = parser.wanted(buffer.len());
+        let read_byte_count = body_reader.read(&mut buffer[..wanted]).map_err(timed_out)?;
+
+        if let Some(request) = parser.feed(received(&buffer, read_byte_count)?)? {
+            return Ok(Request {
+                peer_addr,
+                ..request
+            });
+        }
+    }
+}
+
+/// Returns the bytes of a read, a read of none meaning that the client closed the connection early
+pub(crate) fn received(buffer: &[u8], read_byte_count: usize) -> Result<&[u8], Error> {
+    if read_byte_count == 0 {
+        return Err(error!(
+            BadRequest,
+            "Connection closed before the request was complete"
+        ));
+    }
+
+    Ok(&buffer[..read_byte_count])
+}
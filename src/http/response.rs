@@ -1,9 +1,24 @@
-use crate::http::{header::HttpHeader, status::HttpStatus};
+use crate::error;
+use crate::http::{
+    error::Error,
+    header::HttpHeader,
+    listener::Connection,
+    method::Method,
+    negotiation::{self, Format},
+    request::Request,
+    status::HttpStatus,
+};
 use serde::Serialize;
-use std::fmt::{Debug, Display};
+use std::{
+    fmt::{Debug, Display},
+    io::{BufRead, ErrorKind, Read, Take},
+};
+
+/// Continuation of a connection that switched away from HTTP, run once the response has been written
+pub type TakeOver = Box<dyn FnOnce(Box<dyn Connection>) + Send>;
 
 /// Representation of an HTTP body
-pub trait Body: Debug {
+pub trait Body: Debug + Send {
     /// Convert the body into bytes
     fn bytes(&self) -> Vec<u8>;
 
@@ -11,6 +26,11 @@ pub trait Body: Debug {
     fn content_length(&self) -> usize {
         self.bytes().len()
     }
+
+    /// Takes the handler that owns the connection after a `101 Switching Protocols` response, if any
+    fn take_over(&mut self) -> Option<TakeOver> {
+        None
+    }
 }
 
 impl<T: Body> Body for Option<T> {
@@ -59,6 +79,10 @@ impl Body for Box<dyn Body> {
     fn bytes(&self) -> Vec<u8> {
         Body::bytes(self.as_ref())
     }
+
+    fn take_over(&mut self) -> Option<TakeOver> {
+        self.as_mut().take_over()
+    }
 }
 
 impl<const N: usize> Body for [u8; N] {
@@ -71,6 +95,30 @@ impl<const N: usize> Body for [u8; N] {
     }
 }
 
+/// Conversion of the value returned by a route handler into a response
+pub trait IntoResponse {
+    /// Converts the value into a response with a type-erased body
+    /// # Errors
+    /// - If the handler failed, the error is rendered through `From<Error> for Response<Box<dyn Body>>`
+    fn into_response(self) -> Result<Response<Box<dyn Body>>, Error>;
+}
+
+impl<T: Body + 'static> IntoResponse for Response<T> {
+    fn into_response(self) -> Result<Response<Box<dyn Body>>, Error> {
+        Ok(Response::new(
+            Box::new(self.body),
+            self.headers,
+            self.status,
+        ))
+    }
+}
+
+impl<T: Body + 'static, E: Into<Error>> IntoResponse for Result<Response<T>, E> {
+    fn into_response(self) -> Result<Response<Box<dyn Body>>, Error> {
+        self.map_err(Into::into)?.into_response()
+    }
+}
+
 /// Builder for creating a response
 #[must_use]
 pub struct Builder {
@@ -98,7 +146,86 @@ impl Builder {
     pub fn json<T: Serialize>(mut self, body: T) -> Response<String> {
         self.content_type("application/json");
 
-        match serde_json::to_string(&body) {
+        self.serialized(serde_json::to_string(&body))
+    }
+
+    /// Returns a Response object with an XML body
+    pub fn xml<T: Serialize>(mut self, body: T) -> Response<String> {
+        self.content_type("application/xml");
+
+        self.serialized(xml_serde::to_string(&body))
+    }
+
+    /// Returns a Response object with a YAML body
+    pub fn yaml<T: Serialize>(mut self, body: T) -> Response<String> {
+        self.content_type("application/yaml");
+
+        self.serialized(serde_yaml::to_string(&body))
+    }
+
+    /// Returns a Response object whose body is rendered in the format preferred by the request's `Accept` header.
+    ///
+    /// Strings and scalars are rendered as-is for `text/plain`, anything else is rendered as YAML.
+    /// Responds with a 406 when the client accepts none of the supported formats.
+    pub fn negotiate<T: Serialize>(self, request: &Request, body: T) -> Response<String> {
+        let mut response = match negotiation::negotiate(request, &Format::ALL) {
+            Some(Format::Json) => self.json(body),
+            Some(Format::Xml) => self.xml(body),
+            Some(Format::Yaml) => self.yaml(body),
+            Some(Format::Text) => self.plain(body),
+            None => {
+                let available = Format::ALL.map(|format| format.media_type()).join(", ");
+
+                Response {
+                    body: format!("Acceptable representations: {available}"),
+                    headers: vec![HttpHeader::ContentType("text/plain".to_string())],
+                    status: HttpStatus::NotAcceptable,
+                }
+            }
+        };
+
+        // The representation depends on `Accept`, which caches have to take into account, RFC 9110 Section 12.5.5
+        match response
+            .headers
+            .iter_mut()
+            .find(|header| matches!(header, HttpHeader::Vary(_)))
+        {
+            Some(HttpHeader::Vary(vary))
+                if !vary
+                    .split(',')
+                    .any(|field| field.trim().eq_ignore_ascii_case("Accept")) =>
+            {
+                vary.push_str(", Accept");
+            }
+            Some(_) => {}
+            None => response
+                .headers
+                .push(HttpHeader::Vary("Accept".to_string())),
+        }
+
+        response
+    }
+
+    /// Renders a serializable value as plain text
+    fn plain<T: Serialize>(mut self, body: T) -> Response<String> {
+        self.content_type("text/plain");
+
+        let body = match serde_json::to_value(&body) {
+            Ok(serde_json::Value::String(body)) => Ok(body),
+            Ok(serde_json::Value::Null) => Ok(String::new()),
+            Ok(value @ (serde_json::Value::Bool(_) | serde_json::Value::Number(_))) => {
+                Ok(value.to_string())
+            }
+            Ok(_) => serde_yaml::to_string(&body).map_err(|error| error.to_string()),
+            Err(error) => Err(error.to_string()),
+        };
+
+        self.serialized(body)
+    }
+
+    /// Finishes the response with an already serialized body, or a 500 if serialization failed
+    fn serialized<E: Display>(self, body: Result<String, E>) -> Response<String> {
+        match body {
             Ok(body) => Response {
                 body,
                 headers: self.headers,
@@ -265,3 +392,488 @@ impl<T: Body> From<Response<T>> for Vec<u8> {
         response
     }
 }
+
+/// Implementation of parsing an HTTP Response from a stream of bytes, the counterpart of
+/// [`HttpStream`](super::request::HttpStream).
+///
+/// Connections need to be wrapped in a [`BufReader`](std::io::BufReader), which keeps whatever
+/// follows the response buffered for the next one.
+pub trait HttpResponseStream {
+    /// Parses a single HTTP/1.x response along with its body, interim `1xx` responses are returned as well.
+    ///
+    /// Whether the response has a body depends on the `method` of the request it answers.
+    /// A `chunked` body is decoded, with `Transfer-Encoding` replaced by `Content-Length`
+    /// so that the response can be written out again as it is.
+    /// # Errors
+    /// - If the stream does not contain a valid response, as a [`Error::BadGateway`]
+    /// - If reading from the stream failed or it ended early
+    fn parse_response(&mut self, method: Method) -> Result<Response<Vec<u8>>, Error>;
+}
+
+impl<R: BufRead> HttpResponseStream for R {
+    fn parse_response(&mut self, method: Method) -> Result<Response<Vec<u8>>, Error> {
+        read_response(self, method).map(|(response, _)| response)
+    }
+}
+
+/// Parses a response as [`HttpResponseStream::parse_response`] does, along with whether the server keeps the
+/// connection open after it
+pub(crate) fn read_response(
+    reader: &mut impl BufRead,
+    method: Method,
+) -> Result<(Response<Vec<u8>>, bool), Error> {
+    let (
+        Response {
+            mut headers,
+            status,
+            ..
+        },
+        persistent,
+    ) = read_head(reader)?;
+
+    let mut reader = ResponseBody::new(&mut *reader, method, status, &headers)?;
+    let mut body = vec![];
+
+    reader.read_to_end(&mut body).map_err(framing_error)?;
+
+    if matches!(reader, ResponseBody::Chunked(_)) {
+        headers.retain(|header| {
+            !matches!(
+                header,
+                HttpHeader::TransferEncoding(_) | HttpHeader::ContentLength(_)
+            )
+        });
+        headers.push(HttpHeader::ContentLength(body.len().to_string()));
+    }
+
+    Ok((Response::new(body, headers, status), persistent))
+}
+
+/// Reads the status line and headers of a response, leaving its body unread
+pub(crate) fn read_response_head(reader: &mut impl BufRead) -> Result<Response<()>, Error> {
+    read_head(reader).map(|(head, _)| head)
+}
+
+/// Reads a response head along with whether the connection persists after the response, which HTTP/1.0
+/// connections only do when asked to (RFC 9112 Section 9.3)
+fn read_head(reader: &mut impl BufRead) -> Result<(Response<()>, bool), Error> {
+    let status_line = read_line(reader).map_err(framing_error)?.ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::UnexpectedEof,
+            "Connection closed before the response",
+        )
+    })?;
+
+    let mut status_line_parts = status_line.splitn(3, ' ');
+
+    let (version, code) = match (status_line_parts.next(), status_line_parts.next()) {
+        (Some(version), Some(code)) if version.starts_with("HTTP/1.") && code.len() == 3 => {
+            code.parse::<u16>().ok().map(|code| (version, code))
+        }
+        _ => None,
+    }
+    .ok_or_else(|| error!(BadGateway, format!("Invalid status line: {status_line}")))?;
+
+    // A server answering with an impossible status is as broken as one sending no status line
+    let status =
+        HttpStatus::try_from(code).map_err(|error| error!(BadGateway, error.to_string()))?;
+
+    let mut headers = vec![];
+    let mut header_bytes = 0;
+
+    loop {
+        let line = read_line(reader)
+            .map_err(framing_error)?
+            .ok_or_else(|| error!(BadGateway, "Connection closed in the headers"))?;
+
+        if line.is_empty() {
+            break;
+        }
+
+        header_bytes += line.len();
+
+        if header_bytes > MAX_HEADER_BYTES {
+            return Err(error!(
+                BadGateway,
+                format!("The response headers exceed {MAX_HEADER_BYTES} bytes")
+            ));
+        }
+
+        let (name, value) = line
+            .split_once(':')
+            .ok_or_else(|| error!(BadGateway, format!("Invalid Header: {line}")))?;
+
+        headers.push(
+            HttpHeader::new(name.trim(), value.trim())
+                .map_err(|error| error!(BadGateway, error.to_string()))?,
+        );
+    }
+
+    let persistent = if version == "HTTP/1.0" {
+        has_connection_option(&headers, "keep-alive")
+    } else {
+        !has_connection_option(&headers, "close")
+    };
+
+    Ok((Response::new((), headers, status), persistent))
+}
+
+/// Whether any `Connection` header lists the option, which may be one of several separated by commas
+fn has_connection_option(headers: &[HttpHeader], option: &str) -> bool {
+    headers.iter().any(|header| match header {
+        HttpHeader::Connection(options) => options
+            .split(',')
+            .any(|value| value.trim().eq_ignore_ascii_case(option)),
+        _ => false,
+    })
+}
+
+/// The body of a response as it arrives, framed as described by RFC 9112 Section 6.3
+pub(crate) enum ResponseBody<R> {
+    /// Responses to `HEAD` requests, as well as `1xx`, `204` and `304` responses, have no body
+    Empty,
+    /// Exactly as many bytes as given by `Content-Length`
+    Sized(Take<R>),
+    /// A `chunked` body, decoded while it is read
+    Chunked(Chunked<R>),
+    /// Everything up to the end of the connection
+    UntilClose(R),
+}
+
+impl<R: BufRead> ResponseBody<R> {
+    /// Picks the framing of the body that follows a response head
+    pub(crate) fn new(
+        reader: R,
+        method: Method,
+        status: HttpStatus,
+        headers: &[HttpHeader],
+    ) -> Result<Self, Error> {
+        let chunked = headers.iter().any(|header| match header {
+            HttpHeader::TransferEncoding(encoding) => encoding
+                .rsplit(',')
+                .next()
+                .is_some_and(|last| last.trim().eq_ignore_ascii_case("chunked")),
+            _ => false,
+        });
+
+        let content_length = headers
+            .iter()
+            .find_map(|header| match header {
+                HttpHeader::ContentLength(length) => Some(length.trim().parse::<u64>()),
+                _ => None,
+            })
+            .transpose()
+            .map_err(|_| error!(BadGateway, "Invalid Content-Length"))?;
+
+        Ok(
+            if method == Method::Head || matches!(u16::from(&status), 100..=199 | 204 | 304) {
+                ResponseBody::Empty
+            } else if chunked {
+                ResponseBody::Chunked(Chunked {
+                    reader,
+                    remaining: 0,
+                    started: false,
+                    done: false,
+                })
+            } else if let Some(content_length) = content_length {
+                ResponseBody::Sized(reader.take(content_length))
+            } else {
+                ResponseBody::UntilClose(reader)
+            },
+        )
+    }
+}
+
+impl<R: BufRead> Read for ResponseBody<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            ResponseBody::Empty => Ok(0),
+            ResponseBody::Sized(reader) => {
+                let limit = reader.limit();
+                let read = reader.read(buf)?;
+
+                if read == 0 && limit > 0 && !buf.is_empty() {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::UnexpectedEof,
+                        "Connection closed before the end of the body",
+                    ));
+                }
+
+                Ok(read)
+            }
+            ResponseBody::Chunked(reader) => reader.read(buf),
+            ResponseBody::UntilClose(reader) => reader.read(buf),
+        }
+    }
+}
+
+/// Decodes a `chunked` body, RFC 9112 Section 7.1, discarding chunk extensions and trailers
+pub(crate) struct Chunked<R> {
+    reader: R,
+    remaining: usize,
+    started: bool,
+    done: bool,
+}
+
+impl<R: BufRead> Read for Chunked<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let invalid = |message: &str| std::io::Error::new(ErrorKind::InvalidData, message);
+        let truncated = || invalid("Truncated chunked body");
+
+        if self.done || buf.is_empty() {
+            return Ok(0);
+        }
+
+        if self.remaining == 0 {
+            // Every chunk but the first is preceded by the line ending of the one before
+            if self.started
+                && !read_line(&mut self.reader)?
+                    .ok_or_else(truncated)?
+                    .is_empty()
+            {
+                return Err(invalid("Chunk longer than its size"));
+            }
+
+            self.started = true;
+
+            let line = read_line(&mut self.reader)?.ok_or_else(truncated)?;
+            let size = line.split(';').next().unwrap_or_default().trim();
+
+            self.remaining = usize::from_str_radix(size, 16)
+                .map_err(|_| invalid(&format!("Invalid chunk size: {line}")))?;
+
+            if self.remaining == 0 {
+                // Trailers up to the final empty line
+                while !read_line(&mut self.reader)?
+                    .ok_or_else(truncated)?
+                    .is_empty()
+                {}
+
+                self.done = true;
+
+                return Ok(0);
+            }
+        }
+
+        let limit = buf.len().min(self.remaining);
+        let read = self.reader.read(&mut buf[..limit])?;
+
+        if read == 0 {
+            return Err(truncated());
+        }
+
+        self.remaining -= read;
+
+        Ok(read)
+    }
+}
+
+/// Bytes a single line of a response head or chunked body may take, the limit [`Limits`](super::limits::Limits)
+/// puts on request lines by default
+const MAX_LINE: usize = 8 * 1024;
+
+/// Bytes all header fields of a response may take together, the limit put on those of requests by default
+const MAX_HEADER_BYTES: usize = 64 * 1024;
+
+/// Reads a line without its line ending, `None` at the end of the stream
+fn read_line(reader: &mut impl BufRead) -> std::io::Result<Option<String>> {
+    let mut line = vec![];
+
+    // Reading stops at the limit, so a server sending no line ending cannot make the line grow forever
+    let read = reader
+        .take(MAX_LINE as u64 + 2)
+        .read_until(b'\n', &mut line)?;
+
+    if read == 0 {
+        return Ok(None);
+    }
+
+    while line
+        .last()
+        .is_some_and(|byte| matches!(byte, b'\r' | b'\n'))
+    {
+        line.pop();
+    }
+
+    if line.len() > MAX_LINE {
+        return Err(std::io::Error::new(
+            ErrorKind::InvalidData,
+            format!("Response line exceeds {MAX_LINE} bytes"),
+        ));
+    }
+
+    String::from_utf8(line).map(Some).map_err(|_| {
+        std::io::Error::new(ErrorKind::InvalidData, "Response line is not valid UTF-8")
+    })
+}
+
+/// Malformed framing is the fault of the server that sent the response, anything else is an I/O error
+fn framing_error(error: std::io::Error) -> Error {
+    if error.kind() == ErrorKind::InvalidData {
+        Error::BadGateway(error.to_string())
+    } else {
+        error.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(response: Response<Vec<u8>>, method: Method) -> Response<Vec<u8>> {
+        let bytes = Vec::from(response);
+
+        bytes.as_slice().parse_response(method).unwrap()
+    }
+
+    #[test]
+    fn sized_bodies_round_trip() {
+        let headers = vec![
+            HttpHeader::ContentType("text/plain".to_string()),
+            HttpHeader::ContentLength("5".to_string()),
+        ];
+
+        let response = round_trip(
+            Response::new(b"hello".to_vec(), headers.clone(), HttpStatus::Ok),
+            Method::Get,
+        );
+
+        assert_eq!(response.status, HttpStatus::Ok);
+        assert_eq!(response.headers, headers);
+        assert_eq!(response.body, b"hello");
+    }
+
+    #[test]
+    fn chunked_bodies_round_trip_as_sized_ones() {
+        let response = round_trip(
+            Response::new(
+                b"4\r\nhell\r\n1;extension\r\no\r\n0\r\nExpires: never\r\n\r\n".to_vec(),
+                vec![HttpHeader::TransferEncoding("chunked".to_string())],
+                HttpStatus::Ok,
+            ),
+            Method::Get,
+        );
+
+        assert_eq!(response.body, b"hello");
+        assert_eq!(
+            response.headers,
+            [HttpHeader::ContentLength("5".to_string())]
+        );
+
+        // The decoded response can be written out and parsed again as it is
+        let again = round_trip(response, Method::Get);
+
+        assert_eq!(again.body, b"hello");
+    }
+
+    #[test]
+    fn bodies_without_framing_run_until_close() {
+        let response = round_trip(
+            Response::new(b"all of it\r\n\r\n".to_vec(), vec![], HttpStatus::Ok),
+            Method::Get,
+        );
+
+        assert_eq!(response.body, b"all of it\r\n\r\n");
+    }
+
+    #[test]
+    fn empty_bodies_leave_the_next_response_unread() {
+        let next = b"HTTP/1.1 200 Ok\r\nContent-Length: 4\r\n\r\nnext";
+
+        for (status, method) in [
+            (HttpStatus::NoContent, Method::Get),
+            (HttpStatus::NotModified, Method::Get),
+            (HttpStatus::Continue, Method::Post),
+            (HttpStatus::Ok, Method::Head),
+        ] {
+            let mut bytes = Vec::from(Response::new(
+                vec![],
+                vec![HttpHeader::ContentLength("4".to_string())],
+                status,
+            ));
+            bytes.extend_from_slice(next);
+
+            let mut reader = bytes.as_slice();
+
+            let response = reader.parse_response(method).unwrap();
+
+            assert_eq!(response.status, status);
+            assert!(response.body.is_empty(), "{status:?} has a body");
+
+            let response = reader.parse_response(Method::Get).unwrap();
+
+            assert_eq!(response.body, b"next");
+        }
+    }
+
+    #[test]
+    fn unregistered_statuses_round_trip() {
+        let response = round_trip(
+            Response::new(
+                vec![],
+                vec![HttpHeader::ContentLength("0".to_string())],
+                HttpStatus::Unregistered(299),
+            ),
+            Method::Get,
+        );
+
+        assert_eq!(response.status, HttpStatus::Unregistered(299));
+    }
+
+    #[test]
+    fn invalid_status_codes_are_bad_gateways() {
+        for response in [
+            &b"HTTP/1.1 600 Too High\r\n\r\n"[..],
+            b"HTTP/1.1 099 Too Low\r\n\r\n",
+            b"HTTP/1.1 20 Short\r\n\r\n",
+            b"HTTP/2 200 Ok\r\n\r\n",
+        ] {
+            let mut reader = response;
+
+            assert!(matches!(
+                reader.parse_response(Method::Get),
+                Err(Error::BadGateway(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn long_lines_are_bad_gateways() {
+        let within = format!(
+            "HTTP/1.1 200 Ok\r\nX-Long: {}\r\n\r\n",
+            "a".repeat(MAX_LINE - 8)
+        );
+
+        assert!(within.as_bytes().parse_response(Method::Get).is_ok());
+
+        let status_line = format!("HTTP/1.1 200 {}\r\n\r\n", "a".repeat(MAX_LINE));
+        let header = format!(
+            "HTTP/1.1 200 Ok\r\nX-Long: {}\r\n\r\n",
+            "a".repeat(MAX_LINE)
+        );
+        let chunk_size = format!(
+            "HTTP/1.1 200 Ok\r\nTransfer-Encoding: chunked\r\n\r\n1;{}\r\na\r\n0\r\n\r\n",
+            "a".repeat(MAX_LINE)
+        );
+        // No line ending at all, as from a server that never stops sending
+        let unterminated = format!("HTTP/1.1 200 Ok\r\nX-Long: {}", "a".repeat(MAX_LINE * 4));
+
+        for response in [status_line, header, chunk_size, unterminated] {
+            assert!(matches!(
+                response.as_bytes().parse_response(Method::Get),
+                Err(Error::BadGateway(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn header_sections_are_bounded() {
+        let header = format!("X-Filler: {}\r\n", "a".repeat(1000));
+        let response = format!("HTTP/1.1 200 Ok\r\n{}\r\n", header.repeat(70));
+
+        assert!(matches!(
+            response.as_bytes().parse_response(Method::Get),
+            Err(Error::BadGateway(_))
+        ));
+    }
+}
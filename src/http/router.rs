@@ -1,4 +1,9 @@
+use std::{collections::HashMap, sync::Arc};
+
+use crate::not_found;
+
 use super::{
+    error::Error,
     method::Method,
     request::Request,
     response::{Body, Response},
@@ -12,6 +17,13 @@ pub trait RequestHandler: Send + Sync {
     fn handle(&self, request: Request) -> Result<Response<Box<dyn Body>>, super::error::Error>;
 }
 
+/// Shares one handler, along with any state it keeps, between several routes
+impl<H: RequestHandler + ?Sized> RequestHandler for Arc<H> {
+    fn handle(&self, request: Request) -> Result<Response<Box<dyn Body>>, super::error::Error> {
+        self.as_ref().handle(request)
+    }
+}
+
 /// Identifies a unique HTTP request based on Method and Path
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct RequestIdentifier {
@@ -22,27 +34,171 @@ pub struct RequestIdentifier {
     pub path: String,
 }
 
-/// Routing table for HTTP requests
-pub struct Router {
-    pub(crate) inner: std::collections::HashMap<RequestIdentifier, Box<dyn RequestHandler>>,
+/// Routing table for HTTP requests, generic over the kind of handler it dispatches to
+pub struct Router<H: ?Sized = dyn RequestHandler> {
+    pub(crate) inner: HashMap<RequestIdentifier, Box<H>>,
+
+    /// Routes with `{name}` placeholders, those with the most literal segments first, otherwise in the order given
+    pub(crate) patterns: Vec<RequestIdentifier>,
 }
 
-impl Router {
+impl<H: ?Sized> Router<H> {
     /// Get the designated route for a given request
     #[must_use]
-    pub fn select(&self, identifier: &RequestIdentifier) -> Option<&dyn RequestHandler> {
+    pub fn select(&self, identifier: &RequestIdentifier) -> Option<&H> {
         self.inner.get(identifier).map(std::ops::Deref::deref)
     }
+
+    /// Get the designated route for a given request, falling back to routes with `{name}` placeholder segments.
+    ///
+    /// Exact routes win over patterns, and patterns with more literal segments over those with fewer, so
+    /// `/users/me` is preferred to `/users/{id}`. Returns the handler along with the percent-decoded parameters
+    /// captured by the placeholders.
+    #[must_use]
+    pub fn resolve(&self, identifier: &RequestIdentifier) -> Option<(&H, HashMap<String, String>)> {
+        if let Some(handler) = self.select(identifier) {
+            return Some((handler, HashMap::new()));
+        }
+
+        self.patterns
+            .iter()
+            .filter(|route| route.method == identifier.method)
+            .find_map(|route| {
+                let params = capture(&route.path, &identifier.path)?;
+
+                Some((self.inner.get(route)?.as_ref(), params))
+            })
+    }
+
+    /// Finds the handler of a request, handing the request the parameters captured from its path.
+    ///
+    /// Shared by every server, whichever kind of handler it runs
+    /// # Errors
+    /// - [`Error::NotFound`] if no route matches the request
+    pub fn route(&self, mut request: Request) -> Result<(&H, Request), Error> {
+        let request_identifier = RequestIdentifier {
+            method: request.method,
+            path: request.path.clone(),
+        };
+
+        let Some((handler, params)) = self.resolve(&request_identifier) else {
+            return Err(not_found!(
+                method: request_identifier.method,
+                path: request_identifier.path
+            ));
+        };
+
+        request.params = params;
+
+        Ok((handler, request))
+    }
 }
 
-impl<const N: usize> From<[(RequestIdentifier, Box<dyn RequestHandler>); N]> for Router {
-    fn from(value: [(RequestIdentifier, Box<dyn RequestHandler>); N]) -> Self {
-        Self {
-            inner: std::collections::HashMap::from(value),
+impl Router {
+    /// Handles a request with the matching route, converting a missing route or a failed handler into an error response
+    pub fn dispatch(&self, request: Request) -> Response<Box<dyn Body>> {
+        match self
+            .route(request)
+            .and_then(|(handler, request)| handler.handle(request))
+        {
+            Ok(response) => response,
+            Err(error) => error.into(),
         }
     }
 }
 
+/// Matches a path against a route pattern, capturing the segments that correspond to `{name}` placeholders
+fn capture(pattern: &str, path: &str) -> Option<HashMap<String, String>> {
+    let mut pattern_segments = pattern.split('/');
+    let mut path_segments = path.split('/');
+    let mut params = HashMap::new();
+
+    loop {
+        match (pattern_segments.next(), path_segments.next()) {
+            (None, None) => return Some(params),
+            (Some(expected), Some(segment)) => match placeholder(expected) {
+                Some(name) if !segment.is_empty() => {
+                    params.insert(name.to_string(), percent_decode(segment));
+                }
+                None if expected == segment || expected == percent_decode(segment) => {}
+                _ => return None,
+            },
+            _ => return None,
+        }
+    }
+}
+
+/// Returns the placeholder name of a `{name}` route segment
+fn placeholder(segment: &str) -> Option<&str> {
+    segment.strip_prefix('{')?.strip_suffix('}')
+}
+
+/// Decodes the `%xx` escapes of a path segment, keeping malformed escapes as they are
+fn percent_decode(segment: &str) -> String {
+    let bytes = segment.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+
+    while index < bytes.len() {
+        let escaped = bytes
+            .get(index + 1..index + 3)
+            .filter(|hex| bytes[index] == b'%' && hex.iter().all(u8::is_ascii_hexdigit))
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+
+        if let Some(byte) = escaped {
+            decoded.push(byte);
+            index += 3;
+        } else {
+            decoded.push(bytes[index]);
+            index += 1;
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+impl<H: ?Sized> Router<H> {
+    /// Builds a router from routes in the order they were given, later routes replace earlier identical ones
+    pub(crate) fn from_routes(
+        routes: impl IntoIterator<Item = (RequestIdentifier, Box<H>)>,
+    ) -> Self {
+        let mut inner = HashMap::new();
+        let mut patterns = vec![];
+
+        for (identifier, handler) in routes {
+            if identifier
+                .path
+                .split('/')
+                .any(|segment| placeholder(segment).is_some())
+                && !inner.contains_key(&identifier)
+            {
+                patterns.push(identifier.clone());
+            }
+
+            inner.insert(identifier, handler);
+        }
+
+        // The sort is stable, so patterns with as many literal segments keep the order they were given in
+        patterns.sort_by_key(|route: &RequestIdentifier| {
+            std::cmp::Reverse(
+                route
+                    .path
+                    .split('/')
+                    .filter(|segment| placeholder(segment).is_none())
+                    .count(),
+            )
+        });
+
+        Self { inner, patterns }
+    }
+}
+
+impl<H: ?Sized, const N: usize> From<[(RequestIdentifier, Box<H>); N]> for Router<H> {
+    fn from(value: [(RequestIdentifier, Box<H>); N]) -> Self {
+        Self::from_routes(value)
+    }
+}
+
 /// Creates a new route mapping of `RequestIdentifier` with the given handlers
 #[macro_export]
 macro_rules! routes {
@@ -57,3 +213,111 @@ macro_rules! routes {
 }
 
 pub use routes;
+
+#[cfg(test)]
+mod tests {
+    use crate::http::{method::Method, service, status::HttpStatus, testing::TestClient};
+
+    #[service]
+    mod users {
+        use crate::http::{extract::Path, get, head, post, response::Response, status::HttpStatus};
+        use serde::Deserialize;
+
+        #[derive(Deserialize)]
+        struct Post {
+            id: u32,
+            post: String,
+        }
+
+        fn text(body: String) -> Response<String> {
+            Response::new(body, vec![], HttpStatus::Ok)
+        }
+
+        #[get("/users/me")]
+        fn me() -> Response<String> {
+            text("me".to_string())
+        }
+
+        #[get("/users/{id}")]
+        #[head("/users/{id}")]
+        pub fn user(Path(id): Path<String>) -> Response<String> {
+            text(format!("user {id}"))
+        }
+
+        #[get("/users/{id}/posts/{post}")]
+        fn post_of(Path(Post { id, post }): Path<Post>) -> Response<String> {
+            text(format!("post {post} of {id}"))
+        }
+
+        #[get("/{section}/latest")]
+        fn latest(Path(section): Path<String>) -> Response<String> {
+            text(format!("latest {section}"))
+        }
+
+        #[post("/users")]
+        fn create() -> Response<String> {
+            Response::new("created".to_string(), vec![], HttpStatus::Created)
+        }
+    }
+
+    fn client() -> TestClient {
+        TestClient::new(users::router())
+    }
+
+    #[test]
+    fn exact_routes_win_over_patterns() {
+        client()
+            .get("/users/me")
+            .assert_status(HttpStatus::Ok)
+            .assert_body("me");
+        client().get("/users/42").assert_body("user 42");
+    }
+
+    #[test]
+    fn patterns_with_more_literal_segments_win() {
+        client().get("/users/latest").assert_body("user latest");
+        client().get("/posts/latest").assert_body("latest posts");
+    }
+
+    #[test]
+    fn placeholders_are_captured_and_percent_decoded() {
+        client()
+            .get("/users/7/posts/hello%20world")
+            .assert_body("post hello world of 7");
+        client().get("/users/a%2Fb").assert_body("user a/b");
+    }
+
+    #[test]
+    fn placeholders_do_not_match_empty_or_extra_segments() {
+        client().get("/users/").assert_status(HttpStatus::NotFound);
+        client()
+            .get("/users/7/posts")
+            .assert_status(HttpStatus::NotFound);
+        client()
+            .get("/users/7/posts/1/comments")
+            .assert_status(HttpStatus::NotFound);
+    }
+
+    #[test]
+    fn routes_are_selected_by_method() {
+        client()
+            .post("/users", "")
+            .assert_status(HttpStatus::Created)
+            .assert_body("created");
+        client()
+            .post("/users/me", "")
+            .assert_status(HttpStatus::NotFound);
+        client()
+            .request(Method::Head, "/users/7", vec![], None)
+            .assert_status(HttpStatus::Ok);
+    }
+
+    #[test]
+    fn handlers_list_every_route_they_serve() {
+        assert_eq!(
+            users::user::ROUTES,
+            &[(Method::Get, "/users/{id}"), (Method::Head, "/users/{id}")]
+        );
+        assert_eq!(users::user::METHOD, Method::Get);
+    }
+}
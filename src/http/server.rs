@@ -1,88 +1,274 @@
-use crate::{
-    http::{error::Error, router::RequestIdentifier},
-    not_found,
+use super::{
+    error::{Error, ErrorHook},
+    header::HttpHeader,
+    host::VirtualHosts,
+    http2::{self, connection::Http2Connection, Settings},
+    limits::{timed_out, Deadline, Limits, Timeouts},
+    listener::{Connection, Listener, Rewind},
+    request::{self, Request},
+    response::{Body, Response},
+    status::HttpStatus,
 };
-
-use super::{request::HttpStream, router::Router};
 use std::{
-    cell::RefCell,
     io::Write,
-    net::{TcpListener, TcpStream},
-    sync::Arc,
-    thread::JoinHandle,
+    net::TcpListener,
+    sync::{
+        mpsc::{self, RecvTimeoutError},
+        Arc,
+    },
+    time::{Duration, Instant},
 };
 
-/// Server struct
-pub struct Server {
-    listener: TcpListener,
-    thread_pool: RefCell<Vec<JoinHandle<anyhow::Result<(), super::error::Error>>>>,
-    router: Arc<Router>,
+/// Server struct, generic over the [`Listener`]s it accepts connections from.
+///
+/// Clients may speak HTTP/1.1 or HTTP/2, negotiated through ALPN, prior knowledge, or an `Upgrade: h2c` request on
+/// cleartext connections.
+pub struct Server<L: Listener = TcpListener> {
+    listeners: Vec<L>,
+    hosts: Arc<VirtualHosts>,
+    http2: Settings,
+    timeouts: Timeouts,
+    limits: Limits,
+    on_error: Option<ErrorHook>,
 }
 
-impl Server {
+impl<L: Listener> Server<L> {
     #[must_use]
-    /// Create a new [```HttpServer```] instance
-    pub fn new(listener: TcpListener, router: Router) -> Self {
+    /// Create a new [```HttpServer```] instance from a [`Router`](super::router::Router) or a set of [`VirtualHosts`]
+    pub fn new(listener: L, router: impl Into<VirtualHosts>) -> Self {
         Self {
-            listener,
-            thread_pool: RefCell::new(Vec::new()),
-            router: Arc::new(router),
+            listeners: vec![listener],
+            hosts: Arc::new(router.into()),
+            http2: Settings::default(),
+            timeouts: Timeouts::default(),
+            limits: Limits::default(),
+            on_error: None,
         }
     }
 
-    fn handle_request(
-        stream: &mut TcpStream,
-        router: &Arc<Router>,
-    ) -> anyhow::Result<(), super::error::Error> {
-        let response: Vec<u8> = match stream.parse() {
-            Ok(request) => {
-                let request_identifier = RequestIdentifier {
-                    method: request.method,
-                    path: request.path.clone(),
-                };
-
-                let handler = router.select(&request_identifier);
-
-                let response = handler.map_or(
-                    not_found!(
-                        method: request_identifier.method,
-                        path: request_identifier.path.clone()
-                    )
-                    .into(),
-                    |handler| match handler.handle(request) {
-                        Ok(response) => response,
-                        Err(error) => error.into(),
-                    },
-                );
-
-                response.into()
-            }
-            Err(error) => error.into(),
-        };
+    #[must_use]
+    /// Accept connections from an additional listener as well
+    pub fn bind(mut self, listener: L) -> Self {
+        self.listeners.push(listener);
 
-        stream.write_all(&response)?;
+        self
+    }
 
-        stream.shutdown(std::net::Shutdown::Both)?;
+    #[must_use]
+    /// Use the given settings for HTTP/2 connections
+    pub fn http2(mut self, settings: Settings) -> Self {
+        self.http2 = settings;
 
-        Ok(())
+        self
     }
 
-    /// Starts listening on the given port
-    pub fn listen(&self) {
-        eprintln!("\x1B[2J\x1B[1;1H"); // Clear Screen
-        eprintln!("Server running on http://localhost:8000");
+    #[must_use]
+    /// Give clients and handlers only as much time as the timeouts allow
+    pub fn timeouts(mut self, timeouts: Timeouts) -> Self {
+        self.timeouts = timeouts;
+
+        self
+    }
+
+    #[must_use]
+    /// Refuse requests larger than the limits allow
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+
+        self
+    }
+
+    #[must_use]
+    /// Report the errors of connections that failed, such as HTTP/2 clients breaking the protocol, which are dropped otherwise
+    pub fn on_error(
+        mut self,
+        hook: impl Fn(&dyn std::error::Error) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_error = Some(Arc::new(hook));
 
+        self
+    }
+
+    fn accept_loop(
+        listener: &L,
+        hosts: &Arc<VirtualHosts>,
+        settings: Settings,
+        timeouts: Timeouts,
+        limits: Limits,
+        on_error: Option<&ErrorHook>,
+    ) {
         loop {
-            if let Ok((mut stream, socket_addr)) = self.listener.accept() {
-                let router = self.router.clone();
+            if let Ok(stream) = listener.accept() {
+                let hosts = hosts.clone();
+                let on_error = on_error.cloned();
 
-                let thread = std::thread::spawn(move || {
-                    eprintln!("Connected to client on {socket_addr:?}");
-                    Server::handle_request(&mut stream, &router)
+                std::thread::spawn(move || {
+                    if let Some(socket_addr) = stream.peer_addr() {
+                        eprintln!("Connected to client on {socket_addr:?}");
+                    }
+
+                    if let Err(error) = handle_connection(stream, hosts, settings, timeouts, limits)
+                    {
+                        if let Some(on_error) = on_error {
+                            on_error(&error);
+                        }
+                    }
                 });
+            }
+        }
+    }
 
-                self.thread_pool.borrow_mut().push(thread);
+    /// Starts accepting connections from every listener, each on its own thread
+    pub fn listen(&self) {
+        eprintln!("\x1B[2J\x1B[1;1H"); // Clear Screen
+
+        std::thread::scope(|scope| {
+            for listener in &self.listeners {
+                eprintln!("Server running on {}", listener.address());
+
+                scope.spawn(|| {
+                    Self::accept_loop(
+                        listener,
+                        &self.hosts,
+                        self.http2,
+                        self.timeouts,
+                        self.limits,
+                        self.on_error.as_ref(),
+                    );
+                });
             }
+        });
+    }
+}
+
+/// Serves a single connection, from detecting the protocol to writing the last response
+pub(crate) fn handle_connection<C: Connection>(
+    stream: C,
+    hosts: Arc<VirtualHosts>,
+    settings: Settings,
+    timeouts: Timeouts,
+    limits: Limits,
+) -> anyhow::Result<(), Error> {
+    let accepted = Instant::now();
+    let mut stream = stream;
+
+    stream.set_write_timeout(Some(timeouts.write))?;
+
+    let sniffed = http2::sniff(&mut Deadline::new(
+        &mut stream,
+        accepted,
+        timeouts.header_read,
+        timeouts.min_rate,
+    ));
+
+    let (prior_knowledge, buffered) = match sniffed {
+        Ok(sniffed) => sniffed,
+        Err(error) => return respond(Rewind::new(vec![], stream), &Vec::from(timed_out(error))),
+    };
+
+    let mut stream = Rewind::new(buffered, stream);
+
+    if prior_knowledge {
+        return serve_http2(stream, hosts, settings, timeouts, limits, None);
+    }
+
+    let request = match request::read_request(&mut stream, &timeouts, &limits, accepted) {
+        Ok(request) => request,
+        Err(error) => return respond(stream, &Vec::from(error)),
+    };
+
+    // h2c is cleartext HTTP/2, TLS clients negotiate HTTP/2 through ALPN instead, RFC 7540 Section 3.3
+    let upgrade = http2::upgrade_settings(&request).filter(|_| !stream.is_encrypted());
+
+    if let Some(upgrade) = upgrade {
+        let switching: Vec<u8> = Response::new(
+            (),
+            vec![
+                HttpHeader::Connection("Upgrade".to_string()),
+                HttpHeader::Upgrade("h2c".to_string()),
+            ],
+            HttpStatus::SwitchingProtocols,
+        )
+        .into();
+
+        stream.write_all(&switching)?;
+
+        return serve_http2(
+            stream,
+            hosts,
+            settings,
+            timeouts,
+            limits,
+            Some((request, upgrade)),
+        );
+    }
+
+    let mut response = dispatch(&hosts, request, timeouts.handler);
+
+    // Protocols like WebSocket keep the connection after the handshake response
+    if let Some(take_over) = response.body.take_over() {
+        let head: Vec<u8> = response.into();
+
+        stream.write_all(&head)?;
+        stream.flush()?;
+
+        // Waiting for the client is up to the protocol from here on
+        stream.set_read_timeout(None)?;
+
+        take_over(Box::new(stream));
+
+        return Ok(());
+    }
+
+    respond(stream, &Vec::from(response))
+}
+
+/// Runs the handler for a request, answering with 503 Service Unavailable if it takes longer than `timeout`.
+///
+/// The handler runs on a thread of its own so that it can be waited on with a timeout. One that runs out of time
+/// cannot be stopped, it is left to finish and its response is discarded.
+pub(crate) fn dispatch(
+    hosts: &Arc<VirtualHosts>,
+    request: Request,
+    timeout: Option<Duration>,
+) -> Response<Box<dyn Body>> {
+    let Some(timeout) = timeout else {
+        return hosts.dispatch(request);
+    };
+
+    let (sender, receiver) = mpsc::channel();
+    let hosts = hosts.clone();
+
+    std::thread::spawn(move || {
+        let _ = sender.send(hosts.dispatch(request));
+    });
+
+    match receiver.recv_timeout(timeout) {
+        Ok(response) => response,
+        Err(RecvTimeoutError::Timeout) => {
+            Error::ServiceUnavailable("The request took too long to handle".to_string()).into()
+        }
+        Err(RecvTimeoutError::Disconnected) => {
+            Error::IoError(std::io::Error::other("The handler failed")).into()
         }
     }
 }
+
+fn respond<C: Connection>(mut stream: Rewind<C>, response: &[u8]) -> anyhow::Result<(), Error> {
+    stream.write_all(response)?;
+
+    stream.shutdown()?;
+
+    Ok(())
+}
+
+fn serve_http2<C: Connection>(
+    stream: Rewind<C>,
+    hosts: Arc<VirtualHosts>,
+    settings: Settings,
+    timeouts: Timeouts,
+    limits: Limits,
+    upgrade: Option<(Request, Vec<(u16, u32)>)>,
+) -> anyhow::Result<(), Error> {
+    Ok(Http2Connection::new(stream, hosts, settings, timeouts, limits).serve(upgrade)?)
+}
@@ -0,0 +1,385 @@
+use crate::http::{
+    header::HttpHeader,
+    listener::Connection,
+    request::Request,
+    response::{Body, Response, TakeOver},
+    status::HttpStatus,
+};
+use std::{
+    fmt::{Debug, Display},
+    io::{ErrorKind, Write},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, RecvTimeoutError},
+        Arc, Mutex, MutexGuard,
+    },
+    time::{Duration, Instant},
+};
+
+/// A single server-sent event, built field by field
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Event {
+    id: Option<String>,
+    kind: Option<String>,
+    data: Option<String>,
+    retry: Option<Duration>,
+    comment: Option<String>,
+}
+
+impl Event {
+    /// Creates an event without any fields
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the payload of the event, which may span several lines
+    #[must_use]
+    pub fn data(mut self, data: impl Into<String>) -> Self {
+        self.data = Some(data.into());
+
+        self
+    }
+
+    /// Sets the type of the event, dispatched to `addEventListener` listeners of that name
+    #[must_use]
+    pub fn event(mut self, event: impl Into<String>) -> Self {
+        self.kind = Some(event.into());
+
+        self
+    }
+
+    /// Sets the id the client sends back in `Last-Event-ID` when it reconnects
+    #[must_use]
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+
+        self
+    }
+
+    /// Sets how long the client waits before reconnecting after losing the connection
+    #[must_use]
+    pub fn retry(mut self, retry: Duration) -> Self {
+        self.retry = Some(retry);
+
+        self
+    }
+
+    /// Adds a comment, which clients ignore
+    #[must_use]
+    pub fn comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = Some(comment.into());
+
+        self
+    }
+}
+
+impl Display for Event {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // A line break inside a single line field would start a new field
+        let single_line = |value: &str| value.replace(['\r', '\n'], "");
+
+        if let Some(comment) = &self.comment {
+            for line in lines(comment) {
+                writeln!(f, ": {line}")?;
+            }
+        }
+
+        if let Some(event) = &self.kind {
+            writeln!(f, "event: {}", single_line(event))?;
+        }
+
+        if let Some(id) = &self.id {
+            // Ids containing NULL are ignored by clients
+            writeln!(f, "id: {}", single_line(id).replace('\0', ""))?;
+        }
+
+        if let Some(retry) = self.retry {
+            writeln!(f, "retry: {}", retry.as_millis())?;
+        }
+
+        if let Some(data) = &self.data {
+            // Every line of the payload needs its own field, which the client joins back together
+            for line in lines(data) {
+                writeln!(f, "data: {line}")?;
+            }
+        }
+
+        writeln!(f)
+    }
+}
+
+/// Splits a value at every line break the event stream format knows, `\r\n`, `\r` and `\n`, any of which would
+/// end a field
+fn lines(value: &str) -> impl Iterator<Item = &str> {
+    value
+        .split("\r\n")
+        .flat_map(|line| line.split(['\r', '\n']))
+}
+
+/// The connection an event stream is written to, along with when it was last written to
+struct Stream {
+    connection: Box<dyn Connection>,
+    last_write: Instant,
+}
+
+/// Sends events to a client that keeps a `text/event-stream` response open
+pub struct Sender {
+    stream: Arc<Mutex<Stream>>,
+    connected: Arc<AtomicBool>,
+    last_event_id: Option<String>,
+}
+
+impl Sender {
+    /// The id of the last event the client received before reconnecting, from the `Last-Event-ID` header
+    #[must_use]
+    pub fn last_event_id(&self) -> Option<&str> {
+        self.last_event_id.as_deref()
+    }
+
+    /// Whether the client was still connected as of the last write
+    #[must_use]
+    pub fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::Relaxed)
+    }
+
+    /// Sends an event to the client
+    /// # Errors
+    /// - If the client disconnected, as a [`BrokenPipe`](ErrorKind::BrokenPipe) error once detected
+    pub fn send(&self, event: &Event) -> std::io::Result<()> {
+        write(&self.stream, &self.connected, event.to_string().as_bytes())
+    }
+}
+
+impl Debug for Sender {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Sender")
+            .field("connected", &self.is_connected())
+            .field("last_event_id", &self.last_event_id)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Body of a `text/event-stream` response, written incrementally by a handler once the response head is sent.
+///
+/// The stream ends when the handler returns. Streaming is only supported on HTTP/1.1 connections, HTTP/2 clients
+/// are answered with 505 HTTP Version Not Supported instead, and the handler is never run.
+pub struct EventStream {
+    handler: Option<Box<dyn FnOnce(Sender) + Send>>,
+    last_event_id: Option<String>,
+    keep_alive: Duration,
+}
+
+impl Debug for EventStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventStream")
+            .field("last_event_id", &self.last_event_id)
+            .field("keep_alive", &self.keep_alive)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Body for EventStream {
+    fn bytes(&self) -> Vec<u8> {
+        vec![]
+    }
+
+    fn take_over(&mut self) -> Option<TakeOver> {
+        let handler = self.handler.take()?;
+        let last_event_id = self.last_event_id.take();
+        let keep_alive = self.keep_alive;
+
+        Some(Box::new(move |connection| {
+            serve(connection, handler, last_event_id, keep_alive);
+        }))
+    }
+}
+
+impl Response<EventStream> {
+    /// Sends a comment whenever no event was sent for `interval`, 15 seconds by default,
+    /// so that proxies keep the connection open and disconnects are noticed
+    pub fn keep_alive(mut self, interval: Duration) -> Self {
+        self.body.keep_alive = interval;
+
+        self
+    }
+}
+
+/// Responds with an event stream, running `handler` with a [`Sender`] once the response head is sent
+pub fn stream<F>(request: &Request, handler: F) -> Response<EventStream>
+where
+    F: FnOnce(Sender) + Send + 'static,
+{
+    let last_event_id = request.headers.iter().find_map(|header| match header {
+        HttpHeader::LastEventId(id) => Some(id.trim().to_string()),
+        _ => None,
+    });
+
+    Response::new(
+        EventStream {
+            handler: Some(Box::new(handler)),
+            last_event_id,
+            keep_alive: Duration::from_secs(15),
+        },
+        vec![
+            HttpHeader::ContentType("text/event-stream".to_string()),
+            HttpHeader::CacheControl("no-cache".to_string()),
+        ],
+        HttpStatus::Ok,
+    )
+}
+
+/// Runs the handler while a second thread keeps the connection alive, then closes the connection
+fn serve(
+    connection: Box<dyn Connection>,
+    handler: Box<dyn FnOnce(Sender) + Send>,
+    last_event_id: Option<String>,
+    keep_alive: Duration,
+) {
+    let stream = Arc::new(Mutex::new(Stream {
+        connection,
+        last_write: Instant::now(),
+    }));
+    let connected = Arc::new(AtomicBool::new(true));
+
+    // Dropping the sender half tells the keep-alive thread the handler is done
+    let (done, finished) = mpsc::channel::<()>();
+
+    let keep_alive_thread = {
+        let stream = stream.clone();
+        let connected = connected.clone();
+
+        std::thread::spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = finished.recv_timeout(keep_alive) {
+                let idle = lock(&stream).last_write.elapsed() >= keep_alive;
+
+                if idle && write(&stream, &connected, b": keep-alive\n\n").is_err() {
+                    break;
+                }
+            }
+        })
+    };
+
+    handler(Sender {
+        stream: stream.clone(),
+        connected,
+        last_event_id,
+    });
+
+    drop(done);
+    let _ = keep_alive_thread.join();
+
+    // The client may well be gone already
+    let _ = lock(&stream).connection.shutdown();
+}
+
+fn write(stream: &Mutex<Stream>, connected: &AtomicBool, bytes: &[u8]) -> std::io::Result<()> {
+    if !connected.load(Ordering::Relaxed) {
+        return Err(std::io::Error::new(
+            ErrorKind::BrokenPipe,
+            "client disconnected",
+        ));
+    }
+
+    let mut stream = lock(stream);
+
+    let result = stream
+        .connection
+        .write_all(bytes)
+        .and_then(|()| stream.connection.flush());
+
+    match result {
+        Ok(()) => stream.last_write = Instant::now(),
+        Err(_) => connected.store(false, Ordering::Relaxed),
+    }
+
+    result
+}
+
+/// Locks the stream, a panic while writing leaves nothing half updated that later writes would trip over
+fn lock(stream: &Mutex<Stream>) -> MutexGuard<'_, Stream> {
+    stream
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{stream, Event, EventStream};
+    use crate::http::{
+        header::HttpHeader, method::Method, request::Request, response::Response, route,
+        router::routes, status::HttpStatus, testing::TestClient,
+    };
+    use std::time::Duration;
+
+    #[test]
+    fn events_are_encoded_field_by_field() {
+        let event = Event::new()
+            .comment("keep going")
+            .event("update")
+            .id("42")
+            .retry(Duration::from_secs(3))
+            .data("payload");
+
+        assert_eq!(
+            event.to_string(),
+            ": keep going\nevent: update\nid: 42\nretry: 3000\ndata: payload\n\n"
+        );
+        assert_eq!(Event::new().to_string(), "\n");
+    }
+
+    #[test]
+    fn every_line_break_starts_a_data_field() {
+        let event = Event::new().data("first\nsecond\r\nthird\rfourth\n");
+
+        assert_eq!(
+            event.to_string(),
+            "data: first\ndata: second\ndata: third\ndata: fourth\ndata: \n\n"
+        );
+    }
+
+    #[test]
+    fn line_breaks_cannot_inject_fields() {
+        let event = Event::new()
+            .comment("note\rdata: injected")
+            .event("update\r\nid: 1")
+            .id("4\r2\0");
+
+        assert_eq!(
+            event.to_string(),
+            ": note\n: data: injected\nevent: updateid: 1\nid: 42\n\n"
+        );
+    }
+
+    #[route]
+    fn events(request: &Request) -> Response<EventStream> {
+        stream(request, |sender| {
+            let id = sender.last_event_id().unwrap_or("none").to_string();
+
+            sender
+                .send(&Event::new().id("1").data(format!("after {id}")))
+                .unwrap();
+        })
+    }
+
+    #[test]
+    fn events_are_streamed_after_the_head() {
+        let client = TestClient::new(routes!(Get "/events" -> events,));
+
+        let response = client.get("/events");
+
+        response
+            .assert_status(HttpStatus::Ok)
+            .assert_header(&HttpHeader::ContentType("text/event-stream".to_string()))
+            .assert_body("id: 1\ndata: after none\n\n");
+
+        client
+            .request(
+                Method::Get,
+                "/events",
+                vec![HttpHeader::LastEventId("7".to_string())],
+                None,
+            )
+            .assert_body("id: 1\ndata: after 7\n\n");
+    }
+}
@@ -1,7 +1,12 @@
+/// A status code outside of the 100 to 599 range that HTTP allows, RFC 9110 Section 15
+#[derive(Debug, thiserror::Error, Clone, Copy, PartialEq, Eq)]
+#[error("Invalid status code {0}")]
+pub struct InvalidStatusCode(pub u16);
+
 macro_rules! status_impl {
     ($($status_code:literal $status_text:literal -> $variant:ident,)*) => {
         /// Status code definitions for HTTP Responses
-        #[derive(Debug, PartialEq, Eq, Clone)]
+        #[derive(Debug, PartialEq, Eq, Clone, Copy)]
         pub enum HttpStatus {
             $(
                 /// HTTP `
@@ -10,12 +15,28 @@ macro_rules! status_impl {
                 ///` status
                 $variant,
             )*
+            /// A status code without a registered variant, carried through as received
+            Unregistered(u16),
         }
 
         impl From<&HttpStatus> for u16 {
             fn from(value: &HttpStatus) -> Self {
                 match value {
                     $($crate::http::status::HttpStatus::$variant => $status_code,)*
+                    $crate::http::status::HttpStatus::Unregistered(code) => *code,
+                }
+            }
+        }
+
+        impl TryFrom<u16> for HttpStatus {
+            type Error = $crate::http::status::InvalidStatusCode;
+
+            /// Looks up the status registered for a code, any other code from 100 to 599 is [`HttpStatus::Unregistered`]
+            fn try_from(code: u16) -> Result<Self, Self::Error> {
+                match code {
+                    $($status_code => Ok($crate::http::status::HttpStatus::$variant),)*
+                    100..=599 => Ok($crate::http::status::HttpStatus::Unregistered(code)),
+                    _ => Err($crate::http::status::InvalidStatusCode(code)),
                 }
             }
         }
@@ -24,6 +45,8 @@ macro_rules! status_impl {
             fn as_ref(&self) -> &str {
                 match self {
                     $($crate::http::status::HttpStatus::$variant => $status_text,)*
+                    // The reason phrase is optional, RFC 9112 Section 4
+                    $crate::http::status::HttpStatus::Unregistered(_) => "",
                 }
             }
         }
@@ -50,6 +73,7 @@ status_impl!(
     300 "Multiple Choices" -> MultipleChoices,
     301 "Moved Permanently" -> MovedPermanently,
     302 "Found" -> Found,
+    303 "See Other" -> SeeOther,
     304 "Not Modified" -> NotModified,
     305 "Use Proxy" -> UseProxy,
     306 "Unused" -> Unused,
@@ -98,3 +122,37 @@ status_impl!(
     510 "Not Extended" -> NotExtended,
     511 "Network Authentication Required" -> NetworkAuthenticationRequired,
 );
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_from_100_to_599_are_valid() {
+        assert_eq!(HttpStatus::try_from(99), Err(InvalidStatusCode(99)));
+        assert_eq!(HttpStatus::try_from(100), Ok(HttpStatus::Continue));
+        assert_eq!(HttpStatus::try_from(599), Ok(HttpStatus::Unregistered(599)));
+        assert_eq!(HttpStatus::try_from(600), Err(InvalidStatusCode(600)));
+        assert_eq!(HttpStatus::try_from(0), Err(InvalidStatusCode(0)));
+        assert_eq!(
+            HttpStatus::try_from(u16::MAX),
+            Err(InvalidStatusCode(u16::MAX))
+        );
+    }
+
+    #[test]
+    fn registered_codes_map_to_their_variant() {
+        assert_eq!(HttpStatus::try_from(200), Ok(HttpStatus::Ok));
+        assert_eq!(
+            HttpStatus::try_from(511),
+            Ok(HttpStatus::NetworkAuthenticationRequired)
+        );
+        assert_eq!(HttpStatus::try_from(299), Ok(HttpStatus::Unregistered(299)));
+
+        for code in 100..=599 {
+            let status = HttpStatus::try_from(code).unwrap();
+
+            assert_eq!(u16::from(&status), code);
+        }
+    }
+}
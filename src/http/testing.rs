@@ -0,0 +1,470 @@
+use crate::http::{
+    header::HttpHeader,
+    host::VirtualHosts,
+    http2::Settings,
+    limits::{Limits, Timeouts},
+    listener::Connection,
+    method::Method,
+    request::{Extensions, Request},
+    response::{HttpResponseStream, Response},
+    server,
+    status::HttpStatus,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    collections::HashMap,
+    io::{Cursor, Read, Write},
+    net::{Ipv4Addr, SocketAddr},
+    sync::{Arc, Mutex, PoisonError},
+    time::Duration,
+};
+
+/// Runs requests through the same pipeline as [`Server`](super::server::Server), without opening any socket.
+///
+/// Every request is written to an in-memory connection that the server reads from, and the bytes the server
+/// writes back are parsed into a response. Routing, error conversion and protocol handling are all exercised
+/// exactly as they are for a real client.
+pub struct TestClient {
+    hosts: Arc<VirtualHosts>,
+    peer_addr: Option<SocketAddr>,
+    timeouts: Timeouts,
+    limits: Limits,
+}
+
+impl TestClient {
+    /// Creates a client for a [`Router`](super::router::Router) or a set of [`VirtualHosts`]
+    #[must_use]
+    pub fn new(router: impl Into<VirtualHosts>) -> Self {
+        Self {
+            hosts: Arc::new(router.into()),
+            peer_addr: Some(SocketAddr::from((Ipv4Addr::LOCALHOST, 0))),
+            timeouts: Timeouts::default(),
+            limits: Limits::default(),
+        }
+    }
+
+    /// Sets the address requests appear to come from, `127.0.0.1` by default
+    #[must_use]
+    pub fn peer_addr(mut self, peer_addr: Option<SocketAddr>) -> Self {
+        self.peer_addr = peer_addr;
+
+        self
+    }
+
+    /// Uses the given timeouts, of which only the handler timeout matters for requests held in memory
+    #[must_use]
+    pub fn timeouts(mut self, timeouts: Timeouts) -> Self {
+        self.timeouts = timeouts;
+
+        self
+    }
+
+    /// Refuses requests larger than the limits allow, as the server would
+    #[must_use]
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+
+        self
+    }
+
+    /// Sends a `GET` request for the path, which may include a query string
+    /// # Panics
+    /// - If the server did not answer with a valid HTTP/1.1 response
+    pub fn get(&self, path: &str) -> Response<Vec<u8>> {
+        self.request(Method::Get, path, vec![], None)
+    }
+
+    /// Sends a `POST` request for the path with the given body
+    /// # Panics
+    /// - If the server did not answer with a valid HTTP/1.1 response
+    pub fn post(&self, path: &str, body: impl Into<Vec<u8>>) -> Response<Vec<u8>> {
+        self.request(Method::Post, path, vec![], Some(body.into()))
+    }
+
+    /// Sends a request for the path, which may include a query string
+    /// # Panics
+    /// - If the server did not answer with a valid HTTP/1.1 response
+    pub fn request(
+        &self,
+        method: Method,
+        path: &str,
+        headers: Vec<HttpHeader>,
+        body: Option<Vec<u8>>,
+    ) -> Response<Vec<u8>> {
+        let (path, query) = match path.split_once('?') {
+            Some((path, query)) => (path, Some(query.to_string())),
+            None => (path, None),
+        };
+
+        self.send(Request {
+            method,
+            path: path.to_string(),
+            query,
+            params: HashMap::new(),
+            http_version: "HTTP/1.1".to_string(),
+            headers,
+            body,
+            peer_addr: None,
+            extensions: Extensions::default(),
+        })
+    }
+
+    /// Sends a request, adding a `Content-Length` header for bodies that lack one
+    /// # Panics
+    /// - If the server did not answer with a valid HTTP/1.1 response
+    pub fn send(&self, request: Request) -> Response<Vec<u8>> {
+        let mut request = request;
+
+        let has_length = request
+            .headers
+            .iter()
+            .any(|header| matches!(header, HttpHeader::ContentLength(_)));
+
+        if let (Some(body), false) = (&request.body, has_length) {
+            request
+                .headers
+                .push(HttpHeader::ContentLength(body.len().to_string()));
+        }
+
+        self.send_raw(request)
+    }
+
+    /// Sends the bytes exactly as given, which need not be a valid request
+    /// # Panics
+    /// - If the server did not answer with a valid HTTP/1.1 response
+    pub fn send_raw(&self, bytes: impl Into<Vec<u8>>) -> Response<Vec<u8>> {
+        let bytes = bytes.into();
+
+        // Responses to HEAD requests announce a body they do not carry
+        let method = if bytes.starts_with(b"HEAD ") {
+            Method::Head
+        } else {
+            Method::Get
+        };
+
+        let written = Arc::new(Mutex::new(vec![]));
+
+        let connection = MemoryConnection {
+            input: Cursor::new(bytes),
+            output: written.clone(),
+            peer_addr: self.peer_addr,
+        };
+
+        if let Err(error) = server::handle_connection(
+            connection,
+            self.hosts.clone(),
+            Settings::default(),
+            self.timeouts,
+            self.limits,
+        ) {
+            panic!("Serving the request failed: {error}");
+        }
+
+        let written = std::mem::take(&mut *written.lock().unwrap_or_else(PoisonError::into_inner));
+
+        written
+            .as_slice()
+            .parse_response(method)
+            .unwrap_or_else(|error| {
+                panic!(
+                    "Invalid response ({error:?}): {}",
+                    String::from_utf8_lossy(&written)
+                )
+            })
+    }
+}
+
+/// A connection that reads a fixed request and keeps whatever is written to it
+struct MemoryConnection {
+    input: Cursor<Vec<u8>>,
+    output: Arc<Mutex<Vec<u8>>>,
+    peer_addr: Option<SocketAddr>,
+}
+
+impl Read for MemoryConnection {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.input.read(buf)
+    }
+}
+
+impl Write for MemoryConnection {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.output
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .extend_from_slice(buf);
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Connection for MemoryConnection {
+    fn shutdown(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn peer_addr(&self) -> Option<SocketAddr> {
+        self.peer_addr
+    }
+
+    fn set_read_timeout(&self, _timeout: Option<Duration>) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn set_write_timeout(&self, _timeout: Option<Duration>) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Response<Vec<u8>> {
+    /// Returns the value of the first header with the given name, names are case-insensitive
+    #[must_use]
+    pub fn header(&self, name: &str) -> Option<String> {
+        self.headers
+            .iter()
+            .find(|header| header.name().eq_ignore_ascii_case(name))
+            .map(HttpHeader::value)
+    }
+
+    /// Returns the body as text, replacing invalid UTF-8
+    #[must_use]
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    /// Deserializes the JSON body of the response
+    /// # Errors
+    /// - If the body is not valid JSON for `T`
+    pub fn json<T: DeserializeOwned>(&self) -> serde_json::Result<T> {
+        serde_json::from_slice(&self.body)
+    }
+
+    /// Asserts the status of the response
+    /// # Panics
+    /// - If the status differs, showing the body to tell why
+    #[track_caller]
+    pub fn assert_status(&self, status: HttpStatus) -> &Self {
+        assert_eq!(
+            self.status,
+            status,
+            "Unexpected status, the body was: {}",
+            self.text()
+        );
+
+        self
+    }
+
+    /// Asserts that the response has the header, with exactly the given value
+    /// # Panics
+    /// - If no header with that name and value is present
+    #[track_caller]
+    pub fn assert_header(&self, header: &HttpHeader) -> &Self {
+        assert!(
+            self.headers.contains(header),
+            "Missing header {header}, the headers were: {:?}",
+            self.headers
+        );
+
+        self
+    }
+
+    /// Asserts the body of the response
+    /// # Panics
+    /// - If the body differs
+    #[track_caller]
+    pub fn assert_body(&self, body: impl AsRef<[u8]>) -> &Self {
+        assert!(
+            self.body == body.as_ref(),
+            "Unexpected body: {}, expected: {}",
+            self.text(),
+            String::from_utf8_lossy(body.as_ref())
+        );
+
+        self
+    }
+
+    /// Asserts that the body is JSON equal to `expected`, regardless of formatting and key order
+    /// # Panics
+    /// - If the body is not JSON, or a different value
+    #[track_caller]
+    pub fn assert_json<T: Serialize + ?Sized>(&self, expected: &T) -> &Self {
+        let actual: serde_json::Value = self
+            .json()
+            .unwrap_or_else(|error| panic!("The body is not JSON ({error}): {}", self.text()));
+
+        let expected = serde_json::to_value(expected)
+            .unwrap_or_else(|error| panic!("The expected value is not JSON: {error}"));
+
+        assert_eq!(actual, expected, "Unexpected JSON body");
+
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TestClient;
+    use crate::http::{
+        error::Error,
+        header::HttpHeader,
+        method::Method,
+        request::Request,
+        response::{Body, Response},
+        router::{routes, RequestHandler},
+        status::HttpStatus,
+    };
+    use serde_json::json;
+
+    /// Answers with the body of the request, telling the method, query and client in headers
+    struct Echo;
+
+    impl RequestHandler for Echo {
+        fn handle(&self, request: Request) -> Result<Response<Box<dyn Body>>, Error> {
+            let peer = request
+                .peer_addr
+                .map_or_else(|| "none".to_string(), |address| address.to_string());
+
+            let body = request.body.unwrap_or_default();
+            let length = body.len().to_string();
+
+            Ok(Response::new(
+                Box::new(body),
+                vec![
+                    HttpHeader::ContentLength(length),
+                    HttpHeader::new("X-Method", &format!("{:?}", request.method)).unwrap(),
+                    HttpHeader::new("X-Peer", &peer).unwrap(),
+                    HttpHeader::new("X-Query", &request.query.unwrap_or_default()).unwrap(),
+                ],
+                HttpStatus::Ok,
+            ))
+        }
+    }
+
+    struct Document;
+
+    impl RequestHandler for Document {
+        fn handle(&self, _: Request) -> Result<Response<Box<dyn Body>>, Error> {
+            Ok(Response::new(
+                Box::new(r#"{ "name": "hyperion", "tags": ["http", "server"] }"#.to_string()),
+                vec![HttpHeader::ContentType("application/json".to_string())],
+                HttpStatus::Ok,
+            ))
+        }
+    }
+
+    fn client() -> TestClient {
+        let (echo, head, document) = (Echo, Echo, Document);
+
+        TestClient::new(routes!(
+            Post "/echo" -> echo,
+            Head "/echo" -> head,
+            Get "/document" -> document,
+        ))
+    }
+
+    #[test]
+    fn bodies_are_sent_with_their_length() {
+        let response = client().post("/echo?a=1", "hello");
+
+        response
+            .assert_status(HttpStatus::Ok)
+            .assert_body("hello")
+            .assert_header(&HttpHeader::ContentLength("5".to_string()));
+        assert_eq!(response.header("x-method").as_deref(), Some("Post"));
+        assert_eq!(response.header("X-Query").as_deref(), Some("a=1"));
+        assert_eq!(response.header("X-Peer").as_deref(), Some("127.0.0.1:0"));
+    }
+
+    #[test]
+    fn requests_can_come_from_anywhere() {
+        let response = client()
+            .peer_addr("192.0.2.1:1234".parse().ok())
+            .post("/echo", "");
+
+        assert_eq!(response.header("X-Peer").as_deref(), Some("192.0.2.1:1234"));
+
+        let response = client().peer_addr(None).post("/echo", "");
+
+        assert_eq!(response.header("X-Peer").as_deref(), Some("none"));
+    }
+
+    #[test]
+    fn raw_requests_are_sent_as_they_are() {
+        client()
+            .send_raw("POST /echo HTTP/1.1\r\nHost: localhost\r\nContent-Length: 3\r\n\r\nraw")
+            .assert_status(HttpStatus::Ok)
+            .assert_body("raw");
+
+        client()
+            .send_raw("POST /echo\r\n\r\n")
+            .assert_status(HttpStatus::BadRequest);
+
+        client()
+            .send_raw("GET /missing HTTP/1.1\r\n\r\n")
+            .assert_status(HttpStatus::NotFound);
+    }
+
+    #[test]
+    fn responses_to_head_requests_have_no_body() {
+        let raw = client()
+            .send_raw("HEAD /echo HTTP/1.1\r\nHost: localhost\r\nContent-Length: 5\r\n\r\nhello");
+        let built = client().request(Method::Head, "/echo", vec![], Some(b"hello".to_vec()));
+
+        for response in [raw, built] {
+            response
+                .assert_status(HttpStatus::Ok)
+                .assert_header(&HttpHeader::ContentLength("5".to_string()))
+                .assert_body("");
+        }
+    }
+
+    #[test]
+    fn json_bodies_compare_as_values() {
+        let response = client().get("/document");
+
+        response.assert_json(&json!({ "tags": ["http", "server"], "name": "hyperion" }));
+
+        let name: serde_json::Value = response.json().unwrap();
+        assert_eq!(name["name"], "hyperion");
+    }
+
+    #[test]
+    #[should_panic(expected = "Unexpected status, the body was: hello")]
+    fn status_assertions_show_the_body() {
+        client()
+            .post("/echo", "hello")
+            .assert_status(HttpStatus::Created);
+    }
+
+    #[test]
+    #[should_panic(expected = "Missing header X-Method: Get")]
+    fn header_assertions_compare_values() {
+        client()
+            .post("/echo", "")
+            .assert_header(&HttpHeader::new("X-Method", "Get").unwrap());
+    }
+
+    #[test]
+    #[should_panic(expected = "Unexpected body: hello, expected: goodbye")]
+    fn body_assertions_show_both_bodies() {
+        client().post("/echo", "hello").assert_body("goodbye");
+    }
+
+    #[test]
+    #[should_panic(expected = "Unexpected JSON body")]
+    fn json_assertions_compare_values() {
+        client()
+            .get("/document")
+            .assert_json(&json!({ "name": "hyperion" }));
+    }
+
+    #[test]
+    #[should_panic(expected = "The body is not JSON")]
+    fn json_assertions_need_json() {
+        client().post("/echo", "hello").assert_json(&json!("hello"));
+    }
+}
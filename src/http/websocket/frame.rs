@@ -0,0 +1,189 @@
+use super::WebSocketError;
+use std::io::{Read, Write};
+
+/// Kind of a frame, RFC 6455 Section 5.2
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Opcode {
+    /// Continues a fragmented text or binary message
+    Continuation,
+    /// Starts a UTF-8 text message
+    Text,
+    /// Starts a binary message
+    Binary,
+    /// Starts or answers the closing handshake
+    Close,
+    /// Liveness check that the receiver answers with a pong
+    Ping,
+    /// Answer to a ping, or an unsolicited heartbeat
+    Pong,
+}
+
+impl Opcode {
+    /// Whether the opcode belongs to a control frame, which may not be fragmented
+    #[must_use]
+    pub fn is_control(self) -> bool {
+        matches!(self, Opcode::Close | Opcode::Ping | Opcode::Pong)
+    }
+}
+
+impl TryFrom<u8> for Opcode {
+    type Error = WebSocketError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0x0 => Ok(Opcode::Continuation),
+            0x1 => Ok(Opcode::Text),
+            0x2 => Ok(Opcode::Binary),
+            0x8 => Ok(Opcode::Close),
+            0x9 => Ok(Opcode::Ping),
+            0xA => Ok(Opcode::Pong),
+            _ => Err(WebSocketError::Protocol("reserved opcode")),
+        }
+    }
+}
+
+impl From<Opcode> for u8 {
+    fn from(opcode: Opcode) -> Self {
+        match opcode {
+            Opcode::Continuation => 0x0,
+            Opcode::Text => 0x1,
+            Opcode::Binary => 0x2,
+            Opcode::Close => 0x8,
+            Opcode::Ping => 0x9,
+            Opcode::Pong => 0xA,
+        }
+    }
+}
+
+/// A single WebSocket frame, with its payload already unmasked
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    /// Whether this is the last frame of a message
+    pub fin: bool,
+    /// Kind of the frame
+    pub opcode: Opcode,
+    /// Application data carried by the frame
+    pub payload: Vec<u8>,
+}
+
+impl Frame {
+    /// Largest payload a control frame may carry
+    pub const MAX_CONTROL_PAYLOAD: usize = 125;
+
+    /// Creates a frame that completes a message on its own
+    #[must_use]
+    pub fn new(opcode: Opcode, payload: Vec<u8>) -> Self {
+        Self {
+            fin: true,
+            opcode,
+            payload,
+        }
+    }
+
+    /// Reads a frame, requiring it to be masked as every frame sent by a client must be
+    /// # Errors
+    /// - If the frame is unmasked, uses reserved bits or opcodes, or is a fragmented or oversized control frame
+    /// - If the payload is larger than `max_payload`
+    /// - If reading from the connection failed
+    pub fn read(reader: &mut impl Read, max_payload: usize) -> Result<Self, WebSocketError> {
+        let mut head = [0; 2];
+        reader.read_exact(&mut head)?;
+
+        let fin = head[0] & 0x80 != 0;
+
+        // Extensions that define these bits are never negotiated
+        if head[0] & 0x70 != 0 {
+            return Err(WebSocketError::Protocol("reserved bits set"));
+        }
+
+        let opcode = Opcode::try_from(head[0] & 0x0F)?;
+
+        if head[1] & 0x80 == 0 {
+            return Err(WebSocketError::Protocol("client frames must be masked"));
+        }
+
+        let length = match head[1] & 0x7F {
+            126 => {
+                let mut length = [0; 2];
+                reader.read_exact(&mut length)?;
+                u64::from(u16::from_be_bytes(length))
+            }
+            127 => {
+                let mut length = [0; 8];
+                reader.read_exact(&mut length)?;
+                u64::from_be_bytes(length)
+            }
+            length => u64::from(length),
+        };
+
+        if opcode.is_control() && (!fin || length > Self::MAX_CONTROL_PAYLOAD as u64) {
+            return Err(WebSocketError::Protocol(
+                "control frames must be unfragmented and at most 125 bytes",
+            ));
+        }
+
+        let length = usize::try_from(length)
+            .ok()
+            .filter(|length| *length <= max_payload)
+            .ok_or(WebSocketError::MessageTooLarge)?;
+
+        let mut mask = [0; 4];
+        reader.read_exact(&mut mask)?;
+
+        let mut payload = vec![0; length];
+        reader.read_exact(&mut payload)?;
+
+        apply_mask(&mut payload, mask);
+
+        Ok(Self {
+            fin,
+            opcode,
+            payload,
+        })
+    }
+
+    /// Writes the frame, masking the payload when a key is given as clients have to
+    /// # Errors
+    /// - If writing to the connection failed
+    #[allow(clippy::cast_possible_truncation)] // Each length is checked against the range of its encoding
+    pub fn write(&self, writer: &mut impl Write, mask: Option<[u8; 4]>) -> std::io::Result<()> {
+        let mut buffer = Vec::with_capacity(self.payload.len() + 14);
+
+        buffer.push(u8::from(self.fin) << 7 | u8::from(self.opcode));
+
+        let mask_bit = if mask.is_some() { 0x80 } else { 0 };
+        let length = self.payload.len();
+
+        match length {
+            0..=125 => buffer.push(mask_bit | length as u8),
+            126..=0xFFFF => {
+                buffer.push(mask_bit | 0x7E);
+                buffer.extend((length as u16).to_be_bytes());
+            }
+            _ => {
+                buffer.push(mask_bit | 0x7F);
+                buffer.extend((length as u64).to_be_bytes());
+            }
+        }
+
+        let start = buffer.len();
+
+        if let Some(mask) = mask {
+            buffer.extend(mask);
+            buffer.extend(&self.payload);
+            apply_mask(&mut buffer[start + 4..], mask);
+        } else {
+            buffer.extend(&self.payload);
+        }
+
+        writer.write_all(&buffer)?;
+        writer.flush()
+    }
+}
+
+/// Masks or unmasks a payload, both are the same XOR operation, RFC 6455 Section 5.3
+pub fn apply_mask(payload: &mut [u8], mask: [u8; 4]) {
+    for (index, byte) in payload.iter_mut().enumerate() {
+        *byte ^= mask[index % 4];
+    }
+}
@@ -0,0 +1,549 @@
+use crate::http::{
+    error::Error,
+    header::HttpHeader,
+    listener::Connection,
+    method::Method,
+    request::Request,
+    response::{Body, Response, TakeOver},
+    status::HttpStatus,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use frame::{Frame, Opcode};
+use sha1::{Digest, Sha1};
+use std::{
+    fmt::Debug,
+    io::{Read, Write},
+};
+use thiserror::Error;
+
+/// Encoding and decoding of WebSocket frames
+pub mod frame;
+
+/// Status codes sent in close frames, RFC 6455 Section 7.4.1
+pub mod close_code {
+    /// The purpose of the connection has been fulfilled
+    pub const NORMAL: u16 = 1000;
+    /// The endpoint is going away, such as a server shutting down
+    pub const GOING_AWAY: u16 = 1001;
+    /// The peer broke the protocol
+    pub const PROTOCOL_ERROR: u16 = 1002;
+    /// The peer sent a kind of data the endpoint cannot accept
+    pub const UNSUPPORTED_DATA: u16 = 1003;
+    /// A text message was not valid UTF-8
+    pub const INVALID_PAYLOAD: u16 = 1007;
+    /// A message violated the policy of the endpoint
+    pub const POLICY_VIOLATION: u16 = 1008;
+    /// A message was too large to process
+    pub const MESSAGE_TOO_BIG: u16 = 1009;
+    /// The endpoint encountered an unexpected condition
+    pub const INTERNAL_ERROR: u16 = 1011;
+}
+
+/// The GUID appended to the client's key to compute the accept key, RFC 6455 Section 1.3
+const GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// The only protocol version defined by RFC 6455
+const VERSION: &str = "13";
+
+/// Errors that end a WebSocket connection
+#[derive(Debug, Error)]
+pub enum WebSocketError {
+    /// Reading from or writing to the connection failed
+    #[error("Io error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// The peer broke the protocol
+    #[error("Protocol error: {0}")]
+    Protocol(&'static str),
+
+    /// A text message or close reason was not valid UTF-8
+    #[error("Invalid UTF-8 in a text message")]
+    InvalidUtf8,
+
+    /// A message exceeded the size limit of the connection
+    #[error("Message too large")]
+    MessageTooLarge,
+
+    /// The closing handshake has already taken place
+    #[error("Connection closed")]
+    Closed,
+}
+
+impl WebSocketError {
+    /// Status code telling the peer why the connection is being closed
+    fn close_code(&self) -> u16 {
+        match self {
+            WebSocketError::Protocol(_) => close_code::PROTOCOL_ERROR,
+            WebSocketError::InvalidUtf8 => close_code::INVALID_PAYLOAD,
+            WebSocketError::MessageTooLarge => close_code::MESSAGE_TOO_BIG,
+            WebSocketError::Io(_) | WebSocketError::Closed => close_code::INTERNAL_ERROR,
+        }
+    }
+}
+
+/// A complete message, reassembled from its fragments
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Message {
+    /// A UTF-8 text message
+    Text(String),
+    /// A binary message
+    Binary(Vec<u8>),
+    /// A ping, which has already been answered by the time it is received
+    Ping(Vec<u8>),
+    /// A pong, answering a ping or serving as a heartbeat
+    Pong(Vec<u8>),
+    /// The peer closed the connection, with a status code and reason if it gave any
+    Close(Option<(u16, String)>),
+}
+
+/// A message that is still being received
+struct Fragmented {
+    opcode: Opcode,
+    payload: Vec<u8>,
+}
+
+/// The server side of a WebSocket connection, taking over a connection after the handshake
+pub struct WebSocket<C: Read + Write = Box<dyn Connection>> {
+    connection: C,
+    fragmented: Option<Fragmented>,
+    max_message_size: usize,
+    /// Whether a close frame has been sent, after which no other frame may follow
+    close_sent: bool,
+    /// Whether the peer's close frame has been received, after which it sends nothing else
+    close_received: bool,
+}
+
+impl<C: Read + Write> WebSocket<C> {
+    /// Speaks the WebSocket protocol over a connection whose handshake has completed
+    #[must_use]
+    pub fn new(connection: C) -> Self {
+        Self {
+            connection,
+            fragmented: None,
+            max_message_size: 16 << 20,
+            close_sent: false,
+            close_received: false,
+        }
+    }
+
+    /// Limits the size of received messages, 16 MiB by default
+    #[must_use]
+    pub fn max_message_size(mut self, max_message_size: usize) -> Self {
+        self.max_message_size = max_message_size;
+
+        self
+    }
+
+    /// Waits for the next message, answering pings and close frames along the way.
+    ///
+    /// Protocol violations close the connection with the matching status code before being returned.
+    /// # Errors
+    /// - If the peer broke the protocol, sent invalid UTF-8 or a message larger than the limit
+    /// - If the peer already closed the connection
+    /// - If reading from or writing to the connection failed
+    pub fn recv(&mut self) -> Result<Message, WebSocketError> {
+        if self.close_received {
+            return Err(WebSocketError::Closed);
+        }
+
+        match self.receive() {
+            Ok(message) => Ok(message),
+            Err(error) => {
+                if !self.close_sent && !matches!(error, WebSocketError::Io(_)) {
+                    // The connection is being torn down because of the error either way
+                    let _ = self.close(error.close_code(), "");
+                }
+
+                Err(error)
+            }
+        }
+    }
+
+    fn receive(&mut self) -> Result<Message, WebSocketError> {
+        loop {
+            let buffered = self
+                .fragmented
+                .as_ref()
+                .map_or(0, |fragmented| fragmented.payload.len());
+
+            let frame = Frame::read(
+                &mut self.connection,
+                self.max_message_size.saturating_sub(buffered),
+            )?;
+
+            match frame.opcode {
+                Opcode::Ping => {
+                    if !self.close_sent {
+                        self.write(&Frame::new(Opcode::Pong, frame.payload.clone()))?;
+                    }
+
+                    return Ok(Message::Ping(frame.payload));
+                }
+                Opcode::Pong => return Ok(Message::Pong(frame.payload)),
+                Opcode::Close => {
+                    let close = parse_close(&frame.payload)?;
+
+                    self.close_received = true;
+
+                    // Echo the status code to complete the closing handshake, unless the server started it
+                    if !self.close_sent {
+                        let payload = close
+                            .as_ref()
+                            .map_or(vec![], |(code, _)| code.to_be_bytes().to_vec());
+
+                        self.write(&Frame::new(Opcode::Close, payload))?;
+                    }
+
+                    return Ok(Message::Close(close));
+                }
+                Opcode::Text | Opcode::Binary if self.fragmented.is_some() => {
+                    return Err(WebSocketError::Protocol(
+                        "new message before the previous one was finished",
+                    ));
+                }
+                Opcode::Text | Opcode::Binary => {
+                    self.fragmented = Some(Fragmented {
+                        opcode: frame.opcode,
+                        payload: frame.payload,
+                    });
+                }
+                Opcode::Continuation => {
+                    let Some(fragmented) = &mut self.fragmented else {
+                        return Err(WebSocketError::Protocol(
+                            "continuation without a message to continue",
+                        ));
+                    };
+
+                    fragmented.payload.extend(frame.payload);
+                }
+            }
+
+            if frame.fin {
+                if let Some(Fragmented { opcode, payload }) = self.fragmented.take() {
+                    return match opcode {
+                        Opcode::Text => String::from_utf8(payload)
+                            .map(Message::Text)
+                            .map_err(|_| WebSocketError::InvalidUtf8),
+                        _ => Ok(Message::Binary(payload)),
+                    };
+                }
+            }
+        }
+    }
+
+    /// Sends a message in a single frame, a close message also closes the connection
+    /// # Errors
+    /// - If the connection was already closed
+    /// - If a ping, pong or close payload exceeds 125 bytes
+    /// - If writing to the connection failed
+    pub fn send(&mut self, message: Message) -> Result<(), WebSocketError> {
+        let frame = match message {
+            Message::Text(text) => Frame::new(Opcode::Text, text.into_bytes()),
+            Message::Binary(data) => Frame::new(Opcode::Binary, data),
+            Message::Ping(data) => Frame::new(Opcode::Ping, data),
+            Message::Pong(data) => Frame::new(Opcode::Pong, data),
+            Message::Close(Some((code, reason))) => return self.close(code, &reason),
+            Message::Close(None) => Frame::new(Opcode::Close, vec![]),
+        };
+
+        if frame.opcode.is_control() && frame.payload.len() > Frame::MAX_CONTROL_PAYLOAD {
+            return Err(WebSocketError::Protocol(
+                "control frames must be at most 125 bytes",
+            ));
+        }
+
+        self.write(&frame)
+    }
+
+    /// Sends a message split into frames of at most `fragment_size` bytes
+    /// # Errors
+    /// - If the connection was already closed
+    /// - If writing to the connection failed
+    pub fn send_fragmented(
+        &mut self,
+        message: Message,
+        fragment_size: usize,
+    ) -> Result<(), WebSocketError> {
+        let (opcode, payload) = match message {
+            Message::Text(text) => (Opcode::Text, text.into_bytes()),
+            Message::Binary(data) => (Opcode::Binary, data),
+            // Control frames cannot be fragmented
+            message => return self.send(message),
+        };
+
+        let mut fragments = payload.chunks(fragment_size.max(1)).peekable();
+        let mut opcode = opcode;
+
+        // An empty message still needs one frame
+        if fragments.peek().is_none() {
+            return self.write(&Frame::new(opcode, vec![]));
+        }
+
+        while let Some(fragment) = fragments.next() {
+            self.write(&Frame {
+                fin: fragments.peek().is_none(),
+                opcode,
+                payload: fragment.to_vec(),
+            })?;
+
+            opcode = Opcode::Continuation;
+        }
+
+        Ok(())
+    }
+
+    /// Starts the closing handshake with a status code and reason, the peer's answer is still returned by [`WebSocket::recv`]
+    /// # Errors
+    /// - If the connection was already closed
+    /// - If writing to the connection failed
+    pub fn close(&mut self, code: u16, reason: &str) -> Result<(), WebSocketError> {
+        let mut payload = code.to_be_bytes().to_vec();
+
+        // The reason has to fit a control frame along with the code, cut at a character boundary
+        let mut end = reason.len().min(Frame::MAX_CONTROL_PAYLOAD - 2);
+
+        while !reason.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        payload.extend(&reason.as_bytes()[..end]);
+
+        self.write(&Frame::new(Opcode::Close, payload))
+    }
+
+    /// Gives back the underlying connection
+    pub fn into_inner(self) -> C {
+        self.connection
+    }
+
+    fn write(&mut self, frame: &Frame) -> Result<(), WebSocketError> {
+        if self.close_sent {
+            return Err(WebSocketError::Closed);
+        }
+
+        self.close_sent = frame.opcode == Opcode::Close;
+
+        Ok(frame.write(&mut self.connection, None)?)
+    }
+}
+
+impl<C: Read + Write> Debug for WebSocket<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebSocket")
+            .field("max_message_size", &self.max_message_size)
+            .field("close_sent", &self.close_sent)
+            .field("close_received", &self.close_received)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Body of a `101 Switching Protocols` response, which hands the connection to a WebSocket handler once written
+pub struct Upgrade(Option<TakeOver>);
+
+impl Debug for Upgrade {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Upgrade").finish_non_exhaustive()
+    }
+}
+
+impl Body for Upgrade {
+    fn bytes(&self) -> Vec<u8> {
+        vec![]
+    }
+
+    fn content_length(&self) -> usize {
+        0
+    }
+
+    fn take_over(&mut self) -> Option<TakeOver> {
+        self.0.take()
+    }
+}
+
+/// Completes the opening handshake of a WebSocket request, running `handler` on the connection once the response is sent.
+///
+/// A subprotocol can be selected by adding a `Sec-WebSocket-Protocol` header to the response.
+/// # Errors
+/// - If the request is not a valid WebSocket handshake over HTTP/1.1
+/// - If the client asked for a protocol version other than 13, which answers with `426 Upgrade Required`
+pub fn upgrade<F>(request: &Request, handler: F) -> Result<Response<Upgrade>, Error>
+where
+    F: FnOnce(WebSocket) + Send + 'static,
+{
+    let key = handshake(request)?;
+
+    let take_over: TakeOver = Box::new(move |connection| handler(WebSocket::new(connection)));
+
+    Ok(Response::new(
+        Upgrade(Some(take_over)),
+        vec![
+            HttpHeader::Upgrade("websocket".to_string()),
+            HttpHeader::Connection("Upgrade".to_string()),
+            HttpHeader::SecWebSocketAccept(accept_key(&key)),
+        ],
+        HttpStatus::SwitchingProtocols,
+    ))
+}
+
+/// Validates the opening handshake, RFC 6455 Section 4.2.1, returning the client's key
+/// # Errors
+/// - If the request is not a valid WebSocket handshake over HTTP/1.1
+/// - If the client asked for a protocol version other than 13
+pub fn handshake(request: &Request) -> Result<String, Error> {
+    let header = |matches: fn(&HttpHeader) -> bool| {
+        request
+            .headers
+            .iter()
+            .find(|header| matches(header))
+            .map(HttpHeader::value)
+    };
+
+    let has_token = |value: Option<String>, token: &str| {
+        value.is_some_and(|value| {
+            value
+                .split(',')
+                .any(|item| item.trim().eq_ignore_ascii_case(token))
+        })
+    };
+
+    let invalid =
+        |reason: &str| Error::BadRequest(format!("Invalid WebSocket handshake: {reason}"));
+
+    if request.method != Method::Get || request.http_version != "HTTP/1.1" {
+        return Err(invalid("expected a GET request over HTTP/1.1"));
+    }
+
+    if header(|header| matches!(header, HttpHeader::Host(_))).is_none() {
+        return Err(invalid("missing Host header"));
+    }
+
+    if !has_token(
+        header(|header| matches!(header, HttpHeader::Upgrade(_))),
+        "websocket",
+    ) {
+        return Err(invalid("Upgrade header must contain websocket"));
+    }
+
+    if !has_token(
+        header(|header| matches!(header, HttpHeader::Connection(_))),
+        "upgrade",
+    ) {
+        return Err(invalid("Connection header must contain Upgrade"));
+    }
+
+    let version = header(|header| matches!(header, HttpHeader::SecWebSocketVersion(_)));
+
+    if version.as_deref().map(str::trim) != Some(VERSION) {
+        return Err(Error::UpgradeRequired {
+            supported: vec![HttpHeader::SecWebSocketVersion(VERSION.to_string())],
+        });
+    }
+
+    let key = header(|header| matches!(header, HttpHeader::SecWebSocketKey(_)))
+        .map(|key| key.trim().to_string())
+        .ok_or_else(|| invalid("missing Sec-WebSocket-Key header"))?;
+
+    // The key is a base64 encoded 16 byte nonce
+    if STANDARD.decode(&key).map(|nonce| nonce.len()) != Ok(16) {
+        return Err(invalid("Sec-WebSocket-Key must encode 16 bytes"));
+    }
+
+    Ok(key)
+}
+
+/// Computes the `Sec-WebSocket-Accept` value proving the server understood the handshake
+#[must_use]
+pub fn accept_key(key: &str) -> String {
+    let mut hasher = Sha1::new();
+
+    hasher.update(key.as_bytes());
+    hasher.update(GUID.as_bytes());
+
+    STANDARD.encode(hasher.finalize())
+}
+
+/// Decodes the optional status code and reason of a close frame
+fn parse_close(payload: &[u8]) -> Result<Option<(u16, String)>, WebSocketError> {
+    let Some((code, reason)) = payload.split_first_chunk::<2>() else {
+        return if payload.is_empty() {
+            Ok(None)
+        } else {
+            Err(WebSocketError::Protocol(
+                "close frame with a truncated code",
+            ))
+        };
+    };
+
+    let code = u16::from_be_bytes(*code);
+
+    // Codes that are reserved, or that must never be sent on the wire
+    if !matches!(code, 1000..=1003 | 1007..=1011 | 3000..=4999) {
+        return Err(WebSocketError::Protocol("invalid close code"));
+    }
+
+    let reason = String::from_utf8(reason.to_vec()).map_err(|_| WebSocketError::InvalidUtf8)?;
+
+    Ok(Some((code, reason)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{accept_key, handshake, parse_close};
+    use crate::http::{error::Error, request::HttpStream};
+
+    fn request(head: &str) -> crate::http::request::Request {
+        format!("{head}\r\n\r\n").into_bytes().parse().unwrap()
+    }
+
+    const HANDSHAKE: &str = "GET /chat HTTP/1.1\r\nHost: server.example.com\r\nUpgrade: websocket\r\n\
+        Connection: keep-alive, Upgrade\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13";
+
+    /// RFC 6455 Section 1.3
+    #[test]
+    fn accept_key_of_the_sample_nonce() {
+        assert_eq!(
+            accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+    }
+
+    #[test]
+    fn valid_handshakes_return_the_key() {
+        assert_eq!(
+            handshake(&request(HANDSHAKE)).unwrap(),
+            "dGhlIHNhbXBsZSBub25jZQ=="
+        );
+    }
+
+    #[test]
+    fn invalid_handshakes_are_refused() {
+        for (from, to) in [
+            ("GET", "POST"),
+            ("Upgrade: websocket", "Upgrade: h2c"),
+            ("keep-alive, Upgrade", "keep-alive"),
+            ("dGhlIHNhbXBsZSBub25jZQ==", "c2hvcnQ="),
+        ] {
+            assert!(matches!(
+                handshake(&request(&HANDSHAKE.replace(from, to))),
+                Err(Error::BadRequest(_))
+            ));
+        }
+
+        assert!(matches!(
+            handshake(&request(&HANDSHAKE.replace("Version: 13", "Version: 8"))),
+            Err(Error::UpgradeRequired { .. })
+        ));
+    }
+
+    #[test]
+    fn close_payloads() {
+        assert_eq!(parse_close(&[]).unwrap(), None);
+        assert_eq!(
+            parse_close(b"\x03\xe8bye").unwrap(),
+            Some((1000, "bye".to_string()))
+        );
+        assert!(parse_close(&[3]).is_err());
+        assert!(parse_close(&[0x03, 0xed]).is_err());
+        assert!(parse_close(b"\x03\xe8\xff").is_err());
+    }
+}
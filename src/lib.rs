@@ -3,7 +3,13 @@
 #![deny(clippy::missing_panics_doc)]
 //! Hyperion: HTTP Server in Rust
 
+// Lets the tests of this crate use the code generated by dynamo, which refers to `hyperion::`
+#[cfg(test)]
+extern crate self as hyperion;
+
 /// Implementation of the [HTTP/1.1 Protocol RFC 2616](https://www.ietf.org/rfc/rfc2616.txt)
 pub mod http;
 
-// mod tls;
+/// Implementation of the [TLS Protocol RFC 5246](https://www.ietf.org/rfc/rfc5246.txt) record layer,
+/// along with an HTTPS listener
+pub mod tls;
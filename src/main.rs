@@ -1,40 +1,67 @@
 #![deny(clippy::pedantic)]
 #![deny(clippy::missing_panics_doc)]
 
-use hyperion::http::{response::Response, route, router::routes, server::Server};
-use serde::Serialize;
+use hyperion::http::{server::Server, service};
 use std::net::TcpListener;
 
-#[derive(Serialize)]
-struct Testing<'a> {
-    name: &'a str,
-    place: u8,
-    vectors: Vec<String>,
-}
+#[service]
+mod app {
+    use hyperion::http::{
+        error::Error,
+        extract::{Path, Query},
+        get,
+        request::Request,
+        response::Response,
+    };
+    use serde::{Deserialize, Serialize};
 
-#[route]
-fn application(request: &Request) -> Response<String> {
-    Response::Ok().json(Testing {
-        name: "Hello",
-        place: 8,
-        vectors: vec![],
-    })
-}
+    #[derive(Serialize)]
+    struct Testing<'a> {
+        name: &'a str,
+        place: u8,
+        vectors: Vec<String>,
+    }
+
+    #[derive(Deserialize)]
+    struct Pagination {
+        page: Option<u8>,
+    }
+
+    #[get("/")]
+    fn application(request: &Request) -> Response<String> {
+        Response::Ok().json(Testing {
+            name: "Hello",
+            place: 8,
+            vectors: vec![request.path.clone()],
+        })
+    }
 
-#[route]
-fn another_application(request: &Request) -> Response<&str> {
-    Response::Ok().text("JSONING HARD")
+    #[get("/another")]
+    fn another_application() -> Response<&str> {
+        Response::Ok().text("JSONING HARD")
+    }
+
+    #[get("/users/{id}")]
+    fn user(Path(id): Path<u32>, Query(pagination): Query<Pagination>) -> Response<String> {
+        Response::Ok().json(Testing {
+            name: "User",
+            place: pagination.page.unwrap_or_default(),
+            vectors: vec![id.to_string()],
+        })
+    }
+
+    #[get("/double/{value}")]
+    fn double(Path(value): Path<String>) -> Result<Response<String>, Error> {
+        let value: u32 = value.parse()?;
+
+        Ok(Response::Ok().json(value * 2))
+    }
 }
 
 fn main() {
     let listener = TcpListener::bind("127.0.0.1:8000").expect("Failed to bind to socket");
 
-    let router = routes!(
-        Get "/" -> application,
-        Get "/another" -> another_application,
-    );
-
-    let server = Server::new(listener, router);
+    let server = Server::new(listener, app::router());
 
     server.listen();
 }
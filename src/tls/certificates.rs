@@ -0,0 +1,440 @@
+use rustls::{
+    crypto::CryptoProvider,
+    pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer},
+    server::{ClientHello, ResolvesServerCert},
+    sign::CertifiedKey,
+};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+    time::{Duration, SystemTime},
+};
+
+/// Certificates for several hostnames on one listener, selected by the SNI hostname of each handshake.
+///
+/// Hostnames are matched exactly first, then against wildcard patterns such as `*.example.com`,
+/// which like wildcard certificates only cover a single label. Handshakes without SNI or for any other
+/// hostname get the fallback certificate, or fail when there is none.
+///
+/// The files are read again by [`CertificateStore::reload`] once they change on disk,
+/// so renewed certificates are served without restarting.
+#[derive(Debug)]
+pub struct CertificateStore {
+    provider: Arc<CryptoProvider>,
+    exact: HashMap<String, Entry>,
+    wildcard: HashMap<String, Entry>,
+    default: Option<Entry>,
+}
+
+impl CertificateStore {
+    /// Creates an empty store, using the given provider to load private keys
+    #[must_use]
+    pub fn new(provider: Arc<CryptoProvider>) -> Self {
+        Self {
+            provider,
+            exact: HashMap::new(),
+            wildcard: HashMap::new(),
+            default: None,
+        }
+    }
+
+    /// Serves the PEM certificate chain and private key for the hostname, `*.example.com` matches `www.example.com`
+    /// # Errors
+    /// - If either file cannot be read or does not contain valid PEM data
+    /// - If the private key does not match the certificate
+    pub fn host(
+        mut self,
+        pattern: &str,
+        certificate: impl AsRef<Path>,
+        key: impl AsRef<Path>,
+    ) -> std::io::Result<Self> {
+        let pattern = normalize(pattern);
+        let certificate = Entry::load(&self.provider, certificate.as_ref(), key.as_ref())?;
+
+        match pattern.strip_prefix("*.") {
+            Some(suffix) => self.wildcard.insert(suffix.to_string(), certificate),
+            None => self.exact.insert(pattern, certificate),
+        };
+
+        Ok(self)
+    }
+
+    /// Serves the PEM certificate chain and private key when no hostname matches
+    /// # Errors
+    /// - If either file cannot be read or does not contain valid PEM data
+    /// - If the private key does not match the certificate
+    pub fn fallback(
+        mut self,
+        certificate: impl AsRef<Path>,
+        key: impl AsRef<Path>,
+    ) -> std::io::Result<Self> {
+        self.default = Some(Entry::load(
+            &self.provider,
+            certificate.as_ref(),
+            key.as_ref(),
+        )?);
+
+        Ok(self)
+    }
+
+    /// Get the certificate for the SNI hostname of a handshake
+    #[must_use]
+    pub fn select(&self, server_name: Option<&str>) -> Option<Arc<CertifiedKey>> {
+        let certificate = match server_name.map(normalize) {
+            Some(name) => self
+                .exact
+                .get(&name)
+                .or_else(|| {
+                    let (_, parent) = name.split_once('.')?;
+                    self.wildcard.get(parent)
+                })
+                .or(self.default.as_ref()),
+            None => self.default.as_ref(),
+        }?;
+
+        Some(certificate.current())
+    }
+
+    /// Reads every certificate whose files were modified since they were last loaded.
+    ///
+    /// A certificate that fails to load keeps serving its previous version, and is tried again on the next reload.
+    /// # Errors
+    /// - The last error encountered, after every other certificate has been reloaded
+    pub fn reload(&self) -> std::io::Result<()> {
+        let mut result = Ok(());
+
+        for entry in self
+            .exact
+            .values()
+            .chain(self.wildcard.values())
+            .chain(&self.default)
+        {
+            if let Err(error) = entry.reload(&self.provider) {
+                result = Err(error);
+            }
+        }
+
+        result
+    }
+
+    /// Reloads the certificates on a background thread every `interval`, passing failures to `on_error`
+    pub fn watch(
+        self: &Arc<Self>,
+        interval: Duration,
+        on_error: impl Fn(&dyn std::error::Error) + Send + 'static,
+    ) {
+        let store = Arc::downgrade(self);
+
+        std::thread::spawn(move || loop {
+            std::thread::sleep(interval);
+
+            // Stop once the listener using the store is gone
+            let Some(store) = store.upgrade() else {
+                break;
+            };
+
+            if let Err(error) = store.reload() {
+                on_error(&error);
+            }
+        });
+    }
+}
+
+impl ResolvesServerCert for CertificateStore {
+    fn resolve(&self, client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        self.select(client_hello.server_name())
+    }
+}
+
+/// A certificate chain and private key loaded from a pair of PEM files
+#[derive(Debug)]
+struct Entry {
+    chain: PathBuf,
+    key: PathBuf,
+    loaded: RwLock<Loaded>,
+}
+
+#[derive(Debug)]
+struct Loaded {
+    certified_key: Arc<CertifiedKey>,
+    modified: Option<SystemTime>,
+}
+
+impl Entry {
+    fn load(provider: &CryptoProvider, certificate: &Path, key: &Path) -> std::io::Result<Self> {
+        let modified = modified(certificate, key);
+        let certified_key = read(provider, certificate, key)?;
+
+        Ok(Self {
+            chain: certificate.to_path_buf(),
+            key: key.to_path_buf(),
+            loaded: RwLock::new(Loaded {
+                certified_key,
+                modified,
+            }),
+        })
+    }
+
+    fn current(&self) -> Arc<CertifiedKey> {
+        // A poisoned lock still holds a complete certificate, swapping it is a single assignment
+        match self.loaded.read() {
+            Ok(loaded) => loaded.certified_key.clone(),
+            Err(poisoned) => poisoned.into_inner().certified_key.clone(),
+        }
+    }
+
+    fn reload(&self, provider: &CryptoProvider) -> std::io::Result<()> {
+        let modified = modified(&self.chain, &self.key);
+
+        let unchanged = self
+            .loaded
+            .read()
+            .is_ok_and(|loaded| loaded.modified == modified);
+
+        if unchanged {
+            return Ok(());
+        }
+
+        // Renewals often replace the two files one after the other, a mismatch is retried on the next reload
+        let certified_key = read(provider, &self.chain, &self.key)?;
+
+        let mut loaded = self
+            .loaded
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        *loaded = Loaded {
+            certified_key,
+            modified,
+        };
+
+        Ok(())
+    }
+}
+
+/// Latest modification time of the two files
+fn modified(certificate: &Path, key: &Path) -> Option<SystemTime> {
+    let certificate = std::fs::metadata(certificate).and_then(|metadata| metadata.modified());
+    let key = std::fs::metadata(key).and_then(|metadata| metadata.modified());
+
+    certificate.ok().max(key.ok())
+}
+
+fn read(
+    provider: &CryptoProvider,
+    certificate: &Path,
+    key: &Path,
+) -> std::io::Result<Arc<CertifiedKey>> {
+    let certificates = CertificateDer::pem_file_iter(certificate)
+        .map_err(std::io::Error::other)?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(std::io::Error::other)?;
+
+    let key = PrivateKeyDer::from_pem_file(key).map_err(std::io::Error::other)?;
+
+    CertifiedKey::from_der(certificates, key, provider)
+        .map(Arc::new)
+        .map_err(std::io::Error::other)
+}
+
+/// Lowercases a hostname and strips its trailing dot
+fn normalize(name: &str) -> String {
+    name.trim().trim_end_matches('.').to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CertificateStore;
+    use crate::tls::server::provider;
+    use rustls::pki_types::{pem::PemObject, CertificateDer};
+    use std::{
+        fs::File,
+        path::{Path, PathBuf},
+        time::{Duration, SystemTime},
+    };
+
+    const TESTDATA: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/tls/testdata");
+
+    /// A directory of its own for every test, as tests run in parallel
+    fn directory(test: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!(
+            "hyperion-certificates-{}-{test}",
+            std::process::id()
+        ));
+
+        std::fs::create_dir_all(&directory).unwrap();
+
+        directory
+    }
+
+    /// Copies one of the test certificates and its key into the directory, as `server.pem` and `server.key`
+    fn install(directory: &Path, name: &str, modified: SystemTime) -> (PathBuf, PathBuf) {
+        let files = (directory.join("server.pem"), directory.join("server.key"));
+
+        for (source, destination) in [("pem", &files.0), ("key", &files.1)] {
+            std::fs::copy(format!("{TESTDATA}/{name}.{source}"), destination).unwrap();
+            File::options()
+                .write(true)
+                .open(destination)
+                .unwrap()
+                .set_modified(modified)
+                .unwrap();
+        }
+
+        files
+    }
+
+    fn certificate(name: &str) -> CertificateDer<'static> {
+        CertificateDer::from_pem_file(format!("{TESTDATA}/{name}.pem")).unwrap()
+    }
+
+    fn selected(
+        store: &CertificateStore,
+        server_name: Option<&str>,
+    ) -> Option<CertificateDer<'static>> {
+        store
+            .select(server_name)
+            .map(|certified_key| certified_key.cert[0].clone())
+    }
+
+    fn store() -> CertificateStore {
+        let pair = |name: &str| {
+            (
+                format!("{TESTDATA}/{name}.pem"),
+                format!("{TESTDATA}/{name}.key"),
+            )
+        };
+        let (exact, exact_key) = pair("exact");
+        let (wildcard, wildcard_key) = pair("wildcard");
+
+        CertificateStore::new(provider())
+            .host("www.example.com", exact, exact_key)
+            .unwrap()
+            .host("*.example.com", wildcard, wildcard_key)
+            .unwrap()
+    }
+
+    #[test]
+    fn exact_names_win_over_wildcards() {
+        let store = store();
+
+        assert_eq!(
+            selected(&store, Some("www.example.com")),
+            Some(certificate("exact"))
+        );
+        assert_eq!(
+            selected(&store, Some("WWW.Example.com.")),
+            Some(certificate("exact"))
+        );
+        assert_eq!(
+            selected(&store, Some("api.example.com")),
+            Some(certificate("wildcard"))
+        );
+    }
+
+    #[test]
+    fn wildcards_cover_a_single_label() {
+        let store = store();
+
+        assert_eq!(selected(&store, Some("v1.api.example.com")), None);
+        assert_eq!(selected(&store, Some("example.com")), None);
+        assert_eq!(selected(&store, None), None);
+    }
+
+    #[test]
+    fn other_names_get_the_fallback() {
+        let store = store()
+            .fallback(
+                format!("{TESTDATA}/fallback.pem"),
+                format!("{TESTDATA}/fallback.key"),
+            )
+            .unwrap();
+
+        assert_eq!(
+            selected(&store, Some("v1.api.example.com")),
+            Some(certificate("fallback"))
+        );
+        assert_eq!(
+            selected(&store, Some("other.org")),
+            Some(certificate("fallback"))
+        );
+        assert_eq!(selected(&store, None), Some(certificate("fallback")));
+    }
+
+    #[test]
+    fn mismatched_keys_are_refused() {
+        assert!(CertificateStore::new(provider())
+            .host(
+                "www.example.com",
+                format!("{TESTDATA}/exact.pem"),
+                format!("{TESTDATA}/renewed.key"),
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn reloads_pick_up_changed_files() {
+        let directory = directory("reload");
+        let loaded = SystemTime::now() - Duration::from_secs(60);
+        let (pem, key) = install(&directory, "exact", loaded);
+
+        let store = CertificateStore::new(provider())
+            .host("www.example.com", &pem, &key)
+            .unwrap();
+
+        // Files that did not change are not read again
+        std::fs::write(&pem, "not a certificate").unwrap();
+        File::options()
+            .write(true)
+            .open(&pem)
+            .unwrap()
+            .set_modified(loaded)
+            .unwrap();
+        store.reload().unwrap();
+        assert_eq!(
+            selected(&store, Some("www.example.com")),
+            Some(certificate("exact"))
+        );
+
+        install(&directory, "renewed", SystemTime::now());
+        store.reload().unwrap();
+        assert_eq!(
+            selected(&store, Some("www.example.com")),
+            Some(certificate("renewed"))
+        );
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn failed_reloads_keep_the_previous_certificate() {
+        let directory = directory("failed");
+        let (pem, key) = install(
+            &directory,
+            "exact",
+            SystemTime::now() - Duration::from_secs(60),
+        );
+
+        let store = CertificateStore::new(provider())
+            .host("www.example.com", &pem, &key)
+            .unwrap();
+
+        // Renewals that replaced only one of the files yet
+        std::fs::copy(format!("{TESTDATA}/renewed.pem"), &pem).unwrap();
+        assert!(store.reload().is_err());
+        assert_eq!(
+            selected(&store, Some("www.example.com")),
+            Some(certificate("exact"))
+        );
+
+        std::fs::copy(format!("{TESTDATA}/renewed.key"), &key).unwrap();
+        store.reload().unwrap();
+        assert_eq!(
+            selected(&store, Some("www.example.com")),
+            Some(certificate("renewed"))
+        );
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -1,16 +1,54 @@
+use super::{client_hello::ClientHello, TLSVersion};
+
 macro_rules! cipher_suite_spec {
-    ($([$first_byte:literal,$second_byte:literal] => $cipher_suite:ident)*) => {
-        #[allow(non_camel_case_types)]
+    (
+        $([$first_byte:literal, $second_byte:literal] => $cipher_suite:ident
+            ($key_exchange:ident, $authentication:ident, $cipher:ident, $mac:ident, $version:ident, $security:ident))*
+        ;
+        $([$signaling_first_byte:literal, $signaling_second_byte:literal] => $signaling:ident)*
+    ) => {
+        /// Cipher suites as registered with IANA
+        #[allow(non_camel_case_types, missing_docs)] // Named after the registry
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
         pub enum CipherSuite {
             $($cipher_suite,)*
+            $($signaling,)*
             Unknown(u8, u8)
         }
 
-        impl From<&[u8]> for CipherSuite {
-            fn from(cipher_suite_byte_pair: &[u8]) -> Self {
+        impl CipherSuite {
+            /// Every registered cipher suite that can be negotiated, in registry order
+            pub const ALL: &[CipherSuite] = &[$(CipherSuite::$cipher_suite,)*];
+
+            /// The algorithms and classification of the suite, [`None`] for signaling values and unknown suites
+            #[must_use]
+            pub fn info(self) -> Option<CipherSuiteInfo> {
+                match self {
+                    $(CipherSuite::$cipher_suite => Some(CipherSuiteInfo {
+                        key_exchange: KeyExchange::$key_exchange,
+                        authentication: Authentication::$authentication,
+                        cipher: Cipher::$cipher,
+                        mac: Mac::$mac,
+                        minimum_version: TLSVersion::$version,
+                        security: Security::$security,
+                    }),)*
+                    _ => None,
+                }
+            }
+
+            /// Whether the value only signals something to the server instead of naming a suite, e.g. `TLS_FALLBACK_SCSV`
+            #[must_use]
+            pub fn is_signaling(self) -> bool {
+                matches!(self, $(CipherSuite::$signaling)|*)
+            }
+        }
+
+        impl From<[u8; 2]> for CipherSuite {
+            fn from(cipher_suite_byte_pair: [u8; 2]) -> Self {
                 match cipher_suite_byte_pair {
                     $([$first_byte, $second_byte] => Self::$cipher_suite,)*
-                    _ => Self::Unknown(cipher_suite_byte_pair[0], cipher_suite_byte_pair[1]),
+                    $([$signaling_first_byte, $signaling_second_byte] => Self::$signaling,)*
+                    [first, second] => Self::Unknown(first, second),
                 }
             }
         }
@@ -19,6 +57,7 @@ macro_rules! cipher_suite_spec {
             fn from(cipher_suite: CipherSuite) -> Self {
                 match cipher_suite {
                     $(CipherSuite::$cipher_suite => [$first_byte, $second_byte],)*
+                    $(CipherSuite::$signaling => [$signaling_first_byte, $signaling_second_byte],)*
                     CipherSuite::Unknown(first, second) => [first, second]
                 }
             }
@@ -26,324 +65,818 @@ macro_rules! cipher_suite_spec {
     };
 }
 
+/// How the premaster secret is established
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyExchange {
+    /// No key exchange
+    Null,
+    /// Encrypted with the server's RSA key
+    Rsa,
+    /// Static Diffie-Hellman
+    Dh,
+    /// Ephemeral Diffie-Hellman
+    Dhe,
+    /// Static elliptic curve Diffie-Hellman
+    Ecdh,
+    /// Ephemeral elliptic curve Diffie-Hellman
+    Ecdhe,
+    /// Pre-shared key only
+    Psk,
+    /// Kerberos 5 tickets
+    Krb5,
+    /// Secure Remote Password, RFC 5054
+    Srp,
+    /// Elliptic curve password exchange, RFC 8492
+    Eccpwd,
+    /// GOST R 34.10-2012 key agreement, RFC 9189
+    Gost,
+    /// Negotiated separately through extensions, as in TLS 1.3
+    Any,
+}
+
+/// How the server, and optionally the client, prove their identity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Authentication {
+    /// No authentication at all
+    Anonymous,
+    /// RSA signatures or encryption
+    Rsa,
+    /// DSA signatures
+    Dss,
+    /// ECDSA signatures
+    Ecdsa,
+    /// Knowledge of a pre-shared key
+    Psk,
+    /// Kerberos 5 tickets
+    Krb5,
+    /// Knowledge of a password
+    Password,
+    /// GOST R 34.10-2012 signatures
+    Gost,
+    /// Determined by the certificate and signature algorithms, as in TLS 1.3
+    Any,
+}
+
+/// Bulk encryption algorithm protecting the records
+#[allow(missing_docs)] // Named after the algorithms
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Cipher {
+    Null,
+    Rc4Export,
+    Rc4,
+    Rc2CbcExport,
+    DesCbcExport,
+    DesCbc,
+    TripleDesEdeCbc,
+    IdeaCbc,
+    SeedCbc,
+    Aes128Cbc,
+    Aes256Cbc,
+    Aes128Gcm,
+    Aes256Gcm,
+    Aes128Ccm,
+    Aes256Ccm,
+    Aes128Ccm8,
+    Aes256Ccm8,
+    Camellia128Cbc,
+    Camellia256Cbc,
+    Camellia128Gcm,
+    Camellia256Gcm,
+    Aria128Cbc,
+    Aria256Cbc,
+    Aria128Gcm,
+    Aria256Gcm,
+    ChaCha20Poly1305,
+    Aegis128L,
+    Aegis256,
+    KuznyechikCtr,
+    KuznyechikMgm,
+    MagmaCtr,
+    MagmaMgm,
+    Gost28147Cnt,
+}
+
+impl Cipher {
+    /// Effective strength of the key in bits
+    #[must_use]
+    pub fn key_bits(self) -> u16 {
+        match self {
+            Cipher::Null => 0,
+            Cipher::Rc4Export | Cipher::Rc2CbcExport | Cipher::DesCbcExport => 40,
+            Cipher::DesCbc => 56,
+            Cipher::TripleDesEdeCbc => 112,
+            Cipher::Rc4
+            | Cipher::IdeaCbc
+            | Cipher::SeedCbc
+            | Cipher::Aes128Cbc
+            | Cipher::Aes128Gcm
+            | Cipher::Aes128Ccm
+            | Cipher::Aes128Ccm8
+            | Cipher::Camellia128Cbc
+            | Cipher::Camellia128Gcm
+            | Cipher::Aria128Cbc
+            | Cipher::Aria128Gcm
+            | Cipher::Aegis128L => 128,
+            Cipher::Aes256Cbc
+            | Cipher::Aes256Gcm
+            | Cipher::Aes256Ccm
+            | Cipher::Aes256Ccm8
+            | Cipher::Camellia256Cbc
+            | Cipher::Camellia256Gcm
+            | Cipher::Aria256Cbc
+            | Cipher::Aria256Gcm
+            | Cipher::ChaCha20Poly1305
+            | Cipher::Aegis256
+            | Cipher::KuznyechikCtr
+            | Cipher::KuznyechikMgm
+            | Cipher::MagmaCtr
+            | Cipher::MagmaMgm
+            | Cipher::Gost28147Cnt => 256,
+        }
+    }
+
+    /// Whether the cipher authenticates the records itself, making a separate MAC unnecessary
+    #[must_use]
+    pub fn is_aead(self) -> bool {
+        matches!(
+            self,
+            Cipher::Aes128Gcm
+                | Cipher::Aes256Gcm
+                | Cipher::Aes128Ccm
+                | Cipher::Aes256Ccm
+                | Cipher::Aes128Ccm8
+                | Cipher::Aes256Ccm8
+                | Cipher::Camellia128Gcm
+                | Cipher::Camellia256Gcm
+                | Cipher::Aria128Gcm
+                | Cipher::Aria256Gcm
+                | Cipher::ChaCha20Poly1305
+                | Cipher::Aegis128L
+                | Cipher::Aegis256
+                | Cipher::KuznyechikMgm
+                | Cipher::MagmaMgm
+        )
+    }
+}
+
+/// Message authentication of the records
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Mac {
+    /// No authentication
+    Null,
+    /// HMAC-MD5
+    Md5,
+    /// HMAC-SHA1
+    Sha1,
+    /// HMAC-SHA256
+    Sha256,
+    /// HMAC-SHA384
+    Sha384,
+    /// Built into the AEAD cipher
+    Aead,
+    /// GOST OMAC
+    Omac,
+    /// GOST 28147-89 IMIT
+    Imit,
+}
+
+/// Classification of a suite, ordered from worst to best.
+///
+/// Suites are [`Security::Recommended`] when they are forward secret and use AES-GCM, AES-CCM or ChaCha20-Poly1305,
+/// matching the IANA "Recommended" column. Other forward secret AEAD suites are [`Security::Secure`],
+/// suites without forward secrecy or with CBC and 64 bit block ciphers are [`Security::Weak`],
+/// and anything unauthenticated, unencrypted, export grade, RC4, DES or MD5 based is [`Security::Insecure`].
+#[allow(missing_docs)] // Documented above
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Security {
+    Insecure,
+    Weak,
+    Secure,
+    Recommended,
+}
+
+/// The algorithms making up a cipher suite
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CipherSuiteInfo {
+    /// How the keys are exchanged
+    pub key_exchange: KeyExchange,
+    /// How the peers are authenticated
+    pub authentication: Authentication,
+    /// How the records are encrypted
+    pub cipher: Cipher,
+    /// How the records are authenticated
+    pub mac: Mac,
+    /// The first protocol version the suite is defined for
+    pub minimum_version: TLSVersion,
+    /// How safe the suite is to use today
+    pub security: Security,
+}
+
+impl CipherSuiteInfo {
+    /// Whether a compromised long term key leaves past sessions intact
+    #[must_use]
+    pub fn is_forward_secret(&self) -> bool {
+        matches!(
+            self.key_exchange,
+            KeyExchange::Dhe
+                | KeyExchange::Ecdhe
+                | KeyExchange::Srp
+                | KeyExchange::Eccpwd
+                | KeyExchange::Gost
+                | KeyExchange::Any
+        )
+    }
+
+    /// Whether the suite can be used with the given protocol version, TLS 1.3 suites and older ones don't mix
+    #[must_use]
+    pub fn is_usable_with(&self, version: TLSVersion) -> bool {
+        self.minimum_version <= version
+            && (self.minimum_version == TLSVersion::TLS1_3) == (version == TLSVersion::TLS1_3)
+    }
+}
+
+/// The server's policy for choosing one of the cipher suites offered by a client.
+///
+/// The policy ranks the suites of a decoded [`ClientHello`], for analysis and logging. It is not applied to
+/// [`TlsListener`](super::server::TlsListener) handshakes, where rustls picks among the few modern suites its
+/// provider implements.
+#[derive(Debug, Clone)]
+pub struct SelectionPolicy {
+    supported: Vec<CipherSuite>,
+    minimum_security: Security,
+}
+
+impl SelectionPolicy {
+    /// Accepts the given suites, as long as they are at least [`Security::Secure`]
+    #[must_use]
+    pub fn new(supported: impl IntoIterator<Item = CipherSuite>) -> Self {
+        Self {
+            supported: supported.into_iter().collect(),
+            minimum_security: Security::Secure,
+        }
+    }
+
+    /// Lowers or raises the bar a suite has to meet to be chosen
+    #[must_use]
+    pub fn minimum_security(mut self, security: Security) -> Self {
+        self.minimum_security = security;
+
+        self
+    }
+
+    /// Picks the strongest suite offered by the client that the server supports for the negotiated version.
+    ///
+    /// Suites are ranked by their [`Security`], then forward secrecy, AEAD and key size.
+    /// Ties are broken by the client's order of preference.
+    #[must_use]
+    pub fn select(&self, offered: &[CipherSuite], version: TLSVersion) -> Option<CipherSuite> {
+        let mut strongest = None;
+
+        for &suite in offered {
+            let Some(info) = suite.info() else {
+                continue;
+            };
+
+            if info.security < self.minimum_security
+                || !info.is_usable_with(version)
+                || !self.supported.contains(&suite)
+            {
+                continue;
+            }
+
+            let strength = (
+                info.security,
+                info.is_forward_secret(),
+                info.cipher.is_aead(),
+                info.cipher.key_bits(),
+            );
+
+            // Only a strictly stronger suite may replace one the client prefers
+            if strongest.is_none_or(|(current, _)| strength > current) {
+                strongest = Some((strength, suite));
+            }
+        }
+
+        strongest.map(|(_, suite)| suite)
+    }
+
+    /// Picks a suite for a `ClientHello`, assuming the highest protocol version it offers is negotiated
+    #[must_use]
+    pub fn select_for(&self, hello: &ClientHello) -> Option<CipherSuite> {
+        let version = hello
+            .supported_versions()
+            .into_iter()
+            .max()
+            .unwrap_or(hello.version);
+
+        self.select(&hello.cipher_suites, version)
+    }
+}
+
+impl Default for SelectionPolicy {
+    /// Accepts every registered suite that is at least [`Security::Secure`]
+    fn default() -> Self {
+        Self::new(CipherSuite::ALL.iter().copied())
+    }
+}
+
 cipher_suite_spec!(
-    [0x00, 0x00] => TLS_NULL_WITH_NULL_NULL
-    [0x00, 0x01] => TLS_RSA_WITH_NULL_MD5
-    [0x00, 0x02] => TLS_RSA_WITH_NULL_SHA
-    [0x00, 0x06] => TLS_RSA_EXPORT_WITH_RC2_CBC_40_MD5
-    [0x00, 0x07] => TLS_RSA_WITH_IDEA_CBC_SHA
-    [0x00, 0x08] => TLS_RSA_EXPORT_WITH_DES40_CBC_SHA
-    [0x00, 0x09] => TLS_RSA_WITH_DES_CBC_SHA
-    [0x00, 0x0A] => TLS_RSA_WITH_3DES_EDE_CBC_SHA
-    [0x00, 0x0B] => TLS_DH_DSS_EXPORT_WITH_DES40_CBC_SHA
-    [0x00, 0x0C] => TLS_DH_DSS_WITH_DES_CBC_SHA
-    [0x00, 0x0D] => TLS_DH_DSS_WITH_3DES_EDE_CBC_SHA
-    [0x00, 0x0E] => TLS_DH_RSA_EXPORT_WITH_DES40_CBC_SHA
-    [0x00, 0x0F] => TLS_DH_RSA_WITH_DES_CBC_SHA
-    [0x00, 0x10] => TLS_DH_RSA_WITH_3DES_EDE_CBC_SHA
-    [0x00, 0x11] => TLS_DHE_DSS_EXPORT_WITH_DES40_CBC_SHA
-    [0x00, 0x12] => TLS_DHE_DSS_WITH_DES_CBC_SHA
-    [0x00, 0x13] => TLS_DHE_DSS_WITH_3DES_EDE_CBC_SHA
-    [0x00, 0x14] => TLS_DHE_RSA_EXPORT_WITH_DES40_CBC_SHA
-    [0x00, 0x15] => TLS_DHE_RSA_WITH_DES_CBC_SHA
-    [0x00, 0x16] => TLS_DHE_RSA_WITH_3DES_EDE_CBC_SHA
-    [0x00, 0x19] => TLS_DH_anon_EXPORT_WITH_DES40_CBC_SHA
-    [0x00, 0x1A] => TLS_DH_anon_WITH_DES_CBC_SHA
-    [0x00, 0x1B] => TLS_DH_anon_WITH_3DES_EDE_CBC_SHA
-    [0x00, 0x1E] => TLS_KRB5_WITH_DES_CBC_SHA
-    [0x00, 0x1F] => TLS_KRB5_WITH_3DES_EDE_CBC_SHA
-    [0x00, 0x21] => TLS_KRB5_WITH_IDEA_CBC_SHA
-    [0x00, 0x22] => TLS_KRB5_WITH_DES_CBC_MD5
-    [0x00, 0x23] => TLS_KRB5_WITH_3DES_EDE_CBC_MD5
-    [0x00, 0x25] => TLS_KRB5_WITH_IDEA_CBC_MD5
-    [0x00, 0x26] => TLS_KRB5_EXPORT_WITH_DES_CBC_40_SHA
-    [0x00, 0x27] => TLS_KRB5_EXPORT_WITH_RC2_CBC_40_SHA
-    [0x00, 0x29] => TLS_KRB5_EXPORT_WITH_DES_CBC_40_MD5
-    [0x00, 0x2A] => TLS_KRB5_EXPORT_WITH_RC2_CBC_40_MD5
-    [0x00, 0x2C] => TLS_PSK_WITH_NULL_SHA
-    [0x00, 0x2D] => TLS_DHE_PSK_WITH_NULL_SHA
-    [0x00, 0x2E] => TLS_RSA_PSK_WITH_NULL_SHA
-    [0x00, 0x2F] => TLS_RSA_WITH_AES_128_CBC_SHA
-    [0x00, 0x30] => TLS_DH_DSS_WITH_AES_128_CBC_SHA
-    [0x00, 0x31] => TLS_DH_RSA_WITH_AES_128_CBC_SHA
-    [0x00, 0x32] => TLS_DHE_DSS_WITH_AES_128_CBC_SHA
-    [0x00, 0x33] => TLS_DHE_RSA_WITH_AES_128_CBC_SHA
-    [0x00, 0x34] => TLS_DH_anon_WITH_AES_128_CBC_SHA
-    [0x00, 0x35] => TLS_RSA_WITH_AES_256_CBC_SHA
-    [0x00, 0x36] => TLS_DH_DSS_WITH_AES_256_CBC_SHA
-    [0x00, 0x37] => TLS_DH_RSA_WITH_AES_256_CBC_SHA
-    [0x00, 0x38] => TLS_DHE_DSS_WITH_AES_256_CBC_SHA
-    [0x00, 0x39] => TLS_DHE_RSA_WITH_AES_256_CBC_SHA
-    [0x00, 0x3A] => TLS_DH_anon_WITH_AES_256_CBC_SHA
-    [0x00, 0x3B] => TLS_RSA_WITH_NULL_SHA256
-    [0x00, 0x3C] => TLS_RSA_WITH_AES_128_CBC_SHA256
-    [0x00, 0x3D] => TLS_RSA_WITH_AES_256_CBC_SHA256
-    [0x00, 0x3E] => TLS_DH_DSS_WITH_AES_128_CBC_SHA256
-    [0x00, 0x3F] => TLS_DH_RSA_WITH_AES_128_CBC_SHA256
-    [0x00, 0x40] => TLS_DHE_DSS_WITH_AES_128_CBC_SHA256
-    [0x00, 0x41] => TLS_RSA_WITH_CAMELLIA_128_CBC_SHA
-    [0x00, 0x42] => TLS_DH_DSS_WITH_CAMELLIA_128_CBC_SHA
-    [0x00, 0x43] => TLS_DH_RSA_WITH_CAMELLIA_128_CBC_SHA
-    [0x00, 0x44] => TLS_DHE_DSS_WITH_CAMELLIA_128_CBC_SHA
-    [0x00, 0x45] => TLS_DHE_RSA_WITH_CAMELLIA_128_CBC_SHA
-    [0x00, 0x46] => TLS_DH_anon_WITH_CAMELLIA_128_CBC_SHA
-    [0x00, 0x67] => TLS_DHE_RSA_WITH_AES_128_CBC_SHA256
-    [0x00, 0x68] => TLS_DH_DSS_WITH_AES_256_CBC_SHA256
-    [0x00, 0x69] => TLS_DH_RSA_WITH_AES_256_CBC_SHA256
-    [0x00, 0x6A] => TLS_DHE_DSS_WITH_AES_256_CBC_SHA256
-    [0x00, 0x6B] => TLS_DHE_RSA_WITH_AES_256_CBC_SHA256
-    [0x00, 0x6C] => TLS_DH_anon_WITH_AES_128_CBC_SHA256
-    [0x00, 0x6D] => TLS_DH_anon_WITH_AES_256_CBC_SHA256
-    [0x00, 0x84] => TLS_RSA_WITH_CAMELLIA_256_CBC_SHA
-    [0x00, 0x85] => TLS_DH_DSS_WITH_CAMELLIA_256_CBC_SHA
-    [0x00, 0x86] => TLS_DH_RSA_WITH_CAMELLIA_256_CBC_SHA
-    [0x00, 0x87] => TLS_DHE_DSS_WITH_CAMELLIA_256_CBC_SHA
-    [0x00, 0x88] => TLS_DHE_RSA_WITH_CAMELLIA_256_CBC_SHA
-    [0x00, 0x89] => TLS_DH_anon_WITH_CAMELLIA_256_CBC_SHA
-    [0x00, 0x8B] => TLS_PSK_WITH_3DES_EDE_CBC_SHA
-    [0x00, 0x8C] => TLS_PSK_WITH_AES_128_CBC_SHA
-    [0x00, 0x8D] => TLS_PSK_WITH_AES_256_CBC_SHA
-    [0x00, 0x8F] => TLS_DHE_PSK_WITH_3DES_EDE_CBC_SHA
-    [0x00, 0x90] => TLS_DHE_PSK_WITH_AES_128_CBC_SHA
-    [0x00, 0x91] => TLS_DHE_PSK_WITH_AES_256_CBC_SHA
-    [0x00, 0x93] => TLS_RSA_PSK_WITH_3DES_EDE_CBC_SHA
-    [0x00, 0x94] => TLS_RSA_PSK_WITH_AES_128_CBC_SHA
-    [0x00, 0x95] => TLS_RSA_PSK_WITH_AES_256_CBC_SHA
-    [0x00, 0x96] => TLS_RSA_WITH_SEED_CBC_SHA
-    [0x00, 0x97] => TLS_DH_DSS_WITH_SEED_CBC_SHA
-    [0x00, 0x98] => TLS_DH_RSA_WITH_SEED_CBC_SHA
-    [0x00, 0x99] => TLS_DHE_DSS_WITH_SEED_CBC_SHA
-    [0x00, 0x9A] => TLS_DHE_RSA_WITH_SEED_CBC_SHA
-    [0x00, 0x9B] => TLS_DH_anon_WITH_SEED_CBC_SHA
-    [0x00, 0x9C] => TLS_RSA_WITH_AES_128_GCM_SHA256
-    [0x00, 0x9D] => TLS_RSA_WITH_AES_256_GCM_SHA384
-    [0x00, 0x9E] => TLS_DHE_RSA_WITH_AES_128_GCM_SHA256
-    [0x00, 0x9F] => TLS_DHE_RSA_WITH_AES_256_GCM_SHA384
-    [0x00, 0xA0] => TLS_DH_RSA_WITH_AES_128_GCM_SHA256
-    [0x00, 0xA1] => TLS_DH_RSA_WITH_AES_256_GCM_SHA384
-    [0x00, 0xA2] => TLS_DHE_DSS_WITH_AES_128_GCM_SHA256
-    [0x00, 0xA3] => TLS_DHE_DSS_WITH_AES_256_GCM_SHA384
-    [0x00, 0xA4] => TLS_DH_DSS_WITH_AES_128_GCM_SHA256
-    [0x00, 0xA5] => TLS_DH_DSS_WITH_AES_256_GCM_SHA384
-    [0x00, 0xA6] => TLS_DH_anon_WITH_AES_128_GCM_SHA256
-    [0x00, 0xA7] => TLS_DH_anon_WITH_AES_256_GCM_SHA384
-    [0x00, 0xA8] => TLS_PSK_WITH_AES_128_GCM_SHA256
-    [0x00, 0xA9] => TLS_PSK_WITH_AES_256_GCM_SHA384
-    [0x00, 0xAA] => TLS_DHE_PSK_WITH_AES_128_GCM_SHA256
-    [0x00, 0xAB] => TLS_DHE_PSK_WITH_AES_256_GCM_SHA384
-    [0x00, 0xAC] => TLS_RSA_PSK_WITH_AES_128_GCM_SHA256
-    [0x00, 0xAD] => TLS_RSA_PSK_WITH_AES_256_GCM_SHA384
-    [0x00, 0xAE] => TLS_PSK_WITH_AES_128_CBC_SHA256
-    [0x00, 0xAF] => TLS_PSK_WITH_AES_256_CBC_SHA384
-    [0x00, 0xB0] => TLS_PSK_WITH_NULL_SHA256
-    [0x00, 0xB1] => TLS_PSK_WITH_NULL_SHA384
-    [0x00, 0xB2] => TLS_DHE_PSK_WITH_AES_128_CBC_SHA256
-    [0x00, 0xB3] => TLS_DHE_PSK_WITH_AES_256_CBC_SHA384
-    [0x00, 0xB4] => TLS_DHE_PSK_WITH_NULL_SHA256
-    [0x00, 0xB5] => TLS_DHE_PSK_WITH_NULL_SHA384
-    [0x00, 0xB6] => TLS_RSA_PSK_WITH_AES_128_CBC_SHA256
-    [0x00, 0xB7] => TLS_RSA_PSK_WITH_AES_256_CBC_SHA384
-    [0x00, 0xB8] => TLS_RSA_PSK_WITH_NULL_SHA256
-    [0x00, 0xB9] => TLS_RSA_PSK_WITH_NULL_SHA384
-    [0x00, 0xBA] => TLS_RSA_WITH_CAMELLIA_128_CBC_SHA256
-    [0x00, 0xBB] => TLS_DH_DSS_WITH_CAMELLIA_128_CBC_SHA256
-    [0x00, 0xBC] => TLS_DH_RSA_WITH_CAMELLIA_128_CBC_SHA256
-    [0x00, 0xBD] => TLS_DHE_DSS_WITH_CAMELLIA_128_CBC_SHA256
-    [0x00, 0xBE] => TLS_DHE_RSA_WITH_CAMELLIA_128_CBC_SHA256
-    [0x00, 0xBF] => TLS_DH_anon_WITH_CAMELLIA_128_CBC_SHA256
-    [0x00, 0xC0] => TLS_RSA_WITH_CAMELLIA_256_CBC_SHA256
-    [0x00, 0xC1] => TLS_DH_DSS_WITH_CAMELLIA_256_CBC_SHA256
-    [0x00, 0xC2] => TLS_DH_RSA_WITH_CAMELLIA_256_CBC_SHA256
-    [0x00, 0xC3] => TLS_DHE_DSS_WITH_CAMELLIA_256_CBC_SHA256
-    [0x00, 0xC4] => TLS_DHE_RSA_WITH_CAMELLIA_256_CBC_SHA256
-    [0x00, 0xC5] => TLS_DH_anon_WITH_CAMELLIA_256_CBC_SHA256
+    [0x00, 0x00] => TLS_NULL_WITH_NULL_NULL (Null, Anonymous, Null, Null, TLS1, Insecure)
+    [0x00, 0x01] => TLS_RSA_WITH_NULL_MD5 (Rsa, Rsa, Null, Md5, TLS1, Insecure)
+    [0x00, 0x02] => TLS_RSA_WITH_NULL_SHA (Rsa, Rsa, Null, Sha1, TLS1, Insecure)
+    [0x00, 0x03] => TLS_RSA_EXPORT_WITH_RC4_40_MD5 (Rsa, Rsa, Rc4Export, Md5, TLS1, Insecure)
+    [0x00, 0x04] => TLS_RSA_WITH_RC4_128_MD5 (Rsa, Rsa, Rc4, Md5, TLS1, Insecure)
+    [0x00, 0x05] => TLS_RSA_WITH_RC4_128_SHA (Rsa, Rsa, Rc4, Sha1, TLS1, Insecure)
+    [0x00, 0x06] => TLS_RSA_EXPORT_WITH_RC2_CBC_40_MD5 (Rsa, Rsa, Rc2CbcExport, Md5, TLS1, Insecure)
+    [0x00, 0x07] => TLS_RSA_WITH_IDEA_CBC_SHA (Rsa, Rsa, IdeaCbc, Sha1, TLS1, Weak)
+    [0x00, 0x08] => TLS_RSA_EXPORT_WITH_DES40_CBC_SHA (Rsa, Rsa, DesCbcExport, Sha1, TLS1, Insecure)
+    [0x00, 0x09] => TLS_RSA_WITH_DES_CBC_SHA (Rsa, Rsa, DesCbc, Sha1, TLS1, Insecure)
+    [0x00, 0x0A] => TLS_RSA_WITH_3DES_EDE_CBC_SHA (Rsa, Rsa, TripleDesEdeCbc, Sha1, TLS1, Weak)
+    [0x00, 0x0B] => TLS_DH_DSS_EXPORT_WITH_DES40_CBC_SHA (Dh, Dss, DesCbcExport, Sha1, TLS1, Insecure)
+    [0x00, 0x0C] => TLS_DH_DSS_WITH_DES_CBC_SHA (Dh, Dss, DesCbc, Sha1, TLS1, Insecure)
+    [0x00, 0x0D] => TLS_DH_DSS_WITH_3DES_EDE_CBC_SHA (Dh, Dss, TripleDesEdeCbc, Sha1, TLS1, Weak)
+    [0x00, 0x0E] => TLS_DH_RSA_EXPORT_WITH_DES40_CBC_SHA (Dh, Rsa, DesCbcExport, Sha1, TLS1, Insecure)
+    [0x00, 0x0F] => TLS_DH_RSA_WITH_DES_CBC_SHA (Dh, Rsa, DesCbc, Sha1, TLS1, Insecure)
+    [0x00, 0x10] => TLS_DH_RSA_WITH_3DES_EDE_CBC_SHA (Dh, Rsa, TripleDesEdeCbc, Sha1, TLS1, Weak)
+    [0x00, 0x11] => TLS_DHE_DSS_EXPORT_WITH_DES40_CBC_SHA (Dhe, Dss, DesCbcExport, Sha1, TLS1, Insecure)
+    [0x00, 0x12] => TLS_DHE_DSS_WITH_DES_CBC_SHA (Dhe, Dss, DesCbc, Sha1, TLS1, Insecure)
+    [0x00, 0x13] => TLS_DHE_DSS_WITH_3DES_EDE_CBC_SHA (Dhe, Dss, TripleDesEdeCbc, Sha1, TLS1, Weak)
+    [0x00, 0x14] => TLS_DHE_RSA_EXPORT_WITH_DES40_CBC_SHA (Dhe, Rsa, DesCbcExport, Sha1, TLS1, Insecure)
+    [0x00, 0x15] => TLS_DHE_RSA_WITH_DES_CBC_SHA (Dhe, Rsa, DesCbc, Sha1, TLS1, Insecure)
+    [0x00, 0x16] => TLS_DHE_RSA_WITH_3DES_EDE_CBC_SHA (Dhe, Rsa, TripleDesEdeCbc, Sha1, TLS1, Weak)
+    [0x00, 0x17] => TLS_DH_anon_EXPORT_WITH_RC4_40_MD5 (Dh, Anonymous, Rc4Export, Md5, TLS1, Insecure)
+    [0x00, 0x18] => TLS_DH_anon_WITH_RC4_128_MD5 (Dh, Anonymous, Rc4, Md5, TLS1, Insecure)
+    [0x00, 0x19] => TLS_DH_anon_EXPORT_WITH_DES40_CBC_SHA (Dh, Anonymous, DesCbcExport, Sha1, TLS1, Insecure)
+    [0x00, 0x1A] => TLS_DH_anon_WITH_DES_CBC_SHA (Dh, Anonymous, DesCbc, Sha1, TLS1, Insecure)
+    [0x00, 0x1B] => TLS_DH_anon_WITH_3DES_EDE_CBC_SHA (Dh, Anonymous, TripleDesEdeCbc, Sha1, TLS1, Insecure)
+    [0x00, 0x1E] => TLS_KRB5_WITH_DES_CBC_SHA (Krb5, Krb5, DesCbc, Sha1, TLS1, Insecure)
+    [0x00, 0x1F] => TLS_KRB5_WITH_3DES_EDE_CBC_SHA (Krb5, Krb5, TripleDesEdeCbc, Sha1, TLS1, Weak)
+    [0x00, 0x20] => TLS_KRB5_WITH_RC4_128_SHA (Krb5, Krb5, Rc4, Sha1, TLS1, Insecure)
+    [0x00, 0x21] => TLS_KRB5_WITH_IDEA_CBC_SHA (Krb5, Krb5, IdeaCbc, Sha1, TLS1, Weak)
+    [0x00, 0x22] => TLS_KRB5_WITH_DES_CBC_MD5 (Krb5, Krb5, DesCbc, Md5, TLS1, Insecure)
+    [0x00, 0x23] => TLS_KRB5_WITH_3DES_EDE_CBC_MD5 (Krb5, Krb5, TripleDesEdeCbc, Md5, TLS1, Insecure)
+    [0x00, 0x24] => TLS_KRB5_WITH_RC4_128_MD5 (Krb5, Krb5, Rc4, Md5, TLS1, Insecure)
+    [0x00, 0x25] => TLS_KRB5_WITH_IDEA_CBC_MD5 (Krb5, Krb5, IdeaCbc, Md5, TLS1, Insecure)
+    [0x00, 0x26] => TLS_KRB5_EXPORT_WITH_DES_CBC_40_SHA (Krb5, Krb5, DesCbcExport, Sha1, TLS1, Insecure)
+    [0x00, 0x27] => TLS_KRB5_EXPORT_WITH_RC2_CBC_40_SHA (Krb5, Krb5, Rc2CbcExport, Sha1, TLS1, Insecure)
+    [0x00, 0x28] => TLS_KRB5_EXPORT_WITH_RC4_40_SHA (Krb5, Krb5, Rc4Export, Sha1, TLS1, Insecure)
+    [0x00, 0x29] => TLS_KRB5_EXPORT_WITH_DES_CBC_40_MD5 (Krb5, Krb5, DesCbcExport, Md5, TLS1, Insecure)
+    [0x00, 0x2A] => TLS_KRB5_EXPORT_WITH_RC2_CBC_40_MD5 (Krb5, Krb5, Rc2CbcExport, Md5, TLS1, Insecure)
+    [0x00, 0x2B] => TLS_KRB5_EXPORT_WITH_RC4_40_MD5 (Krb5, Krb5, Rc4Export, Md5, TLS1, Insecure)
+    [0x00, 0x2C] => TLS_PSK_WITH_NULL_SHA (Psk, Psk, Null, Sha1, TLS1, Insecure)
+    [0x00, 0x2D] => TLS_DHE_PSK_WITH_NULL_SHA (Dhe, Psk, Null, Sha1, TLS1, Insecure)
+    [0x00, 0x2E] => TLS_RSA_PSK_WITH_NULL_SHA (Rsa, Psk, Null, Sha1, TLS1, Insecure)
+    [0x00, 0x2F] => TLS_RSA_WITH_AES_128_CBC_SHA (Rsa, Rsa, Aes128Cbc, Sha1, TLS1, Weak)
+    [0x00, 0x30] => TLS_DH_DSS_WITH_AES_128_CBC_SHA (Dh, Dss, Aes128Cbc, Sha1, TLS1, Weak)
+    [0x00, 0x31] => TLS_DH_RSA_WITH_AES_128_CBC_SHA (Dh, Rsa, Aes128Cbc, Sha1, TLS1, Weak)
+    [0x00, 0x32] => TLS_DHE_DSS_WITH_AES_128_CBC_SHA (Dhe, Dss, Aes128Cbc, Sha1, TLS1, Weak)
+    [0x00, 0x33] => TLS_DHE_RSA_WITH_AES_128_CBC_SHA (Dhe, Rsa, Aes128Cbc, Sha1, TLS1, Weak)
+    [0x00, 0x34] => TLS_DH_anon_WITH_AES_128_CBC_SHA (Dh, Anonymous, Aes128Cbc, Sha1, TLS1, Insecure)
+    [0x00, 0x35] => TLS_RSA_WITH_AES_256_CBC_SHA (Rsa, Rsa, Aes256Cbc, Sha1, TLS1, Weak)
+    [0x00, 0x36] => TLS_DH_DSS_WITH_AES_256_CBC_SHA (Dh, Dss, Aes256Cbc, Sha1, TLS1, Weak)
+    [0x00, 0x37] => TLS_DH_RSA_WITH_AES_256_CBC_SHA (Dh, Rsa, Aes256Cbc, Sha1, TLS1, Weak)
+    [0x00, 0x38] => TLS_DHE_DSS_WITH_AES_256_CBC_SHA (Dhe, Dss, Aes256Cbc, Sha1, TLS1, Weak)
+    [0x00, 0x39] => TLS_DHE_RSA_WITH_AES_256_CBC_SHA (Dhe, Rsa, Aes256Cbc, Sha1, TLS1, Weak)
+    [0x00, 0x3A] => TLS_DH_anon_WITH_AES_256_CBC_SHA (Dh, Anonymous, Aes256Cbc, Sha1, TLS1, Insecure)
+    [0x00, 0x3B] => TLS_RSA_WITH_NULL_SHA256 (Rsa, Rsa, Null, Sha256, TLS1_2, Insecure)
+    [0x00, 0x3C] => TLS_RSA_WITH_AES_128_CBC_SHA256 (Rsa, Rsa, Aes128Cbc, Sha256, TLS1_2, Weak)
+    [0x00, 0x3D] => TLS_RSA_WITH_AES_256_CBC_SHA256 (Rsa, Rsa, Aes256Cbc, Sha256, TLS1_2, Weak)
+    [0x00, 0x3E] => TLS_DH_DSS_WITH_AES_128_CBC_SHA256 (Dh, Dss, Aes128Cbc, Sha256, TLS1_2, Weak)
+    [0x00, 0x3F] => TLS_DH_RSA_WITH_AES_128_CBC_SHA256 (Dh, Rsa, Aes128Cbc, Sha256, TLS1_2, Weak)
+    [0x00, 0x40] => TLS_DHE_DSS_WITH_AES_128_CBC_SHA256 (Dhe, Dss, Aes128Cbc, Sha256, TLS1_2, Weak)
+    [0x00, 0x41] => TLS_RSA_WITH_CAMELLIA_128_CBC_SHA (Rsa, Rsa, Camellia128Cbc, Sha1, TLS1, Weak)
+    [0x00, 0x42] => TLS_DH_DSS_WITH_CAMELLIA_128_CBC_SHA (Dh, Dss, Camellia128Cbc, Sha1, TLS1, Weak)
+    [0x00, 0x43] => TLS_DH_RSA_WITH_CAMELLIA_128_CBC_SHA (Dh, Rsa, Camellia128Cbc, Sha1, TLS1, Weak)
+    [0x00, 0x44] => TLS_DHE_DSS_WITH_CAMELLIA_128_CBC_SHA (Dhe, Dss, Camellia128Cbc, Sha1, TLS1, Weak)
+    [0x00, 0x45] => TLS_DHE_RSA_WITH_CAMELLIA_128_CBC_SHA (Dhe, Rsa, Camellia128Cbc, Sha1, TLS1, Weak)
+    [0x00, 0x46] => TLS_DH_anon_WITH_CAMELLIA_128_CBC_SHA (Dh, Anonymous, Camellia128Cbc, Sha1, TLS1, Insecure)
+    [0x00, 0x67] => TLS_DHE_RSA_WITH_AES_128_CBC_SHA256 (Dhe, Rsa, Aes128Cbc, Sha256, TLS1_2, Weak)
+    [0x00, 0x68] => TLS_DH_DSS_WITH_AES_256_CBC_SHA256 (Dh, Dss, Aes256Cbc, Sha256, TLS1_2, Weak)
+    [0x00, 0x69] => TLS_DH_RSA_WITH_AES_256_CBC_SHA256 (Dh, Rsa, Aes256Cbc, Sha256, TLS1_2, Weak)
+    [0x00, 0x6A] => TLS_DHE_DSS_WITH_AES_256_CBC_SHA256 (Dhe, Dss, Aes256Cbc, Sha256, TLS1_2, Weak)
+    [0x00, 0x6B] => TLS_DHE_RSA_WITH_AES_256_CBC_SHA256 (Dhe, Rsa, Aes256Cbc, Sha256, TLS1_2, Weak)
+    [0x00, 0x6C] => TLS_DH_anon_WITH_AES_128_CBC_SHA256 (Dh, Anonymous, Aes128Cbc, Sha256, TLS1_2, Insecure)
+    [0x00, 0x6D] => TLS_DH_anon_WITH_AES_256_CBC_SHA256 (Dh, Anonymous, Aes256Cbc, Sha256, TLS1_2, Insecure)
+    [0x00, 0x84] => TLS_RSA_WITH_CAMELLIA_256_CBC_SHA (Rsa, Rsa, Camellia256Cbc, Sha1, TLS1, Weak)
+    [0x00, 0x85] => TLS_DH_DSS_WITH_CAMELLIA_256_CBC_SHA (Dh, Dss, Camellia256Cbc, Sha1, TLS1, Weak)
+    [0x00, 0x86] => TLS_DH_RSA_WITH_CAMELLIA_256_CBC_SHA (Dh, Rsa, Camellia256Cbc, Sha1, TLS1, Weak)
+    [0x00, 0x87] => TLS_DHE_DSS_WITH_CAMELLIA_256_CBC_SHA (Dhe, Dss, Camellia256Cbc, Sha1, TLS1, Weak)
+    [0x00, 0x88] => TLS_DHE_RSA_WITH_CAMELLIA_256_CBC_SHA (Dhe, Rsa, Camellia256Cbc, Sha1, TLS1, Weak)
+    [0x00, 0x89] => TLS_DH_anon_WITH_CAMELLIA_256_CBC_SHA (Dh, Anonymous, Camellia256Cbc, Sha1, TLS1, Insecure)
+    [0x00, 0x8A] => TLS_PSK_WITH_RC4_128_SHA (Psk, Psk, Rc4, Sha1, TLS1, Insecure)
+    [0x00, 0x8B] => TLS_PSK_WITH_3DES_EDE_CBC_SHA (Psk, Psk, TripleDesEdeCbc, Sha1, TLS1, Weak)
+    [0x00, 0x8C] => TLS_PSK_WITH_AES_128_CBC_SHA (Psk, Psk, Aes128Cbc, Sha1, TLS1, Weak)
+    [0x00, 0x8D] => TLS_PSK_WITH_AES_256_CBC_SHA (Psk, Psk, Aes256Cbc, Sha1, TLS1, Weak)
+    [0x00, 0x8E] => TLS_DHE_PSK_WITH_RC4_128_SHA (Dhe, Psk, Rc4, Sha1, TLS1, Insecure)
+    [0x00, 0x8F] => TLS_DHE_PSK_WITH_3DES_EDE_CBC_SHA (Dhe, Psk, TripleDesEdeCbc, Sha1, TLS1, Weak)
+    [0x00, 0x90] => TLS_DHE_PSK_WITH_AES_128_CBC_SHA (Dhe, Psk, Aes128Cbc, Sha1, TLS1, Weak)
+    [0x00, 0x91] => TLS_DHE_PSK_WITH_AES_256_CBC_SHA (Dhe, Psk, Aes256Cbc, Sha1, TLS1, Weak)
+    [0x00, 0x92] => TLS_RSA_PSK_WITH_RC4_128_SHA (Rsa, Psk, Rc4, Sha1, TLS1, Insecure)
+    [0x00, 0x93] => TLS_RSA_PSK_WITH_3DES_EDE_CBC_SHA (Rsa, Psk, TripleDesEdeCbc, Sha1, TLS1, Weak)
+    [0x00, 0x94] => TLS_RSA_PSK_WITH_AES_128_CBC_SHA (Rsa, Psk, Aes128Cbc, Sha1, TLS1, Weak)
+    [0x00, 0x95] => TLS_RSA_PSK_WITH_AES_256_CBC_SHA (Rsa, Psk, Aes256Cbc, Sha1, TLS1, Weak)
+    [0x00, 0x96] => TLS_RSA_WITH_SEED_CBC_SHA (Rsa, Rsa, SeedCbc, Sha1, TLS1, Weak)
+    [0x00, 0x97] => TLS_DH_DSS_WITH_SEED_CBC_SHA (Dh, Dss, SeedCbc, Sha1, TLS1, Weak)
+    [0x00, 0x98] => TLS_DH_RSA_WITH_SEED_CBC_SHA (Dh, Rsa, SeedCbc, Sha1, TLS1, Weak)
+    [0x00, 0x99] => TLS_DHE_DSS_WITH_SEED_CBC_SHA (Dhe, Dss, SeedCbc, Sha1, TLS1, Weak)
+    [0x00, 0x9A] => TLS_DHE_RSA_WITH_SEED_CBC_SHA (Dhe, Rsa, SeedCbc, Sha1, TLS1, Weak)
+    [0x00, 0x9B] => TLS_DH_anon_WITH_SEED_CBC_SHA (Dh, Anonymous, SeedCbc, Sha1, TLS1, Insecure)
+    [0x00, 0x9C] => TLS_RSA_WITH_AES_128_GCM_SHA256 (Rsa, Rsa, Aes128Gcm, Aead, TLS1_2, Weak)
+    [0x00, 0x9D] => TLS_RSA_WITH_AES_256_GCM_SHA384 (Rsa, Rsa, Aes256Gcm, Aead, TLS1_2, Weak)
+    [0x00, 0x9E] => TLS_DHE_RSA_WITH_AES_128_GCM_SHA256 (Dhe, Rsa, Aes128Gcm, Aead, TLS1_2, Recommended)
+    [0x00, 0x9F] => TLS_DHE_RSA_WITH_AES_256_GCM_SHA384 (Dhe, Rsa, Aes256Gcm, Aead, TLS1_2, Recommended)
+    [0x00, 0xA0] => TLS_DH_RSA_WITH_AES_128_GCM_SHA256 (Dh, Rsa, Aes128Gcm, Aead, TLS1_2, Weak)
+    [0x00, 0xA1] => TLS_DH_RSA_WITH_AES_256_GCM_SHA384 (Dh, Rsa, Aes256Gcm, Aead, TLS1_2, Weak)
+    [0x00, 0xA2] => TLS_DHE_DSS_WITH_AES_128_GCM_SHA256 (Dhe, Dss, Aes128Gcm, Aead, TLS1_2, Secure)
+    [0x00, 0xA3] => TLS_DHE_DSS_WITH_AES_256_GCM_SHA384 (Dhe, Dss, Aes256Gcm, Aead, TLS1_2, Secure)
+    [0x00, 0xA4] => TLS_DH_DSS_WITH_AES_128_GCM_SHA256 (Dh, Dss, Aes128Gcm, Aead, TLS1_2, Weak)
+    [0x00, 0xA5] => TLS_DH_DSS_WITH_AES_256_GCM_SHA384 (Dh, Dss, Aes256Gcm, Aead, TLS1_2, Weak)
+    [0x00, 0xA6] => TLS_DH_anon_WITH_AES_128_GCM_SHA256 (Dh, Anonymous, Aes128Gcm, Aead, TLS1_2, Insecure)
+    [0x00, 0xA7] => TLS_DH_anon_WITH_AES_256_GCM_SHA384 (Dh, Anonymous, Aes256Gcm, Aead, TLS1_2, Insecure)
+    [0x00, 0xA8] => TLS_PSK_WITH_AES_128_GCM_SHA256 (Psk, Psk, Aes128Gcm, Aead, TLS1_2, Weak)
+    [0x00, 0xA9] => TLS_PSK_WITH_AES_256_GCM_SHA384 (Psk, Psk, Aes256Gcm, Aead, TLS1_2, Weak)
+    [0x00, 0xAA] => TLS_DHE_PSK_WITH_AES_128_GCM_SHA256 (Dhe, Psk, Aes128Gcm, Aead, TLS1_2, Recommended)
+    [0x00, 0xAB] => TLS_DHE_PSK_WITH_AES_256_GCM_SHA384 (Dhe, Psk, Aes256Gcm, Aead, TLS1_2, Recommended)
+    [0x00, 0xAC] => TLS_RSA_PSK_WITH_AES_128_GCM_SHA256 (Rsa, Psk, Aes128Gcm, Aead, TLS1_2, Weak)
+    [0x00, 0xAD] => TLS_RSA_PSK_WITH_AES_256_GCM_SHA384 (Rsa, Psk, Aes256Gcm, Aead, TLS1_2, Weak)
+    [0x00, 0xAE] => TLS_PSK_WITH_AES_128_CBC_SHA256 (Psk, Psk, Aes128Cbc, Sha256, TLS1_2, Weak)
+    [0x00, 0xAF] => TLS_PSK_WITH_AES_256_CBC_SHA384 (Psk, Psk, Aes256Cbc, Sha384, TLS1_2, Weak)
+    [0x00, 0xB0] => TLS_PSK_WITH_NULL_SHA256 (Psk, Psk, Null, Sha256, TLS1_2, Insecure)
+    [0x00, 0xB1] => TLS_PSK_WITH_NULL_SHA384 (Psk, Psk, Null, Sha384, TLS1_2, Insecure)
+    [0x00, 0xB2] => TLS_DHE_PSK_WITH_AES_128_CBC_SHA256 (Dhe, Psk, Aes128Cbc, Sha256, TLS1_2, Weak)
+    [0x00, 0xB3] => TLS_DHE_PSK_WITH_AES_256_CBC_SHA384 (Dhe, Psk, Aes256Cbc, Sha384, TLS1_2, Weak)
+    [0x00, 0xB4] => TLS_DHE_PSK_WITH_NULL_SHA256 (Dhe, Psk, Null, Sha256, TLS1_2, Insecure)
+    [0x00, 0xB5] => TLS_DHE_PSK_WITH_NULL_SHA384 (Dhe, Psk, Null, Sha384, TLS1_2, Insecure)
+    [0x00, 0xB6] => TLS_RSA_PSK_WITH_AES_128_CBC_SHA256 (Rsa, Psk, Aes128Cbc, Sha256, TLS1_2, Weak)
+    [0x00, 0xB7] => TLS_RSA_PSK_WITH_AES_256_CBC_SHA384 (Rsa, Psk, Aes256Cbc, Sha384, TLS1_2, Weak)
+    [0x00, 0xB8] => TLS_RSA_PSK_WITH_NULL_SHA256 (Rsa, Psk, Null, Sha256, TLS1_2, Insecure)
+    [0x00, 0xB9] => TLS_RSA_PSK_WITH_NULL_SHA384 (Rsa, Psk, Null, Sha384, TLS1_2, Insecure)
+    [0x00, 0xBA] => TLS_RSA_WITH_CAMELLIA_128_CBC_SHA256 (Rsa, Rsa, Camellia128Cbc, Sha256, TLS1_2, Weak)
+    [0x00, 0xBB] => TLS_DH_DSS_WITH_CAMELLIA_128_CBC_SHA256 (Dh, Dss, Camellia128Cbc, Sha256, TLS1_2, Weak)
+    [0x00, 0xBC] => TLS_DH_RSA_WITH_CAMELLIA_128_CBC_SHA256 (Dh, Rsa, Camellia128Cbc, Sha256, TLS1_2, Weak)
+    [0x00, 0xBD] => TLS_DHE_DSS_WITH_CAMELLIA_128_CBC_SHA256 (Dhe, Dss, Camellia128Cbc, Sha256, TLS1_2, Weak)
+    [0x00, 0xBE] => TLS_DHE_RSA_WITH_CAMELLIA_128_CBC_SHA256 (Dhe, Rsa, Camellia128Cbc, Sha256, TLS1_2, Weak)
+    [0x00, 0xBF] => TLS_DH_anon_WITH_CAMELLIA_128_CBC_SHA256 (Dh, Anonymous, Camellia128Cbc, Sha256, TLS1_2, Insecure)
+    [0x00, 0xC0] => TLS_RSA_WITH_CAMELLIA_256_CBC_SHA256 (Rsa, Rsa, Camellia256Cbc, Sha256, TLS1_2, Weak)
+    [0x00, 0xC1] => TLS_DH_DSS_WITH_CAMELLIA_256_CBC_SHA256 (Dh, Dss, Camellia256Cbc, Sha256, TLS1_2, Weak)
+    [0x00, 0xC2] => TLS_DH_RSA_WITH_CAMELLIA_256_CBC_SHA256 (Dh, Rsa, Camellia256Cbc, Sha256, TLS1_2, Weak)
+    [0x00, 0xC3] => TLS_DHE_DSS_WITH_CAMELLIA_256_CBC_SHA256 (Dhe, Dss, Camellia256Cbc, Sha256, TLS1_2, Weak)
+    [0x00, 0xC4] => TLS_DHE_RSA_WITH_CAMELLIA_256_CBC_SHA256 (Dhe, Rsa, Camellia256Cbc, Sha256, TLS1_2, Weak)
+    [0x00, 0xC5] => TLS_DH_anon_WITH_CAMELLIA_256_CBC_SHA256 (Dh, Anonymous, Camellia256Cbc, Sha256, TLS1_2, Insecure)
+    [0x13, 0x01] => TLS_AES_128_GCM_SHA256 (Any, Any, Aes128Gcm, Aead, TLS1_3, Recommended)
+    [0x13, 0x02] => TLS_AES_256_GCM_SHA384 (Any, Any, Aes256Gcm, Aead, TLS1_3, Recommended)
+    [0x13, 0x03] => TLS_CHACHA20_POLY1305_SHA256 (Any, Any, ChaCha20Poly1305, Aead, TLS1_3, Recommended)
+    [0x13, 0x04] => TLS_AES_128_CCM_SHA256 (Any, Any, Aes128Ccm, Aead, TLS1_3, Recommended)
+    [0x13, 0x05] => TLS_AES_128_CCM_8_SHA256 (Any, Any, Aes128Ccm8, Aead, TLS1_3, Secure)
+    [0x13, 0x06] => TLS_AEGIS_256_SHA512 (Any, Any, Aegis256, Aead, TLS1_3, Secure)
+    [0x13, 0x07] => TLS_AEGIS_128L_SHA256 (Any, Any, Aegis128L, Aead, TLS1_3, Secure)
+    [0xC0, 0x01] => TLS_ECDH_ECDSA_WITH_NULL_SHA (Ecdh, Ecdsa, Null, Sha1, TLS1, Insecure)
+    [0xC0, 0x02] => TLS_ECDH_ECDSA_WITH_RC4_128_SHA (Ecdh, Ecdsa, Rc4, Sha1, TLS1, Insecure)
+    [0xC0, 0x03] => TLS_ECDH_ECDSA_WITH_3DES_EDE_CBC_SHA (Ecdh, Ecdsa, TripleDesEdeCbc, Sha1, TLS1, Weak)
+    [0xC0, 0x04] => TLS_ECDH_ECDSA_WITH_AES_128_CBC_SHA (Ecdh, Ecdsa, Aes128Cbc, Sha1, TLS1, Weak)
+    [0xC0, 0x05] => TLS_ECDH_ECDSA_WITH_AES_256_CBC_SHA (Ecdh, Ecdsa, Aes256Cbc, Sha1, TLS1, Weak)
+    [0xC0, 0x06] => TLS_ECDHE_ECDSA_WITH_NULL_SHA (Ecdhe, Ecdsa, Null, Sha1, TLS1, Insecure)
+    [0xC0, 0x07] => TLS_ECDHE_ECDSA_WITH_RC4_128_SHA (Ecdhe, Ecdsa, Rc4, Sha1, TLS1, Insecure)
+    [0xC0, 0x08] => TLS_ECDHE_ECDSA_WITH_3DES_EDE_CBC_SHA (Ecdhe, Ecdsa, TripleDesEdeCbc, Sha1, TLS1, Weak)
+    [0xC0, 0x09] => TLS_ECDHE_ECDSA_WITH_AES_128_CBC_SHA (Ecdhe, Ecdsa, Aes128Cbc, Sha1, TLS1, Weak)
+    [0xC0, 0x0A] => TLS_ECDHE_ECDSA_WITH_AES_256_CBC_SHA (Ecdhe, Ecdsa, Aes256Cbc, Sha1, TLS1, Weak)
+    [0xC0, 0x0B] => TLS_ECDH_RSA_WITH_NULL_SHA (Ecdh, Rsa, Null, Sha1, TLS1, Insecure)
+    [0xC0, 0x0C] => TLS_ECDH_RSA_WITH_RC4_128_SHA (Ecdh, Rsa, Rc4, Sha1, TLS1, Insecure)
+    [0xC0, 0x0D] => TLS_ECDH_RSA_WITH_3DES_EDE_CBC_SHA (Ecdh, Rsa, TripleDesEdeCbc, Sha1, TLS1, Weak)
+    [0xC0, 0x0E] => TLS_ECDH_RSA_WITH_AES_128_CBC_SHA (Ecdh, Rsa, Aes128Cbc, Sha1, TLS1, Weak)
+    [0xC0, 0x0F] => TLS_ECDH_RSA_WITH_AES_256_CBC_SHA (Ecdh, Rsa, Aes256Cbc, Sha1, TLS1, Weak)
+    [0xC0, 0x10] => TLS_ECDHE_RSA_WITH_NULL_SHA (Ecdhe, Rsa, Null, Sha1, TLS1, Insecure)
+    [0xC0, 0x11] => TLS_ECDHE_RSA_WITH_RC4_128_SHA (Ecdhe, Rsa, Rc4, Sha1, TLS1, Insecure)
+    [0xC0, 0x12] => TLS_ECDHE_RSA_WITH_3DES_EDE_CBC_SHA (Ecdhe, Rsa, TripleDesEdeCbc, Sha1, TLS1, Weak)
+    [0xC0, 0x13] => TLS_ECDHE_RSA_WITH_AES_128_CBC_SHA (Ecdhe, Rsa, Aes128Cbc, Sha1, TLS1, Weak)
+    [0xC0, 0x14] => TLS_ECDHE_RSA_WITH_AES_256_CBC_SHA (Ecdhe, Rsa, Aes256Cbc, Sha1, TLS1, Weak)
+    [0xC0, 0x15] => TLS_ECDH_anon_WITH_NULL_SHA (Ecdh, Anonymous, Null, Sha1, TLS1, Insecure)
+    [0xC0, 0x16] => TLS_ECDH_anon_WITH_RC4_128_SHA (Ecdh, Anonymous, Rc4, Sha1, TLS1, Insecure)
+    [0xC0, 0x17] => TLS_ECDH_anon_WITH_3DES_EDE_CBC_SHA (Ecdh, Anonymous, TripleDesEdeCbc, Sha1, TLS1, Insecure)
+    [0xC0, 0x18] => TLS_ECDH_anon_WITH_AES_128_CBC_SHA (Ecdh, Anonymous, Aes128Cbc, Sha1, TLS1, Insecure)
+    [0xC0, 0x19] => TLS_ECDH_anon_WITH_AES_256_CBC_SHA (Ecdh, Anonymous, Aes256Cbc, Sha1, TLS1, Insecure)
+    [0xC0, 0x1A] => TLS_SRP_SHA_WITH_3DES_EDE_CBC_SHA (Srp, Password, TripleDesEdeCbc, Sha1, TLS1, Weak)
+    [0xC0, 0x1B] => TLS_SRP_SHA_RSA_WITH_3DES_EDE_CBC_SHA (Srp, Rsa, TripleDesEdeCbc, Sha1, TLS1, Weak)
+    [0xC0, 0x1C] => TLS_SRP_SHA_DSS_WITH_3DES_EDE_CBC_SHA (Srp, Dss, TripleDesEdeCbc, Sha1, TLS1, Weak)
+    [0xC0, 0x1D] => TLS_SRP_SHA_WITH_AES_128_CBC_SHA (Srp, Password, Aes128Cbc, Sha1, TLS1, Weak)
+    [0xC0, 0x1E] => TLS_SRP_SHA_RSA_WITH_AES_128_CBC_SHA (Srp, Rsa, Aes128Cbc, Sha1, TLS1, Weak)
+    [0xC0, 0x1F] => TLS_SRP_SHA_DSS_WITH_AES_128_CBC_SHA (Srp, Dss, Aes128Cbc, Sha1, TLS1, Weak)
+    [0xC0, 0x20] => TLS_SRP_SHA_WITH_AES_256_CBC_SHA (Srp, Password, Aes256Cbc, Sha1, TLS1, Weak)
+    [0xC0, 0x21] => TLS_SRP_SHA_RSA_WITH_AES_256_CBC_SHA (Srp, Rsa, Aes256Cbc, Sha1, TLS1, Weak)
+    [0xC0, 0x22] => TLS_SRP_SHA_DSS_WITH_AES_256_CBC_SHA (Srp, Dss, Aes256Cbc, Sha1, TLS1, Weak)
+    [0xC0, 0x23] => TLS_ECDHE_ECDSA_WITH_AES_128_CBC_SHA256 (Ecdhe, Ecdsa, Aes128Cbc, Sha256, TLS1_2, Weak)
+    [0xC0, 0x24] => TLS_ECDHE_ECDSA_WITH_AES_256_CBC_SHA384 (Ecdhe, Ecdsa, Aes256Cbc, Sha384, TLS1_2, Weak)
+    [0xC0, 0x25] => TLS_ECDH_ECDSA_WITH_AES_128_CBC_SHA256 (Ecdh, Ecdsa, Aes128Cbc, Sha256, TLS1_2, Weak)
+    [0xC0, 0x26] => TLS_ECDH_ECDSA_WITH_AES_256_CBC_SHA384 (Ecdh, Ecdsa, Aes256Cbc, Sha384, TLS1_2, Weak)
+    [0xC0, 0x27] => TLS_ECDHE_RSA_WITH_AES_128_CBC_SHA256 (Ecdhe, Rsa, Aes128Cbc, Sha256, TLS1_2, Weak)
+    [0xC0, 0x28] => TLS_ECDHE_RSA_WITH_AES_256_CBC_SHA384 (Ecdhe, Rsa, Aes256Cbc, Sha384, TLS1_2, Weak)
+    [0xC0, 0x29] => TLS_ECDH_RSA_WITH_AES_128_CBC_SHA256 (Ecdh, Rsa, Aes128Cbc, Sha256, TLS1_2, Weak)
+    [0xC0, 0x2A] => TLS_ECDH_RSA_WITH_AES_256_CBC_SHA384 (Ecdh, Rsa, Aes256Cbc, Sha384, TLS1_2, Weak)
+    [0xC0, 0x2B] => TLS_ECDHE_ECDSA_WITH_AES_128_GCM_SHA256 (Ecdhe, Ecdsa, Aes128Gcm, Aead, TLS1_2, Recommended)
+    [0xC0, 0x2C] => TLS_ECDHE_ECDSA_WITH_AES_256_GCM_SHA384 (Ecdhe, Ecdsa, Aes256Gcm, Aead, TLS1_2, Recommended)
+    [0xC0, 0x2D] => TLS_ECDH_ECDSA_WITH_AES_128_GCM_SHA256 (Ecdh, Ecdsa, Aes128Gcm, Aead, TLS1_2, Weak)
+    [0xC0, 0x2E] => TLS_ECDH_ECDSA_WITH_AES_256_GCM_SHA384 (Ecdh, Ecdsa, Aes256Gcm, Aead, TLS1_2, Weak)
+    [0xC0, 0x2F] => TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256 (Ecdhe, Rsa, Aes128Gcm, Aead, TLS1_2, Recommended)
+    [0xC0, 0x30] => TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384 (Ecdhe, Rsa, Aes256Gcm, Aead, TLS1_2, Recommended)
+    [0xC0, 0x31] => TLS_ECDH_RSA_WITH_AES_128_GCM_SHA256 (Ecdh, Rsa, Aes128Gcm, Aead, TLS1_2, Weak)
+    [0xC0, 0x32] => TLS_ECDH_RSA_WITH_AES_256_GCM_SHA384 (Ecdh, Rsa, Aes256Gcm, Aead, TLS1_2, Weak)
+    [0xC0, 0x33] => TLS_ECDHE_PSK_WITH_RC4_128_SHA (Ecdhe, Psk, Rc4, Sha1, TLS1, Insecure)
+    [0xC0, 0x34] => TLS_ECDHE_PSK_WITH_3DES_EDE_CBC_SHA (Ecdhe, Psk, TripleDesEdeCbc, Sha1, TLS1, Weak)
+    [0xC0, 0x35] => TLS_ECDHE_PSK_WITH_AES_128_CBC_SHA (Ecdhe, Psk, Aes128Cbc, Sha1, TLS1, Weak)
+    [0xC0, 0x36] => TLS_ECDHE_PSK_WITH_AES_256_CBC_SHA (Ecdhe, Psk, Aes256Cbc, Sha1, TLS1, Weak)
+    [0xC0, 0x37] => TLS_ECDHE_PSK_WITH_AES_128_CBC_SHA256 (Ecdhe, Psk, Aes128Cbc, Sha256, TLS1_2, Weak)
+    [0xC0, 0x38] => TLS_ECDHE_PSK_WITH_AES_256_CBC_SHA384 (Ecdhe, Psk, Aes256Cbc, Sha384, TLS1_2, Weak)
+    [0xC0, 0x39] => TLS_ECDHE_PSK_WITH_NULL_SHA (Ecdhe, Psk, Null, Sha1, TLS1, Insecure)
+    [0xC0, 0x3A] => TLS_ECDHE_PSK_WITH_NULL_SHA256 (Ecdhe, Psk, Null, Sha256, TLS1_2, Insecure)
+    [0xC0, 0x3B] => TLS_ECDHE_PSK_WITH_NULL_SHA384 (Ecdhe, Psk, Null, Sha384, TLS1_2, Insecure)
+    [0xC0, 0x3C] => TLS_RSA_WITH_ARIA_128_CBC_SHA256 (Rsa, Rsa, Aria128Cbc, Sha256, TLS1_2, Weak)
+    [0xC0, 0x3D] => TLS_RSA_WITH_ARIA_256_CBC_SHA384 (Rsa, Rsa, Aria256Cbc, Sha384, TLS1_2, Weak)
+    [0xC0, 0x3E] => TLS_DH_DSS_WITH_ARIA_128_CBC_SHA256 (Dh, Dss, Aria128Cbc, Sha256, TLS1_2, Weak)
+    [0xC0, 0x3F] => TLS_DH_DSS_WITH_ARIA_256_CBC_SHA384 (Dh, Dss, Aria256Cbc, Sha384, TLS1_2, Weak)
+    [0xC0, 0x40] => TLS_DH_RSA_WITH_ARIA_128_CBC_SHA256 (Dh, Rsa, Aria128Cbc, Sha256, TLS1_2, Weak)
+    [0xC0, 0x41] => TLS_DH_RSA_WITH_ARIA_256_CBC_SHA384 (Dh, Rsa, Aria256Cbc, Sha384, TLS1_2, Weak)
+    [0xC0, 0x42] => TLS_DHE_DSS_WITH_ARIA_128_CBC_SHA256 (Dhe, Dss, Aria128Cbc, Sha256, TLS1_2, Weak)
+    [0xC0, 0x43] => TLS_DHE_DSS_WITH_ARIA_256_CBC_SHA384 (Dhe, Dss, Aria256Cbc, Sha384, TLS1_2, Weak)
+    [0xC0, 0x44] => TLS_DHE_RSA_WITH_ARIA_128_CBC_SHA256 (Dhe, Rsa, Aria128Cbc, Sha256, TLS1_2, Weak)
+    [0xC0, 0x45] => TLS_DHE_RSA_WITH_ARIA_256_CBC_SHA384 (Dhe, Rsa, Aria256Cbc, Sha384, TLS1_2, Weak)
+    [0xC0, 0x46] => TLS_DH_anon_WITH_ARIA_128_CBC_SHA256 (Dh, Anonymous, Aria128Cbc, Sha256, TLS1_2, Insecure)
+    [0xC0, 0x47] => TLS_DH_anon_WITH_ARIA_256_CBC_SHA384 (Dh, Anonymous, Aria256Cbc, Sha384, TLS1_2, Insecure)
+    [0xC0, 0x48] => TLS_ECDHE_ECDSA_WITH_ARIA_128_CBC_SHA256 (Ecdhe, Ecdsa, Aria128Cbc, Sha256, TLS1_2, Weak)
+    [0xC0, 0x49] => TLS_ECDHE_ECDSA_WITH_ARIA_256_CBC_SHA384 (Ecdhe, Ecdsa, Aria256Cbc, Sha384, TLS1_2, Weak)
+    [0xC0, 0x4A] => TLS_ECDH_ECDSA_WITH_ARIA_128_CBC_SHA256 (Ecdh, Ecdsa, Aria128Cbc, Sha256, TLS1_2, Weak)
+    [0xC0, 0x4B] => TLS_ECDH_ECDSA_WITH_ARIA_256_CBC_SHA384 (Ecdh, Ecdsa, Aria256Cbc, Sha384, TLS1_2, Weak)
+    [0xC0, 0x4C] => TLS_ECDHE_RSA_WITH_ARIA_128_CBC_SHA256 (Ecdhe, Rsa, Aria128Cbc, Sha256, TLS1_2, Weak)
+    [0xC0, 0x4D] => TLS_ECDHE_RSA_WITH_ARIA_256_CBC_SHA384 (Ecdhe, Rsa, Aria256Cbc, Sha384, TLS1_2, Weak)
+    [0xC0, 0x4E] => TLS_ECDH_RSA_WITH_ARIA_128_CBC_SHA256 (Ecdh, Rsa, Aria128Cbc, Sha256, TLS1_2, Weak)
+    [0xC0, 0x4F] => TLS_ECDH_RSA_WITH_ARIA_256_CBC_SHA384 (Ecdh, Rsa, Aria256Cbc, Sha384, TLS1_2, Weak)
+    [0xC0, 0x50] => TLS_RSA_WITH_ARIA_128_GCM_SHA256 (Rsa, Rsa, Aria128Gcm, Aead, TLS1_2, Weak)
+    [0xC0, 0x51] => TLS_RSA_WITH_ARIA_256_GCM_SHA384 (Rsa, Rsa, Aria256Gcm, Aead, TLS1_2, Weak)
+    [0xC0, 0x52] => TLS_DHE_RSA_WITH_ARIA_128_GCM_SHA256 (Dhe, Rsa, Aria128Gcm, Aead, TLS1_2, Secure)
+    [0xC0, 0x53] => TLS_DHE_RSA_WITH_ARIA_256_GCM_SHA384 (Dhe, Rsa, Aria256Gcm, Aead, TLS1_2, Secure)
+    [0xC0, 0x54] => TLS_DH_RSA_WITH_ARIA_128_GCM_SHA256 (Dh, Rsa, Aria128Gcm, Aead, TLS1_2, Weak)
+    [0xC0, 0x55] => TLS_DH_RSA_WITH_ARIA_256_GCM_SHA384 (Dh, Rsa, Aria256Gcm, Aead, TLS1_2, Weak)
+    [0xC0, 0x56] => TLS_DHE_DSS_WITH_ARIA_128_GCM_SHA256 (Dhe, Dss, Aria128Gcm, Aead, TLS1_2, Secure)
+    [0xC0, 0x57] => TLS_DHE_DSS_WITH_ARIA_256_GCM_SHA384 (Dhe, Dss, Aria256Gcm, Aead, TLS1_2, Secure)
+    [0xC0, 0x58] => TLS_DH_DSS_WITH_ARIA_128_GCM_SHA256 (Dh, Dss, Aria128Gcm, Aead, TLS1_2, Weak)
+    [0xC0, 0x59] => TLS_DH_DSS_WITH_ARIA_256_GCM_SHA384 (Dh, Dss, Aria256Gcm, Aead, TLS1_2, Weak)
+    [0xC0, 0x5A] => TLS_DH_anon_WITH_ARIA_128_GCM_SHA256 (Dh, Anonymous, Aria128Gcm, Aead, TLS1_2, Insecure)
+    [0xC0, 0x5B] => TLS_DH_anon_WITH_ARIA_256_GCM_SHA384 (Dh, Anonymous, Aria256Gcm, Aead, TLS1_2, Insecure)
+    [0xC0, 0x5C] => TLS_ECDHE_ECDSA_WITH_ARIA_128_GCM_SHA256 (Ecdhe, Ecdsa, Aria128Gcm, Aead, TLS1_2, Secure)
+    [0xC0, 0x5D] => TLS_ECDHE_ECDSA_WITH_ARIA_256_GCM_SHA384 (Ecdhe, Ecdsa, Aria256Gcm, Aead, TLS1_2, Secure)
+    [0xC0, 0x5E] => TLS_ECDH_ECDSA_WITH_ARIA_128_GCM_SHA256 (Ecdh, Ecdsa, Aria128Gcm, Aead, TLS1_2, Weak)
+    [0xC0, 0x5F] => TLS_ECDH_ECDSA_WITH_ARIA_256_GCM_SHA384 (Ecdh, Ecdsa, Aria256Gcm, Aead, TLS1_2, Weak)
+    [0xC0, 0x60] => TLS_ECDHE_RSA_WITH_ARIA_128_GCM_SHA256 (Ecdhe, Rsa, Aria128Gcm, Aead, TLS1_2, Secure)
+    [0xC0, 0x61] => TLS_ECDHE_RSA_WITH_ARIA_256_GCM_SHA384 (Ecdhe, Rsa, Aria256Gcm, Aead, TLS1_2, Secure)
+    [0xC0, 0x62] => TLS_ECDH_RSA_WITH_ARIA_128_GCM_SHA256 (Ecdh, Rsa, Aria128Gcm, Aead, TLS1_2, Weak)
+    [0xC0, 0x63] => TLS_ECDH_RSA_WITH_ARIA_256_GCM_SHA384 (Ecdh, Rsa, Aria256Gcm, Aead, TLS1_2, Weak)
+    [0xC0, 0x64] => TLS_PSK_WITH_ARIA_128_CBC_SHA256 (Psk, Psk, Aria128Cbc, Sha256, TLS1_2, Weak)
+    [0xC0, 0x65] => TLS_PSK_WITH_ARIA_256_CBC_SHA384 (Psk, Psk, Aria256Cbc, Sha384, TLS1_2, Weak)
+    [0xC0, 0x66] => TLS_DHE_PSK_WITH_ARIA_128_CBC_SHA256 (Dhe, Psk, Aria128Cbc, Sha256, TLS1_2, Weak)
+    [0xC0, 0x67] => TLS_DHE_PSK_WITH_ARIA_256_CBC_SHA384 (Dhe, Psk, Aria256Cbc, Sha384, TLS1_2, Weak)
+    [0xC0, 0x68] => TLS_RSA_PSK_WITH_ARIA_128_CBC_SHA256 (Rsa, Psk, Aria128Cbc, Sha256, TLS1_2, Weak)
+    [0xC0, 0x69] => TLS_RSA_PSK_WITH_ARIA_256_CBC_SHA384 (Rsa, Psk, Aria256Cbc, Sha384, TLS1_2, Weak)
+    [0xC0, 0x6A] => TLS_PSK_WITH_ARIA_128_GCM_SHA256 (Psk, Psk, Aria128Gcm, Aead, TLS1_2, Weak)
+    [0xC0, 0x6B] => TLS_PSK_WITH_ARIA_256_GCM_SHA384 (Psk, Psk, Aria256Gcm, Aead, TLS1_2, Weak)
+    [0xC0, 0x6C] => TLS_DHE_PSK_WITH_ARIA_128_GCM_SHA256 (Dhe, Psk, Aria128Gcm, Aead, TLS1_2, Secure)
+    [0xC0, 0x6D] => TLS_DHE_PSK_WITH_ARIA_256_GCM_SHA384 (Dhe, Psk, Aria256Gcm, Aead, TLS1_2, Secure)
+    [0xC0, 0x6E] => TLS_RSA_PSK_WITH_ARIA_128_GCM_SHA256 (Rsa, Psk, Aria128Gcm, Aead, TLS1_2, Weak)
+    [0xC0, 0x6F] => TLS_RSA_PSK_WITH_ARIA_256_GCM_SHA384 (Rsa, Psk, Aria256Gcm, Aead, TLS1_2, Weak)
+    [0xC0, 0x70] => TLS_ECDHE_PSK_WITH_ARIA_128_CBC_SHA256 (Ecdhe, Psk, Aria128Cbc, Sha256, TLS1_2, Weak)
+    [0xC0, 0x71] => TLS_ECDHE_PSK_WITH_ARIA_256_CBC_SHA384 (Ecdhe, Psk, Aria256Cbc, Sha384, TLS1_2, Weak)
+    [0xC0, 0x72] => TLS_ECDHE_ECDSA_WITH_CAMELLIA_128_CBC_SHA256 (Ecdhe, Ecdsa, Camellia128Cbc, Sha256, TLS1_2, Weak)
+    [0xC0, 0x73] => TLS_ECDHE_ECDSA_WITH_CAMELLIA_256_CBC_SHA384 (Ecdhe, Ecdsa, Camellia256Cbc, Sha384, TLS1_2, Weak)
+    [0xC0, 0x74] => TLS_ECDH_ECDSA_WITH_CAMELLIA_128_CBC_SHA256 (Ecdh, Ecdsa, Camellia128Cbc, Sha256, TLS1_2, Weak)
+    [0xC0, 0x75] => TLS_ECDH_ECDSA_WITH_CAMELLIA_256_CBC_SHA384 (Ecdh, Ecdsa, Camellia256Cbc, Sha384, TLS1_2, Weak)
+    [0xC0, 0x76] => TLS_ECDHE_RSA_WITH_CAMELLIA_128_CBC_SHA256 (Ecdhe, Rsa, Camellia128Cbc, Sha256, TLS1_2, Weak)
+    [0xC0, 0x77] => TLS_ECDHE_RSA_WITH_CAMELLIA_256_CBC_SHA384 (Ecdhe, Rsa, Camellia256Cbc, Sha384, TLS1_2, Weak)
+    [0xC0, 0x78] => TLS_ECDH_RSA_WITH_CAMELLIA_128_CBC_SHA256 (Ecdh, Rsa, Camellia128Cbc, Sha256, TLS1_2, Weak)
+    [0xC0, 0x79] => TLS_ECDH_RSA_WITH_CAMELLIA_256_CBC_SHA384 (Ecdh, Rsa, Camellia256Cbc, Sha384, TLS1_2, Weak)
+    [0xC0, 0x7A] => TLS_RSA_WITH_CAMELLIA_128_GCM_SHA256 (Rsa, Rsa, Camellia128Gcm, Aead, TLS1_2, Weak)
+    [0xC0, 0x7B] => TLS_RSA_WITH_CAMELLIA_256_GCM_SHA384 (Rsa, Rsa, Camellia256Gcm, Aead, TLS1_2, Weak)
+    [0xC0, 0x7C] => TLS_DHE_RSA_WITH_CAMELLIA_128_GCM_SHA256 (Dhe, Rsa, Camellia128Gcm, Aead, TLS1_2, Secure)
+    [0xC0, 0x7D] => TLS_DHE_RSA_WITH_CAMELLIA_256_GCM_SHA384 (Dhe, Rsa, Camellia256Gcm, Aead, TLS1_2, Secure)
+    [0xC0, 0x7E] => TLS_DH_RSA_WITH_CAMELLIA_128_GCM_SHA256 (Dh, Rsa, Camellia128Gcm, Aead, TLS1_2, Weak)
+    [0xC0, 0x7F] => TLS_DH_RSA_WITH_CAMELLIA_256_GCM_SHA384 (Dh, Rsa, Camellia256Gcm, Aead, TLS1_2, Weak)
+    [0xC0, 0x80] => TLS_DHE_DSS_WITH_CAMELLIA_128_GCM_SHA256 (Dhe, Dss, Camellia128Gcm, Aead, TLS1_2, Secure)
+    [0xC0, 0x81] => TLS_DHE_DSS_WITH_CAMELLIA_256_GCM_SHA384 (Dhe, Dss, Camellia256Gcm, Aead, TLS1_2, Secure)
+    [0xC0, 0x82] => TLS_DH_DSS_WITH_CAMELLIA_128_GCM_SHA256 (Dh, Dss, Camellia128Gcm, Aead, TLS1_2, Weak)
+    [0xC0, 0x83] => TLS_DH_DSS_WITH_CAMELLIA_256_GCM_SHA384 (Dh, Dss, Camellia256Gcm, Aead, TLS1_2, Weak)
+    [0xC0, 0x84] => TLS_DH_anon_WITH_CAMELLIA_128_GCM_SHA256 (Dh, Anonymous, Camellia128Gcm, Aead, TLS1_2, Insecure)
+    [0xC0, 0x85] => TLS_DH_anon_WITH_CAMELLIA_256_GCM_SHA384 (Dh, Anonymous, Camellia256Gcm, Aead, TLS1_2, Insecure)
+    [0xC0, 0x86] => TLS_ECDHE_ECDSA_WITH_CAMELLIA_128_GCM_SHA256 (Ecdhe, Ecdsa, Camellia128Gcm, Aead, TLS1_2, Secure)
+    [0xC0, 0x87] => TLS_ECDHE_ECDSA_WITH_CAMELLIA_256_GCM_SHA384 (Ecdhe, Ecdsa, Camellia256Gcm, Aead, TLS1_2, Secure)
+    [0xC0, 0x88] => TLS_ECDH_ECDSA_WITH_CAMELLIA_128_GCM_SHA256 (Ecdh, Ecdsa, Camellia128Gcm, Aead, TLS1_2, Weak)
+    [0xC0, 0x89] => TLS_ECDH_ECDSA_WITH_CAMELLIA_256_GCM_SHA384 (Ecdh, Ecdsa, Camellia256Gcm, Aead, TLS1_2, Weak)
+    [0xC0, 0x8A] => TLS_ECDHE_RSA_WITH_CAMELLIA_128_GCM_SHA256 (Ecdhe, Rsa, Camellia128Gcm, Aead, TLS1_2, Secure)
+    [0xC0, 0x8B] => TLS_ECDHE_RSA_WITH_CAMELLIA_256_GCM_SHA384 (Ecdhe, Rsa, Camellia256Gcm, Aead, TLS1_2, Secure)
+    [0xC0, 0x8C] => TLS_ECDH_RSA_WITH_CAMELLIA_128_GCM_SHA256 (Ecdh, Rsa, Camellia128Gcm, Aead, TLS1_2, Weak)
+    [0xC0, 0x8D] => TLS_ECDH_RSA_WITH_CAMELLIA_256_GCM_SHA384 (Ecdh, Rsa, Camellia256Gcm, Aead, TLS1_2, Weak)
+    [0xC0, 0x8E] => TLS_PSK_WITH_CAMELLIA_128_GCM_SHA256 (Psk, Psk, Camellia128Gcm, Aead, TLS1_2, Weak)
+    [0xC0, 0x8F] => TLS_PSK_WITH_CAMELLIA_256_GCM_SHA384 (Psk, Psk, Camellia256Gcm, Aead, TLS1_2, Weak)
+    [0xC0, 0x90] => TLS_DHE_PSK_WITH_CAMELLIA_128_GCM_SHA256 (Dhe, Psk, Camellia128Gcm, Aead, TLS1_2, Secure)
+    [0xC0, 0x91] => TLS_DHE_PSK_WITH_CAMELLIA_256_GCM_SHA384 (Dhe, Psk, Camellia256Gcm, Aead, TLS1_2, Secure)
+    [0xC0, 0x92] => TLS_RSA_PSK_WITH_CAMELLIA_128_GCM_SHA256 (Rsa, Psk, Camellia128Gcm, Aead, TLS1_2, Weak)
+    [0xC0, 0x93] => TLS_RSA_PSK_WITH_CAMELLIA_256_GCM_SHA384 (Rsa, Psk, Camellia256Gcm, Aead, TLS1_2, Weak)
+    [0xC0, 0x94] => TLS_PSK_WITH_CAMELLIA_128_CBC_SHA256 (Psk, Psk, Camellia128Cbc, Sha256, TLS1_2, Weak)
+    [0xC0, 0x95] => TLS_PSK_WITH_CAMELLIA_256_CBC_SHA384 (Psk, Psk, Camellia256Cbc, Sha384, TLS1_2, Weak)
+    [0xC0, 0x96] => TLS_DHE_PSK_WITH_CAMELLIA_128_CBC_SHA256 (Dhe, Psk, Camellia128Cbc, Sha256, TLS1_2, Weak)
+    [0xC0, 0x97] => TLS_DHE_PSK_WITH_CAMELLIA_256_CBC_SHA384 (Dhe, Psk, Camellia256Cbc, Sha384, TLS1_2, Weak)
+    [0xC0, 0x98] => TLS_RSA_PSK_WITH_CAMELLIA_128_CBC_SHA256 (Rsa, Psk, Camellia128Cbc, Sha256, TLS1_2, Weak)
+    [0xC0, 0x99] => TLS_RSA_PSK_WITH_CAMELLIA_256_CBC_SHA384 (Rsa, Psk, Camellia256Cbc, Sha384, TLS1_2, Weak)
+    [0xC0, 0x9A] => TLS_ECDHE_PSK_WITH_CAMELLIA_128_CBC_SHA256 (Ecdhe, Psk, Camellia128Cbc, Sha256, TLS1_2, Weak)
+    [0xC0, 0x9B] => TLS_ECDHE_PSK_WITH_CAMELLIA_256_CBC_SHA384 (Ecdhe, Psk, Camellia256Cbc, Sha384, TLS1_2, Weak)
+    [0xC0, 0x9C] => TLS_RSA_WITH_AES_128_CCM (Rsa, Rsa, Aes128Ccm, Aead, TLS1_2, Weak)
+    [0xC0, 0x9D] => TLS_RSA_WITH_AES_256_CCM (Rsa, Rsa, Aes256Ccm, Aead, TLS1_2, Weak)
+    [0xC0, 0x9E] => TLS_DHE_RSA_WITH_AES_128_CCM (Dhe, Rsa, Aes128Ccm, Aead, TLS1_2, Recommended)
+    [0xC0, 0x9F] => TLS_DHE_RSA_WITH_AES_256_CCM (Dhe, Rsa, Aes256Ccm, Aead, TLS1_2, Recommended)
+    [0xC0, 0xA0] => TLS_RSA_WITH_AES_128_CCM_8 (Rsa, Rsa, Aes128Ccm8, Aead, TLS1_2, Weak)
+    [0xC0, 0xA1] => TLS_RSA_WITH_AES_256_CCM_8 (Rsa, Rsa, Aes256Ccm8, Aead, TLS1_2, Weak)
+    [0xC0, 0xA2] => TLS_DHE_RSA_WITH_AES_128_CCM_8 (Dhe, Rsa, Aes128Ccm8, Aead, TLS1_2, Secure)
+    [0xC0, 0xA3] => TLS_DHE_RSA_WITH_AES_256_CCM_8 (Dhe, Rsa, Aes256Ccm8, Aead, TLS1_2, Secure)
+    [0xC0, 0xA4] => TLS_PSK_WITH_AES_128_CCM (Psk, Psk, Aes128Ccm, Aead, TLS1_2, Weak)
+    [0xC0, 0xA5] => TLS_PSK_WITH_AES_256_CCM (Psk, Psk, Aes256Ccm, Aead, TLS1_2, Weak)
+    [0xC0, 0xA6] => TLS_DHE_PSK_WITH_AES_128_CCM (Dhe, Psk, Aes128Ccm, Aead, TLS1_2, Recommended)
+    [0xC0, 0xA7] => TLS_DHE_PSK_WITH_AES_256_CCM (Dhe, Psk, Aes256Ccm, Aead, TLS1_2, Recommended)
+    [0xC0, 0xA8] => TLS_PSK_WITH_AES_128_CCM_8 (Psk, Psk, Aes128Ccm8, Aead, TLS1_2, Weak)
+    [0xC0, 0xA9] => TLS_PSK_WITH_AES_256_CCM_8 (Psk, Psk, Aes256Ccm8, Aead, TLS1_2, Weak)
+    [0xC0, 0xAA] => TLS_PSK_DHE_WITH_AES_128_CCM_8 (Dhe, Psk, Aes128Ccm8, Aead, TLS1_2, Secure)
+    [0xC0, 0xAB] => TLS_PSK_DHE_WITH_AES_256_CCM_8 (Dhe, Psk, Aes256Ccm8, Aead, TLS1_2, Secure)
+    [0xC0, 0xAC] => TLS_ECDHE_ECDSA_WITH_AES_128_CCM (Ecdhe, Ecdsa, Aes128Ccm, Aead, TLS1_2, Recommended)
+    [0xC0, 0xAD] => TLS_ECDHE_ECDSA_WITH_AES_256_CCM (Ecdhe, Ecdsa, Aes256Ccm, Aead, TLS1_2, Recommended)
+    [0xC0, 0xAE] => TLS_ECDHE_ECDSA_WITH_AES_128_CCM_8 (Ecdhe, Ecdsa, Aes128Ccm8, Aead, TLS1_2, Secure)
+    [0xC0, 0xAF] => TLS_ECDHE_ECDSA_WITH_AES_256_CCM_8 (Ecdhe, Ecdsa, Aes256Ccm8, Aead, TLS1_2, Secure)
+    [0xC0, 0xB0] => TLS_ECCPWD_WITH_AES_128_GCM_SHA256 (Eccpwd, Password, Aes128Gcm, Aead, TLS1_2, Secure)
+    [0xC0, 0xB1] => TLS_ECCPWD_WITH_AES_256_GCM_SHA384 (Eccpwd, Password, Aes256Gcm, Aead, TLS1_2, Secure)
+    [0xC0, 0xB2] => TLS_ECCPWD_WITH_AES_128_CCM_SHA256 (Eccpwd, Password, Aes128Ccm, Aead, TLS1_2, Secure)
+    [0xC0, 0xB3] => TLS_ECCPWD_WITH_AES_256_CCM_SHA384 (Eccpwd, Password, Aes256Ccm, Aead, TLS1_2, Secure)
+    [0xC0, 0xB4] => TLS_SHA256_SHA256 (Any, Any, Null, Sha256, TLS1_3, Insecure)
+    [0xC0, 0xB5] => TLS_SHA384_SHA384 (Any, Any, Null, Sha384, TLS1_3, Insecure)
+    [0xC1, 0x00] => TLS_GOSTR341112_256_WITH_KUZNYECHIK_CTR_OMAC (Gost, Gost, KuznyechikCtr, Omac, TLS1_2, Weak)
+    [0xC1, 0x01] => TLS_GOSTR341112_256_WITH_MAGMA_CTR_OMAC (Gost, Gost, MagmaCtr, Omac, TLS1_2, Weak)
+    [0xC1, 0x02] => TLS_GOSTR341112_256_WITH_28147_CNT_IMIT (Gost, Gost, Gost28147Cnt, Imit, TLS1_2, Weak)
+    [0xC1, 0x03] => TLS_GOSTR341112_256_WITH_KUZNYECHIK_MGM_L (Gost, Gost, KuznyechikMgm, Aead, TLS1_3, Secure)
+    [0xC1, 0x04] => TLS_GOSTR341112_256_WITH_MAGMA_MGM_L (Gost, Gost, MagmaMgm, Aead, TLS1_3, Weak)
+    [0xC1, 0x05] => TLS_GOSTR341112_256_WITH_KUZNYECHIK_MGM_S (Gost, Gost, KuznyechikMgm, Aead, TLS1_3, Secure)
+    [0xC1, 0x06] => TLS_GOSTR341112_256_WITH_MAGMA_MGM_S (Gost, Gost, MagmaMgm, Aead, TLS1_3, Weak)
+    [0xCC, 0xA8] => TLS_ECDHE_RSA_WITH_CHACHA20_POLY1305_SHA256 (Ecdhe, Rsa, ChaCha20Poly1305, Aead, TLS1_2, Recommended)
+    [0xCC, 0xA9] => TLS_ECDHE_ECDSA_WITH_CHACHA20_POLY1305_SHA256 (Ecdhe, Ecdsa, ChaCha20Poly1305, Aead, TLS1_2, Recommended)
+    [0xCC, 0xAA] => TLS_DHE_RSA_WITH_CHACHA20_POLY1305_SHA256 (Dhe, Rsa, ChaCha20Poly1305, Aead, TLS1_2, Recommended)
+    [0xCC, 0xAB] => TLS_PSK_WITH_CHACHA20_POLY1305_SHA256 (Psk, Psk, ChaCha20Poly1305, Aead, TLS1_2, Weak)
+    [0xCC, 0xAC] => TLS_ECDHE_PSK_WITH_CHACHA20_POLY1305_SHA256 (Ecdhe, Psk, ChaCha20Poly1305, Aead, TLS1_2, Recommended)
+    [0xCC, 0xAD] => TLS_DHE_PSK_WITH_CHACHA20_POLY1305_SHA256 (Dhe, Psk, ChaCha20Poly1305, Aead, TLS1_2, Recommended)
+    [0xCC, 0xAE] => TLS_RSA_PSK_WITH_CHACHA20_POLY1305_SHA256 (Rsa, Psk, ChaCha20Poly1305, Aead, TLS1_2, Weak)
+    [0xD0, 0x01] => TLS_ECDHE_PSK_WITH_AES_128_GCM_SHA256 (Ecdhe, Psk, Aes128Gcm, Aead, TLS1_2, Recommended)
+    [0xD0, 0x02] => TLS_ECDHE_PSK_WITH_AES_256_GCM_SHA384 (Ecdhe, Psk, Aes256Gcm, Aead, TLS1_2, Recommended)
+    [0xD0, 0x03] => TLS_ECDHE_PSK_WITH_AES_128_CCM_8_SHA256 (Ecdhe, Psk, Aes128Ccm8, Aead, TLS1_2, Secure)
+    [0xD0, 0x05] => TLS_ECDHE_PSK_WITH_AES_128_CCM_SHA256 (Ecdhe, Psk, Aes128Ccm, Aead, TLS1_2, Recommended)
+    ;
     [0x00, 0xFF] => TLS_EMPTY_RENEGOTIATION_INFO_SCSV
     [0x56, 0x00] => TLS_FALLBACK_SCSV
-    [0xC0, 0x01] => TLS_ECDH_ECDSA_WITH_NULL_SHA
-    [0xC0, 0x03] => TLS_ECDH_ECDSA_WITH_3DES_EDE_CBC_SHA
-    [0xC0, 0x04] => TLS_ECDH_ECDSA_WITH_AES_128_CBC_SHA
-    [0xC0, 0x05] => TLS_ECDH_ECDSA_WITH_AES_256_CBC_SHA
-    [0xC0, 0x06] => TLS_ECDHE_ECDSA_WITH_NULL_SHA
-    [0xC0, 0x08] => TLS_ECDHE_ECDSA_WITH_3DES_EDE_CBC_SHA
-    [0xC0, 0x09] => TLS_ECDHE_ECDSA_WITH_AES_128_CBC_SHA
-    [0xC0, 0x0A] => TLS_ECDHE_ECDSA_WITH_AES_256_CBC_SHA
-    [0xC0, 0x0B] => TLS_ECDH_RSA_WITH_NULL_SHA
-    [0xC0, 0x0D] => TLS_ECDH_RSA_WITH_3DES_EDE_CBC_SHA
-    [0xC0, 0x0E] => TLS_ECDH_RSA_WITH_AES_128_CBC_SHA
-    [0xC0, 0x0F] => TLS_ECDH_RSA_WITH_AES_256_CBC_SHA
-    [0xC0, 0x10] => TLS_ECDHE_RSA_WITH_NULL_SHA
-    [0xC0, 0x12] => TLS_ECDHE_RSA_WITH_3DES_EDE_CBC_SHA
-    [0xC0, 0x13] => TLS_ECDHE_RSA_WITH_AES_128_CBC_SHA
-    [0xC0, 0x14] => TLS_ECDHE_RSA_WITH_AES_256_CBC_SHA
-    [0xC0, 0x15] => TLS_ECDH_anon_WITH_NULL_SHA
-    [0xC0, 0x17] => TLS_ECDH_anon_WITH_3DES_EDE_CBC_SHA
-    [0xC0, 0x18] => TLS_ECDH_anon_WITH_AES_128_CBC_SHA
-    [0xC0, 0x19] => TLS_ECDH_anon_WITH_AES_256_CBC_SHA
-    [0xC0, 0x1A] => TLS_SRP_SHA_WITH_3DES_EDE_CBC_SHA
-    [0xC0, 0x1B] => TLS_SRP_SHA_RSA_WITH_3DES_EDE_CBC_SHA
-    [0xC0, 0x1C] => TLS_SRP_SHA_DSS_WITH_3DES_EDE_CBC_SHA
-    [0xC0, 0x1D] => TLS_SRP_SHA_WITH_AES_128_CBC_SHA
-    [0xC0, 0x1E] => TLS_SRP_SHA_RSA_WITH_AES_128_CBC_SHA
-    [0xC0, 0x1F] => TLS_SRP_SHA_DSS_WITH_AES_128_CBC_SHA
-    [0xC0, 0x20] => TLS_SRP_SHA_WITH_AES_256_CBC_SHA
-    [0xC0, 0x21] => TLS_SRP_SHA_RSA_WITH_AES_256_CBC_SHA
-    [0xC0, 0x22] => TLS_SRP_SHA_DSS_WITH_AES_256_CBC_SHA
-    [0xC0, 0x23] => TLS_ECDHE_ECDSA_WITH_AES_128_CBC_SHA256
-    [0xC0, 0x24] => TLS_ECDHE_ECDSA_WITH_AES_256_CBC_SHA384
-    [0xC0, 0x25] => TLS_ECDH_ECDSA_WITH_AES_128_CBC_SHA256
-    [0xC0, 0x26] => TLS_ECDH_ECDSA_WITH_AES_256_CBC_SHA384
-    [0xC0, 0x27] => TLS_ECDHE_RSA_WITH_AES_128_CBC_SHA256
-    [0xC0, 0x28] => TLS_ECDHE_RSA_WITH_AES_256_CBC_SHA384
-    [0xC0, 0x29] => TLS_ECDH_RSA_WITH_AES_128_CBC_SHA256
-    [0xC0, 0x2A] => TLS_ECDH_RSA_WITH_AES_256_CBC_SHA384
-    [0xC0, 0x2B] => TLS_ECDHE_ECDSA_WITH_AES_128_GCM_SHA256
-    [0xC0, 0x2C] => TLS_ECDHE_ECDSA_WITH_AES_256_GCM_SHA384
-    [0xC0, 0x2D] => TLS_ECDH_ECDSA_WITH_AES_128_GCM_SHA256
-    [0xC0, 0x2E] => TLS_ECDH_ECDSA_WITH_AES_256_GCM_SHA384
-    [0xC0, 0x2F] => TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256
-    [0xC0, 0x30] => TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384
-    [0xC0, 0x31] => TLS_ECDH_RSA_WITH_AES_128_GCM_SHA256
-    [0xC0, 0x32] => TLS_ECDH_RSA_WITH_AES_256_GCM_SHA384
-    [0xC0, 0x34] => TLS_ECDHE_PSK_WITH_3DES_EDE_CBC_SHA
-    [0xC0, 0x35] => TLS_ECDHE_PSK_WITH_AES_128_CBC_SHA
-    [0xC0, 0x36] => TLS_ECDHE_PSK_WITH_AES_256_CBC_SHA
-    [0xC0, 0x37] => TLS_ECDHE_PSK_WITH_AES_128_CBC_SHA256
-    [0xC0, 0x38] => TLS_ECDHE_PSK_WITH_AES_256_CBC_SHA384
-    [0xC0, 0x39] => TLS_ECDHE_PSK_WITH_NULL_SHA
-    [0xC0, 0x3A] => TLS_ECDHE_PSK_WITH_NULL_SHA256
-    [0xC0, 0x3B] => TLS_ECDHE_PSK_WITH_NULL_SHA384
-    [0xC0, 0x3C] => TLS_RSA_WITH_ARIA_128_CBC_SHA256
-    [0xC0, 0x3D] => TLS_RSA_WITH_ARIA_256_CBC_SHA384
-    [0xC0, 0x3E] => TLS_DH_DSS_WITH_ARIA_128_CBC_SHA256
-    [0xC0, 0x3F] => TLS_DH_DSS_WITH_ARIA_256_CBC_SHA384
-    [0xC0, 0x40] => TLS_DH_RSA_WITH_ARIA_128_CBC_SHA256
-    [0xC0, 0x41] => TLS_DH_RSA_WITH_ARIA_256_CBC_SHA384
-    [0xC0, 0x42] => TLS_DHE_DSS_WITH_ARIA_128_CBC_SHA256
-    [0xC0, 0x43] => TLS_DHE_DSS_WITH_ARIA_256_CBC_SHA384
-    [0xC0, 0x44] => TLS_DHE_RSA_WITH_ARIA_128_CBC_SHA256
-    [0xC0, 0x45] => TLS_DHE_RSA_WITH_ARIA_256_CBC_SHA384
-    [0xC0, 0x46] => TLS_DH_anon_WITH_ARIA_128_CBC_SHA256
-    [0xC0, 0x47] => TLS_DH_anon_WITH_ARIA_256_CBC_SHA384
-    [0xC0, 0x48] => TLS_ECDHE_ECDSA_WITH_ARIA_128_CBC_SHA256
-    [0xC0, 0x49] => TLS_ECDHE_ECDSA_WITH_ARIA_256_CBC_SHA384
-    [0xC0, 0x4A] => TLS_ECDH_ECDSA_WITH_ARIA_128_CBC_SHA256
-    [0xC0, 0x4B] => TLS_ECDH_ECDSA_WITH_ARIA_256_CBC_SHA384
-    [0xC0, 0x4C] => TLS_ECDHE_RSA_WITH_ARIA_128_CBC_SHA256
-    [0xC0, 0x4D] => TLS_ECDHE_RSA_WITH_ARIA_256_CBC_SHA384
-    [0xC0, 0x4E] => TLS_ECDH_RSA_WITH_ARIA_128_CBC_SHA256
-    [0xC0, 0x4F] => TLS_ECDH_RSA_WITH_ARIA_256_CBC_SHA384
-    [0xC0, 0x50] => TLS_RSA_WITH_ARIA_128_GCM_SHA256
-    [0xC0, 0x51] => TLS_RSA_WITH_ARIA_256_GCM_SHA384
-    [0xC0, 0x52] => TLS_DHE_RSA_WITH_ARIA_128_GCM_SHA256
-    [0xC0, 0x53] => TLS_DHE_RSA_WITH_ARIA_256_GCM_SHA384
-    [0xC0, 0x54] => TLS_DH_RSA_WITH_ARIA_128_GCM_SHA256
-    [0xC0, 0x55] => TLS_DH_RSA_WITH_ARIA_256_GCM_SHA384
-    [0xC0, 0x56] => TLS_DHE_DSS_WITH_ARIA_128_GCM_SHA256
-    [0xC0, 0x57] => TLS_DHE_DSS_WITH_ARIA_256_GCM_SHA384
-    [0xC0, 0x58] => TLS_DH_DSS_WITH_ARIA_128_GCM_SHA256
-    [0xC0, 0x59] => TLS_DH_DSS_WITH_ARIA_256_GCM_SHA384
-    [0xC0, 0x5A] => TLS_DH_anon_WITH_ARIA_128_GCM_SHA256
-    [0xC0, 0x5B] => TLS_DH_anon_WITH_ARIA_256_GCM_SHA384
-    [0xC0, 0x5C] => TLS_ECDHE_ECDSA_WITH_ARIA_128_GCM_SHA256
-    [0xC0, 0x5D] => TLS_ECDHE_ECDSA_WITH_ARIA_256_GCM_SHA384
-    [0xC0, 0x5E] => TLS_ECDH_ECDSA_WITH_ARIA_128_GCM_SHA256
-    [0xC0, 0x5F] => TLS_ECDH_ECDSA_WITH_ARIA_256_GCM_SHA384
-    [0xC0, 0x60] => TLS_ECDHE_RSA_WITH_ARIA_128_GCM_SHA256
-    [0xC0, 0x61] => TLS_ECDHE_RSA_WITH_ARIA_256_GCM_SHA384
-    [0xC0, 0x62] => TLS_ECDH_RSA_WITH_ARIA_128_GCM_SHA256
-    [0xC0, 0x63] => TLS_ECDH_RSA_WITH_ARIA_256_GCM_SHA384
-    [0xC0, 0x64] => TLS_PSK_WITH_ARIA_128_CBC_SHA256
-    [0xC0, 0x65] => TLS_PSK_WITH_ARIA_256_CBC_SHA384
-    [0xC0, 0x66] => TLS_DHE_PSK_WITH_ARIA_128_CBC_SHA256
-    [0xC0, 0x67] => TLS_DHE_PSK_WITH_ARIA_256_CBC_SHA384
-    [0xC0, 0x68] => TLS_RSA_PSK_WITH_ARIA_128_CBC_SHA256
-    [0xC0, 0x69] => TLS_RSA_PSK_WITH_ARIA_256_CBC_SHA384
-    [0xC0, 0x6A] => TLS_PSK_WITH_ARIA_128_GCM_SHA256
-    [0xC0, 0x6B] => TLS_PSK_WITH_ARIA_256_GCM_SHA384
-    [0xC0, 0x6C] => TLS_DHE_PSK_WITH_ARIA_128_GCM_SHA256
-    [0xC0, 0x6D] => TLS_DHE_PSK_WITH_ARIA_256_GCM_SHA384
-    [0xC0, 0x6E] => TLS_RSA_PSK_WITH_ARIA_128_GCM_SHA256
-    [0xC0, 0x6F] => TLS_RSA_PSK_WITH_ARIA_256_GCM_SHA384
-    [0xC0, 0x70] => TLS_ECDHE_PSK_WITH_ARIA_128_CBC_SHA256
-    [0xC0, 0x71] => TLS_ECDHE_PSK_WITH_ARIA_256_CBC_SHA384
-    [0xC0, 0x72] => TLS_ECDHE_ECDSA_WITH_CAMELLIA_128_CBC_SHA256
-    [0xC0, 0x73] => TLS_ECDHE_ECDSA_WITH_CAMELLIA_256_CBC_SHA384
-    [0xC0, 0x74] => TLS_ECDH_ECDSA_WITH_CAMELLIA_128_CBC_SHA256
-    [0xC0, 0x75] => TLS_ECDH_ECDSA_WITH_CAMELLIA_256_CBC_SHA384
-    [0xC0, 0x76] => TLS_ECDHE_RSA_WITH_CAMELLIA_128_CBC_SHA256
-    [0xC0, 0x77] => TLS_ECDHE_RSA_WITH_CAMELLIA_256_CBC_SHA384
-    [0xC0, 0x78] => TLS_ECDH_RSA_WITH_CAMELLIA_128_CBC_SHA256
-    [0xC0, 0x79] => TLS_ECDH_RSA_WITH_CAMELLIA_256_CBC_SHA384
-    [0xC0, 0x7A] => TLS_RSA_WITH_CAMELLIA_128_GCM_SHA256
-    [0xC0, 0x7B] => TLS_RSA_WITH_CAMELLIA_256_GCM_SHA384
-    [0xC0, 0x7C] => TLS_DHE_RSA_WITH_CAMELLIA_128_GCM_SHA256
-    [0xC0, 0x7D] => TLS_DHE_RSA_WITH_CAMELLIA_256_GCM_SHA384
-    [0xC0, 0x7E] => TLS_DH_RSA_WITH_CAMELLIA_128_GCM_SHA256
-    [0xC0, 0x7F] => TLS_DH_RSA_WITH_CAMELLIA_256_GCM_SHA384
-    [0xC0, 0x80] => TLS_DHE_DSS_WITH_CAMELLIA_128_GCM_SHA256
-    [0xC0, 0x81] => TLS_DHE_DSS_WITH_CAMELLIA_256_GCM_SHA384
-    [0xC0, 0x82] => TLS_DH_DSS_WITH_CAMELLIA_128_GCM_SHA256
-    [0xC0, 0x83] => TLS_DH_DSS_WITH_CAMELLIA_256_GCM_SHA384
-    [0xC0, 0x84] => TLS_DH_anon_WITH_CAMELLIA_128_GCM_SHA256
-    [0xC0, 0x85] => TLS_DH_anon_WITH_CAMELLIA_256_GCM_SHA384
-    [0xC0, 0x86] => TLS_ECDHE_ECDSA_WITH_CAMELLIA_128_GCM_SHA256
-    [0xC0, 0x87] => TLS_ECDHE_ECDSA_WITH_CAMELLIA_256_GCM_SHA384
-    [0xC0, 0x88] => TLS_ECDH_ECDSA_WITH_CAMELLIA_128_GCM_SHA256
-    [0xC0, 0x89] => TLS_ECDH_ECDSA_WITH_CAMELLIA_256_GCM_SHA384
-    [0xC0, 0x8A] => TLS_ECDHE_RSA_WITH_CAMELLIA_128_GCM_SHA256
-    [0xC0, 0x8B] => TLS_ECDHE_RSA_WITH_CAMELLIA_256_GCM_SHA384
-    [0xC0, 0x8C] => TLS_ECDH_RSA_WITH_CAMELLIA_128_GCM_SHA256
-    [0xC0, 0x8D] => TLS_ECDH_RSA_WITH_CAMELLIA_256_GCM_SHA384
-    [0xC0, 0x8E] => TLS_PSK_WITH_CAMELLIA_128_GCM_SHA256
-    [0xC0, 0x8F] => TLS_PSK_WITH_CAMELLIA_256_GCM_SHA384
-    [0xC0, 0x90] => TLS_DHE_PSK_WITH_CAMELLIA_128_GCM_SHA256
-    [0xC0, 0x91] => TLS_DHE_PSK_WITH_CAMELLIA_256_GCM_SHA384
-    [0xC0, 0x92] => TLS_RSA_PSK_WITH_CAMELLIA_128_GCM_SHA256
-    [0xC0, 0x93] => TLS_RSA_PSK_WITH_CAMELLIA_256_GCM_SHA384
-    [0xC0, 0x94] => TLS_PSK_WITH_CAMELLIA_128_CBC_SHA256
-    [0xC0, 0x95] => TLS_PSK_WITH_CAMELLIA_256_CBC_SHA384
-    [0xC0, 0x96] => TLS_DHE_PSK_WITH_CAMELLIA_128_CBC_SHA256
-    [0xC0, 0x97] => TLS_DHE_PSK_WITH_CAMELLIA_256_CBC_SHA384
-    [0xC0, 0x98] => TLS_RSA_PSK_WITH_CAMELLIA_128_CBC_SHA256
-    [0xC0, 0x99] => TLS_RSA_PSK_WITH_CAMELLIA_256_CBC_SHA384
-    [0xC0, 0x9A] => TLS_ECDHE_PSK_WITH_CAMELLIA_128_CBC_SHA256
-    [0xC0, 0x9B] => TLS_ECDHE_PSK_WITH_CAMELLIA_256_CBC_SHA384
-    [0xC0, 0x9C] => TLS_RSA_WITH_AES_128_CCM
-    [0xC0, 0x9D] => TLS_RSA_WITH_AES_256_CCM
-    [0xC0, 0x9E] => TLS_DHE_RSA_WITH_AES_128_CCM
-    [0xC0, 0x9F] => TLS_DHE_RSA_WITH_AES_256_CCM
-    [0xC0, 0xA0] => TLS_RSA_WITH_AES_128_CCM_8
-    [0xC0, 0xA1] => TLS_RSA_WITH_AES_256_CCM_8
-    [0xC0, 0xA2] => TLS_DHE_RSA_WITH_AES_128_CCM_8
-    [0xC0, 0xA3] => TLS_DHE_RSA_WITH_AES_256_CCM_8
-    [0xC0, 0xA4] => TLS_PSK_WITH_AES_128_CCM
-    [0xC0, 0xA5] => TLS_PSK_WITH_AES_256_CCM
-    [0xC0, 0xA6] => TLS_DHE_PSK_WITH_AES_128_CCM
-    [0xC0, 0xA7] => TLS_DHE_PSK_WITH_AES_256_CCM
-    [0xC0, 0xA8] => TLS_PSK_WITH_AES_128_CCM_8
-    [0xC0, 0xA9] => TLS_PSK_WITH_AES_256_CCM_8
-    [0xC0, 0xAA] => TLS_PSK_DHE_WITH_AES_128_CCM_8
-    [0xC0, 0xAB] => TLS_PSK_DHE_WITH_AES_256_CCM_8
-    [0xC0, 0xAC] => TLS_ECDHE_ECDSA_WITH_AES_128_CCM
-    [0xC0, 0xAD] => TLS_ECDHE_ECDSA_WITH_AES_256_CCM
-    [0xC0, 0xAE] => TLS_ECDHE_ECDSA_WITH_AES_128_CCM_8
-    [0xC0, 0xAF] => TLS_ECDHE_ECDSA_WITH_AES_256_CCM_8
-    [0xC0, 0x33] => TLS_ECDHE_PSK_WITH_RC4_128_SHA
-    [0xC0, 0x02] => TLS_ECDH_ECDSA_WITH_RC4_128_SHA
-    [0xC0, 0x07] => TLS_ECDHE_ECDSA_WITH_RC4_128_SHA
-    [0xC0, 0x0C] => TLS_ECDH_RSA_WITH_RC4_128_SHA
-    [0xC0, 0x11] => TLS_ECDHE_RSA_WITH_RC4_128_SHA
-    [0xC0, 0x16] => TLS_ECDH_anon_WITH_RC4_128_SHA
-    [0x00, 0x8A] => TLS_PSK_WITH_RC4_128_SHA
-    [0x00, 0x8E] => TLS_DHE_PSK_WITH_RC4_128_SHA
-    [0x00, 0x92] => TLS_RSA_PSK_WITH_RC4_128_SHA
-    [0x00, 0x24] => TLS_KRB5_WITH_RC4_128_MD5
-    [0x00, 0x20] => TLS_KRB5_WITH_RC4_128_SHA
-    [0x00, 0x17] => TLS_DH_anon_EXPORT_WITH_RC4_40_MD5
-    [0x00, 0x18] => TLS_DH_anon_WITH_RC4_128_MD5
-    [0x00, 0x03] => TLS_RSA_EXPORT_WITH_RC4_40_MD5
-    [0x00, 0x04] => TLS_RSA_WITH_RC4_128_MD5
-    [0x00, 0x05] => TLS_RSA_WITH_RC4_128_SHA
-    [0x00, 0x28] => TLS_KRB5_EXPORT_WITH_RC4_40_SHA
-    [0x00, 0x2B] => TLS_KRB5_EXPORT_WITH_RC4_40_MD5
 );
+
+#[cfg(test)]
+mod tests {
+    use super::{CipherSuite, Security, SelectionPolicy};
+    use crate::tls::{
+        client_hello::{ClientHello, Extension},
+        compression_methods::CompressionMethods,
+        TLSVersion,
+    };
+
+    use CipherSuite::{
+        TLS_AES_128_GCM_SHA256, TLS_CHACHA20_POLY1305_SHA256,
+        TLS_ECDHE_ECDSA_WITH_AES_256_GCM_SHA384, TLS_ECDHE_RSA_WITH_AES_128_CBC_SHA,
+        TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384, TLS_RSA_WITH_AES_128_GCM_SHA256,
+    };
+
+    #[test]
+    fn the_strongest_suite_wins() {
+        let policy = SelectionPolicy::default().minimum_security(Security::Weak);
+
+        // Forward secrecy ranks above AEAD, which ranks above key size
+        let offered = [
+            TLS_RSA_WITH_AES_128_GCM_SHA256,
+            TLS_ECDHE_RSA_WITH_AES_128_CBC_SHA,
+        ];
+        assert_eq!(
+            policy.select(&offered, TLSVersion::TLS1_2),
+            Some(TLS_ECDHE_RSA_WITH_AES_128_CBC_SHA)
+        );
+
+        let offered = [
+            TLS_ECDHE_RSA_WITH_AES_128_CBC_SHA,
+            TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384,
+        ];
+        assert_eq!(
+            policy.select(&offered, TLSVersion::TLS1_2),
+            Some(TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384)
+        );
+
+        let offered = [TLS_AES_128_GCM_SHA256, TLS_CHACHA20_POLY1305_SHA256];
+        assert_eq!(
+            policy.select(&offered, TLSVersion::TLS1_3),
+            Some(TLS_CHACHA20_POLY1305_SHA256)
+        );
+    }
+
+    #[test]
+    fn ties_go_to_the_client_preference() {
+        let policy = SelectionPolicy::default();
+
+        for offered in [
+            [
+                TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384,
+                TLS_ECDHE_ECDSA_WITH_AES_256_GCM_SHA384,
+            ],
+            [
+                TLS_ECDHE_ECDSA_WITH_AES_256_GCM_SHA384,
+                TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384,
+            ],
+        ] {
+            assert_eq!(
+                policy.select(&offered, TLSVersion::TLS1_2),
+                Some(offered[0])
+            );
+        }
+    }
+
+    #[test]
+    fn weaker_suites_are_refused() {
+        let offered = [
+            TLS_RSA_WITH_AES_128_GCM_SHA256,
+            TLS_ECDHE_RSA_WITH_AES_128_CBC_SHA,
+        ];
+
+        assert_eq!(
+            SelectionPolicy::default().select(&offered, TLSVersion::TLS1_2),
+            None
+        );
+        assert_eq!(
+            SelectionPolicy::default()
+                .minimum_security(Security::Recommended)
+                .select(&[TLS_AES_128_GCM_SHA256], TLSVersion::TLS1_3),
+            Some(TLS_AES_128_GCM_SHA256)
+        );
+    }
+
+    #[test]
+    fn unsupported_suites_are_refused() {
+        let policy = SelectionPolicy::new([TLS_ECDHE_ECDSA_WITH_AES_256_GCM_SHA384]);
+        let offered = [
+            TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384,
+            TLS_ECDHE_ECDSA_WITH_AES_256_GCM_SHA384,
+        ];
+
+        assert_eq!(
+            policy.select(&offered, TLSVersion::TLS1_2),
+            Some(TLS_ECDHE_ECDSA_WITH_AES_256_GCM_SHA384)
+        );
+        assert_eq!(policy.select(&offered[..1], TLSVersion::TLS1_2), None);
+    }
+
+    #[test]
+    fn tls_1_3_suites_are_kept_apart() {
+        let policy = SelectionPolicy::default();
+        let offered = [
+            TLS_AES_128_GCM_SHA256,
+            TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384,
+        ];
+
+        assert_eq!(
+            policy.select(&offered, TLSVersion::TLS1_3),
+            Some(TLS_AES_128_GCM_SHA256)
+        );
+        assert_eq!(
+            policy.select(&offered, TLSVersion::TLS1_2),
+            Some(TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384)
+        );
+        assert_eq!(policy.select(&offered[..1], TLSVersion::TLS1_2), None);
+    }
+
+    #[test]
+    fn hellos_are_answered_for_their_highest_version() {
+        let hello = |extensions| ClientHello {
+            version: TLSVersion::TLS1_2,
+            random: [0; 32],
+            session_id: vec![],
+            cipher_suites: vec![
+                TLS_AES_128_GCM_SHA256,
+                TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384,
+            ],
+            compression_methods: vec![CompressionMethods::Null],
+            extensions,
+        };
+        let policy = SelectionPolicy::default();
+
+        assert_eq!(
+            policy.select_for(&hello(vec![Extension::SupportedVersions(vec![
+                0x0304, 0x0303
+            ])])),
+            Some(TLS_AES_128_GCM_SHA256)
+        );
+        assert_eq!(
+            policy.select_for(&hello(vec![])),
+            Some(TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384)
+        );
+    }
+}
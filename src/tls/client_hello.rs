@@ -0,0 +1,591 @@
+use crate::tls::{
+    cipher_suite::CipherSuite, compression_methods::CompressionMethods, error::TlsError,
+    reader::Reader, HandshakeHeader, HandshakeType, RecordHeader, RecordType, TLSVersion,
+};
+
+/// A key share offered by the client for a named group, RFC 8446 Section 4.2.8
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyShareEntry {
+    /// The named group the key belongs to
+    pub group: u16,
+    /// The public key
+    pub key_exchange: Vec<u8>,
+}
+
+/// An extension sent with a `ClientHello`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Extension {
+    /// `server_name` (0), the host names the client is connecting to, RFC 6066 Section 3
+    ServerName(Vec<String>),
+    /// `supported_groups` (10), the named groups the client supports for key exchange, RFC 8422 Section 5.1.1
+    SupportedGroups(Vec<u16>),
+    /// `ec_point_formats` (11), RFC 8422 Section 5.1.2
+    EcPointFormats(Vec<u8>),
+    /// `signature_algorithms` (13), RFC 8446 Section 4.2.3
+    SignatureAlgorithms(Vec<u16>),
+    /// `application_layer_protocol_negotiation` (16), the protocols the client can speak, RFC 7301
+    ApplicationLayerProtocolNegotiation(Vec<Vec<u8>>),
+    /// `supported_versions` (43), the TLS versions the client supports, RFC 8446 Section 4.2.1
+    SupportedVersions(Vec<u16>),
+    /// `key_share` (51), RFC 8446 Section 4.2.8
+    KeyShare(Vec<KeyShareEntry>),
+    /// Any other extension, kept as-is
+    Unknown {
+        /// The registered extension type
+        extension_type: u16,
+        /// The raw extension data
+        data: Vec<u8>,
+    },
+}
+
+impl Extension {
+    /// The registered extension type
+    #[must_use]
+    pub fn extension_type(&self) -> u16 {
+        match self {
+            Extension::ServerName(_) => 0,
+            Extension::SupportedGroups(_) => 10,
+            Extension::EcPointFormats(_) => 11,
+            Extension::SignatureAlgorithms(_) => 13,
+            Extension::ApplicationLayerProtocolNegotiation(_) => 16,
+            Extension::SupportedVersions(_) => 43,
+            Extension::KeyShare(_) => 51,
+            Extension::Unknown { extension_type, .. } => *extension_type,
+        }
+    }
+
+    /// Reads a single extension, including its type and length
+    /// # Errors
+    /// - If the extension is truncated or its contents do not match its length
+    pub fn read(reader: &mut Reader) -> Result<Self, TlsError> {
+        let extension_type = reader.u16()?;
+        let mut data = reader.vector_u16()?;
+
+        let extension = match extension_type {
+            0 => {
+                let mut list = data.vector_u16()?;
+                let mut names = vec![];
+
+                while !list.is_empty() {
+                    let name_type = list.u8()?;
+                    let mut name = list.vector_u16()?;
+
+                    // 0 is `host_name`, the only name type ever registered
+                    if name_type == 0 {
+                        let name = std::str::from_utf8(name.rest())
+                            .map_err(|_| TlsError::Malformed("server name"))?;
+
+                        names.push(name.to_string());
+                    }
+                }
+
+                Extension::ServerName(names)
+            }
+            10 => Extension::SupportedGroups(u16_list(data.vector_u16()?)?),
+            11 => {
+                let mut formats = data.vector_u8()?;
+
+                Extension::EcPointFormats(formats.rest().to_vec())
+            }
+            13 => Extension::SignatureAlgorithms(u16_list(data.vector_u16()?)?),
+            16 => {
+                let mut list = data.vector_u16()?;
+                let mut protocols = vec![];
+
+                while !list.is_empty() {
+                    let mut protocol = list.vector_u8()?;
+
+                    protocols.push(protocol.rest().to_vec());
+                }
+
+                Extension::ApplicationLayerProtocolNegotiation(protocols)
+            }
+            43 => Extension::SupportedVersions(u16_list(data.vector_u8()?)?),
+            51 => {
+                let mut list = data.vector_u16()?;
+                let mut shares = vec![];
+
+                while !list.is_empty() {
+                    let group = list.u16()?;
+                    let mut key_exchange = list.vector_u16()?;
+
+                    shares.push(KeyShareEntry {
+                        group,
+                        key_exchange: key_exchange.rest().to_vec(),
+                    });
+                }
+
+                Extension::KeyShare(shares)
+            }
+            extension_type => {
+                return Ok(Extension::Unknown {
+                    extension_type,
+                    data: data.rest().to_vec(),
+                })
+            }
+        };
+
+        if !data.is_empty() {
+            return Err(TlsError::Malformed("extension length"));
+        }
+
+        Ok(extension)
+    }
+}
+
+/// Reads a list of 16 bit values that fills the whole reader
+fn u16_list(mut reader: Reader) -> Result<Vec<u16>, TlsError> {
+    if !reader.remaining().is_multiple_of(2) {
+        return Err(TlsError::Malformed("list length"));
+    }
+
+    let mut values = Vec::with_capacity(reader.remaining() / 2);
+
+    while !reader.is_empty() {
+        values.push(reader.u16()?);
+    }
+
+    Ok(values)
+}
+
+/// GREASE values (RFC 8701) are random placeholders that clients sprinkle in to keep servers tolerant
+fn is_grease(value: u16) -> bool {
+    value & 0x0f0f == 0x0a0a && value >> 8 == value & 0xff
+}
+
+/// The first message of a TLS handshake, RFC 8446 Section 4.1.2
+///
+/// The TLS listener leaves the handshake to rustls, this decoder is for looking at a connection before that. Peek
+/// at the first bytes of an accepted `TcpStream` and hand them to [`ClientHello::from_records`], which leaves the
+/// stream untouched, to fingerprint clients through [`ClientHello::ja3`] or to route them by
+/// [`ClientHello::server_name`] without terminating TLS. Peek again with a larger buffer while it reports
+/// [`TlsError::Truncated`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientHello {
+    /// The legacy version field, TLS 1.3 clients announce their versions through [`Extension::SupportedVersions`]
+    pub version: TLSVersion,
+    /// 32 random bytes generated by the client
+    pub random: [u8; 32],
+    /// The legacy session id
+    pub session_id: Vec<u8>,
+    /// The cipher suites offered by the client, in order of preference
+    pub cipher_suites: Vec<CipherSuite>,
+    /// The compression methods offered by the client
+    pub compression_methods: Vec<CompressionMethods>,
+    /// The extensions sent by the client, in the order they were sent
+    pub extensions: Vec<Extension>,
+}
+
+impl ClientHello {
+    /// Reads the body of a `ClientHello` handshake message, without the handshake header
+    /// # Errors
+    /// - If the message is truncated, or has trailing bytes
+    /// - If any of the fields or extensions are malformed
+    pub fn read(reader: &mut Reader) -> Result<Self, TlsError> {
+        let version = TLSVersion::try_from(reader.array()?)?;
+        let random = reader.array()?;
+
+        let mut session_id = reader.vector_u8()?;
+        let session_id = session_id.rest().to_vec();
+
+        let mut suites = reader.vector_u16()?;
+        if !suites.remaining().is_multiple_of(2) {
+            return Err(TlsError::Malformed("cipher suites length"));
+        }
+
+        let mut cipher_suites = Vec::with_capacity(suites.remaining() / 2);
+        while !suites.is_empty() {
+            cipher_suites.push(CipherSuite::from(suites.array::<2>()?));
+        }
+
+        let mut methods = reader.vector_u8()?;
+        let compression_methods = methods
+            .take(methods.remaining())?
+            .iter()
+            .map(|method| CompressionMethods::from(*method))
+            .collect();
+
+        let mut extensions = vec![];
+
+        // The extensions block is optional for clients that predate it
+        if !reader.is_empty() {
+            let mut block = reader.vector_u16()?;
+
+            while !block.is_empty() {
+                extensions.push(Extension::read(&mut block)?);
+            }
+        }
+
+        if !reader.is_empty() {
+            return Err(TlsError::Malformed("ClientHello length"));
+        }
+
+        Ok(Self {
+            version,
+            random,
+            session_id,
+            cipher_suites,
+            compression_methods,
+            extensions,
+        })
+    }
+
+    /// Reads a `ClientHello` from the raw bytes a client sends at the start of a connection.
+    ///
+    /// The handshake message may be fragmented over several consecutive handshake records.
+    /// # Errors
+    /// - If the bytes do not start with handshake records containing a complete `ClientHello`
+    pub fn from_records(bytes: &[u8]) -> Result<Self, TlsError> {
+        let mut records = Reader::new(bytes);
+        let mut message = vec![];
+
+        loop {
+            let header = RecordHeader::read(&mut records)?;
+
+            if header.record_type != RecordType::Handshake {
+                return Err(TlsError::Malformed("ClientHello record type"));
+            }
+
+            message.extend_from_slice(records.take(header.record_length.into())?);
+
+            if message.len() < HandshakeHeader::LENGTH {
+                continue;
+            }
+
+            let mut reader = Reader::new(&message);
+            let handshake = HandshakeHeader::read(&mut reader)?;
+
+            if handshake.handshake_type != HandshakeType::ClientHello {
+                return Err(TlsError::UnknownHandshakeType(message[0]));
+            }
+
+            let length = usize::try_from(handshake.data_length)
+                .map_err(|_| TlsError::Malformed("ClientHello length"))?;
+
+            if reader.remaining() >= length {
+                return Self::read(&mut Reader::new(reader.take(length)?));
+            }
+        }
+    }
+
+    /// The host name sent through SNI, used to select a certificate
+    #[must_use]
+    pub fn server_name(&self) -> Option<&str> {
+        self.extensions
+            .iter()
+            .find_map(|extension| match extension {
+                Extension::ServerName(names) => names.first().map(String::as_str),
+                _ => None,
+            })
+    }
+
+    /// The application protocols offered through ALPN, e.g. `h2` and `http/1.1`
+    #[must_use]
+    pub fn alpn_protocols(&self) -> Vec<&[u8]> {
+        self.extensions
+            .iter()
+            .find_map(|extension| match extension {
+                Extension::ApplicationLayerProtocolNegotiation(protocols) => {
+                    Some(protocols.iter().map(Vec::as_slice).collect())
+                }
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
+    /// The TLS versions the client supports, falling back to the legacy version field for clients
+    /// that do not send the `supported_versions` extension
+    #[must_use]
+    pub fn supported_versions(&self) -> Vec<TLSVersion> {
+        self.extensions
+            .iter()
+            .find_map(|extension| match extension {
+                Extension::SupportedVersions(versions) => Some(
+                    versions
+                        .iter()
+                        .filter_map(|version| TLSVersion::try_from(version.to_be_bytes()).ok())
+                        .collect(),
+                ),
+                _ => None,
+            })
+            .unwrap_or_else(|| vec![self.version])
+    }
+
+    /// The signature algorithms the client accepts
+    #[must_use]
+    pub fn signature_algorithms(&self) -> &[u16] {
+        self.extensions
+            .iter()
+            .find_map(|extension| match extension {
+                Extension::SignatureAlgorithms(algorithms) => Some(algorithms.as_slice()),
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
+    /// The key shares the client sent for a TLS 1.3 handshake
+    #[must_use]
+    pub fn key_shares(&self) -> &[KeyShareEntry] {
+        self.extensions
+            .iter()
+            .find_map(|extension| match extension {
+                Extension::KeyShare(shares) => Some(shares.as_slice()),
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
+    /// The JA3 fingerprint string of the `ClientHello`, GREASE values excluded.
+    ///
+    /// The fields are the version, cipher suites, extension types, supported groups and point formats,
+    /// e.g. `771,4865-4866,0-10-11,29-23,0`. The usual JA3 digest is the MD5 hash of this string.
+    #[must_use]
+    pub fn ja3(&self) -> String {
+        fn join(values: impl Iterator<Item = u16>) -> String {
+            values
+                .filter(|value| !is_grease(*value))
+                .map(|value| value.to_string())
+                .collect::<Vec<_>>()
+                .join("-")
+        }
+
+        let version = match self.version {
+            TLSVersion::TLS1 => 0x301,
+            TLSVersion::TLS1_1 => 0x302,
+            TLSVersion::TLS1_2 => 0x303,
+            TLSVersion::TLS1_3 => 0x304,
+        };
+
+        let ciphers = join(
+            self.cipher_suites
+                .iter()
+                .map(|suite| u16::from_be_bytes((*suite).into())),
+        );
+
+        let extensions = join(self.extensions.iter().map(Extension::extension_type));
+
+        let groups = self
+            .extensions
+            .iter()
+            .find_map(|extension| match extension {
+                Extension::SupportedGroups(groups) => Some(join(groups.iter().copied())),
+                _ => None,
+            })
+            .unwrap_or_default();
+
+        let formats = self
+            .extensions
+            .iter()
+            .find_map(|extension| match extension {
+                Extension::EcPointFormats(formats) => {
+                    Some(join(formats.iter().map(|format| u16::from(*format))))
+                }
+                _ => None,
+            })
+            .unwrap_or_default();
+
+        format!("{version},{ciphers},{extensions},{groups},{formats}")
+    }
+}
+
+impl TryFrom<&[u8]> for ClientHello {
+    type Error = TlsError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        Self::read(&mut Reader::new(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ClientHello, Extension};
+    use crate::tls::{error::TlsError, TLSVersion};
+
+    fn vector_u8(bytes: &[u8]) -> Vec<u8> {
+        let mut vector = vec![u8::try_from(bytes.len()).unwrap()];
+        vector.extend_from_slice(bytes);
+        vector
+    }
+
+    fn vector_u16(bytes: &[u8]) -> Vec<u8> {
+        let mut vector = u16::try_from(bytes.len()).unwrap().to_be_bytes().to_vec();
+        vector.extend_from_slice(bytes);
+        vector
+    }
+
+    fn extension(extension_type: u16, data: &[u8]) -> Vec<u8> {
+        let mut extension = extension_type.to_be_bytes().to_vec();
+        extension.extend(vector_u16(data));
+        extension
+    }
+
+    /// Length of the fields before the optional extensions block of [`hello`]
+    const FIELDS_LENGTH: usize = 2 + 32 + 1 + 2 + 6 + 2;
+
+    /// The body of a `ClientHello` as sent by a browser, GREASE values included
+    fn hello() -> Vec<u8> {
+        let mut body = vec![3, 3];
+        body.extend([0; 32]);
+        body.extend(vector_u8(&[]));
+        body.extend(vector_u16(&[0x0a, 0x0a, 0x13, 0x01, 0xc0, 0x2f]));
+        body.extend(vector_u8(&[0]));
+
+        let mut server_name = vec![0];
+        server_name.extend(vector_u16(b"example.com"));
+
+        let mut alpn = vector_u8(b"h2");
+        alpn.extend(vector_u8(b"http/1.1"));
+
+        let extensions = [
+            extension(0x1a1a, &[]),
+            extension(0, &vector_u16(&server_name)),
+            extension(10, &vector_u16(&[0x2a, 0x2a, 0, 0x1d, 0, 0x17])),
+            extension(11, &vector_u8(&[0])),
+            extension(16, &vector_u16(&alpn)),
+            extension(43, &vector_u8(&[3, 4, 3, 3])),
+        ]
+        .concat();
+
+        body.extend(vector_u16(&extensions));
+        body
+    }
+
+    /// Wraps a message body in a handshake header and splits it over records of at most `size` bytes
+    fn records(body: &[u8], handshake_type: u8, size: usize) -> Vec<u8> {
+        let mut message = vec![handshake_type];
+        message.extend(&u32::try_from(body.len()).unwrap().to_be_bytes()[1..]);
+        message.extend_from_slice(body);
+
+        message
+            .chunks(size)
+            .flat_map(|chunk| {
+                let mut record = vec![22, 3, 1];
+                record.extend(vector_u16(chunk));
+                record
+            })
+            .collect()
+    }
+
+    #[test]
+    fn hello_is_decoded() {
+        let hello = ClientHello::try_from(hello().as_slice()).unwrap();
+
+        assert_eq!(hello.version, TLSVersion::TLS1_2);
+        assert_eq!(hello.cipher_suites.len(), 3);
+        assert_eq!(hello.extensions.len(), 6);
+        assert_eq!(hello.server_name(), Some("example.com"));
+        assert_eq!(hello.alpn_protocols(), [b"h2".as_slice(), b"http/1.1"]);
+        assert_eq!(
+            hello.supported_versions(),
+            [TLSVersion::TLS1_3, TLSVersion::TLS1_2]
+        );
+    }
+
+    #[test]
+    fn ja3_skips_grease() {
+        let hello = ClientHello::try_from(hello().as_slice()).unwrap();
+
+        assert_eq!(hello.ja3(), "771,4865-49199,0-10-11-16-43,29-23,0");
+    }
+
+    #[test]
+    fn fragmented_records_are_joined() {
+        let expected = ClientHello::try_from(hello().as_slice()).unwrap();
+
+        for size in [1, 7, 64, 1 << 14] {
+            assert_eq!(
+                ClientHello::from_records(&records(&hello(), 1, size)),
+                Ok(expected.clone())
+            );
+        }
+    }
+
+    #[test]
+    fn truncated_hellos_are_rejected() {
+        let body = hello();
+
+        // Cutting off the whole extensions block leaves a valid hello from before extensions existed
+        for length in (0..body.len()).filter(|length| *length != FIELDS_LENGTH) {
+            assert!(
+                ClientHello::try_from(&body[..length]).is_err(),
+                "{length} bytes were accepted"
+            );
+        }
+
+        let records = records(&body, 1, 64);
+
+        for length in 0..records.len() {
+            assert!(ClientHello::from_records(&records[..length]).is_err());
+        }
+    }
+
+    #[test]
+    fn malformed_hellos_are_rejected() {
+        let body = hello();
+
+        let mut trailing = body.clone();
+        trailing.push(0);
+        assert_eq!(
+            ClientHello::try_from(trailing.as_slice()),
+            Err(TlsError::Malformed("ClientHello length"))
+        );
+
+        let mut odd_suites = body[..FIELDS_LENGTH].to_vec();
+        odd_suites[35..37].copy_from_slice(&[0, 5]);
+        assert_eq!(
+            ClientHello::try_from(odd_suites.as_slice()),
+            Err(TlsError::Malformed("cipher suites length"))
+        );
+
+        let mut padded_extension = body[..FIELDS_LENGTH].to_vec();
+        padded_extension.extend(vector_u16(&extension(11, &[1, 0, 0])));
+        assert_eq!(
+            ClientHello::try_from(padded_extension.as_slice()),
+            Err(TlsError::Malformed("extension length"))
+        );
+
+        let mut odd_groups = body[..FIELDS_LENGTH].to_vec();
+        odd_groups.extend(vector_u16(&extension(10, &vector_u16(&[0, 0x1d, 0]))));
+        assert_eq!(
+            ClientHello::try_from(odd_groups.as_slice()),
+            Err(TlsError::Malformed("list length"))
+        );
+
+        let mut invalid_name = body[..FIELDS_LENGTH].to_vec();
+        let mut server_name = vec![0];
+        server_name.extend(vector_u16(&[0xff, 0xfe]));
+        invalid_name.extend(vector_u16(&extension(0, &vector_u16(&server_name))));
+        assert_eq!(
+            ClientHello::try_from(invalid_name.as_slice()),
+            Err(TlsError::Malformed("server name"))
+        );
+    }
+
+    #[test]
+    fn other_messages_are_rejected() {
+        assert_eq!(
+            ClientHello::from_records(&records(&hello(), 2, 64)),
+            Err(TlsError::UnknownHandshakeType(2))
+        );
+
+        let mut alert = records(&hello(), 1, 64);
+        alert[0] = 21;
+        assert_eq!(
+            ClientHello::from_records(&alert),
+            Err(TlsError::Malformed("ClientHello record type"))
+        );
+    }
+
+    #[test]
+    fn unknown_extensions_are_kept() {
+        let hello = ClientHello::try_from(hello().as_slice()).unwrap();
+
+        assert_eq!(
+            hello.extensions[0],
+            Extension::Unknown {
+                extension_type: 0x1a1a,
+                data: vec![]
+            }
+        );
+    }
+}
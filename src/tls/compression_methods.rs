@@ -1,9 +1,15 @@
-#[derive(Debug)]
+/// Compression methods as registered with IANA
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CompressionMethods {
+    /// No compression
     Null,
+    /// DEFLATE compression, RFC 3749
     Deflate,
+    /// A value that is not assigned to any method
     Unassigned(u8),
+    /// Lempel-Ziv-Stac compression, RFC 3943
     Lzs,
+    /// A value reserved for private use
     Reserved(u8),
 }
 
@@ -0,0 +1,30 @@
+use thiserror::Error;
+
+/// Errors that can happen while decoding TLS records and handshake messages
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum TlsError {
+    /// The input ended before a complete value could be read
+    #[error("Truncated input: needed {needed} bytes but only {available} remain")]
+    Truncated {
+        /// Number of bytes the value required
+        needed: usize,
+        /// Number of bytes that were left
+        available: usize,
+    },
+
+    /// The record content type is not registered
+    #[error("Unknown record type {0}")]
+    UnknownRecordType(u8),
+
+    /// The protocol version is not one of the supported TLS versions
+    #[error("Unknown TLS version {0:#06x}")]
+    UnknownVersion(u16),
+
+    /// The handshake message type is not registered
+    #[error("Unknown handshake type {0}")]
+    UnknownHandshakeType(u8),
+
+    /// A length field or value does not fit the structure it describes
+    #[error("Malformed {0}")]
+    Malformed(&'static str),
+}
@@ -1,7 +1,32 @@
+/// Cipher suite identifiers as registered with IANA
 pub mod cipher_suite;
+
+/// Compression method identifiers sent in the handshake
 pub mod compression_methods;
 
-#[derive(Debug)]
+/// Decoding of the `ClientHello` handshake message and its extensions
+pub mod client_hello;
+
+/// HTTPS listener backed by rustls, serving certificates loaded from PEM files
+#[cfg(feature = "tls")]
+pub mod server;
+
+/// Certificates selected by SNI hostname and reloaded from disk when they change
+#[cfg(feature = "tls")]
+pub mod certificates;
+
+/// Errors produced while decoding TLS structures
+pub mod error;
+
+/// Bounds-checked reader for TLS wire formats
+pub mod reader;
+
+use error::TlsError;
+use reader::Reader;
+
+/// Content type of a TLS record
+#[allow(missing_docs)] // Self explanatory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RecordType {
     ChangeCipherSpec,
     Alert,
@@ -9,58 +34,102 @@ pub enum RecordType {
     ApplicationData,
     Heartbeat,
 }
-#[derive(Debug)]
+
+impl TryFrom<u8> for RecordType {
+    type Error = TlsError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            20 => Ok(RecordType::ChangeCipherSpec),
+            21 => Ok(RecordType::Alert),
+            22 => Ok(RecordType::Handshake),
+            23 => Ok(RecordType::ApplicationData),
+            24 => Ok(RecordType::Heartbeat),
+            record_type => Err(TlsError::UnknownRecordType(record_type)),
+        }
+    }
+}
+
+/// Protocol version carried in a TLS record header or a handshake message
+#[allow(missing_docs)] // Self explanatory
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum TLSVersion {
     TLS1,
     TLS1_1,
     TLS1_2,
+    TLS1_3,
 }
 
-impl From<[u8; 2]> for TLSVersion {
-    #[allow(clippy::similar_names)]
-    fn from([msb, lsb]: [u8; 2]) -> Self {
-        let version = u16::from_ne_bytes([lsb, msb]);
+impl TryFrom<[u8; 2]> for TLSVersion {
+    type Error = TlsError;
 
-        match version {
-            0x301 => TLSVersion::TLS1,
-            0x302 => TLSVersion::TLS1_1,
-            0x303 => TLSVersion::TLS1_2,
-            _ => panic!("Invalid TLS version"),
+    fn try_from(value: [u8; 2]) -> Result<Self, Self::Error> {
+        match u16::from_be_bytes(value) {
+            0x301 => Ok(TLSVersion::TLS1),
+            0x302 => Ok(TLSVersion::TLS1_1),
+            0x303 => Ok(TLSVersion::TLS1_2),
+            0x304 => Ok(TLSVersion::TLS1_3),
+            version => Err(TlsError::UnknownVersion(version)),
         }
     }
 }
 
-#[derive(Debug)]
+/// The 5 byte header that precedes every TLS record
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct RecordHeader {
-    record_type: RecordType,
-    tls_version: TLSVersion,
-    record_length: u16,
+    /// Content type of the record
+    pub record_type: RecordType,
+    /// Protocol version of the record layer
+    pub tls_version: TLSVersion,
+    /// Length of the record payload in bytes
+    pub record_length: u16,
 }
 
-impl From<&[u8]> for RecordHeader {
-    fn from(value: &[u8]) -> Self {
-        let record_type = value[0];
+impl RecordHeader {
+    /// Size of the header on the wire
+    pub const LENGTH: usize = 5;
+
+    /// Records may not carry more than 2^14 bytes of plaintext plus 2048 bytes of expansion, RFC 5246 Section 6.2.3
+    pub const MAX_RECORD_LENGTH: u16 = (1 << 14) + 2048;
 
-        let record_length = u16::from_ne_bytes([value[4], value[3]]);
+    /// Reads a record header from the reader
+    /// # Errors
+    /// - If the header is truncated, or has an unknown type or version
+    /// - If the record length exceeds [`RecordHeader::MAX_RECORD_LENGTH`]
+    /// - If a record other than application data is empty
+    pub fn read(reader: &mut Reader) -> Result<Self, TlsError> {
+        let record_type = RecordType::try_from(reader.u8()?)?;
+        let tls_version = TLSVersion::try_from(reader.array()?)?;
+        let record_length = reader.u16()?;
+
+        if record_length > Self::MAX_RECORD_LENGTH {
+            return Err(TlsError::Malformed("record length"));
+        }
 
-        let tls_version = TLSVersion::from([value[1], value[2]]);
+        // Only application data may be sent in empty records, RFC 8446 Section 5.1
+        if record_length == 0 && record_type != RecordType::ApplicationData {
+            return Err(TlsError::Malformed("record length"));
+        }
 
-        Self {
-            record_type: match record_type {
-                20 => RecordType::ChangeCipherSpec,
-                21 => RecordType::Alert,
-                22 => RecordType::Handshake,
-                23 => RecordType::ApplicationData,
-                24 => RecordType::Heartbeat,
-                _ => panic!("invalid record_type"),
-            },
+        Ok(Self {
+            record_type,
             tls_version,
             record_length,
-        }
+        })
+    }
+}
+
+impl TryFrom<&[u8]> for RecordHeader {
+    type Error = TlsError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        Self::read(&mut Reader::new(value))
     }
 }
 
-#[derive(Debug)]
+/// Type of a handshake message
+#[allow(missing_docs)] // Self explanatory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum HandshakeType {
     HelloRequest,
     ClientHello,
@@ -75,34 +144,194 @@ pub enum HandshakeType {
     Finished,
 }
 
-#[derive(Debug)]
+impl TryFrom<u8> for HandshakeType {
+    type Error = TlsError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(HandshakeType::HelloRequest),
+            1 => Ok(HandshakeType::ClientHello),
+            2 => Ok(HandshakeType::ServerHello),
+            4 => Ok(HandshakeType::NewSessionTicket),
+            11 => Ok(HandshakeType::Certificate),
+            12 => Ok(HandshakeType::ServerKeyExchange),
+            13 => Ok(HandshakeType::CertificateRequest),
+            14 => Ok(HandshakeType::ServerDone),
+            15 => Ok(HandshakeType::CertificateVerify),
+            16 => Ok(HandshakeType::ClientKeyExchange),
+            20 => Ok(HandshakeType::Finished),
+            handshake_type => Err(TlsError::UnknownHandshakeType(handshake_type)),
+        }
+    }
+}
+
+/// The 4 byte header that precedes every handshake message
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct HandshakeHeader {
+    /// Type of the handshake message
     pub handshake_type: HandshakeType,
+    /// Length of the handshake message body in bytes
     pub data_length: u32,
 }
 
-impl From<&[u8]> for HandshakeHeader {
-    fn from(value: &[u8]) -> Self {
-        let handshake_type = match value[0] {
-            0 => HandshakeType::HelloRequest,
-            1 => HandshakeType::ClientHello,
-            2 => HandshakeType::ServerHello,
-            4 => HandshakeType::NewSessionTicket,
-            11 => HandshakeType::Certificate,
-            12 => HandshakeType::ServerKeyExchange,
-            13 => HandshakeType::CertificateRequest,
-            14 => HandshakeType::ServerDone,
-            15 => HandshakeType::CertificateVerify,
-            16 => HandshakeType::ClientKeyExchange,
-            20 => HandshakeType::Finished,
-            _ => panic!("invalid handshake type"),
-        };
-
-        Self {
-            handshake_type,
-            data_length: u32::from_le_bytes([value[3], value[2], value[1], 0]),
-        }
+impl HandshakeHeader {
+    /// Size of the header on the wire
+    pub const LENGTH: usize = 4;
+
+    /// Reads a handshake header from the reader
+    /// # Errors
+    /// - If the header is truncated or has an unknown type
+    pub fn read(reader: &mut Reader) -> Result<Self, TlsError> {
+        Ok(Self {
+            handshake_type: HandshakeType::try_from(reader.u8()?)?,
+            data_length: reader.u24()?,
+        })
+    }
+}
+
+impl TryFrom<&[u8]> for HandshakeHeader {
+    type Error = TlsError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        Self::read(&mut Reader::new(value))
     }
 }
 
-enum ClientVersion {}
+#[cfg(test)]
+mod tests {
+    use super::{
+        client_hello::ClientHello, error::TlsError, HandshakeHeader, RecordHeader, RecordType,
+        TLSVersion,
+    };
+
+    /// A handshake record header announcing 5 bytes, followed by a `ClientHello` header announcing 1
+    const RECORD: &[u8] = &[22, 3, 3, 0, 5, 1, 0, 0, 1, 0];
+
+    /// Records with headers that must be rejected
+    fn corpus() -> Vec<Vec<u8>> {
+        let mut corpus = vec![];
+
+        // Truncated headers
+        corpus.extend((0..RecordHeader::LENGTH).map(|length| RECORD[..length].to_vec()));
+
+        // Zero-length and oversized records
+        corpus.push(vec![22, 3, 3, 0, 0]);
+        corpus.push(vec![22, 3, 3, 0x48, 0x01]);
+        corpus.push(vec![22, 3, 3, 0xff, 0xff]);
+
+        // Unknown content types and versions
+        corpus.extend(
+            (0..=u8::MAX)
+                .filter(|byte| !(20..=24).contains(byte))
+                .map(|byte| vec![byte, 3, 3, 0, 5]),
+        );
+        corpus.push(vec![22, 3, 5, 0, 5]);
+        corpus.push(vec![22, 0, 0, 0, 5]);
+
+        corpus
+    }
+
+    /// Records with valid headers but length fields past the end of the buffer
+    fn overruns() -> Vec<Vec<u8>> {
+        vec![
+            vec![22, 3, 3, 0x40, 0],
+            vec![22, 3, 3, 0, 5, 1, 0xff, 0xff, 0xff],
+            vec![22, 3, 3, 0, 4, 1, 0, 0, 1, 22, 3, 3, 0, 0],
+        ]
+    }
+
+    #[test]
+    fn records_reject_lengths_past_the_end() {
+        for input in overruns() {
+            assert!(RecordHeader::try_from(input.as_slice()).is_ok());
+            assert!(
+                ClientHello::from_records(&input).is_err(),
+                "{input:?} was accepted"
+            );
+        }
+    }
+
+    #[test]
+    fn record_headers_reject_the_corpus() {
+        for input in corpus() {
+            assert!(
+                RecordHeader::try_from(input.as_slice()).is_err(),
+                "{input:?} was accepted"
+            );
+            assert!(
+                ClientHello::from_records(&input).is_err(),
+                "{input:?} was accepted"
+            );
+        }
+    }
+
+    #[test]
+    fn record_headers_are_decoded() {
+        assert_eq!(
+            RecordHeader::try_from(RECORD),
+            Ok(RecordHeader {
+                record_type: RecordType::Handshake,
+                tls_version: TLSVersion::TLS1_2,
+                record_length: 5,
+            })
+        );
+    }
+
+    #[test]
+    fn empty_application_data_is_allowed() {
+        assert!(RecordHeader::try_from([23, 3, 3, 0, 0].as_slice()).is_ok());
+        assert_eq!(
+            RecordHeader::try_from([22, 3, 3, 0, 0].as_slice()),
+            Err(TlsError::Malformed("record length"))
+        );
+    }
+
+    #[test]
+    fn unknown_values_are_reported() {
+        assert_eq!(
+            RecordType::try_from(25),
+            Err(TlsError::UnknownRecordType(25))
+        );
+        assert_eq!(
+            TLSVersion::try_from([3, 5]),
+            Err(TlsError::UnknownVersion(0x305))
+        );
+        assert_eq!(
+            HandshakeHeader::try_from([3, 0, 0, 0].as_slice()),
+            Err(TlsError::UnknownHandshakeType(3))
+        );
+    }
+
+    #[test]
+    fn handshake_headers_reject_truncation() {
+        for length in 0..HandshakeHeader::LENGTH {
+            assert!(matches!(
+                HandshakeHeader::try_from(&RECORD[5..5 + length]),
+                Err(TlsError::Truncated { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn decoders_never_panic() {
+        // Every prefix of the record, and every single byte of it replaced by each possible value
+        let mut inputs: Vec<Vec<u8>> = (0..=RECORD.len())
+            .map(|length| RECORD[..length].to_vec())
+            .collect();
+
+        for index in 0..RECORD.len() {
+            for byte in 0..=u8::MAX {
+                let mut input = RECORD.to_vec();
+                input[index] = byte;
+                inputs.push(input);
+            }
+        }
+
+        for input in inputs.iter().chain(&corpus()).chain(&overruns()) {
+            let _ = RecordHeader::try_from(input.as_slice());
+            let _ = HandshakeHeader::try_from(input.as_slice());
+            let _ = ClientHello::try_from(input.as_slice());
+            let _ = ClientHello::from_records(input);
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use crate::tls::error::TlsError;
+
+/// Bounds-checked cursor over the bytes of a TLS structure, all integers are read in network byte order
+#[derive(Debug, Clone)]
+pub struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    /// Creates a reader over the given bytes
+    #[must_use]
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    /// Number of bytes that have not been read yet
+    #[must_use]
+    pub fn remaining(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Whether every byte has been read
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Reads the next `count` bytes
+    /// # Errors
+    /// - If fewer than `count` bytes remain
+    pub fn take(&mut self, count: usize) -> Result<&'a [u8], TlsError> {
+        if count > self.bytes.len() {
+            return Err(TlsError::Truncated {
+                needed: count,
+                available: self.bytes.len(),
+            });
+        }
+
+        let (taken, rest) = self.bytes.split_at(count);
+
+        self.bytes = rest;
+
+        Ok(taken)
+    }
+
+    /// Reads every remaining byte
+    pub fn rest(&mut self) -> &'a [u8] {
+        std::mem::take(&mut self.bytes)
+    }
+
+    /// Reads the next `N` bytes into an array
+    /// # Errors
+    /// - If fewer than `N` bytes remain
+    pub fn array<const N: usize>(&mut self) -> Result<[u8; N], TlsError> {
+        let mut array = [0; N];
+
+        array.copy_from_slice(self.take(N)?);
+
+        Ok(array)
+    }
+
+    /// Reads a single byte
+    /// # Errors
+    /// - If no bytes remain
+    pub fn u8(&mut self) -> Result<u8, TlsError> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    /// Reads a 16 bit integer
+    /// # Errors
+    /// - If fewer than 2 bytes remain
+    pub fn u16(&mut self) -> Result<u16, TlsError> {
+        Ok(u16::from_be_bytes(self.array()?))
+    }
+
+    /// Reads a 24 bit integer, as used by handshake message lengths
+    /// # Errors
+    /// - If fewer than 3 bytes remain
+    pub fn u24(&mut self) -> Result<u32, TlsError> {
+        let [high, middle, low] = self.array()?;
+
+        Ok(u32::from_be_bytes([0, high, middle, low]))
+    }
+
+    /// Reads a vector prefixed by its length in a single byte, returning a reader over its contents
+    /// # Errors
+    /// - If the length or the contents are truncated
+    pub fn vector_u8(&mut self) -> Result<Reader<'a>, TlsError> {
+        let length = self.u8()?;
+
+        Ok(Reader::new(self.take(length.into())?))
+    }
+
+    /// Reads a vector prefixed by its length in two bytes, returning a reader over its contents
+    /// # Errors
+    /// - If the length or the contents are truncated
+    pub fn vector_u16(&mut self) -> Result<Reader<'a>, TlsError> {
+        let length = self.u16()?;
+
+        Ok(Reader::new(self.take(length.into())?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Reader;
+    use crate::tls::error::TlsError;
+
+    #[test]
+    fn integers_are_big_endian() {
+        let mut reader = Reader::new(&[1, 2, 3, 4, 5, 6]);
+
+        assert_eq!(reader.u8(), Ok(1));
+        assert_eq!(reader.u16(), Ok(0x0203));
+        assert_eq!(reader.u24(), Ok(0x0004_0506));
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn truncated_reads_fail_without_consuming() {
+        let mut reader = Reader::new(&[1, 2]);
+
+        assert_eq!(
+            reader.u24(),
+            Err(TlsError::Truncated {
+                needed: 3,
+                available: 2
+            })
+        );
+        assert_eq!(reader.remaining(), 2);
+        assert_eq!(
+            reader.take(usize::MAX),
+            Err(TlsError::Truncated {
+                needed: usize::MAX,
+                available: 2
+            })
+        );
+    }
+
+    #[test]
+    fn vector_lengths_past_the_end_fail() {
+        assert_eq!(
+            Reader::new(&[4, 1, 2])
+                .vector_u8()
+                .map(|vector| vector.remaining()),
+            Err(TlsError::Truncated {
+                needed: 4,
+                available: 2
+            })
+        );
+        assert_eq!(
+            Reader::new(&[0xff, 0xff, 1])
+                .vector_u16()
+                .map(|vector| vector.remaining()),
+            Err(TlsError::Truncated {
+                needed: 0xffff,
+                available: 1
+            })
+        );
+        assert!(matches!(
+            Reader::new(&[0])
+                .vector_u16()
+                .map(|vector| vector.remaining()),
+            Err(TlsError::Truncated { .. })
+        ));
+    }
+
+    #[test]
+    fn zero_length_vectors_are_empty() {
+        let mut reader = Reader::new(&[0, 0, 0, 7]);
+
+        assert!(reader.vector_u8().is_ok_and(|vector| vector.is_empty()));
+        assert!(reader.vector_u16().is_ok_and(|vector| vector.is_empty()));
+        assert_eq!(reader.rest(), &[7]);
+    }
+
+    #[test]
+    fn reads_never_panic() {
+        let bytes = [3, 0, 2, 0xff, 1, 0, 0, 4];
+
+        for length in 0..=bytes.len() {
+            let mut reader = Reader::new(&bytes[..length]);
+
+            while !reader.is_empty() {
+                let _ = reader.u24();
+                let _ = reader
+                    .vector_u16()
+                    .and_then(|mut vector| vector.vector_u8());
+                let _ = reader.array::<2>();
+                let _ = reader.u8();
+            }
+        }
+    }
+}
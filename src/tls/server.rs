@@ -0,0 +1,161 @@
+use super::certificates::CertificateStore;
+use crate::http::listener::{Connection, Listener};
+use rustls::{crypto::CryptoProvider, ServerConfig, ServerConnection, StreamOwned};
+use std::{
+    io::{Read, Write},
+    net::{SocketAddr, TcpListener},
+    path::Path,
+    sync::Arc,
+    time::Duration,
+};
+
+/// The cryptography used for every handshake
+#[must_use]
+pub fn provider() -> Arc<CryptoProvider> {
+    Arc::new(rustls::crypto::ring::default_provider())
+}
+
+/// Builds a server configuration for TLS 1.2 and 1.3 from a PEM certificate chain and a PEM private key
+/// # Errors
+/// - If either file cannot be read or does not contain valid PEM data
+/// - If the private key does not match the certificate
+pub fn config_from_pem(
+    certificate: impl AsRef<Path>,
+    key: impl AsRef<Path>,
+) -> std::io::Result<Arc<ServerConfig>> {
+    config_from_store(Arc::new(
+        CertificateStore::new(provider()).fallback(certificate, key)?,
+    ))
+}
+
+/// Builds a server configuration for TLS 1.2 and 1.3 that picks certificates from the store by SNI hostname.
+///
+/// The cipher suites are those of the [`provider`], chosen by rustls rather than a
+/// [`SelectionPolicy`](super::cipher_suite::SelectionPolicy)
+/// # Errors
+/// - If the provider does not support the default protocol versions
+pub fn config_from_store(store: Arc<CertificateStore>) -> std::io::Result<Arc<ServerConfig>> {
+    let mut config = ServerConfig::builder_with_provider(provider())
+        .with_safe_default_protocol_versions()
+        .map_err(std::io::Error::other)?
+        .with_no_client_auth()
+        .with_cert_resolver(store);
+
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+    Ok(Arc::new(config))
+}
+
+/// A [`Listener`] that performs a TLS handshake on every connection accepted from the inner listener
+pub struct TlsListener<L: Listener = TcpListener> {
+    inner: L,
+    config: Arc<ServerConfig>,
+}
+
+impl<L: Listener> TlsListener<L> {
+    /// Wraps a listener with the given TLS configuration
+    #[must_use]
+    pub fn new(inner: L, config: Arc<ServerConfig>) -> Self {
+        Self { inner, config }
+    }
+
+    /// Wraps a listener, serving the certificate chain and private key from the given PEM files
+    /// # Errors
+    /// - If the certificate or key could not be loaded, see [`config_from_pem`]
+    pub fn from_pem(
+        inner: L,
+        certificate: impl AsRef<Path>,
+        key: impl AsRef<Path>,
+    ) -> std::io::Result<Self> {
+        Ok(Self::new(inner, config_from_pem(certificate, key)?))
+    }
+
+    /// Wraps a listener, serving the certificate matching the SNI hostname of each handshake
+    /// # Errors
+    /// - If the configuration could not be built, see [`config_from_store`]
+    pub fn from_store(inner: L, store: Arc<CertificateStore>) -> std::io::Result<Self> {
+        Ok(Self::new(inner, config_from_store(store)?))
+    }
+}
+
+impl<L: Listener> Listener for TlsListener<L> {
+    type Connection = TlsConnection<L::Connection>;
+
+    fn accept(&self) -> std::io::Result<Self::Connection> {
+        let connection = self.inner.accept()?;
+
+        // The handshake itself happens lazily on the first read, on the connection's own thread
+        let session = ServerConnection::new(self.config.clone()).map_err(std::io::Error::other)?;
+
+        Ok(TlsConnection(StreamOwned::new(session, connection)))
+    }
+
+    fn address(&self) -> String {
+        let address = self.inner.address();
+
+        match address.strip_prefix("http://") {
+            Some(address) => format!("https://{address}"),
+            None => address,
+        }
+    }
+}
+
+/// A connection encrypted with TLS
+pub struct TlsConnection<C: Connection>(StreamOwned<ServerConnection, C>);
+
+impl<C: Connection> TlsConnection<C> {
+    /// The hostname the client asked for through SNI, available once the handshake has completed
+    #[must_use]
+    pub fn server_name(&self) -> Option<&str> {
+        self.0.conn.server_name()
+    }
+
+    /// The application protocol negotiated through ALPN, available once the handshake has completed
+    #[must_use]
+    pub fn alpn_protocol(&self) -> Option<&[u8]> {
+        self.0.conn.alpn_protocol()
+    }
+}
+
+impl<C: Connection> Read for TlsConnection<C> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl<C: Connection> Write for TlsConnection<C> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.0.flush()
+    }
+}
+
+impl<C: Connection> Connection for TlsConnection<C> {
+    fn shutdown(&mut self) -> std::io::Result<()> {
+        self.0.conn.send_close_notify();
+
+        // The peer may already be gone, the socket is closed either way
+        let _ = self.0.flush();
+
+        self.0.sock.shutdown()
+    }
+
+    fn peer_addr(&self) -> Option<SocketAddr> {
+        self.0.sock.peer_addr()
+    }
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> std::io::Result<()> {
+        self.0.sock.set_read_timeout(timeout)
+    }
+
+    fn set_write_timeout(&self, timeout: Option<Duration>) -> std::io::Result<()> {
+        self.0.sock.set_write_timeout(timeout)
+    }
+
+    fn is_encrypted(&self) -> bool {
+        true
+    }
+}